@@ -2,6 +2,8 @@
 pub mod replay;
 /// Radix Engine Simulator CLI.
 pub mod resim;
+/// Radix transaction manifest language server.
+pub mod rtm_lsp;
 /// Radix transaction manifest compiler CLI.
 pub mod rtmc;
 /// Radix transaction manifest decompiler CLI.
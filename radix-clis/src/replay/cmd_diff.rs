@@ -0,0 +1,497 @@
+use super::ledger_transaction::*;
+use super::ledger_transaction_execution::*;
+use super::txn_reader::TxnReader;
+use super::Error;
+use clap::Parser;
+use flate2::read::GzDecoder;
+use flume;
+use radix_common::prelude::*;
+use radix_engine::system::system_callback::{SystemBoot, BOOT_LOADER_SYSTEM_SUBSTATE_FIELD_KEY};
+use radix_engine::transaction::{CostingParameters, TransactionResult};
+use radix_engine::updates::{ProtocolBuilder, ProtocolVersion};
+use radix_engine::vm::wasm::*;
+use radix_engine::vm::ScryptoVm;
+use radix_engine_interface::types::BOOT_LOADER_PARTITION;
+use radix_substate_store_impls::memory_db::InMemorySubstateDatabase;
+use radix_substate_store_interface::db_key_mapper::{
+    MappedSubstateDatabase, SpreadPrefixKeyMapper,
+};
+use radix_substate_store_interface::interface::CommittableSubstateDatabase;
+use std::fs::File;
+use std::path::PathBuf;
+use std::thread;
+use tar::Archive;
+
+/// Run transactions in archive against two configurations, using in-memory databases, and report
+/// any divergence in their execution
+#[derive(Parser, Debug)]
+pub struct TxnDiff {
+    /// The transaction file, in `.tar.gz` format, with entries sorted
+    pub source: PathBuf,
+
+    /// The network to use, [mainnet | stokenet]
+    #[clap(short, long)]
+    pub network: Option<String>,
+    /// The max version to execute
+    #[clap(short, long)]
+    pub max_version: Option<u64>,
+
+    /// The protocol version of the first configuration, [babylon | anemone | bottlenose]
+    #[clap(long, default_value = "babylon")]
+    pub protocol_version_a: String,
+    /// The protocol version of the second configuration, [babylon | anemone | bottlenose]
+    #[clap(long, default_value = "babylon")]
+    pub protocol_version_b: String,
+
+    /// The WASM engine of the first configuration, [wasmi | wasmer]
+    #[clap(long, default_value = "wasmi")]
+    pub wasm_engine_a: String,
+    /// The WASM engine of the second configuration, [wasmi | wasmer]
+    #[clap(long, default_value = "wasmi")]
+    pub wasm_engine_b: String,
+
+    /// Costing parameters of the first configuration to override, as comma-separated
+    /// `field=value` pairs, e.g. `execution_cost_unit_price=0.00000001`
+    #[clap(long)]
+    pub costing_parameters_a: Option<String>,
+    /// Costing parameters of the second configuration to override, as comma-separated
+    /// `field=value` pairs, e.g. `execution_cost_unit_price=0.00000001`
+    #[clap(long)]
+    pub costing_parameters_b: Option<String>,
+
+    /// Stop at the first transaction which executes differently
+    #[clap(long)]
+    pub stop_at_first_divergence: bool,
+}
+
+/// The Scrypto VM of a configuration, running on the selected WASM engine.
+enum DiffVm {
+    Wasmi(ScryptoVm<WasmiEngine>),
+    #[cfg(feature = "wasmer")]
+    Wasmer(ScryptoVm<WasmerEngine>),
+}
+
+impl DiffVm {
+    fn new(wasm_engine: &str) -> Result<Self, Error> {
+        match wasm_engine {
+            "wasmi" => Ok(Self::Wasmi(ScryptoVm::default())),
+            #[cfg(feature = "wasmer")]
+            "wasmer" => Ok(Self::Wasmer(ScryptoVm::default())),
+            _ => Err(Error::InvalidWasmEngine(wasm_engine.to_string())),
+        }
+    }
+}
+
+/// Costing parameters overridden on top of the ones stored on ledger.
+#[derive(Default)]
+struct CostingParametersOverrides(Vec<(String, String)>);
+
+impl CostingParametersOverrides {
+    fn parse(overrides: &str) -> Result<Self, Error> {
+        let overrides = Self(
+            overrides
+                .split(',')
+                .map(|pair| {
+                    pair.split_once('=')
+                        .map(|(field, value)| (field.trim().to_string(), value.trim().to_string()))
+                        .ok_or_else(|| Error::InvalidCostingParameters(pair.to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+        );
+        // Fail early on unknown fields and malformed values
+        overrides.apply(CostingParameters::babylon_genesis())?;
+        Ok(overrides)
+    }
+
+    fn apply(&self, mut parameters: CostingParameters) -> Result<CostingParameters, Error> {
+        for (field, value) in &self.0 {
+            let invalid = || Error::InvalidCostingParameters(format!("{}={}", field, value));
+            match field.as_str() {
+                "execution_cost_unit_price" => {
+                    parameters.execution_cost_unit_price =
+                        Decimal::from_str(value).map_err(|_| invalid())?
+                }
+                "execution_cost_unit_limit" => {
+                    parameters.execution_cost_unit_limit = value.parse().map_err(|_| invalid())?
+                }
+                "execution_cost_unit_loan" => {
+                    parameters.execution_cost_unit_loan = value.parse().map_err(|_| invalid())?
+                }
+                "finalization_cost_unit_price" => {
+                    parameters.finalization_cost_unit_price =
+                        Decimal::from_str(value).map_err(|_| invalid())?
+                }
+                "finalization_cost_unit_limit" => {
+                    parameters.finalization_cost_unit_limit =
+                        value.parse().map_err(|_| invalid())?
+                }
+                "usd_price" => {
+                    parameters.usd_price = Decimal::from_str(value).map_err(|_| invalid())?
+                }
+                "state_storage_price" => {
+                    parameters.state_storage_price =
+                        Decimal::from_str(value).map_err(|_| invalid())?
+                }
+                "archive_storage_price" => {
+                    parameters.archive_storage_price =
+                        Decimal::from_str(value).map_err(|_| invalid())?
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(parameters)
+    }
+}
+
+/// A configuration to execute the transactions against.
+struct DiffSide {
+    database: InMemorySubstateDatabase,
+    protocol_version: ProtocolVersion,
+    vm: DiffVm,
+    costing_parameters_overrides: Option<CostingParametersOverrides>,
+}
+
+impl DiffSide {
+    fn new(
+        protocol_version: &str,
+        wasm_engine: &str,
+        costing_parameters_overrides: Option<&str>,
+    ) -> Result<Self, Error> {
+        let protocol_version = ProtocolVersion::try_from_logical_name(protocol_version)
+            .ok_or_else(|| Error::InvalidProtocolVersion(protocol_version.to_string()))?;
+        Ok(Self {
+            database: InMemorySubstateDatabase::standard(),
+            protocol_version,
+            vm: DiffVm::new(wasm_engine)?,
+            costing_parameters_overrides: costing_parameters_overrides
+                .map(CostingParametersOverrides::parse)
+                .transpose()?,
+        })
+    }
+
+    /// Applies the overrides to the costing parameters currently stored on ledger.
+    fn costing_parameters(&self) -> Option<CostingParameters> {
+        let overrides = self.costing_parameters_overrides.as_ref()?;
+        let on_ledger = self
+            .database
+            .get_mapped::<SpreadPrefixKeyMapper, SystemBoot>(
+                TRANSACTION_TRACKER.as_node_id(),
+                BOOT_LOADER_PARTITION,
+                &SubstateKey::Field(BOOT_LOADER_SYSTEM_SUBSTATE_FIELD_KEY),
+            )
+            .map(|system_boot| match system_boot {
+                SystemBoot::V1(system_parameters) => system_parameters.costing_parameters,
+            })
+            .unwrap_or_else(CostingParameters::babylon_genesis);
+        // The overrides were validated when parsed
+        Some(overrides.apply(on_ledger).unwrap())
+    }
+
+    /// Enacts all protocol updates up to the configured protocol version.
+    fn enact_protocol_updates(&mut self, network: &NetworkDefinition) {
+        ProtocolBuilder::for_network(network)
+            .until(self.protocol_version)
+            .commit_each_protocol_update(&mut self.database);
+    }
+
+    fn execute_and_commit(
+        &mut self,
+        network: &NetworkDefinition,
+        prepared: &PreparedLedgerTransaction,
+    ) -> LedgerTransactionReceipt {
+        let costing_parameters = self.costing_parameters();
+        let receipt = match &self.vm {
+            DiffVm::Wasmi(scrypto_vm) => {
+                execute_prepared_ledger_transaction_with_costing_parameters(
+                    &self.database,
+                    scrypto_vm,
+                    network,
+                    prepared,
+                    false,
+                    costing_parameters,
+                )
+            }
+            #[cfg(feature = "wasmer")]
+            DiffVm::Wasmer(scrypto_vm) => {
+                execute_prepared_ledger_transaction_with_costing_parameters(
+                    &self.database,
+                    scrypto_vm,
+                    network,
+                    prepared,
+                    false,
+                    costing_parameters,
+                )
+            }
+        };
+        let committed_state_updates = match &receipt {
+            LedgerTransactionReceipt::Flash(receipt) => Some(&receipt.state_updates),
+            LedgerTransactionReceipt::Standard(receipt) => match &receipt.result {
+                TransactionResult::Commit(commit) => Some(&commit.state_updates),
+                TransactionResult::Reject(_) | TransactionResult::Abort(_) => None,
+            },
+        };
+        if let Some(state_updates) = committed_state_updates {
+            self.database
+                .commit(&state_updates.create_database_updates::<SpreadPrefixKeyMapper>());
+        }
+        receipt
+    }
+}
+
+/// The aspects in which the executions of a single transaction differ.
+#[derive(Default)]
+struct Divergence {
+    outcome: bool,
+    state_updates: bool,
+    events: bool,
+    fee_summary: bool,
+}
+
+impl Divergence {
+    fn between(a: &LedgerTransactionReceipt, b: &LedgerTransactionReceipt) -> Self {
+        match (a, b) {
+            (LedgerTransactionReceipt::Flash(a), LedgerTransactionReceipt::Flash(b)) => Self {
+                state_updates: a.state_updates != b.state_updates,
+                ..Default::default()
+            },
+            (LedgerTransactionReceipt::Standard(a), LedgerTransactionReceipt::Standard(b)) => {
+                match (&a.result, &b.result) {
+                    (TransactionResult::Commit(a_commit), TransactionResult::Commit(b_commit)) => {
+                        Self {
+                            outcome: a_commit.outcome != b_commit.outcome,
+                            state_updates: a_commit.state_updates != b_commit.state_updates,
+                            events: a_commit.application_events != b_commit.application_events,
+                            fee_summary: a.fee_summary != b.fee_summary,
+                        }
+                    }
+                    (a_result, b_result) => Self {
+                        outcome: a_result != b_result,
+                        fee_summary: a.fee_summary != b.fee_summary,
+                        ..Default::default()
+                    },
+                }
+            }
+            _ => Self {
+                outcome: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !(self.outcome || self.state_updates || self.events || self.fee_summary)
+    }
+
+    fn describe(&self) -> String {
+        [
+            (self.outcome, "outcome"),
+            (self.state_updates, "state updates"),
+            (self.events, "events"),
+            (self.fee_summary, "fee summary"),
+        ]
+        .into_iter()
+        .filter_map(|(diverged, aspect)| diverged.then_some(aspect))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+fn describe_outcome(receipt: &LedgerTransactionReceipt) -> String {
+    match receipt {
+        LedgerTransactionReceipt::Flash(_) => "Flash".to_string(),
+        LedgerTransactionReceipt::Standard(receipt) => match &receipt.result {
+            TransactionResult::Commit(commit) => format!("Commit({:?})", commit.outcome),
+            TransactionResult::Reject(reject) => format!("Reject({:?})", reject.reason),
+            TransactionResult::Abort(abort) => format!("Abort({:?})", abort.reason),
+        },
+    }
+}
+
+impl TxnDiff {
+    pub fn run(&self) -> Result<(), String> {
+        let network = match &self.network {
+            Some(n) => NetworkDefinition::from_str(n).map_err(Error::ParseNetworkError)?,
+            None => NetworkDefinition::mainnet(),
+        };
+        let mut side_a = DiffSide::new(
+            &self.protocol_version_a,
+            &self.wasm_engine_a,
+            self.costing_parameters_a.as_deref(),
+        )?;
+        let mut side_b = DiffSide::new(
+            &self.protocol_version_b,
+            &self.wasm_engine_b,
+            self.costing_parameters_b.as_deref(),
+        )?;
+
+        let cur_version = 0;
+        let to_version = self.max_version.clone();
+
+        let start = std::time::Instant::now();
+        let (tx, rx) = flume::bounded(10);
+
+        // txn reader
+        let mut txn_reader = if self.source.is_file() {
+            let tar_gz = File::open(&self.source).map_err(Error::IOError)?;
+            let tar = GzDecoder::new(tar_gz);
+            let archive = Archive::new(tar);
+            TxnReader::TransactionFile(archive)
+        } else if self.source.is_dir() {
+            TxnReader::StateManagerDatabaseDir(self.source.clone())
+        } else {
+            return Err(Error::InvalidTransactionSource.into());
+        };
+        let txn_read_thread_handle =
+            thread::spawn(move || txn_reader.read(cur_version, to_version, tx));
+
+        // txn executor
+        let stop_at_first_divergence = self.stop_at_first_divergence;
+        let txn_diff_thread_handle = thread::spawn(move || {
+            let mut protocol_updates_enacted = false;
+            let mut version = 0u64;
+            let mut divergent_count = 0u64;
+            for tx_payload in rx.iter() {
+                let prepared = prepare_ledger_transaction(&tx_payload);
+
+                // The protocol updates are enacted right after genesis
+                let is_genesis =
+                    matches!(prepared.inner, PreparedLedgerTransactionInner::Genesis(_));
+                if !is_genesis && !protocol_updates_enacted {
+                    side_a.enact_protocol_updates(&network);
+                    side_b.enact_protocol_updates(&network);
+                    protocol_updates_enacted = true;
+                }
+
+                let receipt_a = side_a.execute_and_commit(&network, &prepared);
+                let receipt_b = side_b.execute_and_commit(&network, &prepared);
+                version += 1;
+
+                let divergence = Divergence::between(&receipt_a, &receipt_b);
+                if !divergence.is_empty() {
+                    divergent_count += 1;
+                    println!(
+                        "Divergence at version {}: {}",
+                        version,
+                        divergence.describe()
+                    );
+                    if divergence.outcome {
+                        println!("  A: {}", describe_outcome(&receipt_a));
+                        println!("  B: {}", describe_outcome(&receipt_b));
+                    }
+                    if stop_at_first_divergence {
+                        break;
+                    }
+                }
+
+                if version % 1000 == 0 {
+                    println!(
+                        "Compared up to version {} ({} divergent), {:?} elapsed",
+                        version,
+                        divergent_count,
+                        start.elapsed()
+                    );
+                }
+            }
+
+            println!("Time elapsed: {:?}", start.elapsed());
+            println!("Transactions compared: {}", version);
+            println!("Transactions with divergence: {}", divergent_count);
+        });
+
+        txn_diff_thread_handle.join().unwrap();
+        // Note: the reader may fail to send further transactions if the comparison was stopped
+        if !stop_at_first_divergence {
+            txn_read_thread_handle.join().unwrap()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radix_engine::system::bootstrap::Bootstrapper;
+    use radix_engine::vm::{NoExtension, VmInit};
+    use radix_transactions::prelude::*;
+
+    fn bootstrapped_side(costing_parameters_overrides: Option<&str>) -> DiffSide {
+        let mut side = DiffSide::new("babylon", "wasmi", costing_parameters_overrides).unwrap();
+        let scrypto_vm = ScryptoVm::<WasmiEngine>::default();
+        Bootstrapper::new(
+            NetworkDefinition::simulator(),
+            &mut side.database,
+            VmInit {
+                scrypto_vm: &scrypto_vm,
+                native_vm_extension: NoExtension,
+            },
+            false,
+        )
+        .bootstrap_test_default()
+        .unwrap();
+        side
+    }
+
+    fn free_xrd_transaction() -> PreparedLedgerTransaction {
+        let notary_private_key = Secp256k1PrivateKey::from_u64(1).unwrap();
+        let account =
+            ComponentAddress::virtual_account_from_public_key(&notary_private_key.public_key());
+        let transaction = TransactionBuilder::new()
+            .header(TransactionHeaderV1 {
+                network_id: NetworkDefinition::simulator().id,
+                start_epoch_inclusive: Epoch::zero(),
+                end_epoch_exclusive: Epoch::of(10),
+                nonce: 0,
+                notary_public_key: notary_private_key.public_key().into(),
+                notary_is_signatory: false,
+                tip_percentage: 0,
+            })
+            .manifest(
+                ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .get_free_xrd_from_faucet()
+                    .try_deposit_entire_worktop_or_abort(account, None)
+                    .build(),
+            )
+            .notarize(&notary_private_key)
+            .build();
+        LedgerTransaction::UserV1(Box::new(transaction))
+            .prepare()
+            .unwrap()
+    }
+
+    #[test]
+    fn costing_parameters_overrides_diverge_in_fees_only() {
+        let network = NetworkDefinition::simulator();
+        let prepared = free_xrd_transaction();
+        let mut side_a = bootstrapped_side(None);
+        let mut side_b = bootstrapped_side(Some("execution_cost_unit_price=0.0000001"));
+
+        let receipt_a = side_a.execute_and_commit(&network, &prepared);
+        let receipt_b = side_b.execute_and_commit(&network, &prepared);
+
+        let fee_summary_a = receipt_a.fee_summary().unwrap();
+        let fee_summary_b = receipt_b.fee_summary().unwrap();
+        assert_eq!(
+            fee_summary_a.total_execution_cost_units_consumed,
+            fee_summary_b.total_execution_cost_units_consumed
+        );
+        assert!(
+            fee_summary_b.total_execution_cost_in_xrd > fee_summary_a.total_execution_cost_in_xrd
+        );
+        let divergence = Divergence::between(&receipt_a, &receipt_b);
+        assert!(!divergence.outcome);
+        assert!(divergence.fee_summary);
+    }
+
+    #[test]
+    fn invalid_costing_parameters_overrides_are_rejected() {
+        assert!(CostingParametersOverrides::parse("execution_cost_unit_price").is_err());
+        assert!(CostingParametersOverrides::parse("unknown_field=1").is_err());
+        assert!(CostingParametersOverrides::parse("execution_cost_unit_limit=-1").is_err());
+        assert!(CostingParametersOverrides::parse(
+            "execution_cost_unit_price=0.0000001, usd_price=10"
+        )
+        .is_ok());
+    }
+}
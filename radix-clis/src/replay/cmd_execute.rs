@@ -35,6 +35,9 @@ pub struct TxnExecute {
     /// Trace transaction execution
     #[clap(long)]
     pub trace: bool,
+    /// The number of most recent versions which can be rolled back (see the `rollback` command)
+    #[clap(long)]
+    pub rollback_history: Option<u64>,
 }
 
 impl TxnExecute {
@@ -72,7 +75,8 @@ impl TxnExecute {
             thread::spawn(move || txn_reader.read(cur_version, to_version, tx));
 
         // txn executor
-        let mut database = RocksDBWithMerkleTreeSubstateStore::standard(self.database_dir.clone());
+        let mut database = RocksDBWithMerkleTreeSubstateStore::standard(self.database_dir.clone())
+            .with_rollback_history(self.rollback_history.unwrap_or(0));
         let trace = self.trace;
         let txn_write_thread_handle = thread::spawn(move || {
             let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
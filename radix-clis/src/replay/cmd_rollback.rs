@@ -0,0 +1,26 @@
+use super::Error;
+use clap::Parser;
+use radix_substate_store_impls::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+use std::path::PathBuf;
+
+/// Roll back the state to a previous version, using RocksDB
+#[derive(Parser, Debug)]
+pub struct TxnRollback {
+    /// Path to a folder for storing state
+    pub database_dir: PathBuf,
+    /// The version to roll back to
+    pub to_version: u64,
+}
+
+impl TxnRollback {
+    pub fn run(&self) -> Result<(), String> {
+        let mut database = RocksDBWithMerkleTreeSubstateStore::standard(self.database_dir.clone());
+        database
+            .rollback_to(self.to_version)
+            .map_err(Error::RollbackError)?;
+
+        println!("State version: {}", database.get_current_version());
+        println!("State root hash: {}", database.get_current_root_hash());
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use radix_common::prelude::ParseNetworkError;
+use radix_substate_store_impls::rocks_db_with_merkle_tree::RollbackError;
 use std::fmt;
 
 #[derive(Debug)]
@@ -9,6 +10,10 @@ pub enum Error {
     InvalidTransactionArchive,
     InvalidTransactionSource,
     InvalidBreakpoints(String),
+    InvalidProtocolVersion(String),
+    InvalidWasmEngine(String),
+    InvalidCostingParameters(String),
+    RollbackError(RollbackError),
 }
 
 impl fmt::Display for Error {
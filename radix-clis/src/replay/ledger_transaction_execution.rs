@@ -5,7 +5,8 @@ use radix_common::prelude::*;
 use radix_engine::system::bootstrap::*;
 use radix_engine::track::StateUpdates;
 use radix_engine::transaction::{
-    execute_transaction, ExecutionConfig, TransactionFeeSummary, TransactionReceipt,
+    execute_transaction, CostingParameters, ExecutionConfig, TransactionFeeSummary,
+    TransactionReceipt,
 };
 use radix_engine::vm::wasm::*;
 use radix_engine::vm::{NoExtension, ScryptoVm, VmInit};
@@ -58,13 +59,40 @@ pub fn prepare_ledger_transaction(tx_payload: &[u8]) -> PreparedLedgerTransactio
     prepared
 }
 
-pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
+pub fn execute_prepared_ledger_transaction<S: SubstateDatabase, W: WasmEngine>(
     database: &S,
-    scrypto_vm: &ScryptoVm<DefaultWasmEngine>,
+    scrypto_vm: &ScryptoVm<W>,
     network: &NetworkDefinition,
     prepared: &PreparedLedgerTransaction,
     trace: bool,
 ) -> LedgerTransactionReceipt {
+    execute_prepared_ledger_transaction_with_costing_parameters(
+        database, scrypto_vm, network, prepared, trace, None,
+    )
+}
+
+/// Same as [`execute_prepared_ledger_transaction`], but overrides the costing parameters stored
+/// on ledger with the given ones, if any.
+pub fn execute_prepared_ledger_transaction_with_costing_parameters<
+    S: SubstateDatabase,
+    W: WasmEngine,
+>(
+    database: &S,
+    scrypto_vm: &ScryptoVm<W>,
+    network: &NetworkDefinition,
+    prepared: &PreparedLedgerTransaction,
+    trace: bool,
+    costing_parameters: Option<CostingParameters>,
+) -> LedgerTransactionReceipt {
+    let with_costing_parameters = |mut execution_config: ExecutionConfig| {
+        if let Some(costing_parameters) = costing_parameters {
+            execution_config
+                .system_overrides
+                .get_or_insert_with(Default::default)
+                .costing_parameters = Some(costing_parameters);
+        }
+        execution_config
+    };
     match &prepared.inner {
         PreparedLedgerTransactionInner::Genesis(prepared_genesis_tx) => {
             match prepared_genesis_tx.as_ref() {
@@ -79,9 +107,11 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
                             scrypto_vm,
                             native_vm_extension: NoExtension,
                         },
-                        &ExecutionConfig::for_genesis_transaction(network.clone())
-                            .with_kernel_trace(trace)
-                            .with_cost_breakdown(trace),
+                        &with_costing_parameters(
+                            ExecutionConfig::for_genesis_transaction(network.clone())
+                                .with_kernel_trace(trace)
+                                .with_cost_breakdown(trace),
+                        ),
                         &tx.get_executable(btreeset!(AuthAddresses::system_role())),
                     );
                     LedgerTransactionReceipt::Standard(receipt)
@@ -95,9 +125,11 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
                     scrypto_vm,
                     native_vm_extension: NoExtension,
                 },
-                &ExecutionConfig::for_notarized_transaction(network.clone())
-                    .with_kernel_trace(trace)
-                    .with_cost_breakdown(trace),
+                &with_costing_parameters(
+                    ExecutionConfig::for_notarized_transaction(network.clone())
+                        .with_kernel_trace(trace)
+                        .with_cost_breakdown(trace),
+                ),
                 &NotarizedTransactionValidator::new(ValidationConfig::default(network.id))
                     .validate(tx.as_ref().clone())
                     .expect("Transaction validation failure")
@@ -112,9 +144,11 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
                     scrypto_vm,
                     native_vm_extension: NoExtension,
                 },
-                &ExecutionConfig::for_system_transaction(network.clone())
-                    .with_kernel_trace(trace)
-                    .with_cost_breakdown(trace),
+                &with_costing_parameters(
+                    ExecutionConfig::for_system_transaction(network.clone())
+                        .with_kernel_trace(trace)
+                        .with_cost_breakdown(trace),
+                ),
                 &tx.get_executable(),
             );
             LedgerTransactionReceipt::Standard(receipt)
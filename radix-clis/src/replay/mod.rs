@@ -3,18 +3,22 @@ pub mod ledger_transaction_execution;
 pub mod txn_reader;
 
 mod cmd_alloc_dump;
+mod cmd_diff;
 mod cmd_execute;
 mod cmd_execute_in_memory;
 mod cmd_measure;
 mod cmd_prepare;
+mod cmd_rollback;
 mod cmd_sync;
 mod error;
 
 pub use cmd_alloc_dump::*;
+pub use cmd_diff::*;
 pub use cmd_execute::*;
 pub use cmd_execute_in_memory::*;
 pub use cmd_measure::*;
 pub use cmd_prepare::*;
+pub use cmd_rollback::*;
 pub use cmd_sync::*;
 pub use error::*;
 
@@ -36,6 +40,8 @@ pub enum Command {
     Sync(TxnSync),
     Measure(TxnMeasure),
     AllocDump(TxnAllocDump),
+    Rollback(TxnRollback),
+    Diff(TxnDiff),
 }
 
 pub fn run() -> Result<(), String> {
@@ -48,5 +54,7 @@ pub fn run() -> Result<(), String> {
         Command::Sync(cmd) => cmd.sync(),
         Command::Measure(cmd) => cmd.run(),
         Command::AllocDump(cmd) => cmd.run(),
+        Command::Rollback(cmd) => cmd.run(),
+        Command::Diff(cmd) => cmd.run(),
     }
 }
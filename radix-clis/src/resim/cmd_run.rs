@@ -1,10 +1,15 @@
 use crate::resim::*;
 use clap::Parser;
-use radix_engine::utils::validate_call_arguments_to_native_components;
+use colored::*;
+use radix_engine::system::system_modules::debugger::DebuggerConfig;
+use radix_engine::utils::{
+    instruction_schema_validation_error_diagnostics, validate_call_arguments_to_blueprints,
+};
 use radix_transactions::manifest::{
-    compile, compiler::compile_error_diagnostics, compiler::CompileErrorDiagnosticsStyle,
-    BlobProvider,
+    compiler::compile_error_diagnostics, compiler::compile_with_spans,
+    compiler::CompileErrorDiagnosticsStyle, token::Span, BlobProvider,
 };
+use radix_transactions::validation::{ResourceFlowAnalyzer, ResourceFlowWarning};
 use regex::{Captures, Regex};
 use std::env;
 use std::path::PathBuf;
@@ -30,6 +35,16 @@ pub struct Run {
     /// Turn on tracing
     #[clap(short, long)]
     pub trace: bool,
+
+    /// Run the transaction in the interactive debugger, pausing at the first invocation
+    #[clap(long)]
+    pub debug: bool,
+
+    /// The breakpoints of the debugger, in the format of `method:<name>`, `blueprint:<name>`,
+    /// `node:<address>` or `write:<address>:<partition number>`; implies `--debug` without
+    /// pausing at the first invocation
+    #[clap(long, multiple = true)]
+    pub breakpoint: Vec<SimulatorBreakpoint>,
 }
 
 impl Run {
@@ -54,7 +69,7 @@ impl Run {
                 blobs.push(std::fs::read(path).map_err(Error::IOError)?);
             }
         }
-        let compiled_manifest = compile(
+        let (compiled_manifest, instruction_spans) = compile_with_spans(
             &pre_processed_manifest,
             &network,
             BlobProvider::new_with_blobs(blobs),
@@ -67,19 +82,130 @@ impl Run {
             )
         })?;
 
-        validate_call_arguments_to_native_components(&compiled_manifest.instructions)
-            .map_err(Error::InstructionSchemaValidationError)?;
+        {
+            let SimulatorEnvironment { db, .. } = SimulatorEnvironment::new()?;
+            validate_call_arguments_to_blueprints(&compiled_manifest.instructions, &db).map_err(
+                |err| {
+                    instruction_schema_validation_error_diagnostics(
+                        &pre_processed_manifest,
+                        &instruction_spans,
+                        &err,
+                        CompileErrorDiagnosticsStyle::TextTerminalColors,
+                    )
+                },
+            )?;
+        }
 
-        handle_manifest(
+        let analyzer = self.resource_flow_analyzer()?;
+        for warning in analyzer.analyze(&compiled_manifest.instructions) {
+            writeln!(
+                out,
+                "{} {}",
+                "Warning:".yellow(),
+                resource_flow_warning_message(&warning, &instruction_spans, &network)
+            )
+            .map_err(Error::IOError)?;
+        }
+
+        let debugger = if self.debug || !self.breakpoint.is_empty() {
+            let address_decoder = AddressBech32Decoder::new(&network);
+            let mut config =
+                DebuggerConfig::new(ConsoleDebugger::new(&network)).with_step(self.debug);
+            for breakpoint in &self.breakpoint {
+                config = config.with_breakpoint(
+                    breakpoint
+                        .to_breakpoint(&address_decoder)
+                        .map_err(Error::InvalidBreakpoint)?,
+                );
+            }
+            Some(config)
+        } else {
+            None
+        };
+
+        handle_manifest_with_debugger(
             compiled_manifest,
             &self.signing_keys,
             &self.network,
             &None,
             self.trace,
+            debugger,
             true,
             out,
         )
         .map(|_| ())
         .map_err(|err| err.into())
     }
+
+    /// Returns the analyzer of the manifest, treating the accounts of the signers as controlled.
+    fn resource_flow_analyzer(&self) -> Result<ResourceFlowAnalyzer, Error> {
+        let mut analyzer = get_signing_keys(&self.signing_keys)?
+            .into_iter()
+            .fold(ResourceFlowAnalyzer::new(), |analyzer, private_key| {
+                analyzer.with_signer(&private_key.public_key().into())
+            });
+        // The accounts created by the simulator are owned by the badge of their key, rather than
+        // virtual, so the default account is controlled by the default key.
+        if self.signing_keys.is_none() {
+            if let Ok(account) = get_default_account() {
+                analyzer = analyzer.with_controlled_account(account);
+            }
+        }
+        Ok(analyzer)
+    }
+}
+
+fn resource_flow_warning_message(
+    warning: &ResourceFlowWarning,
+    instruction_spans: &[Span],
+    network: &NetworkDefinition,
+) -> String {
+    let encoder = AddressBech32Encoder::new(network);
+    let line = |index: &usize| {
+        instruction_spans
+            .get(*index)
+            .map_or(0, |span| span.start.line_idx + 1)
+    };
+    match warning {
+        ResourceFlowWarning::BucketNeverDeposited { created_at, .. } => format!(
+            "the bucket taken at line {} is never deposited",
+            line(created_at)
+        ),
+        ResourceFlowWarning::MissingWorktopAssertion { call_index } => format!(
+            "the resources returned by the call at line {} aren't asserted on the worktop",
+            line(call_index)
+        ),
+        ResourceFlowWarning::ProofLeftOnAuthZone { pushed_at } => format!(
+            "the proof pushed at line {} is left on the auth zone",
+            line(pushed_at)
+        ),
+        ResourceFlowWarning::DepositIntoUncontrolledAccount {
+            instruction_index,
+            account,
+        } => format!(
+            "the deposit at line {} is into {}, which isn't controlled by the signers",
+            line(instruction_index),
+            account.display(&encoder)
+        ),
+        ResourceFlowWarning::InsufficientWorktopContents {
+            instruction_index,
+            resource_address,
+            required,
+            available,
+        } => format!(
+            "line {} takes {} of {} from the worktop, which only holds {}",
+            line(instruction_index),
+            required,
+            resource_address.display(&encoder),
+            available
+        ),
+        ResourceFlowWarning::ResourcesLeftOnWorktop {
+            resource_address,
+            amount,
+        } => format!(
+            "{} of {} is left on the worktop",
+            amount,
+            resource_address.display(&encoder)
+        ),
+    }
 }
@@ -4,7 +4,6 @@ use colored::*;
 use radix_common::time::Instant;
 use radix_common::time::UtcDateTime;
 use radix_engine_interface::blueprints::consensus_manager::*;
-use radix_substate_store_impls::rocks_db::RocksdbSubstateStore;
 use radix_substate_store_interface::{
     db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper},
     interface::ListableSubstateDatabase,
@@ -48,7 +47,7 @@ impl ShowLedger {
 
     pub fn list_entries<O: std::io::Write>(
         out: &mut O,
-        substate_db: &RocksdbSubstateStore,
+        substate_db: &SimulatorDatabase,
     ) -> Result<(), Error> {
         let address_bech32_encoder = AddressBech32Encoder::new(&NetworkDefinition::simulator());
         let mut packages: Vec<PackageAddress> = vec![];
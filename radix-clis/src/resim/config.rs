@@ -1,14 +1,76 @@
 use crate::resim::*;
 use radix_common::prelude::*;
 use radix_engine::updates::*;
+use radix_substate_store_interface::interface::*;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 
+/// The database of the simulator, in RocksDB by default, or else in a single SQLite file if the
+/// [`ENV_DATABASE_BACKEND`] environment variable is `sqlite`.
+pub enum SimulatorDatabase {
+    Rocksdb(RocksdbSubstateStore),
+    Sqlite(SqliteSubstateStore),
+}
+
+impl SimulatorDatabase {
+    pub fn open(data_dir: PathBuf) -> Result<Self, Error> {
+        match env::var(ENV_DATABASE_BACKEND).as_deref() {
+            Err(..) | Ok("rocksdb") => Ok(Self::Rocksdb(RocksdbSubstateStore::standard(data_dir))),
+            Ok("sqlite") => Ok(Self::Sqlite(SqliteSubstateStore::standard(
+                data_dir.join(SQLITE_DATABASE_FILE_NAME),
+            ))),
+            Ok(other) => Err(Error::InvalidDatabaseBackend(other.to_owned())),
+        }
+    }
+}
+
+impl SubstateDatabase for SimulatorDatabase {
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        match self {
+            Self::Rocksdb(db) => db.get_substate(partition_key, sort_key),
+            Self::Sqlite(db) => db.get_substate(partition_key, sort_key),
+        }
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        match self {
+            Self::Rocksdb(db) => db.list_entries_from(partition_key, from_sort_key),
+            Self::Sqlite(db) => db.list_entries_from(partition_key, from_sort_key),
+        }
+    }
+}
+
+impl CommittableSubstateDatabase for SimulatorDatabase {
+    fn commit(&mut self, database_updates: &DatabaseUpdates) {
+        match self {
+            Self::Rocksdb(db) => db.commit(database_updates),
+            Self::Sqlite(db) => db.commit(database_updates),
+        }
+    }
+}
+
+impl ListableSubstateDatabase for SimulatorDatabase {
+    fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
+        match self {
+            Self::Rocksdb(db) => db.list_partition_keys(),
+            Self::Sqlite(db) => db.list_partition_keys(),
+        }
+    }
+}
+
 /// The environment that the simulator runs in.
 pub struct SimulatorEnvironment {
     // Db
-    pub db: RocksdbSubstateStore,
+    pub db: SimulatorDatabase,
     // VMs
     pub scrypto_vm: ScryptoVm<DefaultWasmEngine>,
     pub network_definition: NetworkDefinition,
@@ -17,7 +79,7 @@ pub struct SimulatorEnvironment {
 impl SimulatorEnvironment {
     pub fn new() -> Result<Self, Error> {
         // Create the database
-        let db = RocksdbSubstateStore::standard(get_data_dir()?);
+        let db = SimulatorDatabase::open(get_data_dir()?)?;
 
         // Create the VMs
         let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
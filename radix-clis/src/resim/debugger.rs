@@ -0,0 +1,210 @@
+use radix_common::prelude::*;
+use radix_engine::system::system_modules::debugger::*;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// A breakpoint, in the `<kind>:<argument>` format accepted on the command line:
+/// - `method:<name>`
+/// - `blueprint:<name>`
+/// - `node:<address>`
+/// - `write:<address>:<partition number>`
+///
+/// The addresses are only decoded once the network is known, see [`Self::to_breakpoint`].
+#[derive(Debug, Clone)]
+pub struct SimulatorBreakpoint(String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBreakpointError(String);
+
+impl std::error::Error for InvalidBreakpointError {}
+
+impl fmt::Display for InvalidBreakpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid breakpoint: {}", self.0)
+    }
+}
+
+impl FromStr for SimulatorBreakpoint {
+    type Err = InvalidBreakpointError;
+
+    fn from_str(breakpoint: &str) -> Result<Self, Self::Err> {
+        match breakpoint.split_once(':') {
+            Some(("method" | "blueprint" | "node" | "write", _)) => {
+                Ok(Self(breakpoint.to_string()))
+            }
+            _ => Err(InvalidBreakpointError(breakpoint.to_string())),
+        }
+    }
+}
+
+impl SimulatorBreakpoint {
+    /// Returns the breakpoint, with its addresses decoded for the given network.
+    pub fn to_breakpoint(
+        &self,
+        address_decoder: &AddressBech32Decoder,
+    ) -> Result<Breakpoint, InvalidBreakpointError> {
+        let invalid = || InvalidBreakpointError(self.0.clone());
+        let decode_node_id = |address: &str| {
+            let (_, data) = address_decoder.validate_and_decode(address).ok()?;
+            data.try_into().ok().map(NodeId)
+        };
+        let (kind, argument) = self.0.split_once(':').ok_or_else(invalid)?;
+        let breakpoint = match kind {
+            "method" => Breakpoint::Method(argument.to_string()),
+            "blueprint" => Breakpoint::Blueprint(argument.to_string()),
+            "node" => Breakpoint::Node(decode_node_id(argument).ok_or_else(invalid)?),
+            "write" => {
+                let (address, partition_num) = argument.split_once(':').ok_or_else(invalid)?;
+                Breakpoint::SubstateWrite {
+                    node_id: decode_node_id(address).ok_or_else(invalid)?,
+                    partition_num: PartitionNumber(
+                        u8::from_str(partition_num).map_err(|_| invalid())?,
+                    ),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(breakpoint)
+    }
+}
+
+/// A [`TransactionDebugger`] which prompts for commands on the standard input whenever the
+/// execution is paused.
+pub struct ConsoleDebugger {
+    address_encoder: AddressBech32Encoder,
+}
+
+impl ConsoleDebugger {
+    pub fn new(network: &NetworkDefinition) -> Self {
+        Self {
+            address_encoder: AddressBech32Encoder::new(network),
+        }
+    }
+
+    fn print_help() {
+        println!("Commands:");
+        println!("  s, step        resume until the next event");
+        println!("  c, continue    resume until the next breakpoint");
+        println!("  bt, stack      show the call frame stack");
+        println!("  heap           show the nodes on the heap");
+        println!("  substates      show the opened substates");
+        println!("  h, help        show this help");
+    }
+
+    fn describe_event(&self, event: &DebugEvent) -> String {
+        match event {
+            DebugEvent::Invoke { actor, input } => {
+                format!("invoking {:?}, input = {:?}", actor, input)
+            }
+            DebugEvent::Return { actor, output } => {
+                format!("returning from {:?}, output = {:?}", actor, output)
+            }
+            DebugEvent::CreateNode { node_id } => {
+                format!("creating node {}", self.display_node_id(node_id))
+            }
+            DebugEvent::DropNode { node_id } => {
+                format!("dropping node {}", self.display_node_id(node_id))
+            }
+            DebugEvent::OpenSubstate { handle, substate } => format!(
+                "opened substate {} with handle {}",
+                self.display_substate(substate),
+                handle
+            ),
+            DebugEvent::WriteSubstate {
+                node_id,
+                partition_num,
+                substate_key,
+                value,
+            } => format!(
+                "writing substate {} {:?} {:?}, value = {:?}",
+                self.display_node_id(node_id),
+                partition_num,
+                substate_key,
+                value
+            ),
+            DebugEvent::CloseSubstate { handle } => {
+                format!("closing substate with handle {}", handle)
+            }
+        }
+    }
+
+    fn display_node_id(&self, node_id: &NodeId) -> String {
+        self.address_encoder
+            .encode(node_id.as_bytes())
+            .unwrap_or_else(|_| node_id.to_hex())
+    }
+
+    fn display_substate(&self, substate: &DebugOpenedSubstate) -> String {
+        format!(
+            "{} {:?} {:?} ({:?})",
+            self.display_node_id(&substate.node_id),
+            substate.partition_num,
+            substate.substate_key,
+            substate.flags
+        )
+    }
+
+    fn print_state(&self, command: &str, state: &DebuggerState) -> bool {
+        match command {
+            "bt" | "stack" => {
+                for (depth, actor) in state.call_frames.iter().enumerate().rev() {
+                    println!("  [{}] {:?}", depth, actor);
+                }
+            }
+            "heap" => {
+                for node_id in &state.heap_nodes {
+                    println!(
+                        "  {} ({:?})",
+                        self.display_node_id(node_id),
+                        node_id.entity_type()
+                    );
+                }
+            }
+            "substates" => {
+                for (handle, substate) in &state.opened_substates {
+                    println!(
+                        "  {}: {} opened at depth {}",
+                        handle,
+                        self.display_substate(substate),
+                        substate.depth
+                    );
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl TransactionDebugger for ConsoleDebugger {
+    fn on_break(&mut self, event: &DebugEvent, state: &DebuggerState) -> DebugAction {
+        println!(
+            "[{}] Paused: {}",
+            state.call_frames.len().saturating_sub(1),
+            self.describe_event(event)
+        );
+        let stdin = io::stdin();
+        loop {
+            print!("(debug) ");
+            io::stdout().flush().ok();
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                // Nothing more to read, so run to completion
+                Ok(0) | Err(_) => return DebugAction::Continue,
+                Ok(_) => {}
+            }
+            match line.trim() {
+                "" | "s" | "step" => return DebugAction::Step,
+                "c" | "continue" => return DebugAction::Continue,
+                "h" | "help" => Self::print_help(),
+                command => {
+                    if !self.print_state(command, state) {
+                        println!("Unknown command: {}", command);
+                        Self::print_help();
+                    }
+                }
+            }
+        }
+    }
+}
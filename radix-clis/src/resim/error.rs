@@ -15,7 +15,7 @@ use radix_transactions::manifest::DecompileError;
 use radix_transactions::model::PrepareError as TransactionPrepareError;
 use sbor::*;
 
-use crate::resim::EntityDumpError;
+use crate::resim::{EntityDumpError, InvalidBreakpointError};
 use crate::utils::*;
 
 /// Represents a resim error.
@@ -83,6 +83,10 @@ pub enum Error {
     InvalidResourceSpecifier(String),
 
     RemoteGenericSubstitutionNotSupported,
+
+    InvalidDatabaseBackend(String),
+
+    InvalidBreakpoint(InvalidBreakpointError),
 }
 
 impl fmt::Display for Error {
@@ -21,6 +21,7 @@ mod cmd_show_configs;
 mod cmd_show_ledger;
 mod cmd_transfer;
 mod config;
+mod debugger;
 mod dumper;
 mod error;
 
@@ -46,12 +47,17 @@ pub use cmd_show_configs::*;
 pub use cmd_show_ledger::*;
 pub use cmd_transfer::*;
 pub use config::*;
+pub use debugger::*;
 pub use dumper::*;
 pub use error::*;
 
 pub const DEFAULT_SCRYPTO_DIR_UNDER_HOME: &'static str = ".scrypto";
 pub const ENV_DATA_DIR: &'static str = "DATA_DIR";
 pub const ENV_DISABLE_MANIFEST_OUTPUT: &'static str = "DISABLE_MANIFEST_OUTPUT";
+pub const ENV_DATABASE_BACKEND: &'static str = "DATABASE_BACKEND";
+
+/// The file of the SQLite database, within the data directory.
+pub const SQLITE_DATABASE_FILE_NAME: &'static str = "ledger.sqlite";
 
 use clap::{Parser, Subcommand};
 use radix_common::crypto::{hash, Secp256k1PrivateKey};
@@ -63,6 +69,7 @@ use radix_engine::blueprints::consensus_manager::{
 use radix_engine::blueprints::models::FieldPayload;
 use radix_engine::system::bootstrap::Bootstrapper;
 use radix_engine::system::system_db_reader::*;
+use radix_engine::system::system_modules::debugger::DebuggerConfig;
 use radix_engine::transaction::execute_and_commit_transaction;
 use radix_engine::transaction::ExecutionConfig;
 use radix_engine::transaction::TransactionOutcome;
@@ -79,6 +86,7 @@ use radix_engine_interface::prelude::*;
 use radix_engine_interface::types::FromPublicKey;
 use radix_rust::ContextualDisplay;
 use radix_substate_store_impls::rocks_db::RocksdbSubstateStore;
+use radix_substate_store_impls::sqlite_db::SqliteSubstateStore;
 use radix_substate_store_queries::typed_substate_layout::*;
 use radix_transactions::manifest::decompile;
 use radix_transactions::model::TestTransaction;
@@ -213,6 +221,29 @@ pub fn handle_manifest<O: std::io::Write>(
     trace: bool,
     print_receipt: bool,
     out: &mut O,
+) -> Result<Option<TransactionReceipt>, String> {
+    handle_manifest_with_debugger(
+        manifest,
+        signing_keys,
+        network,
+        write_manifest,
+        trace,
+        None,
+        print_receipt,
+        out,
+    )
+}
+
+/// Same as [`handle_manifest`], but pauses the execution for the given debugger, if any.
+pub fn handle_manifest_with_debugger<O: std::io::Write>(
+    manifest: TransactionManifestV1,
+    signing_keys: &Option<String>,
+    network: &Option<String>,
+    write_manifest: &Option<PathBuf>,
+    trace: bool,
+    debugger: Option<DebuggerConfig>,
+    print_receipt: bool,
+    out: &mut O,
 ) -> Result<Option<TransactionReceipt>, String> {
     let network = match network {
         Some(n) => NetworkDefinition::from_str(&n).map_err(Error::ParseNetworkError)?,
@@ -253,7 +284,9 @@ pub fn handle_manifest<O: std::io::Write>(
             let receipt = execute_and_commit_transaction(
                 &mut db,
                 vm_init,
-                &ExecutionConfig::for_test_transaction().with_kernel_trace(trace),
+                &ExecutionConfig::for_test_transaction()
+                    .with_kernel_trace(trace)
+                    .with_debugger(debugger),
                 &transaction
                     .prepare()
                     .map_err(Error::TransactionPrepareError)?
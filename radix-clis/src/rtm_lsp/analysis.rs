@@ -0,0 +1,944 @@
+use super::docs::*;
+use radix_common::prelude::*;
+use radix_engine::system::system_db_reader::SystemDatabaseReader;
+use radix_engine::utils::*;
+use radix_engine_interface::api::ModuleId;
+use radix_substate_store_interface::interface::SubstateDatabase;
+use radix_transactions::manifest::compiler::{compile_error_title_and_label, compile_with_spans};
+use radix_transactions::manifest::lexer::tokenize;
+use radix_transactions::manifest::token::{Position, Span, Token, TokenWithSpan};
+use radix_transactions::manifest::{format_manifest, MockBlobProvider};
+use std::ops::Range;
+
+/// The kinds of the values which are referred to by name within a manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Bucket,
+    Proof,
+    AddressReservation,
+    NamedAddress,
+}
+
+impl NameKind {
+    fn from_ident(ident: &str) -> Option<Self> {
+        match ident {
+            "Bucket" => Some(Self::Bucket),
+            "Proof" => Some(Self::Proof),
+            "AddressReservation" => Some(Self::AddressReservation),
+            "NamedAddress" => Some(Self::NamedAddress),
+            _ => None,
+        }
+    }
+}
+
+/// A named value introduced by an instruction, e.g. the bucket of a `TAKE_FROM_WORKTOP`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    pub kind: NameKind,
+    pub name: String,
+    /// The span of the string literal holding the name
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Instruction,
+    Value,
+    ValueKind,
+    NamedValue,
+    Address,
+    Blueprint,
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: Option<String>,
+    /// Markdown documentation of the item
+    pub documentation: Option<String>,
+    /// The text to insert in place of the label, as a snippet if it contains placeholders
+    pub insert_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticTokenType {
+    Keyword,
+    Type,
+    Class,
+    Function,
+    Variable,
+    String,
+    Number,
+    Operator,
+    Comment,
+}
+
+impl SemanticTokenType {
+    pub const ALL: [SemanticTokenType; 9] = [
+        Self::Keyword,
+        Self::Type,
+        Self::Class,
+        Self::Function,
+        Self::Variable,
+        Self::String,
+        Self::Number,
+        Self::Operator,
+        Self::Comment,
+    ];
+
+    /// The name of the token type, as standardized by the LSP specification.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Type => "type",
+            Self::Class => "class",
+            Self::Function => "function",
+            Self::Variable => "variable",
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Operator => "operator",
+            Self::Comment => "comment",
+        }
+    }
+}
+
+/// A manifest being edited.
+///
+/// The text is split into statements at the semicolons, and each statement is tokenized on its
+/// own, so that a lexer error in one instruction doesn't prevent the analysis of the others.
+/// All offsets are in chars, like the [`Position`]s of the manifest toolchain.
+pub struct Document {
+    text: String,
+    chars: Vec<char>,
+    line_starts: Vec<usize>,
+    tokens: Vec<TokenWithSpan>,
+    /// The ranges of the tokens of each statement, excluding its semicolon
+    statements: Vec<Range<usize>>,
+    comments: Vec<Span>,
+}
+
+impl Document {
+    pub fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            chars
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c == '\n')
+                .map(|(index, _)| index + 1),
+        );
+
+        let scan = scan(&chars);
+        let mut segments = scan.segments;
+        if scan.tail_start.full_index < chars.len() {
+            segments.push((scan.tail_start, chars.len()));
+        }
+
+        let mut tokens = Vec::new();
+        let mut statements = Vec::new();
+        for (start, end) in segments {
+            let segment: String = chars[start.full_index..end].iter().collect();
+            let Ok(segment_tokens) = tokenize(&segment) else {
+                continue;
+            };
+            let first = tokens.len();
+            tokens.extend(segment_tokens.into_iter().map(|token| TokenWithSpan {
+                token: token.token,
+                span: Span {
+                    start: shift(token.span.start, start),
+                    end: shift(token.span.end, start),
+                },
+            }));
+            let mut last = tokens.len();
+            if last > first && tokens[last - 1].token == Token::Semicolon {
+                last -= 1;
+            }
+            if last > first {
+                statements.push(first..last);
+            }
+        }
+
+        Self {
+            text: text.to_string(),
+            chars,
+            line_starts,
+            tokens,
+            statements,
+            comments: scan.comments,
+        }
+    }
+
+    /// Converts an LSP position, in lines and UTF-16 code units, into a char offset.
+    pub fn offset_at(&self, line: usize, character: usize) -> usize {
+        let Some(&line_start) = self.line_starts.get(line) else {
+            return self.chars.len();
+        };
+        let mut offset = line_start;
+        let mut code_units = 0;
+        while offset < self.chars.len() && self.chars[offset] != '\n' && code_units < character {
+            code_units += self.chars[offset].len_utf16();
+            offset += 1;
+        }
+        offset
+    }
+
+    /// Converts a position into an LSP position, in lines and UTF-16 code units.
+    pub fn lsp_position(&self, position: Position) -> (usize, usize) {
+        let line = position.line_idx.min(self.line_starts.len() - 1);
+        let line_start = self.line_starts[line];
+        let end = position.full_index.clamp(line_start, self.chars.len());
+        let character = self.chars[line_start..end]
+            .iter()
+            .map(|c| c.len_utf16())
+            .sum();
+        (line, character)
+    }
+
+    /// Compiles the manifest and validates the arguments of its invocations, against the schemas
+    /// in the ledger if one is given, or else against those of the native blueprints.
+    pub fn diagnostics(
+        &self,
+        network: &NetworkDefinition,
+        ledger: Option<&dyn SubstateDatabase>,
+    ) -> Vec<Diagnostic> {
+        let (manifest, instruction_spans) =
+            match compile_with_spans(&self.text, network, MockBlobProvider::new()) {
+                Ok(compiled) => compiled,
+                Err(err) => {
+                    let span = err.span();
+                    let (title, _) = compile_error_title_and_label(err);
+                    return vec![Diagnostic {
+                        span,
+                        message: title,
+                    }];
+                }
+            };
+
+        let validation_result = match ledger {
+            Some(ledger) => validate_call_arguments_to_blueprints(&manifest.instructions, ledger),
+            None => validate_call_arguments_to_native_components(&manifest.instructions),
+        };
+        match validation_result {
+            Ok(()) => vec![],
+            Err(err) => {
+                let (title, _) = instruction_schema_validation_error_title_and_label(&err.cause);
+                instruction_spans
+                    .get(err.instruction_index)
+                    .map(|span| Diagnostic {
+                        span: *span,
+                        message: title,
+                    })
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the completions at the given offset.
+    pub fn completions(
+        &self,
+        offset: usize,
+        network: &NetworkDefinition,
+        ledger: Option<&dyn SubstateDatabase>,
+    ) -> Vec<Completion> {
+        let scan = scan(&self.chars[..offset.min(self.chars.len())]);
+        match scan.state {
+            ScanState::Comment => vec![],
+            ScanState::String { .. } => {
+                let code: String = self.chars[scan.tail_start.full_index..scan.string_start]
+                    .iter()
+                    .collect();
+                match tokenize(&code) {
+                    Ok(tokens) => {
+                        let tokens: Vec<Token> = tokens.into_iter().map(|t| t.token).collect();
+                        self.string_completions(&tokens, scan.tail_start, network, ledger)
+                    }
+                    Err(_) => vec![],
+                }
+            }
+            ScanState::Code => {
+                let code: String = self.chars[scan.tail_start.full_index..offset]
+                    .iter()
+                    .collect();
+                let Ok(mut tokens) = tokenize(&code) else {
+                    return vec![];
+                };
+                // The identifier being typed is the one to complete.
+                if let Some(last) = tokens.last() {
+                    if matches!(last.token, Token::Ident(_))
+                        && last.span.end.full_index == code.chars().count()
+                    {
+                        tokens.pop();
+                    }
+                }
+
+                if tokens.is_empty() {
+                    return INSTRUCTION_DOCS
+                        .iter()
+                        .map(|doc| Completion {
+                            label: doc.name.to_string(),
+                            kind: CompletionKind::Instruction,
+                            detail: None,
+                            documentation: Some(doc.markdown()),
+                            insert_text: Some(doc.snippet()),
+                        })
+                        .collect();
+                }
+
+                let open_angle_brackets = tokens
+                    .iter()
+                    .map(|t| match t.token {
+                        Token::LessThan => 1,
+                        Token::GreaterThan => -1,
+                        _ => 0,
+                    })
+                    .sum::<i32>();
+                let (idents, kind) = if open_angle_brackets > 0 {
+                    (VALUE_KIND_IDENTS, CompletionKind::ValueKind)
+                } else {
+                    (VALUE_IDENTS, CompletionKind::Value)
+                };
+                idents
+                    .iter()
+                    .map(|ident| Completion {
+                        label: ident.to_string(),
+                        kind,
+                        detail: None,
+                        documentation: None,
+                        insert_text: None,
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the completions within a string literal, given the tokens of the statement before
+    /// the literal.
+    fn string_completions(
+        &self,
+        tokens: &[Token],
+        statement_start: Position,
+        network: &NetworkDefinition,
+        ledger: Option<&dyn SubstateDatabase>,
+    ) -> Vec<Completion> {
+        let encoder = AddressBech32Encoder::new(network);
+
+        if let [.., Token::Ident(ident), Token::OpenParenthesis] = tokens {
+            if let Some(kind) = NameKind::from_ident(ident) {
+                let mut names: Vec<String> = self
+                    .declarations()
+                    .into_iter()
+                    .filter(|declaration| {
+                        declaration.kind == kind
+                            && declaration.span.start.full_index < statement_start.full_index
+                    })
+                    .map(|declaration| declaration.name)
+                    .collect();
+                names.dedup();
+                return names
+                    .into_iter()
+                    .map(|name| Completion {
+                        label: name,
+                        kind: CompletionKind::NamedValue,
+                        detail: Some(ident.clone()),
+                        documentation: None,
+                        insert_text: None,
+                    })
+                    .collect();
+            }
+            if ident == "Address" {
+                return well_known_addresses()
+                    .into_iter()
+                    .filter_map(|well_known| {
+                        let address = encoder
+                            .encode(well_known.address.as_node_id().as_bytes())
+                            .ok()?;
+                        Some(Completion {
+                            label: well_known.name.to_string(),
+                            kind: CompletionKind::Address,
+                            detail: Some(address.clone()),
+                            documentation: Some(well_known.description.to_string()),
+                            insert_text: Some(address),
+                        })
+                    })
+                    .collect();
+            }
+        }
+
+        let Some(ledger) = ledger else {
+            return vec![];
+        };
+        let reader = SystemDatabaseReader::new(ledger);
+        let decoder = AddressBech32Decoder::new(network);
+        match tokens {
+            [Token::Ident(instruction), Token::Ident(address_ident), Token::OpenParenthesis, Token::StringLiteral(address), Token::CloseParenthesis]
+                if address_ident == "Address" =>
+            {
+                if instruction == "CALL_FUNCTION" {
+                    let Some(package_address) = PackageAddress::try_from_bech32(&decoder, address)
+                    else {
+                        return vec![];
+                    };
+                    return reader
+                        .get_package_definition(package_address)
+                        .into_keys()
+                        .map(|key| Completion {
+                            label: key.blueprint,
+                            kind: CompletionKind::Blueprint,
+                            detail: None,
+                            documentation: None,
+                            insert_text: None,
+                        })
+                        .collect();
+                }
+
+                let Some(module_id) = method_module(instruction) else {
+                    return vec![];
+                };
+                let Some((_, node_id)) = decode_address(&decoder, address) else {
+                    return vec![];
+                };
+                let Ok(blueprint_id) = reader.get_blueprint_id(&node_id, module_id) else {
+                    return vec![];
+                };
+                function_completions(&reader, &blueprint_id, true)
+            }
+            [Token::Ident(instruction), Token::Ident(address_ident), Token::OpenParenthesis, Token::StringLiteral(address), Token::CloseParenthesis, Token::StringLiteral(blueprint_name)]
+                if instruction == "CALL_FUNCTION" && address_ident == "Address" =>
+            {
+                let Some(package_address) = PackageAddress::try_from_bech32(&decoder, address)
+                else {
+                    return vec![];
+                };
+                function_completions(
+                    &reader,
+                    &BlueprintId::new(&package_address, blueprint_name),
+                    false,
+                )
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Returns the hover documentation of the token at the given offset, with its span.
+    pub fn hover(
+        &self,
+        offset: usize,
+        network: &NetworkDefinition,
+        ledger: Option<&dyn SubstateDatabase>,
+    ) -> Option<(String, Span)> {
+        let index = self.token_at(offset)?;
+        let token = &self.tokens[index];
+        match &token.token {
+            Token::Ident(ident) if self.is_statement_start(index) => {
+                instruction_doc(ident).map(|doc| (doc.markdown(), token.span))
+            }
+            Token::StringLiteral(address) if self.is_address_string(index) => {
+                address_markdown(address, network, ledger).map(|markdown| (markdown, token.span))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the span of the declaration of the named value at the given offset.
+    ///
+    /// As names can be reused, e.g. after a bucket has been consumed, this is the latest
+    /// declaration before the reference, if any, or else the first one after it.
+    pub fn definition(&self, offset: usize) -> Option<Span> {
+        let index = self.token_at(offset)?;
+        let (kind, name) = self.named_value(index)?;
+        let reference_start = self.tokens[index].span.start.full_index;
+        let declarations: Vec<Declaration> = self
+            .declarations()
+            .into_iter()
+            .filter(|declaration| declaration.kind == kind && declaration.name == name)
+            .collect();
+        declarations
+            .iter()
+            .rev()
+            .find(|declaration| declaration.span.start.full_index <= reference_start)
+            .or(declarations.first())
+            .map(|declaration| declaration.span)
+    }
+
+    /// Returns the text formatted in the canonical style, with the span of the text it replaces,
+    /// unless the manifest doesn't parse.
+    pub fn formatted(&self) -> Option<(String, Span)> {
+        let formatted = format_manifest(&self.text).ok()?;
+        let line_idx = self.line_starts.len() - 1;
+        let end = Position {
+            full_index: self.chars.len(),
+            line_idx,
+            line_char_index: self.chars.len() - self.line_starts[line_idx],
+        };
+        Some((
+            formatted,
+            Span {
+                start: Position {
+                    full_index: 0,
+                    line_idx: 0,
+                    line_char_index: 0,
+                },
+                end,
+            },
+        ))
+    }
+
+    /// Returns the spans of the tokens and comments, with their semantic types, in order.
+    pub fn semantic_tokens(&self) -> Vec<(Span, SemanticTokenType)> {
+        let mut semantic_tokens: Vec<(Span, SemanticTokenType)> = self
+            .comments
+            .iter()
+            .map(|span| (*span, SemanticTokenType::Comment))
+            .collect();
+        let string_types = self.string_semantic_types();
+
+        for (index, token) in self.tokens.iter().enumerate() {
+            let token_type = match &token.token {
+                Token::Ident(_) if self.is_statement_start(index) => SemanticTokenType::Keyword,
+                Token::Ident(_) => SemanticTokenType::Type,
+                Token::Placeholder(_) => SemanticTokenType::Variable,
+                Token::StringLiteral(_) => string_types
+                    .get(&index)
+                    .copied()
+                    .unwrap_or(SemanticTokenType::String),
+                Token::BoolLiteral(_) => SemanticTokenType::Keyword,
+                Token::I8Literal(_)
+                | Token::I16Literal(_)
+                | Token::I32Literal(_)
+                | Token::I64Literal(_)
+                | Token::I128Literal(_)
+                | Token::U8Literal(_)
+                | Token::U16Literal(_)
+                | Token::U32Literal(_)
+                | Token::U64Literal(_)
+                | Token::U128Literal(_) => SemanticTokenType::Number,
+                Token::FatArrow => SemanticTokenType::Operator,
+                Token::OpenParenthesis
+                | Token::CloseParenthesis
+                | Token::LessThan
+                | Token::GreaterThan
+                | Token::Comma
+                | Token::Semicolon
+                | Token::Colon => continue,
+            };
+            semantic_tokens.push((token.span, token_type));
+        }
+
+        semantic_tokens.sort_by_key(|(span, _)| span.start.full_index);
+        semantic_tokens
+    }
+
+    /// Returns the values named by the instructions of the manifest, in order.
+    pub fn declarations(&self) -> Vec<Declaration> {
+        let mut declarations = Vec::new();
+        for statement in &self.statements {
+            let Token::Ident(instruction) = &self.tokens[statement.start].token else {
+                continue;
+            };
+            let declared_arguments = match instruction.as_str() {
+                "ALLOCATE_GLOBAL_ADDRESS" => 2,
+                "TAKE_FROM_WORKTOP"
+                | "TAKE_NON_FUNGIBLES_FROM_WORKTOP"
+                | "TAKE_ALL_FROM_WORKTOP"
+                | "POP_FROM_AUTH_ZONE"
+                | "CREATE_PROOF_FROM_AUTH_ZONE_OF_AMOUNT"
+                | "CREATE_PROOF_FROM_AUTH_ZONE_OF_NON_FUNGIBLES"
+                | "CREATE_PROOF_FROM_AUTH_ZONE_OF_ALL"
+                | "CREATE_PROOF_FROM_BUCKET_OF_AMOUNT"
+                | "CREATE_PROOF_FROM_BUCKET_OF_NON_FUNGIBLES"
+                | "CREATE_PROOF_FROM_BUCKET_OF_ALL"
+                | "CLONE_PROOF" => 1,
+                _ => continue,
+            };
+            let arguments = self.arguments(statement);
+            let first_declared = arguments.len().saturating_sub(declared_arguments);
+            for argument in &arguments[first_declared..] {
+                if argument.len() != 4 {
+                    continue;
+                }
+                let string_index = argument.start + 2;
+                if let Some((kind, name)) = self.named_value(string_index) {
+                    declarations.push(Declaration {
+                        kind,
+                        name: name.to_string(),
+                        span: self.tokens[string_index].span,
+                    });
+                }
+            }
+        }
+        declarations
+    }
+
+    /// Splits the arguments of a statement into the ranges of their tokens.
+    fn arguments(&self, statement: &Range<usize>) -> Vec<Range<usize>> {
+        let mut arguments = Vec::new();
+        let mut index = statement.start + 1;
+        while index < statement.end {
+            let start = index;
+            index += 1;
+            if matches!(self.tokens[start].token, Token::Ident(_)) {
+                if index < statement.end && self.tokens[index].token == Token::LessThan {
+                    index = self.skip_delimited(
+                        index,
+                        statement.end,
+                        Token::LessThan,
+                        Token::GreaterThan,
+                    );
+                }
+                if index < statement.end && self.tokens[index].token == Token::OpenParenthesis {
+                    index = self.skip_delimited(
+                        index,
+                        statement.end,
+                        Token::OpenParenthesis,
+                        Token::CloseParenthesis,
+                    );
+                }
+            }
+            arguments.push(start..index);
+        }
+        arguments
+    }
+
+    fn skip_delimited(&self, mut index: usize, end: usize, open: Token, close: Token) -> usize {
+        let mut depth = 0;
+        while index < end {
+            if self.tokens[index].token == open {
+                depth += 1;
+            } else if self.tokens[index].token == close {
+                depth -= 1;
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            index += 1;
+        }
+        end
+    }
+
+    /// Returns the semantic types of the string literals naming values, blueprints or functions.
+    fn string_semantic_types(&self) -> IndexMap<usize, SemanticTokenType> {
+        let mut types = index_map_new();
+        for statement in &self.statements {
+            let Token::Ident(instruction) = &self.tokens[statement.start].token else {
+                continue;
+            };
+            let arguments = self.arguments(statement);
+            let mut name_argument = |position: usize, token_type: SemanticTokenType| {
+                if let Some(argument) = arguments.get(position) {
+                    if argument.len() == 1
+                        && matches!(self.tokens[argument.start].token, Token::StringLiteral(_))
+                    {
+                        types.insert(argument.start, token_type);
+                    }
+                }
+            };
+            if instruction == "CALL_FUNCTION" {
+                name_argument(1, SemanticTokenType::Class);
+                name_argument(2, SemanticTokenType::Function);
+            } else if method_module(instruction).is_some() {
+                name_argument(1, SemanticTokenType::Function);
+            }
+        }
+        for index in 0..self.tokens.len() {
+            if self.named_value(index).is_some() {
+                types.insert(index, SemanticTokenType::Variable);
+            }
+        }
+        types
+    }
+
+    fn token_at(&self, offset: usize) -> Option<usize> {
+        self.tokens.iter().position(|token| {
+            token.span.start.full_index <= offset && offset <= token.span.end.full_index
+        })
+    }
+
+    fn is_statement_start(&self, index: usize) -> bool {
+        self.statements
+            .iter()
+            .any(|statement| statement.start == index)
+    }
+
+    /// Returns the kind and the name of the value named by the string literal at the given index,
+    /// as in `Bucket("name")`.
+    fn named_value(&self, index: usize) -> Option<(NameKind, &str)> {
+        let Token::StringLiteral(name) = &self.tokens.get(index)?.token else {
+            return None;
+        };
+        if index < 2 || self.tokens[index - 1].token != Token::OpenParenthesis {
+            return None;
+        }
+        let Token::Ident(ident) = &self.tokens[index - 2].token else {
+            return None;
+        };
+        NameKind::from_ident(ident).map(|kind| (kind, name.as_str()))
+    }
+
+    fn is_address_string(&self, index: usize) -> bool {
+        index >= 2
+            && self.tokens[index - 1].token == Token::OpenParenthesis
+            && self.tokens[index - 2].token == Token::Ident("Address".to_string())
+    }
+}
+
+/// Returns the module targeted by an instruction calling methods.
+fn method_module(instruction: &str) -> Option<ModuleId> {
+    match instruction {
+        "CALL_METHOD" | "CALL_DIRECT_VAULT_METHOD" => Some(ModuleId::Main),
+        "CALL_ROYALTY_METHOD" => Some(ModuleId::Royalty),
+        "CALL_METADATA_METHOD" => Some(ModuleId::Metadata),
+        "CALL_ROLE_ASSIGNMENT_METHOD" => Some(ModuleId::RoleAssignment),
+        _ => None,
+    }
+}
+
+fn function_completions<S: SubstateDatabase + ?Sized>(
+    reader: &SystemDatabaseReader<S>,
+    blueprint_id: &BlueprintId,
+    methods: bool,
+) -> Vec<Completion> {
+    let Ok(definition) = reader.get_blueprint_definition(blueprint_id) else {
+        return vec![];
+    };
+    definition
+        .interface
+        .functions
+        .iter()
+        .filter(|(_, schema)| schema.receiver.is_some() == methods)
+        .map(|(name, _)| Completion {
+            label: name.clone(),
+            kind: CompletionKind::Function,
+            detail: Some(blueprint_id.blueprint_name.clone()),
+            documentation: None,
+            insert_text: None,
+        })
+        .collect()
+}
+
+fn decode_address(decoder: &AddressBech32Decoder, address: &str) -> Option<(EntityType, NodeId)> {
+    let (entity_type, bytes) = decoder.validate_and_decode(address).ok()?;
+    Some((entity_type, NodeId(bytes.try_into().ok()?)))
+}
+
+fn address_markdown(
+    address: &str,
+    network: &NetworkDefinition,
+    ledger: Option<&dyn SubstateDatabase>,
+) -> Option<String> {
+    let (entity_type, node_id) = decode_address(&AddressBech32Decoder::new(network), address)?;
+    let mut lines = Vec::new();
+    let well_known = well_known_addresses()
+        .into_iter()
+        .find(|well_known| well_known.address.as_node_id() == &node_id);
+    if let Some(well_known) = &well_known {
+        lines.push(format!("**{}**", well_known.name));
+    }
+    lines.push(format!("Entity type: `{:?}`", entity_type));
+    if let Some(ledger) = ledger {
+        if let Ok(object_info) = SystemDatabaseReader::new(ledger).get_object_info(node_id) {
+            let blueprint_id = object_info.blueprint_info.blueprint_id;
+            let package_address = AddressBech32Encoder::new(network)
+                .encode(blueprint_id.package_address.as_node_id().as_bytes())
+                .ok()?;
+            lines.push(format!(
+                "Blueprint: `{}` of package `{}`",
+                blueprint_id.blueprint_name, package_address
+            ));
+        }
+    }
+    if let Some(well_known) = &well_known {
+        lines.push(well_known.description.to_string());
+    }
+    Some(lines.join("\n\n"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Code,
+    String { escaped: bool },
+    Comment,
+}
+
+struct Scan {
+    /// The start of each complete statement, and the char offset after its semicolon
+    segments: Vec<(Position, usize)>,
+    /// The start of the text after the last semicolon
+    tail_start: Position,
+    comments: Vec<Span>,
+    state: ScanState,
+    /// The char offset of the opening quote of the last string literal
+    string_start: usize,
+}
+
+/// Finds the semicolons and the comments of a manifest, ignoring those within string literals.
+fn scan(chars: &[char]) -> Scan {
+    let mut position = Position {
+        full_index: 0,
+        line_idx: 0,
+        line_char_index: 0,
+    };
+    let mut segments = Vec::new();
+    let mut tail_start = position;
+    let mut comments = Vec::new();
+    let mut comment_start = position;
+    let mut string_start = 0;
+    let mut state = ScanState::Code;
+
+    for &c in chars {
+        let next = position.advance(c);
+        state = match state {
+            ScanState::Code => match c {
+                '"' => {
+                    string_start = position.full_index;
+                    ScanState::String { escaped: false }
+                }
+                '#' => {
+                    comment_start = position;
+                    ScanState::Comment
+                }
+                ';' => {
+                    segments.push((tail_start, next.full_index));
+                    tail_start = next;
+                    ScanState::Code
+                }
+                _ => ScanState::Code,
+            },
+            ScanState::String { escaped: true } => ScanState::String { escaped: false },
+            ScanState::String { escaped: false } => match c {
+                '\\' => ScanState::String { escaped: true },
+                '"' => ScanState::Code,
+                _ => ScanState::String { escaped: false },
+            },
+            ScanState::Comment => {
+                if c == '\n' {
+                    comments.push(Span {
+                        start: comment_start,
+                        end: position,
+                    });
+                    ScanState::Code
+                } else {
+                    ScanState::Comment
+                }
+            }
+        };
+        position = next;
+    }
+    if state == ScanState::Comment {
+        comments.push(Span {
+            start: comment_start,
+            end: position,
+        });
+    }
+
+    Scan {
+        segments,
+        tail_start,
+        comments,
+        state,
+        string_start,
+    }
+}
+
+/// Shifts a position within a statement by the position of the start of the statement.
+fn shift(position: Position, base: Position) -> Position {
+    Position {
+        full_index: base.full_index + position.full_index,
+        line_idx: base.line_idx + position.line_idx,
+        line_char_index: if position.line_idx == 0 {
+            base.line_char_index + position.line_char_index
+        } else {
+            position.line_char_index
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"# Take some XRD; then deposit it
+TAKE_ALL_FROM_WORKTOP
+    Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3")
+    Bucket("xrd; bucket")
+;
+CALL_METHOD
+    Address("account_sim1cyvgx33089ukm2pl97pv4max0x40ruvfy4lt60yvya744cve475w0q")
+    "deposit"
+    Bucket("xrd; bucket")
+;
+"#;
+
+    fn offset_of(text: &str, pattern: &str, nth: usize) -> usize {
+        let byte_index = text.match_indices(pattern).nth(nth).unwrap().0;
+        text[..byte_index].chars().count()
+    }
+
+    #[test]
+    fn test_statements_are_split_outside_of_strings_and_comments() {
+        let document = Document::new(MANIFEST);
+
+        assert_eq!(document.statements.len(), 2);
+        assert_eq!(document.comments.len(), 1);
+        assert_eq!(document.comments[0].start.line_idx, 0);
+        assert_eq!(
+            document.declarations(),
+            vec![Declaration {
+                kind: NameKind::Bucket,
+                name: "xrd; bucket".to_string(),
+                span: document.tokens[7].span,
+            }]
+        );
+        assert_eq!(document.tokens[7].span.start.line_idx, 3);
+        assert_eq!(document.tokens[7].span.start.line_char_index, 11);
+    }
+
+    #[test]
+    fn test_definition_of_named_values() {
+        let document = Document::new(MANIFEST);
+        let reference = offset_of(MANIFEST, "xrd; bucket", 1);
+
+        let definition = document.definition(reference).unwrap();
+
+        assert_eq!(
+            definition.start.full_index,
+            offset_of(MANIFEST, "\"xrd;", 0)
+        );
+        assert_eq!(document.definition(offset_of(MANIFEST, "deposit", 0)), None);
+    }
+
+    #[test]
+    fn test_completions() {
+        let document = Document::new(MANIFEST);
+        let network = NetworkDefinition::simulator();
+
+        let instructions = document.completions(MANIFEST.chars().count(), &network, None);
+        let names = document.completions(offset_of(MANIFEST, "xrd; bucket", 1), &network, None);
+        let values = document.completions(offset_of(MANIFEST, "Bucket", 1), &network, None);
+
+        assert_eq!(instructions.len(), INSTRUCTION_DOCS.len());
+        assert_eq!(
+            names.iter().map(|c| c.label.as_str()).collect::<Vec<_>>(),
+            vec!["xrd; bucket"]
+        );
+        assert!(values.iter().all(|c| c.kind == CompletionKind::Value));
+    }
+
+    #[test]
+    fn test_lsp_positions_are_in_utf16_code_units() {
+        let document = Document::new("# 🚀\nDROP_ALL_PROOFS;");
+
+        assert_eq!(document.offset_at(0, 4), 3);
+        assert_eq!(document.offset_at(1, 4), 8);
+        assert_eq!(document.lsp_position(document.comments[0].end), (0, 4));
+    }
+}
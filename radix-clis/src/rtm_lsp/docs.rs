@@ -0,0 +1,280 @@
+use radix_common::prelude::*;
+
+/// The documentation of a manifest instruction.
+pub struct InstructionDoc {
+    pub name: &'static str,
+    pub arguments: &'static [&'static str],
+    pub description: &'static str,
+}
+
+impl InstructionDoc {
+    pub fn signature(&self) -> String {
+        if self.arguments.is_empty() {
+            format!("{};", self.name)
+        } else {
+            format!("{}\n    {}\n;", self.name, self.arguments.join("\n    "))
+        }
+    }
+
+    /// The text inserted on completion, with a placeholder for each of the arguments.
+    pub fn snippet(&self) -> String {
+        let mut snippet = self.name.to_string();
+        for (index, argument) in self.arguments.iter().enumerate() {
+            snippet.push_str(&format!("\n    ${{{}:{}}}", index + 1, argument));
+        }
+        snippet.push_str("\n;");
+        snippet
+    }
+
+    pub fn markdown(&self) -> String {
+        format!("```\n{}\n```\n\n{}", self.signature(), self.description)
+    }
+}
+
+macro_rules! instruction_docs {
+    ($($name:literal [$($argument:literal),*] => $description:literal,)*) => {
+        pub const INSTRUCTION_DOCS: &[InstructionDoc] = &[
+            $(InstructionDoc {
+                name: $name,
+                arguments: &[$($argument),*],
+                description: $description,
+            },)*
+        ];
+    };
+}
+
+instruction_docs! {
+    "TAKE_FROM_WORKTOP" ["resource_address: Address", "amount: Decimal", "new_bucket: Bucket"] =>
+        "Takes the given amount of a resource from the worktop into a new bucket.",
+    "TAKE_NON_FUNGIBLES_FROM_WORKTOP" ["resource_address: Address", "ids: Array<NonFungibleLocalId>", "new_bucket: Bucket"] =>
+        "Takes the given non-fungibles from the worktop into a new bucket.",
+    "TAKE_ALL_FROM_WORKTOP" ["resource_address: Address", "new_bucket: Bucket"] =>
+        "Takes all of a resource from the worktop into a new bucket.",
+    "RETURN_TO_WORKTOP" ["bucket: Bucket"] =>
+        "Returns a bucket to the worktop.",
+    "ASSERT_WORKTOP_CONTAINS" ["resource_address: Address", "amount: Decimal"] =>
+        "Asserts that the worktop contains at least the given amount of a resource.",
+    "ASSERT_WORKTOP_CONTAINS_NON_FUNGIBLES" ["resource_address: Address", "ids: Array<NonFungibleLocalId>"] =>
+        "Asserts that the worktop contains the given non-fungibles.",
+    "ASSERT_WORKTOP_CONTAINS_ANY" ["resource_address: Address"] =>
+        "Asserts that the worktop contains a non-zero amount of a resource.",
+    "POP_FROM_AUTH_ZONE" ["new_proof: Proof"] =>
+        "Pops the most recently pushed proof from the auth zone.",
+    "PUSH_TO_AUTH_ZONE" ["proof: Proof"] =>
+        "Pushes a proof to the auth zone.",
+    "CREATE_PROOF_FROM_AUTH_ZONE_OF_AMOUNT" ["resource_address: Address", "amount: Decimal", "new_proof: Proof"] =>
+        "Creates a proof of the given amount of a resource from the proofs in the auth zone.",
+    "CREATE_PROOF_FROM_AUTH_ZONE_OF_NON_FUNGIBLES" ["resource_address: Address", "ids: Array<NonFungibleLocalId>", "new_proof: Proof"] =>
+        "Creates a proof of the given non-fungibles from the proofs in the auth zone.",
+    "CREATE_PROOF_FROM_AUTH_ZONE_OF_ALL" ["resource_address: Address", "new_proof: Proof"] =>
+        "Creates a proof of all of a resource from the proofs in the auth zone.",
+    "DROP_AUTH_ZONE_PROOFS" [] =>
+        "Drops all the proofs in the auth zone.",
+    "DROP_AUTH_ZONE_REGULAR_PROOFS" [] =>
+        "Drops the regular proofs in the auth zone, keeping the signature proofs.",
+    "DROP_AUTH_ZONE_SIGNATURE_PROOFS" [] =>
+        "Drops the signature proofs in the auth zone.",
+    "CREATE_PROOF_FROM_BUCKET_OF_AMOUNT" ["bucket: Bucket", "amount: Decimal", "new_proof: Proof"] =>
+        "Creates a proof of the given amount of the resource in a bucket.",
+    "CREATE_PROOF_FROM_BUCKET_OF_NON_FUNGIBLES" ["bucket: Bucket", "ids: Array<NonFungibleLocalId>", "new_proof: Proof"] =>
+        "Creates a proof of the given non-fungibles in a bucket.",
+    "CREATE_PROOF_FROM_BUCKET_OF_ALL" ["bucket: Bucket", "new_proof: Proof"] =>
+        "Creates a proof of all the resource in a bucket.",
+    "BURN_RESOURCE" ["bucket: Bucket"] =>
+        "Burns the resource in a bucket.",
+    "CLONE_PROOF" ["proof: Proof", "new_proof: Proof"] =>
+        "Clones a proof.",
+    "DROP_PROOF" ["proof: Proof"] =>
+        "Drops a proof.",
+    "CALL_FUNCTION" ["package_address: Address", "blueprint_name: String", "function_name: String", "arguments..."] =>
+        "Calls a function of a blueprint.",
+    "CALL_METHOD" ["address: Address", "method_name: String", "arguments..."] =>
+        "Calls a method of a global component.",
+    "CALL_ROYALTY_METHOD" ["address: Address", "method_name: String", "arguments..."] =>
+        "Calls a method of the royalty module of a global component.",
+    "CALL_METADATA_METHOD" ["address: Address", "method_name: String", "arguments..."] =>
+        "Calls a method of the metadata module of a global entity.",
+    "CALL_ROLE_ASSIGNMENT_METHOD" ["address: Address", "method_name: String", "arguments..."] =>
+        "Calls a method of the role assignment module of a global entity.",
+    "CALL_DIRECT_VAULT_METHOD" ["vault_address: Address", "method_name: String", "arguments..."] =>
+        "Calls a method of a vault directly, as its resource's recaller or freezer.",
+    "DROP_NAMED_PROOFS" [] =>
+        "Drops all the named proofs.",
+    "DROP_ALL_PROOFS" [] =>
+        "Drops all the named proofs and all the proofs in the auth zone.",
+    "ALLOCATE_GLOBAL_ADDRESS" ["package_address: Address", "blueprint_name: String", "new_address_reservation: AddressReservation", "new_named_address: NamedAddress"] =>
+        "Allocates a global address for an object of a blueprint, to be used before the object is created.",
+    "RECALL_FROM_VAULT" ["vault_address: Address", "amount: Decimal"] =>
+        "Recalls the given amount of resource from a vault.",
+    "FREEZE_VAULT" ["vault_address: Address", "flags: Tuple(U32)"] =>
+        "Freezes the withdrawals, deposits and/or burns of a vault.",
+    "UNFREEZE_VAULT" ["vault_address: Address", "flags: Tuple(U32)"] =>
+        "Unfreezes the withdrawals, deposits and/or burns of a vault.",
+    "RECALL_NON_FUNGIBLES_FROM_VAULT" ["vault_address: Address", "ids: Array<NonFungibleLocalId>"] =>
+        "Recalls the given non-fungibles from a vault.",
+    "PUBLISH_PACKAGE" ["code: Blob", "definition: Tuple", "metadata: Map<String, Tuple>"] =>
+        "Publishes a package owned by nobody.",
+    "PUBLISH_PACKAGE_ADVANCED" ["owner_role: Enum", "code: Blob", "definition: Tuple", "metadata: Map<String, Tuple>", "address_reservation: Enum"] =>
+        "Publishes a package with an owner role and, optionally, a reserved address.",
+    "CREATE_FUNGIBLE_RESOURCE" ["owner_role: Enum", "track_total_supply: Bool", "divisibility: U8", "resource_roles: Tuple", "metadata: Tuple", "address_reservation: Enum"] =>
+        "Creates a fungible resource without an initial supply.",
+    "CREATE_FUNGIBLE_RESOURCE_WITH_INITIAL_SUPPLY" ["owner_role: Enum", "track_total_supply: Bool", "divisibility: U8", "initial_supply: Decimal", "resource_roles: Tuple", "metadata: Tuple", "address_reservation: Enum"] =>
+        "Creates a fungible resource, putting the initial supply on the worktop.",
+    "CREATE_NON_FUNGIBLE_RESOURCE" ["owner_role: Enum", "id_type: Enum", "track_total_supply: Bool", "non_fungible_schema: Enum", "resource_roles: Tuple", "metadata: Tuple", "address_reservation: Enum"] =>
+        "Creates a non-fungible resource without an initial supply.",
+    "CREATE_NON_FUNGIBLE_RESOURCE_WITH_INITIAL_SUPPLY" ["owner_role: Enum", "id_type: Enum", "track_total_supply: Bool", "non_fungible_schema: Enum", "entries: Map<NonFungibleLocalId, Tuple>", "resource_roles: Tuple", "metadata: Tuple", "address_reservation: Enum"] =>
+        "Creates a non-fungible resource, putting the initial supply on the worktop.",
+    "CREATE_ACCESS_CONTROLLER" ["controlled_asset: Bucket", "rule_set: Tuple", "timed_recovery_delay_in_minutes: Enum", "address_reservation: Enum"] =>
+        "Creates an access controller for the badge in a bucket.",
+    "CREATE_IDENTITY" [] =>
+        "Creates an identity owned by a badge, which is put on the worktop.",
+    "CREATE_IDENTITY_ADVANCED" ["owner_role: Enum"] =>
+        "Creates an identity with the given owner role.",
+    "CREATE_ACCOUNT" [] =>
+        "Creates an account owned by a badge, which is put on the worktop.",
+    "CREATE_ACCOUNT_ADVANCED" ["owner_role: Enum", "address_reservation: Enum"] =>
+        "Creates an account with the given owner role.",
+    "SET_METADATA" ["address: Address", "key: String", "value: Enum"] =>
+        "Sets a metadata entry of a global entity.",
+    "REMOVE_METADATA" ["address: Address", "key: String"] =>
+        "Removes a metadata entry of a global entity.",
+    "LOCK_METADATA" ["address: Address", "key: String"] =>
+        "Locks a metadata entry of a global entity, preventing further updates.",
+    "SET_COMPONENT_ROYALTY" ["address: Address", "method: String", "amount: Enum"] =>
+        "Sets the component royalty of a method.",
+    "LOCK_COMPONENT_ROYALTY" ["address: Address", "method: String"] =>
+        "Locks the component royalty of a method, preventing further updates.",
+    "CLAIM_COMPONENT_ROYALTIES" ["address: Address"] =>
+        "Claims the component royalties accumulated by a component, putting them on the worktop.",
+    "SET_OWNER_ROLE" ["address: Address", "rule: Enum"] =>
+        "Sets the owner role of a global entity.",
+    "LOCK_OWNER_ROLE" ["address: Address"] =>
+        "Locks the owner role of a global entity, preventing further updates.",
+    "SET_ROLE" ["address: Address", "module: Enum", "role_key: String", "rule: Enum"] =>
+        "Sets the rule of a role of a global entity.",
+    "CLAIM_PACKAGE_ROYALTIES" ["package_address: Address"] =>
+        "Claims the package royalties accumulated by a package, putting them on the worktop.",
+    "MINT_FUNGIBLE" ["resource_address: Address", "amount: Decimal"] =>
+        "Mints the given amount of a fungible resource, putting it on the worktop.",
+    "MINT_NON_FUNGIBLE" ["resource_address: Address", "entries: Map<NonFungibleLocalId, Tuple>"] =>
+        "Mints non-fungibles with the given ids and data, putting them on the worktop.",
+    "MINT_RUID_NON_FUNGIBLE" ["resource_address: Address", "entries: Array<Tuple>"] =>
+        "Mints non-fungibles with random ids and the given data, putting them on the worktop.",
+    "CREATE_VALIDATOR" ["key: Bytes", "fee_factor: Decimal", "xrd_payment: Bucket"] =>
+        "Creates a validator with the given public key, paying for it with a bucket of XRD.",
+}
+
+pub fn instruction_doc(name: &str) -> Option<&'static InstructionDoc> {
+    INSTRUCTION_DOCS.iter().find(|doc| doc.name == name)
+}
+
+/// The identifiers of the manifest values which can be used as arguments.
+pub const VALUE_IDENTS: &[&str] = &[
+    "Enum",
+    "Array",
+    "Tuple",
+    "Map",
+    "Some",
+    "None",
+    "Ok",
+    "Err",
+    "Bytes",
+    "NonFungibleGlobalId",
+    "Address",
+    "NamedAddress",
+    "Bucket",
+    "Proof",
+    "Expression",
+    "Blob",
+    "Decimal",
+    "PreciseDecimal",
+    "NonFungibleLocalId",
+    "AddressReservation",
+];
+
+/// The identifiers of the manifest value kinds, as used in the type arguments of `Array`, `Map`
+/// and `Enum`.
+pub const VALUE_KIND_IDENTS: &[&str] = &[
+    "Bool",
+    "I8",
+    "I16",
+    "I32",
+    "I64",
+    "I128",
+    "U8",
+    "U16",
+    "U32",
+    "U64",
+    "U128",
+    "String",
+    "Enum",
+    "Array",
+    "Tuple",
+    "Map",
+    "Bytes",
+    "NonFungibleGlobalId",
+    "Address",
+    "PackageAddress",
+    "ComponentAddress",
+    "ResourceAddress",
+    "NamedAddress",
+    "Bucket",
+    "Proof",
+    "Expression",
+    "Blob",
+    "Decimal",
+    "PreciseDecimal",
+    "NonFungibleLocalId",
+    "AddressReservation",
+];
+
+/// A well-known address, which is the same on every network.
+pub struct WellKnownAddress {
+    pub name: &'static str,
+    pub address: GlobalAddress,
+    pub description: &'static str,
+}
+
+pub fn well_known_addresses() -> Vec<WellKnownAddress> {
+    macro_rules! well_known {
+        ($($name:ident => $description:literal,)*) => {
+            vec![$(WellKnownAddress {
+                name: stringify!($name),
+                address: $name.into(),
+                description: $description,
+            },)*]
+        };
+    }
+
+    well_known! {
+        XRD => "The native token of the Radix ledger, used for paying fees and staking.",
+        SECP256K1_SIGNATURE_VIRTUAL_BADGE => "The badge of the virtual proofs of Secp256k1 signatures.",
+        ED25519_SIGNATURE_VIRTUAL_BADGE => "The badge of the virtual proofs of Ed25519 signatures.",
+        PACKAGE_OF_DIRECT_CALLER_VIRTUAL_BADGE => "The badge of the virtual proofs of the package of the immediate caller.",
+        GLOBAL_CALLER_VIRTUAL_BADGE => "The badge of the virtual proofs of the global ancestor of the caller.",
+        SYSTEM_TRANSACTION_BADGE => "The badge of the virtual proofs of system transactions.",
+        PACKAGE_OWNER_BADGE => "The badge of the owners of packages.",
+        VALIDATOR_OWNER_BADGE => "The badge of the owners of validators.",
+        ACCOUNT_OWNER_BADGE => "The badge of the owners of virtual accounts.",
+        IDENTITY_OWNER_BADGE => "The badge of the owners of virtual identities.",
+        PACKAGE_PACKAGE => "The native package of packages.",
+        RESOURCE_PACKAGE => "The native package of fungible and non-fungible resources, vaults, buckets and proofs.",
+        ACCOUNT_PACKAGE => "The native package of accounts.",
+        IDENTITY_PACKAGE => "The native package of identities.",
+        CONSENSUS_MANAGER_PACKAGE => "The native package of the consensus manager and validators.",
+        ACCESS_CONTROLLER_PACKAGE => "The native package of access controllers.",
+        POOL_PACKAGE => "The native package of the one, two and multi resource pools.",
+        TRANSACTION_PROCESSOR_PACKAGE => "The native package of the transaction processor.",
+        METADATA_MODULE_PACKAGE => "The native package of the metadata module.",
+        ROYALTY_MODULE_PACKAGE => "The native package of the royalty module.",
+        ROLE_ASSIGNMENT_MODULE_PACKAGE => "The native package of the role assignment module.",
+        TRANSACTION_TRACKER_PACKAGE => "The native package of the transaction tracker.",
+        LOCKER_PACKAGE => "The native package of account lockers.",
+        FAUCET_PACKAGE => "The package of the faucet.",
+        GENESIS_HELPER_PACKAGE => "The package of the genesis helper.",
+        CONSENSUS_MANAGER => "The consensus manager component.",
+        GENESIS_HELPER => "The genesis helper component.",
+        FAUCET => "The faucet component, which gives out free XRD on test networks.",
+        TRANSACTION_TRACKER => "The transaction tracker component.",
+    }
+}
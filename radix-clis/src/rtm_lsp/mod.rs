@@ -0,0 +1,63 @@
+mod analysis;
+mod docs;
+mod server;
+mod transport;
+
+use clap::Parser;
+use radix_common::network::{NetworkDefinition, ParseNetworkError};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Radix transaction manifest language server, communicating over stdin and stdout
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None, name = "rtm-lsp")]
+pub struct Args {
+    /// Network to Use [Simulator | Alphanet | Mainnet]
+    #[clap(short, long)]
+    network: Option<String>,
+
+    /// The directory of a substate database, e.g. that of resim, for blueprint-aware
+    /// diagnostics and completions
+    #[clap(short, long)]
+    ledger: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    IoError(std::io::Error),
+    ParseNetworkError(ParseNetworkError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl From<Error> for String {
+    fn from(err: Error) -> String {
+        err.to_string()
+    }
+}
+
+pub fn run() -> Result<(), String> {
+    let args = Args::parse();
+
+    let network = match args.network {
+        Some(n) => NetworkDefinition::from_str(&n).map_err(Error::ParseNetworkError)?,
+        None => NetworkDefinition::simulator(),
+    };
+
+    server::Server::new(
+        io::stdin().lock(),
+        io::stdout().lock(),
+        network,
+        args.ledger,
+    )
+    .serve()
+    .map_err(Error::IoError)?;
+
+    Ok(())
+}
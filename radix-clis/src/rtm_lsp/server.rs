@@ -0,0 +1,350 @@
+use super::analysis::*;
+use super::transport::*;
+use radix_common::network::NetworkDefinition;
+use radix_substate_store_impls::rocks_db::RocksdbSubstateStore;
+use radix_substate_store_interface::interface::SubstateDatabase;
+use radix_transactions::manifest::token::Span;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const MESSAGE_TYPE_ERROR: i64 = 1;
+const MESSAGE_TYPE_WARNING: i64 = 2;
+
+/// A language server for transaction manifests, speaking JSON-RPC over a reader and a writer.
+pub struct Server<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+    network: NetworkDefinition,
+    /// A substate database, for blueprint-aware diagnostics and completions
+    ledger: Option<RocksdbSubstateStore>,
+    /// The directory of the ledger's secondary instance, deleted when the server is dropped (after
+    /// the ledger, as the fields are dropped in order)
+    _ledger_secondary_dir: Option<TempDir>,
+    /// Why the given substate database couldn't be opened, to be logged to the client
+    ledger_error: Option<String>,
+    documents: HashMap<String, Document>,
+}
+
+impl<R: BufRead, W: Write> Server<R, W> {
+    pub fn new(
+        reader: R,
+        writer: W,
+        network: NetworkDefinition,
+        ledger_dir: Option<PathBuf>,
+    ) -> Self {
+        let (ledger, ledger_secondary_dir, ledger_error) =
+            match ledger_dir.map(open_ledger).transpose() {
+                Ok(Some((ledger, secondary_dir))) => (Some(ledger), Some(secondary_dir), None),
+                Ok(None) => (None, None, None),
+                Err(error) => (None, None, Some(error)),
+            };
+        Self {
+            reader,
+            writer,
+            network,
+            ledger,
+            _ledger_secondary_dir: ledger_secondary_dir,
+            ledger_error,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Serves requests until the client exits or closes the connection.
+    pub fn serve(mut self) -> io::Result<()> {
+        // Log messages may be sent even before the client's initialization request
+        if let Some(error) = self.ledger_error.take() {
+            self.log_message(MESSAGE_TYPE_ERROR, &error)?;
+        }
+
+        while let Some(message) = read_message(&mut self.reader)? {
+            let Some(method) = message.get("method").and_then(Value::as_str) else {
+                // The server doesn't send requests, so there are no responses to handle.
+                continue;
+            };
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+            self.catch_up_with_ledger()?;
+
+            match message.get("id") {
+                Some(id) => {
+                    let response = match self.handle_request(method, &params) {
+                        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        Err((code, error_message)) => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": code, "message": error_message },
+                        }),
+                    };
+                    write_message(&mut self.writer, &response)?;
+                }
+                None => {
+                    if method == "exit" {
+                        break;
+                    }
+                    self.handle_notification(method, &params)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "completionProvider": { "triggerCharacters": ["\"", "(", "<"] },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "documentFormattingProvider": true,
+                    "semanticTokensProvider": {
+                        "legend": {
+                            "tokenTypes": SemanticTokenType::ALL
+                                .iter()
+                                .map(|token_type| token_type.name())
+                                .collect::<Vec<_>>(),
+                            "tokenModifiers": [],
+                        },
+                        "full": true,
+                    },
+                },
+                "serverInfo": { "name": "rtm-lsp", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/completion" => {
+                let (document, offset) = self.document_and_offset(params)?;
+                let completions = document.completions(offset, &self.network, self.ledger());
+                Ok(Value::Array(
+                    completions.into_iter().map(completion_item).collect(),
+                ))
+            }
+            "textDocument/hover" => {
+                let (document, offset) = self.document_and_offset(params)?;
+                let hover = document.hover(offset, &self.network, self.ledger());
+                Ok(match hover {
+                    Some((markdown, span)) => json!({
+                        "contents": { "kind": "markdown", "value": markdown },
+                        "range": range(document, span),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/definition" => {
+                let (document, offset) = self.document_and_offset(params)?;
+                Ok(match document.definition(offset) {
+                    Some(span) => json!({
+                        "uri": params["textDocument"]["uri"],
+                        "range": range(document, span),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/formatting" => {
+                let document = self.document(params)?;
+                Ok(match document.formatted() {
+                    Some((text, span)) => {
+                        json!([{ "range": range(document, span), "newText": text }])
+                    }
+                    None => Value::Null,
+                })
+            }
+            "textDocument/semanticTokens/full" => {
+                let document = self.document(params)?;
+                Ok(json!({ "data": semantic_tokens_data(document) }))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method `{}`", method))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> io::Result<()> {
+        let Some(uri) = params["textDocument"]["uri"].as_str() else {
+            return Ok(());
+        };
+        let uri = uri.to_string();
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.clone(), Document::new(text));
+            }
+            "textDocument/didChange" => {
+                // The full text is sent on every change, as advertised on initialization.
+                let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                else {
+                    return Ok(());
+                };
+                self.documents.insert(uri.clone(), Document::new(text));
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+            }
+            _ => return Ok(()),
+        }
+        self.publish_diagnostics(&uri)
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str) -> io::Result<()> {
+        let diagnostics = match self.documents.get(uri) {
+            Some(document) => document
+                .diagnostics(&self.network, self.ledger())
+                .into_iter()
+                .map(|diagnostic| {
+                    json!({
+                        "range": range(document, diagnostic.span),
+                        "severity": 1,
+                        "source": "rtm",
+                        "message": diagnostic.message,
+                    })
+                })
+                .collect(),
+            None => vec![],
+        };
+        write_message(
+            &mut self.writer,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": uri, "diagnostics": diagnostics },
+            }),
+        )
+    }
+
+    fn document(&self, params: &Value) -> Result<&Document, (i64, String)> {
+        params["textDocument"]["uri"]
+            .as_str()
+            .and_then(|uri| self.documents.get(uri))
+            .ok_or_else(|| (INVALID_PARAMS, "unknown document".to_string()))
+    }
+
+    fn document_and_offset(&self, params: &Value) -> Result<(&Document, usize), (i64, String)> {
+        let document = self.document(params)?;
+        let (Some(line), Some(character)) = (
+            params["position"]["line"].as_u64(),
+            params["position"]["character"].as_u64(),
+        ) else {
+            return Err((INVALID_PARAMS, "invalid position".to_string()));
+        };
+        Ok((
+            document,
+            document.offset_at(line as usize, character as usize),
+        ))
+    }
+
+    fn ledger(&self) -> Option<&dyn SubstateDatabase> {
+        self.ledger.as_ref().map(|db| db as &dyn SubstateDatabase)
+    }
+
+    /// Picks up the writes made to the ledger since the last message, e.g. by `resim`.
+    fn catch_up_with_ledger(&mut self) -> io::Result<()> {
+        let Some(ledger) = &self.ledger else {
+            return Ok(());
+        };
+        match ledger.try_catch_up_with_primary() {
+            Ok(()) => Ok(()),
+            Err(error) => self.log_message(
+                MESSAGE_TYPE_WARNING,
+                &format!("failed to read the latest ledger state: {}", error),
+            ),
+        }
+    }
+
+    fn log_message(&mut self, message_type: i64, message: &str) -> io::Result<()> {
+        write_message(
+            &mut self.writer,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": "window/logMessage",
+                "params": { "type": message_type, "message": message },
+            }),
+        )
+    }
+}
+
+/// Opens the ledger as a secondary instance, so that it isn't held locked while another tool,
+/// e.g. `resim`, writes to it.
+fn open_ledger(dir: PathBuf) -> Result<(RocksdbSubstateStore, TempDir), String> {
+    // Each server keeps the logs of its secondary instance apart
+    let secondary_dir = tempfile::Builder::new()
+        .prefix("rtm-lsp-")
+        .tempdir()
+        .map_err(|error| format!("failed to create a temporary directory: {}", error))?;
+    let ledger = RocksdbSubstateStore::secondary(dir.clone(), secondary_dir.path().into())
+        .map_err(|error| format!("failed to open the ledger at {}: {}", dir.display(), error))?;
+    Ok((ledger, secondary_dir))
+}
+
+fn range(document: &Document, span: Span) -> Value {
+    let (start_line, start_character) = document.lsp_position(span.start);
+    let (end_line, end_character) = document.lsp_position(span.end);
+    json!({
+        "start": { "line": start_line, "character": start_character },
+        "end": { "line": end_line, "character": end_character },
+    })
+}
+
+fn completion_item(completion: Completion) -> Value {
+    let kind = match completion.kind {
+        CompletionKind::Instruction => 14,
+        CompletionKind::Value => 12,
+        CompletionKind::ValueKind => 25,
+        CompletionKind::NamedValue => 6,
+        CompletionKind::Address => 21,
+        CompletionKind::Blueprint => 7,
+        CompletionKind::Function => 3,
+    };
+    let mut item = json!({ "label": completion.label, "kind": kind });
+    if let Some(detail) = completion.detail {
+        item["detail"] = json!(detail);
+    }
+    if let Some(documentation) = completion.documentation {
+        item["documentation"] = json!({ "kind": "markdown", "value": documentation });
+    }
+    if let Some(insert_text) = completion.insert_text {
+        item["insertText"] = json!(insert_text);
+        if completion.kind == CompletionKind::Instruction {
+            item["insertTextFormat"] = json!(2);
+        }
+    }
+    item
+}
+
+/// Encodes the semantic tokens as relative positions, as required by the LSP specification.
+///
+/// Tokens spanning multiple lines are skipped, as not all clients support them.
+fn semantic_tokens_data(document: &Document) -> Vec<usize> {
+    let mut data = Vec::new();
+    let (mut previous_line, mut previous_character) = (0, 0);
+    for (span, token_type) in document.semantic_tokens() {
+        let (line, character) = document.lsp_position(span.start);
+        let (end_line, end_character) = document.lsp_position(span.end);
+        if line != end_line {
+            continue;
+        }
+        let token_type = SemanticTokenType::ALL
+            .iter()
+            .position(|t| *t == token_type)
+            .unwrap();
+        let delta_character = if line == previous_line {
+            character - previous_character
+        } else {
+            character
+        };
+        data.extend([
+            line - previous_line,
+            delta_character,
+            end_character - character,
+            token_type,
+            0,
+        ]);
+        (previous_line, previous_character) = (line, character);
+    }
+    data
+}
@@ -5,11 +5,12 @@ use radix_common::{
 };
 use radix_engine::utils::*;
 use radix_transactions::manifest::{
-    compile, compiler::compile_error_diagnostics, compiler::CompileErrorDiagnosticsStyle,
-    BlobProvider,
+    compile_template, compiler::compile_error_diagnostics, compiler::CompileErrorDiagnosticsStyle,
+    format_manifest, BlobProvider, TemplateValue,
 };
+use std::collections::HashMap;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 /// Radix transaction manifest compiler
@@ -17,8 +18,8 @@ use std::str::FromStr;
 #[clap(author, version, about, long_about = None, name = "rtmc")]
 pub struct Args {
     /// Path to the output file
-    #[clap(short, long)]
-    output: PathBuf,
+    #[clap(short, long, required_unless_present = "fmt")]
+    output: Option<PathBuf>,
 
     /// Network to Use [Simulator | Alphanet | Mainnet]
     #[clap(short, long)]
@@ -28,6 +29,15 @@ pub struct Args {
     #[clap(short, long, multiple = true)]
     blobs: Option<Vec<String>>,
 
+    /// The path to a JSON or TOML file with the values of the parameters of a manifest template
+    #[clap(long)]
+    bindings: Option<PathBuf>,
+
+    /// Formats the manifest in the canonical style instead of compiling it, in place unless an
+    /// output file is given
+    #[clap(long)]
+    fmt: bool,
+
     /// Input file
     #[clap(required = true)]
     input: PathBuf,
@@ -38,6 +48,7 @@ pub enum Error {
     IoError(std::io::Error),
     EncodeError(sbor::EncodeError),
     ParseNetworkError(ParseNetworkError),
+    InvalidBindings(String),
     InstructionSchemaValidationError(radix_engine::utils::LocatedInstructionSchemaValidationError),
 }
 
@@ -58,6 +69,18 @@ pub fn run() -> Result<(), String> {
     let args = Args::parse();
 
     let content = std::fs::read_to_string(&args.input).map_err(Error::IoError)?;
+    if args.fmt {
+        let formatted = format_manifest(&content).map_err(|err| {
+            compile_error_diagnostics(
+                &content,
+                err,
+                CompileErrorDiagnosticsStyle::TextTerminalColors,
+            )
+        })?;
+        std::fs::write(args.output.unwrap_or(args.input), formatted).map_err(Error::IoError)?;
+        return Ok(());
+    }
+
     let network = match args.network {
         Some(n) => NetworkDefinition::from_str(&n).map_err(Error::ParseNetworkError)?,
         None => NetworkDefinition::simulator(),
@@ -69,22 +92,89 @@ pub fn run() -> Result<(), String> {
         }
     }
 
-    let transaction =
-        compile(&content, &network, BlobProvider::new_with_blobs(blobs)).map_err(|err| {
-            compile_error_diagnostics(
-                &content,
-                err,
-                CompileErrorDiagnosticsStyle::TextTerminalColors,
-            )
-        })?;
+    let bindings = match args.bindings {
+        Some(path) => read_bindings(&path)?,
+        None => HashMap::new(),
+    };
+
+    let transaction = compile_template(
+        &content,
+        &network,
+        BlobProvider::new_with_blobs(blobs),
+        &bindings,
+    )
+    .map_err(|err| {
+        compile_error_diagnostics(
+            &content,
+            err,
+            CompileErrorDiagnosticsStyle::TextTerminalColors,
+        )
+    })?;
 
     validate_call_arguments_to_native_components(&transaction.instructions)
         .map_err(Error::InstructionSchemaValidationError)?;
     std::fs::write(
-        args.output,
+        args.output
+            .expect("The output is required unless formatting"),
         manifest_encode(&transaction).map_err(Error::EncodeError)?,
     )
     .map_err(Error::IoError)?;
 
     Ok(())
 }
+
+/// Reads the values of the parameters of a manifest template, from a TOML file if it has the
+/// `.toml` extension, or else from a JSON file.
+///
+/// Both map the names of the parameters to their values, in the textual form of
+/// [`TemplateValue::Text`]. Numbers and booleans are accepted too, for convenience, but decimals
+/// should be given as strings to avoid rounding.
+fn read_bindings(path: &Path) -> Result<HashMap<String, TemplateValue>, Error> {
+    let content = std::fs::read_to_string(path).map_err(Error::IoError)?;
+
+    let values: Vec<(String, Result<String, String>)> = if path
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        content
+            .parse::<toml::Table>()
+            .map_err(|err| Error::InvalidBindings(err.to_string()))?
+            .into_iter()
+            .map(|(name, value)| {
+                let text = match value {
+                    toml::Value::String(value) => Ok(value),
+                    toml::Value::Integer(value) => Ok(value.to_string()),
+                    toml::Value::Float(value) => Ok(value.to_string()),
+                    toml::Value::Boolean(value) => Ok(value.to_string()),
+                    value => Err(value.to_string()),
+                };
+                (name, text)
+            })
+            .collect()
+    } else {
+        serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&content)
+            .map_err(|err| Error::InvalidBindings(err.to_string()))?
+            .into_iter()
+            .map(|(name, value)| {
+                let text = match value {
+                    serde_json::Value::String(value) => Ok(value),
+                    serde_json::Value::Number(value) => Ok(value.to_string()),
+                    serde_json::Value::Bool(value) => Ok(value.to_string()),
+                    value => Err(value.to_string()),
+                };
+                (name, text)
+            })
+            .collect()
+    };
+
+    values
+        .into_iter()
+        .map(|(name, text)| match text {
+            Ok(text) => Ok((name, TemplateValue::Text(text))),
+            Err(value) => Err(Error::InvalidBindings(format!(
+                "unsupported value of parameter `{}`: {}",
+                name, value
+            ))),
+        })
+        .collect()
+}
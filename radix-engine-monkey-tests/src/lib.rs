@@ -1,4 +1,5 @@
 pub mod consensus_manager;
+pub mod manifest_fuzzer;
 pub mod multi_pool;
 pub mod one_pool;
 pub mod resource;
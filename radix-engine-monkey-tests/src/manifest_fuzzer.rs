@@ -0,0 +1,1093 @@
+use crate::{OnePoolMeta, SystemTestFuzzer, TwoPoolMeta};
+use radix_common::data::manifest::converter::{
+    from_decimal, from_non_fungible_local_id, from_precise_decimal,
+};
+use radix_common::prelude::*;
+use radix_engine::blueprints::pool::v1::constants::*;
+use radix_engine::errors::{NativeRuntimeError, RuntimeError, VmError};
+use radix_engine::system::checkers::{
+    ResourceDatabaseChecker, ResourceEventChecker, ResourceReconciler,
+};
+use radix_engine::system::system_db_reader::SystemDatabaseReader;
+use radix_engine::transaction::{TransactionOutcome, TransactionReceipt, TransactionResult};
+use radix_engine_interface::blueprints::access_controller::{
+    AccessControllerContributeRecoveryFeeManifestInput,
+    ACCESS_CONTROLLER_CONTRIBUTE_RECOVERY_FEE_IDENT,
+};
+use radix_engine_interface::blueprints::package::BlueprintPayloadDef;
+use radix_engine_interface::blueprints::pool::{
+    OneResourcePoolContributeManifestInput, OneResourcePoolRedeemManifestInput,
+    TwoResourcePoolContributeManifestInput, TwoResourcePoolInstantiateManifestInput,
+    TwoResourcePoolRedeemManifestInput, ONE_RESOURCE_POOL_CONTRIBUTE_IDENT,
+    ONE_RESOURCE_POOL_REDEEM_IDENT, TWO_RESOURCE_POOL_CONTRIBUTE_IDENT,
+    TWO_RESOURCE_POOL_INSTANTIATE_IDENT, TWO_RESOURCE_POOL_REDEEM_IDENT,
+};
+use radix_engine_interface::prelude::*;
+use radix_transactions::builder::ManifestBuilder;
+use radix_transactions::manifest::decompile;
+use radix_transactions::model::TransactionManifestV1;
+use rayon::iter::IntoParallelIterator;
+use rayon::iter::ParallelIterator;
+use scrypto_test::prelude::{DefaultLedgerSimulator, LedgerSimulatorBuilder};
+use std::any::Any;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+
+/// The maximum depth of the arguments generated from blueprint schemas.
+const MAX_VALUE_DEPTH: usize = 6;
+
+/// The initial supply of the fungible resource held by the fuzzed account.
+pub const INITIAL_FUNGIBLE_SUPPLY: Decimal = dec!(1000);
+
+/// An additional invariant checked against every transaction receipt.
+pub type ReceiptInvariant =
+    fn(&NativeBlueprintsEnvironment, &TransactionReceipt) -> Result<(), String>;
+
+/// A method of a global component whose arguments can be generated from its schema.
+#[derive(Clone)]
+pub struct SchemaMethod {
+    pub address: GlobalAddress,
+    pub method_name: String,
+    pub schema: Rc<VersionedScryptoSchema>,
+    pub input: LocalTypeId,
+}
+
+/// The ledger and the native blueprint instances a manifest fuzz test runs against.
+///
+/// The setup doesn't depend on the seed, so that a failing sequence of transactions can be
+/// replayed against a fresh environment while shrinking it.
+pub struct NativeBlueprintsEnvironment {
+    pub ledger: DefaultLedgerSimulator,
+    pub public_key: Secp256k1PublicKey,
+    pub account: ComponentAddress,
+    pub other_account: ComponentAddress,
+    pub fungible: ResourceAddress,
+    pub fungible_vault: InternalAddress,
+    pub non_fungible: ResourceAddress,
+    pub non_fungible_vault: InternalAddress,
+    pub one_resource_pool: OnePoolMeta,
+    pub two_resource_pool: TwoPoolMeta,
+    pub access_controller: ComponentAddress,
+    pub account_locker: ComponentAddress,
+    pub known_addresses: Vec<(GlobalAddress, BlueprintId)>,
+    pub methods: Vec<SchemaMethod>,
+}
+
+impl NativeBlueprintsEnvironment {
+    pub fn new() -> Self {
+        let mut ledger = LedgerSimulatorBuilder::new().without_kernel_trace().build();
+        let (public_key, _, account) = ledger.new_allocated_account();
+        let (_, _, other_account) = ledger.new_allocated_account();
+        let virtual_signature_badge = NonFungibleGlobalId::from_public_key(&public_key);
+
+        let fungible = {
+            let receipt = ledger.execute_manifest(
+                ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .create_fungible_resource(
+                        OwnerRole::None,
+                        true,
+                        18u8,
+                        FungibleResourceRoles {
+                            mint_roles: mint_roles! {
+                                minter => rule!(allow_all);
+                                minter_updater => rule!(deny_all);
+                            },
+                            burn_roles: burn_roles! {
+                                burner => rule!(allow_all);
+                                burner_updater => rule!(deny_all);
+                            },
+                            freeze_roles: freeze_roles! {
+                                freezer => rule!(allow_all);
+                                freezer_updater => rule!(deny_all);
+                            },
+                            recall_roles: recall_roles! {
+                                recaller => rule!(allow_all);
+                                recaller_updater => rule!(deny_all);
+                            },
+                            ..Default::default()
+                        },
+                        metadata!(),
+                        Some(INITIAL_FUNGIBLE_SUPPLY),
+                    )
+                    .deposit_batch(account)
+                    .build(),
+                vec![],
+            );
+            receipt.expect_commit_success().new_resource_addresses()[0]
+        };
+
+        let non_fungible = {
+            let receipt = ledger.execute_manifest(
+                ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .create_non_fungible_resource(
+                        OwnerRole::None,
+                        NonFungibleIdType::Integer,
+                        true,
+                        NonFungibleResourceRoles {
+                            mint_roles: mint_roles! {
+                                minter => rule!(allow_all);
+                                minter_updater => rule!(deny_all);
+                            },
+                            burn_roles: burn_roles! {
+                                burner => rule!(allow_all);
+                                burner_updater => rule!(deny_all);
+                            },
+                            recall_roles: recall_roles! {
+                                recaller => rule!(allow_all);
+                                recaller_updater => rule!(deny_all);
+                            },
+                            ..Default::default()
+                        },
+                        metadata!(),
+                        Some((0u64..4u64).map(|id| (NonFungibleLocalId::integer(id), ()))),
+                    )
+                    .deposit_batch(account)
+                    .build(),
+                vec![],
+            );
+            receipt.expect_commit_success().new_resource_addresses()[0]
+        };
+
+        let fungible_vault = ledger.get_component_vaults(account, fungible)[0];
+        let non_fungible_vault = ledger.get_component_vaults(account, non_fungible)[0];
+
+        let one_resource_pool = {
+            let (pool_address, pool_unit_resource_address) = ledger.create_one_resource_pool(
+                fungible,
+                rule!(require(virtual_signature_badge.clone())),
+            );
+            OnePoolMeta {
+                pool_address,
+                pool_unit_resource_address,
+                resource_address: fungible,
+            }
+        };
+
+        let two_resource_pool = {
+            let other_fungible = ledger.create_freely_mintable_and_burnable_fungible_resource(
+                OwnerRole::None,
+                None,
+                18u8,
+                account,
+            );
+            let receipt = ledger.execute_manifest(
+                ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .call_function(
+                        POOL_PACKAGE,
+                        TWO_RESOURCE_POOL_BLUEPRINT_IDENT,
+                        TWO_RESOURCE_POOL_INSTANTIATE_IDENT,
+                        TwoResourcePoolInstantiateManifestInput {
+                            resource_addresses: (fungible, other_fungible),
+                            pool_manager_rule: rule!(require(virtual_signature_badge.clone())),
+                            owner_role: OwnerRole::None,
+                            address_reservation: None,
+                        },
+                    )
+                    .build(),
+                vec![],
+            );
+            let commit_result = receipt.expect_commit_success();
+            TwoPoolMeta {
+                pool_address: commit_result.new_component_addresses()[0],
+                pool_unit_resource_address: commit_result.new_resource_addresses()[0],
+                resource_address1: fungible,
+                resource_address2: other_fungible,
+            }
+        };
+
+        let access_controller = {
+            let controlled_badge = ledger.create_fungible_resource(dec!(1), 0u8, account);
+            let receipt = ledger.execute_manifest(
+                ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .withdraw_from_account(account, controlled_badge, dec!(1))
+                    .take_all_from_worktop(controlled_badge, "controlled_badge")
+                    .create_access_controller(
+                        "controlled_badge",
+                        rule!(require(controlled_badge)),
+                        rule!(require(controlled_badge)),
+                        rule!(require(controlled_badge)),
+                        Some(1u32),
+                    )
+                    .build(),
+                vec![virtual_signature_badge.clone()],
+            );
+            receipt.expect_commit_success().new_component_addresses()[0]
+        };
+
+        let account_locker = {
+            let receipt = ledger.execute_manifest(
+                ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .call_function(
+                        LOCKER_PACKAGE,
+                        ACCOUNT_LOCKER_BLUEPRINT,
+                        ACCOUNT_LOCKER_INSTANTIATE_IDENT,
+                        AccountLockerInstantiateManifestInput {
+                            owner_role: OwnerRole::None,
+                            storer_role: rule!(allow_all),
+                            storer_updater_role: rule!(deny_all),
+                            recoverer_role: rule!(allow_all),
+                            recoverer_updater_role: rule!(deny_all),
+                            address_reservation: None,
+                        },
+                    )
+                    .build(),
+                vec![],
+            );
+            receipt.expect_commit_success().new_component_addresses()[0]
+        };
+
+        let method_targets: Vec<GlobalAddress> = vec![
+            fungible.into(),
+            non_fungible.into(),
+            one_resource_pool.pool_address.into(),
+            two_resource_pool.pool_address.into(),
+            account.into(),
+            access_controller.into(),
+            account_locker.into(),
+        ];
+        let reference_targets: Vec<GlobalAddress> = vec![
+            XRD.into(),
+            one_resource_pool.pool_unit_resource_address.into(),
+            two_resource_pool.pool_unit_resource_address.into(),
+            two_resource_pool.resource_address2.into(),
+            other_account.into(),
+            RESOURCE_PACKAGE.into(),
+            ACCOUNT_PACKAGE.into(),
+            POOL_PACKAGE.into(),
+            ACCESS_CONTROLLER_PACKAGE.into(),
+            LOCKER_PACKAGE.into(),
+        ];
+
+        let (known_addresses, methods) = {
+            let reader = SystemDatabaseReader::new(ledger.substate_db());
+            let known_addresses: Vec<(GlobalAddress, BlueprintId)> = method_targets
+                .iter()
+                .chain(reference_targets.iter())
+                .map(|address| {
+                    let object_info = reader
+                        .get_object_info(*address.as_node_id())
+                        .expect("Known addresses should be objects");
+                    (*address, object_info.blueprint_info.blueprint_id)
+                })
+                .collect();
+
+            let mut methods = Vec::new();
+            for (address, blueprint_id) in &known_addresses {
+                if !method_targets.contains(address) {
+                    continue;
+                }
+                let definition = reader
+                    .get_blueprint_definition(blueprint_id)
+                    .expect("Blueprint definition should exist");
+                for (method_name, function_schema) in &definition.interface.functions {
+                    if function_schema.receiver.is_none() {
+                        continue;
+                    }
+                    let BlueprintPayloadDef::Static(ScopedTypeId(schema_hash, input)) =
+                        &function_schema.input
+                    else {
+                        continue;
+                    };
+                    let schema = reader
+                        .get_schema(blueprint_id.package_address.as_node_id(), schema_hash)
+                        .expect("Schema should exist");
+                    methods.push(SchemaMethod {
+                        address: *address,
+                        method_name: method_name.clone(),
+                        schema,
+                        input: *input,
+                    });
+                }
+            }
+
+            (known_addresses, methods)
+        };
+
+        Self {
+            ledger,
+            public_key,
+            account,
+            other_account,
+            fungible,
+            fungible_vault: InternalAddress::try_from(fungible_vault).unwrap(),
+            non_fungible,
+            non_fungible_vault: InternalAddress::try_from(non_fungible_vault).unwrap(),
+            one_resource_pool,
+            two_resource_pool,
+            access_controller,
+            account_locker,
+            known_addresses,
+            methods,
+        }
+    }
+
+    pub fn manifest(&self, ops: &[ManifestFuzzOp]) -> TransactionManifestV1 {
+        let mut builder = ManifestBuilder::new().lock_fee_from_faucet();
+        for op in ops {
+            builder = op.add_to_manifest(builder, self);
+        }
+        builder
+            .drop_all_proofs()
+            .deposit_batch(self.account)
+            .build()
+    }
+
+    /// Executes a transaction made of the given operations, and checks the invariants of the
+    /// resulting ledger state.
+    pub fn execute(
+        &mut self,
+        ops: &[ManifestFuzzOp],
+        invariant: Option<ReceiptInvariant>,
+    ) -> Result<TransactionReceipt, String> {
+        let manifest = self.manifest(ops);
+        let proofs = vec![NonFungibleGlobalId::from_public_key(&self.public_key)];
+        let receipt = catch_unwind(AssertUnwindSafe(|| {
+            self.ledger.execute_manifest(manifest, proofs)
+        }))
+        .map_err(|payload| format!("Execution panicked: {}", panic_message(payload)))?;
+
+        match &receipt.result {
+            TransactionResult::Commit(commit_result) => {
+                if let TransactionOutcome::Failure(RuntimeError::VmError(VmError::Native(
+                    NativeRuntimeError::Trap {
+                        export_name,
+                        input,
+                        error,
+                    },
+                ))) = &commit_result.outcome
+                {
+                    return Err(format!(
+                        "Native panic: {:?} {:?} {:?}",
+                        export_name, input, error
+                    ));
+                }
+            }
+            TransactionResult::Reject(_) => {}
+            TransactionResult::Abort(abort_result) => {
+                return Err(format!("Transaction was aborted: {:?}", abort_result));
+            }
+        }
+
+        catch_unwind(AssertUnwindSafe(|| self.check_invariants()))
+            .map_err(|payload| format!("Invariant check panicked: {}", panic_message(payload)))??;
+
+        if let Some(invariant) = invariant {
+            invariant(self, &receipt)?;
+        }
+
+        Ok(receipt)
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        let (_, db_results) = self
+            .ledger
+            .check_db::<ResourceDatabaseChecker>()
+            .map_err(|error| format!("System database check failed: {:?}", error))?;
+        let event_results = self
+            .ledger
+            .check_events::<ResourceEventChecker>()
+            .map_err(|error| format!("Resource event check failed: {:?}", error))?;
+        ResourceReconciler::reconcile(&db_results, &event_results)
+            .map_err(|error| format!("Resource reconciliation failed: {:?}", error))
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
+/// A self-contained step of a fuzzed manifest. All of the randomness is drawn when the operation
+/// is generated, so that the same operations always produce the same manifest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManifestFuzzOp {
+    MintFungible {
+        amount: Decimal,
+    },
+    MintNonFungible {
+        id: u64,
+    },
+    BurnFungible {
+        amount: Decimal,
+    },
+    WithdrawFungible {
+        amount: Decimal,
+    },
+    WithdrawNonFungibles {
+        ids: BTreeSet<NonFungibleLocalId>,
+    },
+    CreateProofOfAmount {
+        amount: Decimal,
+    },
+    LockFee {
+        amount: Decimal,
+    },
+    Recall {
+        amount: Decimal,
+    },
+    FreezeWithdraw,
+    UnfreezeWithdraw,
+    OnePoolContribute {
+        amount: Decimal,
+    },
+    OnePoolRedeem {
+        amount: Decimal,
+    },
+    TwoPoolContribute {
+        amount1: Decimal,
+        amount2: Decimal,
+    },
+    TwoPoolRedeem {
+        amount: Decimal,
+    },
+    LockerStore {
+        amount: Decimal,
+        try_direct_send: bool,
+    },
+    LockerClaim {
+        amount: Decimal,
+    },
+    LockerRecover {
+        amount: Decimal,
+    },
+    ContributeRecoveryFee {
+        amount: Decimal,
+    },
+    DepositToOtherAccount,
+    CallMethod {
+        address: GlobalAddress,
+        method_name: String,
+        args: ManifestValue,
+    },
+}
+
+impl ManifestFuzzOp {
+    pub fn next(fuzzer: &mut SystemTestFuzzer, env: &NativeBlueprintsEnvironment) -> Self {
+        loop {
+            let op = match fuzzer.next(0u8..20u8) {
+                0u8 => ManifestFuzzOp::MintFungible {
+                    amount: next_amount(fuzzer),
+                },
+                1u8 => ManifestFuzzOp::MintNonFungible {
+                    id: fuzzer.next(0u64..16u64),
+                },
+                2u8 => ManifestFuzzOp::BurnFungible {
+                    amount: next_amount(fuzzer),
+                },
+                3u8 => ManifestFuzzOp::WithdrawFungible {
+                    amount: next_amount(fuzzer),
+                },
+                4u8 => ManifestFuzzOp::WithdrawNonFungibles {
+                    ids: fuzzer.next_non_fungible_id_set(),
+                },
+                5u8 => ManifestFuzzOp::CreateProofOfAmount {
+                    amount: next_amount(fuzzer),
+                },
+                6u8 => ManifestFuzzOp::LockFee {
+                    amount: next_amount(fuzzer),
+                },
+                7u8 => ManifestFuzzOp::Recall {
+                    amount: next_amount(fuzzer),
+                },
+                8u8 => ManifestFuzzOp::FreezeWithdraw,
+                9u8 => ManifestFuzzOp::UnfreezeWithdraw,
+                10u8 => ManifestFuzzOp::OnePoolContribute {
+                    amount: next_amount(fuzzer),
+                },
+                11u8 => ManifestFuzzOp::OnePoolRedeem {
+                    amount: next_amount(fuzzer),
+                },
+                12u8 => ManifestFuzzOp::TwoPoolContribute {
+                    amount1: next_amount(fuzzer),
+                    amount2: next_amount(fuzzer),
+                },
+                13u8 => ManifestFuzzOp::TwoPoolRedeem {
+                    amount: next_amount(fuzzer),
+                },
+                14u8 => ManifestFuzzOp::LockerStore {
+                    amount: next_amount(fuzzer),
+                    try_direct_send: fuzzer.next(0u8..2u8) == 0u8,
+                },
+                15u8 => ManifestFuzzOp::LockerClaim {
+                    amount: next_amount(fuzzer),
+                },
+                16u8 => ManifestFuzzOp::LockerRecover {
+                    amount: next_amount(fuzzer),
+                },
+                17u8 => ManifestFuzzOp::ContributeRecoveryFee {
+                    amount: next_amount(fuzzer),
+                },
+                18u8 => ManifestFuzzOp::DepositToOtherAccount,
+                _ => {
+                    let method = &env.methods[fuzzer.next_usize(env.methods.len())];
+                    let schema = method.schema.v1();
+                    match next_value(fuzzer, env, schema, method.input, 0) {
+                        Some(args) => ManifestFuzzOp::CallMethod {
+                            address: method.address,
+                            method_name: method.method_name.clone(),
+                            args,
+                        },
+                        // Not all arguments can be generated, e.g. buckets
+                        None => continue,
+                    }
+                }
+            };
+            return op;
+        }
+    }
+
+    pub fn add_to_manifest(
+        &self,
+        builder: ManifestBuilder,
+        env: &NativeBlueprintsEnvironment,
+    ) -> ManifestBuilder {
+        match self {
+            ManifestFuzzOp::MintFungible { amount } => builder.mint_fungible(env.fungible, *amount),
+            ManifestFuzzOp::MintNonFungible { id } => builder
+                .mint_non_fungible(env.non_fungible, [(NonFungibleLocalId::integer(*id), ())]),
+            ManifestFuzzOp::BurnFungible { amount } => {
+                builder.burn_from_worktop(*amount, env.fungible)
+            }
+            ManifestFuzzOp::WithdrawFungible { amount } => {
+                builder.withdraw_from_account(env.account, env.fungible, *amount)
+            }
+            ManifestFuzzOp::WithdrawNonFungibles { ids } => builder
+                .withdraw_non_fungibles_from_account(
+                    env.account,
+                    env.non_fungible,
+                    ids.iter().cloned(),
+                ),
+            ManifestFuzzOp::CreateProofOfAmount { amount } => {
+                builder.create_proof_from_account_of_amount(env.account, env.fungible, *amount)
+            }
+            ManifestFuzzOp::LockFee { amount } => builder.lock_fee(env.account, *amount),
+            ManifestFuzzOp::Recall { amount } => builder.recall(env.fungible_vault, *amount),
+            ManifestFuzzOp::FreezeWithdraw => builder.freeze_withdraw(env.fungible_vault),
+            ManifestFuzzOp::UnfreezeWithdraw => builder.unfreeze_withdraw(env.fungible_vault),
+            ManifestFuzzOp::OnePoolContribute { amount } => {
+                let bucket = builder.generate_bucket_name("contribution");
+                builder
+                    .mint_fungible(env.fungible, *amount)
+                    .take_all_from_worktop(env.fungible, &bucket)
+                    .with_name_lookup(|builder, lookup| {
+                        builder.call_method(
+                            env.one_resource_pool.pool_address,
+                            ONE_RESOURCE_POOL_CONTRIBUTE_IDENT,
+                            OneResourcePoolContributeManifestInput {
+                                bucket: lookup.bucket(&bucket),
+                            },
+                        )
+                    })
+            }
+            ManifestFuzzOp::OnePoolRedeem { amount } => {
+                let bucket = builder.generate_bucket_name("pool_units");
+                builder
+                    .take_from_worktop(
+                        env.one_resource_pool.pool_unit_resource_address,
+                        *amount,
+                        &bucket,
+                    )
+                    .with_name_lookup(|builder, lookup| {
+                        builder.call_method(
+                            env.one_resource_pool.pool_address,
+                            ONE_RESOURCE_POOL_REDEEM_IDENT,
+                            OneResourcePoolRedeemManifestInput {
+                                bucket: lookup.bucket(&bucket),
+                            },
+                        )
+                    })
+            }
+            ManifestFuzzOp::TwoPoolContribute { amount1, amount2 } => {
+                let pool = &env.two_resource_pool;
+                let bucket1 = builder.generate_bucket_name("contribution1");
+                let bucket2 = builder.generate_bucket_name("contribution2");
+                builder
+                    .mint_fungible(pool.resource_address1, *amount1)
+                    .mint_fungible(pool.resource_address2, *amount2)
+                    .take_all_from_worktop(pool.resource_address1, &bucket1)
+                    .take_all_from_worktop(pool.resource_address2, &bucket2)
+                    .with_name_lookup(|builder, lookup| {
+                        builder.call_method(
+                            pool.pool_address,
+                            TWO_RESOURCE_POOL_CONTRIBUTE_IDENT,
+                            TwoResourcePoolContributeManifestInput {
+                                buckets: (lookup.bucket(&bucket1), lookup.bucket(&bucket2)),
+                            },
+                        )
+                    })
+            }
+            ManifestFuzzOp::TwoPoolRedeem { amount } => {
+                let bucket = builder.generate_bucket_name("pool_units");
+                builder
+                    .take_from_worktop(
+                        env.two_resource_pool.pool_unit_resource_address,
+                        *amount,
+                        &bucket,
+                    )
+                    .with_name_lookup(|builder, lookup| {
+                        builder.call_method(
+                            env.two_resource_pool.pool_address,
+                            TWO_RESOURCE_POOL_REDEEM_IDENT,
+                            TwoResourcePoolRedeemManifestInput {
+                                bucket: lookup.bucket(&bucket),
+                            },
+                        )
+                    })
+            }
+            ManifestFuzzOp::LockerStore {
+                amount,
+                try_direct_send,
+            } => {
+                let bucket = builder.generate_bucket_name("to_store");
+                builder
+                    .mint_fungible(env.fungible, *amount)
+                    .take_all_from_worktop(env.fungible, &bucket)
+                    .with_name_lookup(|builder, lookup| {
+                        builder.call_method(
+                            env.account_locker,
+                            ACCOUNT_LOCKER_STORE_IDENT,
+                            AccountLockerStoreManifestInput {
+                                claimant: env.account,
+                                bucket: lookup.bucket(&bucket),
+                                try_direct_send: *try_direct_send,
+                            },
+                        )
+                    })
+            }
+            ManifestFuzzOp::LockerClaim { amount } => builder.call_method(
+                env.account_locker,
+                ACCOUNT_LOCKER_CLAIM_IDENT,
+                AccountLockerClaimManifestInput {
+                    claimant: env.account,
+                    resource_address: env.fungible,
+                    amount: *amount,
+                },
+            ),
+            ManifestFuzzOp::LockerRecover { amount } => builder.call_method(
+                env.account_locker,
+                ACCOUNT_LOCKER_RECOVER_IDENT,
+                AccountLockerRecoverManifestInput {
+                    claimant: env.account,
+                    resource_address: env.fungible,
+                    amount: *amount,
+                },
+            ),
+            ManifestFuzzOp::ContributeRecoveryFee { amount } => {
+                let bucket = builder.generate_bucket_name("recovery_fee");
+                builder
+                    .withdraw_from_account(env.account, XRD, *amount)
+                    .take_from_worktop(XRD, *amount, &bucket)
+                    .with_name_lookup(|builder, lookup| {
+                        builder.call_method(
+                            env.access_controller,
+                            ACCESS_CONTROLLER_CONTRIBUTE_RECOVERY_FEE_IDENT,
+                            AccessControllerContributeRecoveryFeeManifestInput {
+                                bucket: lookup.bucket(&bucket),
+                            },
+                        )
+                    })
+            }
+            ManifestFuzzOp::DepositToOtherAccount => {
+                builder.try_deposit_entire_worktop_or_refund(env.other_account, None)
+            }
+            ManifestFuzzOp::CallMethod {
+                address,
+                method_name,
+                args,
+            } => builder.call_method_raw(*address, method_name, args.clone()),
+        }
+    }
+}
+
+/// Amounts are mostly small, so that operations have a fair chance of succeeding, but sometimes
+/// span the whole range of decimals.
+fn next_amount(fuzzer: &mut SystemTestFuzzer) -> Decimal {
+    match fuzzer.next(0u8..4u8) {
+        0u8 => fuzzer.next_amount(),
+        _ => Decimal::from(fuzzer.next(0u64..100u64)),
+    }
+}
+
+fn next_value(
+    fuzzer: &mut SystemTestFuzzer,
+    env: &NativeBlueprintsEnvironment,
+    schema: &SchemaV1<ScryptoCustomSchema>,
+    type_id: LocalTypeId,
+    depth: usize,
+) -> Option<ManifestValue> {
+    if depth > MAX_VALUE_DEPTH {
+        return None;
+    }
+
+    let value = match schema.resolve_type_kind(type_id)? {
+        TypeKind::Any => return None,
+        TypeKind::Bool => Value::Bool {
+            value: fuzzer.next(0u8..2u8) == 0u8,
+        },
+        TypeKind::I8 => Value::I8 {
+            value: fuzzer.next(i8::MIN..=i8::MAX),
+        },
+        TypeKind::I16 => Value::I16 {
+            value: fuzzer.next(i16::MIN..=i16::MAX),
+        },
+        TypeKind::I32 => Value::I32 {
+            value: fuzzer.next(i32::MIN..=i32::MAX),
+        },
+        TypeKind::I64 => Value::I64 {
+            value: fuzzer.next(i64::MIN..=i64::MAX),
+        },
+        TypeKind::I128 => Value::I128 {
+            value: fuzzer.next(i128::MIN..=i128::MAX),
+        },
+        TypeKind::U8 => Value::U8 {
+            value: fuzzer.next(u8::MIN..=u8::MAX),
+        },
+        TypeKind::U16 => Value::U16 {
+            value: fuzzer.next(u16::MIN..=u16::MAX),
+        },
+        TypeKind::U32 => Value::U32 {
+            value: fuzzer.next(u32::MIN..=u32::MAX),
+        },
+        TypeKind::U64 => Value::U64 {
+            value: fuzzer.next(u64::MIN..=u64::MAX),
+        },
+        TypeKind::U128 => Value::U128 {
+            value: fuzzer.next(u128::MIN..=u128::MAX),
+        },
+        TypeKind::String => Value::String {
+            value: match fuzzer.next(0u8..3u8) {
+                0u8 => String::new(),
+                1u8 => "name".to_string(),
+                _ => fuzzer.next(0u64..1000u64).to_string(),
+            },
+        },
+        TypeKind::Array { element_type } => {
+            let element_value_kind = value_kind(schema, *element_type)?;
+            let mut elements = Vec::new();
+            for _ in 0..fuzzer.next(0usize..3usize) {
+                elements.push(next_value(fuzzer, env, schema, *element_type, depth + 1)?);
+            }
+            Value::Array {
+                element_value_kind,
+                elements,
+            }
+        }
+        TypeKind::Tuple { field_types } => {
+            let mut fields = Vec::new();
+            for field_type in field_types {
+                fields.push(next_value(fuzzer, env, schema, *field_type, depth + 1)?);
+            }
+            Value::Tuple { fields }
+        }
+        TypeKind::Enum { variants } => {
+            if variants.is_empty() {
+                return None;
+            }
+            let (discriminator, field_types) = variants
+                .get_index(fuzzer.next_usize(variants.len()))
+                .unwrap();
+            let mut fields = Vec::new();
+            for field_type in field_types {
+                fields.push(next_value(fuzzer, env, schema, *field_type, depth + 1)?);
+            }
+            Value::Enum {
+                discriminator: *discriminator,
+                fields,
+            }
+        }
+        TypeKind::Map {
+            key_type,
+            value_type,
+        } => {
+            let key_value_kind = value_kind(schema, *key_type)?;
+            let value_value_kind = value_kind(schema, *value_type)?;
+            let mut entries = Vec::new();
+            for _ in 0..fuzzer.next(0usize..3usize) {
+                entries.push((
+                    next_value(fuzzer, env, schema, *key_type, depth + 1)?,
+                    next_value(fuzzer, env, schema, *value_type, depth + 1)?,
+                ));
+            }
+            Value::Map {
+                key_value_kind,
+                value_value_kind,
+                entries,
+            }
+        }
+        TypeKind::Custom(ScryptoCustomTypeKind::Reference) => {
+            let validation = match schema.resolve_type_validation(type_id) {
+                Some(TypeValidation::Custom(ScryptoCustomTypeValidation::Reference(
+                    validation,
+                ))) => Some(validation),
+                _ => None,
+            };
+            let candidates: Vec<GlobalAddress> = env
+                .known_addresses
+                .iter()
+                .filter(|(address, blueprint_id)| match validation {
+                    None | Some(ReferenceValidation::IsGlobal) => true,
+                    Some(ReferenceValidation::IsGlobalPackage) => {
+                        address.as_node_id().is_global_package()
+                    }
+                    Some(ReferenceValidation::IsGlobalComponent) => {
+                        address.as_node_id().is_global_component()
+                    }
+                    Some(ReferenceValidation::IsGlobalResourceManager) => {
+                        address.as_node_id().is_global_resource_manager()
+                    }
+                    Some(ReferenceValidation::IsGlobalTyped(package_address, blueprint_name)) => {
+                        package_address.map_or(true, |package_address| {
+                            blueprint_id.package_address == package_address
+                        }) && blueprint_id.blueprint_name.eq(blueprint_name)
+                    }
+                    Some(ReferenceValidation::IsInternal)
+                    | Some(ReferenceValidation::IsInternalTyped(..)) => false,
+                })
+                .map(|(address, _)| *address)
+                .collect();
+            if candidates.is_empty() {
+                return None;
+            }
+            let address = candidates[fuzzer.next_usize(candidates.len())];
+            Value::Custom {
+                value: ManifestCustomValue::Address(ManifestAddress::Static(*address.as_node_id())),
+            }
+        }
+        TypeKind::Custom(ScryptoCustomTypeKind::Own) => return None,
+        TypeKind::Custom(ScryptoCustomTypeKind::Decimal) => Value::Custom {
+            value: ManifestCustomValue::Decimal(from_decimal(&next_amount(fuzzer))),
+        },
+        TypeKind::Custom(ScryptoCustomTypeKind::PreciseDecimal) => Value::Custom {
+            value: ManifestCustomValue::PreciseDecimal(from_precise_decimal(
+                &PreciseDecimal::from(next_amount(fuzzer)),
+            )),
+        },
+        TypeKind::Custom(ScryptoCustomTypeKind::NonFungibleLocalId) => Value::Custom {
+            value: ManifestCustomValue::NonFungibleLocalId(from_non_fungible_local_id(
+                fuzzer.next_integer_non_fungible_id(),
+            )),
+        },
+    };
+
+    Some(value)
+}
+
+fn value_kind(
+    schema: &SchemaV1<ScryptoCustomSchema>,
+    type_id: LocalTypeId,
+) -> Option<ManifestValueKind> {
+    let value_kind = match schema.resolve_type_kind(type_id)? {
+        TypeKind::Any => return None,
+        TypeKind::Bool => ValueKind::Bool,
+        TypeKind::I8 => ValueKind::I8,
+        TypeKind::I16 => ValueKind::I16,
+        TypeKind::I32 => ValueKind::I32,
+        TypeKind::I64 => ValueKind::I64,
+        TypeKind::I128 => ValueKind::I128,
+        TypeKind::U8 => ValueKind::U8,
+        TypeKind::U16 => ValueKind::U16,
+        TypeKind::U32 => ValueKind::U32,
+        TypeKind::U64 => ValueKind::U64,
+        TypeKind::U128 => ValueKind::U128,
+        TypeKind::String => ValueKind::String,
+        TypeKind::Array { .. } => ValueKind::Array,
+        TypeKind::Tuple { .. } => ValueKind::Tuple,
+        TypeKind::Enum { .. } => ValueKind::Enum,
+        TypeKind::Map { .. } => ValueKind::Map,
+        TypeKind::Custom(ScryptoCustomTypeKind::Reference) => {
+            ValueKind::Custom(ManifestCustomValueKind::Address)
+        }
+        TypeKind::Custom(ScryptoCustomTypeKind::Own) => return None,
+        TypeKind::Custom(ScryptoCustomTypeKind::Decimal) => {
+            ValueKind::Custom(ManifestCustomValueKind::Decimal)
+        }
+        TypeKind::Custom(ScryptoCustomTypeKind::PreciseDecimal) => {
+            ValueKind::Custom(ManifestCustomValueKind::PreciseDecimal)
+        }
+        TypeKind::Custom(ScryptoCustomTypeKind::NonFungibleLocalId) => {
+            ValueKind::Custom(ManifestCustomValueKind::NonFungibleLocalId)
+        }
+    };
+
+    Some(value_kind)
+}
+
+/// A failing sequence of transactions, shrunk to a minimal one.
+#[derive(Debug)]
+pub struct FuzzFailure {
+    pub seed: u64,
+    pub reason: String,
+    pub transactions: Vec<Vec<ManifestFuzzOp>>,
+    pub manifests: Vec<String>,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Fuzz test with seed {} failed: {}",
+            self.seed, self.reason
+        )?;
+        for (index, manifest) in self.manifests.iter().enumerate() {
+            writeln!(f, "Transaction {}:", index)?;
+            writeln!(f, "{}", manifest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fuzzes the native blueprints with random but well-typed manifests, checking the system,
+/// resource database and resource event invariants after each transaction.
+pub struct ManifestFuzzTest {
+    seed: u64,
+    fuzzer: SystemTestFuzzer,
+    invariant: Option<ReceiptInvariant>,
+}
+
+impl ManifestFuzzTest {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            fuzzer: SystemTestFuzzer::new(seed),
+            invariant: None,
+        }
+    }
+
+    pub fn with_invariant(mut self, invariant: ReceiptInvariant) -> Self {
+        self.invariant = Some(invariant);
+        self
+    }
+
+    pub fn run_fuzz(num_tests: u64, num_txns: u64) {
+        let failures: Vec<FuzzFailure> = (1u64..=num_tests)
+            .into_par_iter()
+            .filter_map(|seed| Self::new(seed).run(num_txns).err())
+            .collect();
+
+        if let Some(failure) = failures.first() {
+            panic!("{}", failure);
+        }
+    }
+
+    /// Generates the operations of the next transaction.
+    pub fn next_transaction(&mut self, env: &NativeBlueprintsEnvironment) -> Vec<ManifestFuzzOp> {
+        (0..self.fuzzer.next(1usize..=4usize))
+            .map(|_| ManifestFuzzOp::next(&mut self.fuzzer, env))
+            .collect()
+    }
+
+    /// Runs the given number of transactions, and returns the shrunk failing sequence of
+    /// transactions if any invariant has been broken.
+    pub fn run(mut self, num_txns: u64) -> Result<(), FuzzFailure> {
+        let mut env = NativeBlueprintsEnvironment::new();
+        let mut transactions = Vec::new();
+        for _ in 0u64..num_txns {
+            let ops = self.next_transaction(&env);
+            transactions.push(ops);
+            if let Err(reason) = env.execute(transactions.last().unwrap(), self.invariant) {
+                return Err(self.shrink(transactions, reason));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replays the transactions against a fresh environment, returning the index of the first
+    /// failing transaction and the reason of its failure.
+    fn replay(&self, transactions: &[Vec<ManifestFuzzOp>]) -> Option<(usize, String)> {
+        let mut env = NativeBlueprintsEnvironment::new();
+        transactions
+            .iter()
+            .enumerate()
+            .find_map(|(index, ops)| env.execute(ops, self.invariant).err().map(|e| (index, e)))
+    }
+
+    /// Replays the transactions against a fresh environment, returning the index of the first
+    /// failing transaction only if it fails for the given reason.
+    fn replay_failing_with(
+        &self,
+        transactions: &[Vec<ManifestFuzzOp>],
+        reason: &str,
+    ) -> Option<usize> {
+        self.replay(transactions)
+            .filter(|(_, failing_reason)| failing_reason == reason)
+            .map(|(failing_index, _)| failing_index)
+    }
+
+    /// Greedily removes transactions, then operations, for as long as the sequence still fails
+    /// for the same reason (so that shrinking doesn't drift towards an unrelated failure).
+    fn shrink(&self, mut transactions: Vec<Vec<ManifestFuzzOp>>, reason: String) -> FuzzFailure {
+        loop {
+            let mut shrunk = false;
+
+            let mut index = 0;
+            while index < transactions.len() {
+                let mut candidate = transactions.clone();
+                candidate.remove(index);
+                match self.replay_failing_with(&candidate, &reason) {
+                    Some(failing_index) => {
+                        candidate.truncate(failing_index + 1);
+                        transactions = candidate;
+                        shrunk = true;
+                    }
+                    None => index += 1,
+                }
+            }
+
+            let mut index = 0;
+            while index < transactions.len() {
+                let mut op_index = 0;
+                while op_index < transactions[index].len() {
+                    let mut candidate = transactions.clone();
+                    candidate[index].remove(op_index);
+                    match self.replay_failing_with(&candidate, &reason) {
+                        Some(failing_index) => {
+                            candidate.truncate(failing_index + 1);
+                            transactions = candidate;
+                            shrunk = true;
+                            if index >= transactions.len() {
+                                break;
+                            }
+                        }
+                        None => op_index += 1,
+                    }
+                }
+                index += 1;
+            }
+
+            if !shrunk {
+                break;
+            }
+        }
+
+        let env = NativeBlueprintsEnvironment::new();
+        let manifests = transactions
+            .iter()
+            .map(|ops| {
+                decompile(
+                    &env.manifest(ops).instructions,
+                    &NetworkDefinition::simulator(),
+                )
+                .unwrap_or_else(|error| format!("{:?}", error))
+            })
+            .collect();
+
+        FuzzFailure {
+            seed: self.seed,
+            reason,
+            transactions,
+            manifests,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use radix_common::prelude::*;
+use radix_engine::transaction::{TransactionReceipt, TransactionResult};
+use radix_engine_interface::prelude::*;
+use radix_engine_monkey_tests::manifest_fuzzer::{ManifestFuzzTest, NativeBlueprintsEnvironment};
+
+#[test]
+fn fuzz_native_blueprints() {
+    ManifestFuzzTest::run_fuzz(8, 20);
+}
+
+#[test]
+fn manifest_fuzzer_is_deterministic() {
+    let env = NativeBlueprintsEnvironment::new();
+    let mut fuzz_test1 = ManifestFuzzTest::new(42);
+    let mut fuzz_test2 = ManifestFuzzTest::new(42);
+
+    for _ in 0..20 {
+        assert_eq!(
+            fuzz_test1.next_transaction(&env),
+            fuzz_test2.next_transaction(&env)
+        );
+    }
+}
+
+#[test]
+fn failing_transactions_are_shrunk_to_a_single_operation() {
+    fn no_fungible_mint(
+        env: &NativeBlueprintsEnvironment,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), String> {
+        if let TransactionResult::Commit(commit_result) = &receipt.result {
+            if commit_result.outcome.is_success()
+                && commit_result.application_events.iter().any(
+                    |(EventTypeIdentifier(emitter, name), _)| {
+                        emitter == &Emitter::Method(env.fungible.into_node_id(), ModuleId::Main)
+                            && name == "MintFungibleResourceEvent"
+                    },
+                )
+            {
+                return Err("Fungible resource has been minted".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    let failure = ManifestFuzzTest::new(1)
+        .with_invariant(no_fungible_mint)
+        .run(50)
+        .expect_err("Fungible resource should be minted");
+
+    assert_eq!(
+        failure.reason, "Fungible resource has been minted",
+        "{}",
+        failure
+    );
+    assert_eq!(failure.transactions.last().unwrap().len(), 1, "{}", failure);
+}
@@ -332,6 +332,96 @@ fn notary_key_is_not_in_initial_proofs_when_notary_as_signatory_is_false() {
         });
 }
 
+#[test]
+fn test_preview_with_state_overrides() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().without_kernel_trace().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let initial_balance = ledger.get_component_balance(account, XRD);
+    let preview_flags = PreviewFlags {
+        use_free_credit: true,
+        assume_all_signature_proofs: false,
+        skip_epoch_check: false,
+        disable_auth: false,
+    };
+    // The account would hold more XRD after topping it up from the faucet.
+    let top_up_state_updates = ledger
+        .preview_manifest(
+            ManifestBuilder::new()
+                .get_free_xrd_from_faucet()
+                .try_deposit_entire_worktop_or_abort(account, None)
+                .build(),
+            vec![],
+            0,
+            preview_flags.clone(),
+        )
+        .expect_commit_success()
+        .state_updates
+        .clone();
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .withdraw_from_account(account, XRD, initial_balance + dec!(1))
+        .try_deposit_entire_worktop_or_abort(account, None)
+        .build();
+
+    // Act
+    let receipt_without_overrides = ledger.preview_manifest(
+        manifest.clone(),
+        vec![public_key.into()],
+        0,
+        preview_flags.clone(),
+    );
+    let receipt_with_overrides = ledger.preview_manifest_with_state_overrides(
+        manifest,
+        vec![public_key.into()],
+        0,
+        preview_flags,
+        &top_up_state_updates,
+    );
+
+    // Assert
+    receipt_without_overrides.expect_commit_failure();
+    receipt_with_overrides.expect_commit_success();
+    assert_eq!(ledger.get_component_balance(account, XRD), initial_balance);
+}
+
+#[test]
+fn test_preview_batch_executes_against_state_of_previous_intents() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().without_kernel_trace().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let initial_balance = ledger.get_component_balance(account, XRD);
+    let top_up_manifest = ManifestBuilder::new()
+        .get_free_xrd_from_faucet()
+        .try_deposit_entire_worktop_or_abort(account, None)
+        .build();
+    // Only succeeds after the top up
+    let withdraw_manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .withdraw_from_account(account, XRD, initial_balance + dec!(1))
+        .try_deposit_entire_worktop_or_abort(account, None)
+        .build();
+
+    // Act
+    let receipts = ledger.preview_manifest_batch(
+        vec![top_up_manifest, withdraw_manifest],
+        vec![public_key.into()],
+        0,
+        PreviewFlags {
+            use_free_credit: true,
+            assume_all_signature_proofs: false,
+            skip_epoch_check: false,
+            disable_auth: false,
+        },
+    );
+
+    // Assert
+    assert_eq!(receipts.len(), 2);
+    receipts[0].expect_commit_success();
+    receipts[1].expect_commit_success();
+    assert_eq!(ledger.get_component_balance(account, XRD), initial_balance);
+}
+
 fn prepare_matching_test_tx_and_preview_intent(
     ledger: &mut DefaultLedgerSimulator,
     network: &NetworkDefinition,
@@ -0,0 +1,236 @@
+use radix_engine::utils::{
+    instruction_schema_validation_error_diagnostics, validate_call_arguments_to_blueprints,
+    InstructionSchemaValidationError,
+};
+use radix_engine_tests::common::*;
+use radix_transactions::manifest::compiler::{compile_with_spans, CompileErrorDiagnosticsStyle};
+use radix_transactions::manifest::MockBlobProvider;
+use scrypto_test::prelude::*;
+
+fn setup() -> (DefaultLedgerSimulator, PackageAddress, ComponentAddress) {
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let package_address = ledger.publish_package_simple(PackageLoader::get("royalty"));
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_function(
+                package_address,
+                "RoyaltyTest",
+                "create_component_with_royalty_enabled",
+                manifest_args!(),
+            )
+            .build(),
+        vec![],
+    );
+    let component_address: ComponentAddress = receipt.expect_commit(true).output(1);
+    (ledger, package_address, component_address)
+}
+
+#[test]
+fn validator_sees_valid_calls_to_scrypto_blueprints_as_valid() {
+    // Arrange
+    let (ledger, package_address, component_address) = setup();
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_function(
+            package_address,
+            "RoyaltyTest",
+            "create_component_with_royalty",
+            manifest_args!(dec!("1")),
+        )
+        .call_method(component_address, "paid_method", manifest_args!())
+        .call_metadata_method(
+            component_address,
+            METADATA_GET_IDENT,
+            manifest_args!("name"),
+        )
+        .build();
+
+    // Act
+    let validation_result =
+        validate_call_arguments_to_blueprints(&manifest.instructions, ledger.substate_db());
+
+    // Assert
+    validation_result
+        .clone()
+        .unwrap_or_else(|_| panic!("Validation failed: {:?}", validation_result))
+}
+
+#[test]
+fn validator_invalidates_calls_with_arguments_not_matching_the_scrypto_schema() {
+    // Arrange
+    let (ledger, package_address, component_address) = setup();
+    let function_manifest = ManifestBuilder::new()
+        .call_function(
+            package_address,
+            "RoyaltyTest",
+            "create_component_with_royalty",
+            manifest_args!("1"),
+        )
+        .build();
+    let method_manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(component_address, "paid_method", manifest_args!(1u32))
+        .build();
+
+    // Act
+    let function_result = validate_call_arguments_to_blueprints(
+        &function_manifest.instructions,
+        ledger.substate_db(),
+    );
+    let method_result =
+        validate_call_arguments_to_blueprints(&method_manifest.instructions, ledger.substate_db());
+
+    // Assert
+    let error = function_result.unwrap_err();
+    assert_eq!(error.instruction_index, 0);
+    assert!(matches!(
+        error.cause,
+        InstructionSchemaValidationError::SchemaValidationError(..)
+    ));
+    let error = method_result.unwrap_err();
+    assert_eq!(error.instruction_index, 1);
+    assert!(matches!(
+        error.cause,
+        InstructionSchemaValidationError::SchemaValidationError(..)
+    ));
+}
+
+#[test]
+fn validator_invalidates_unknown_methods_blueprints_and_receivers() {
+    // Arrange
+    let (ledger, package_address, component_address) = setup();
+    let unknown_method = ManifestBuilder::new()
+        .call_method(component_address, "paid_methd", manifest_args!())
+        .build();
+    let unknown_blueprint = ManifestBuilder::new()
+        .call_function(
+            package_address,
+            "RoyaltyTst",
+            "free_method",
+            manifest_args!(),
+        )
+        .build();
+    let method_called_as_function = ManifestBuilder::new()
+        .call_function(
+            package_address,
+            "RoyaltyTest",
+            "free_method",
+            manifest_args!(),
+        )
+        .build();
+
+    // Act & Assert
+    assert!(matches!(
+        validate_call_arguments_to_blueprints(&unknown_method.instructions, ledger.substate_db())
+            .unwrap_err()
+            .cause,
+        InstructionSchemaValidationError::MethodNotFound(method_name) if method_name == "paid_methd"
+    ));
+    assert!(matches!(
+        validate_call_arguments_to_blueprints(
+            &unknown_blueprint.instructions,
+            ledger.substate_db()
+        )
+        .unwrap_err()
+        .cause,
+        InstructionSchemaValidationError::InvalidBlueprint(..)
+    ));
+    assert!(matches!(
+        validate_call_arguments_to_blueprints(
+            &method_called_as_function.instructions,
+            ledger.substate_db()
+        )
+        .unwrap_err()
+        .cause,
+        InstructionSchemaValidationError::InvalidReceiver
+    ));
+}
+
+#[test]
+fn validator_sees_calls_to_virtual_accounts_not_yet_in_the_ledger_as_valid() {
+    // Arrange
+    let (ledger, _, _) = setup();
+    let public_key = Secp256k1PrivateKey::from_u64(1000).unwrap().public_key();
+    let account = ComponentAddress::virtual_account_from_public_key(&public_key);
+    let identity = ComponentAddress::virtual_identity_from_public_key(&public_key);
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .get_free_xrd_from_faucet()
+        .try_deposit_entire_worktop_or_abort(account, None)
+        .call_metadata_method(account, METADATA_GET_IDENT, manifest_args!("name"))
+        .call_method(identity, IDENTITY_SECURIFY_IDENT, manifest_args!())
+        .build();
+    let royalty_manifest = ManifestBuilder::new()
+        .call_royalty_method(
+            account,
+            COMPONENT_ROYALTY_LOCK_ROYALTY_IDENT,
+            manifest_args!("deposit"),
+        )
+        .build();
+
+    // Act
+    let validation_result =
+        validate_call_arguments_to_blueprints(&manifest.instructions, ledger.substate_db());
+
+    // Assert
+    validation_result
+        .clone()
+        .unwrap_or_else(|_| panic!("Validation failed: {:?}", validation_result));
+    assert!(matches!(
+        validate_call_arguments_to_blueprints(
+            &royalty_manifest.instructions,
+            ledger.substate_db()
+        )
+        .unwrap_err()
+        .cause,
+        InstructionSchemaValidationError::ModuleNotAttached(..)
+    ));
+}
+
+#[test]
+fn validation_errors_are_reported_at_the_span_of_the_instruction() {
+    // Arrange
+    let (ledger, _, component_address) = setup();
+    let encoder = AddressBech32Encoder::for_simulator();
+    let manifest_string = format!(
+        r#"CALL_METHOD
+    Address("{faucet}")
+    "lock_fee"
+    Decimal("500")
+;
+CALL_METHOD
+    Address("{component}")
+    "paid_method"
+    "unexpected argument"
+;
+"#,
+        faucet = encoder.encode(FAUCET.as_node_id().as_bytes()).unwrap(),
+        component = encoder
+            .encode(component_address.as_node_id().as_bytes())
+            .unwrap(),
+    );
+    let (manifest, instruction_spans) = compile_with_spans(
+        &manifest_string,
+        &NetworkDefinition::simulator(),
+        MockBlobProvider::new(),
+    )
+    .unwrap();
+
+    // Act
+    let error = validate_call_arguments_to_blueprints(&manifest.instructions, ledger.substate_db())
+        .unwrap_err();
+    let diagnostics = instruction_schema_validation_error_diagnostics(
+        &manifest_string,
+        &instruction_spans,
+        &error,
+        CompileErrorDiagnosticsStyle::PlainText,
+    );
+
+    // Assert
+    assert_eq!(instruction_spans.len(), 2);
+    assert_eq!(instruction_spans[1].start.line_number(), 6);
+    assert_eq!(error.instruction_index, 1);
+    assert!(diagnostics.contains("arguments don't match the input schema"));
+    assert!(diagnostics.contains("invalid arguments"));
+}
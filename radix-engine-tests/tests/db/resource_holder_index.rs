@@ -0,0 +1,110 @@
+use radix_substate_store_impls::memory_db::*;
+use radix_substate_store_queries::query::*;
+use radix_transactions::builder::*;
+use scrypto::prelude::*;
+use scrypto_test::ledger_simulator::*;
+
+#[test]
+fn resource_holder_index_tracks_vaults_and_their_owners() {
+    // Arrange
+    let database = ResourceHolderIndexingDatabase::new(InMemorySubstateDatabase::standard());
+    let mut ledger = LedgerSimulatorBuilder::new()
+        .with_custom_database(database)
+        .without_kernel_trace()
+        .build();
+    let (public_key, _, account) = ledger.new_account(false);
+    let (_, _, other_account) = ledger.new_account(false);
+    let resource_address = ledger.create_fungible_resource(dec!(100), 18, account);
+
+    // Act
+    ledger
+        .execute_manifest(
+            ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .withdraw_from_account(account, resource_address, dec!(40))
+                .try_deposit_entire_worktop_or_abort(other_account, None)
+                .build(),
+            vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        )
+        .expect_commit_success();
+
+    // Assert
+    let mut expected_holders = index_map_new();
+    for holder in [account, other_account] {
+        for vault_id in ledger.get_component_vaults(holder, resource_address) {
+            expected_holders.insert(vault_id, Some(GlobalAddress::from(holder)));
+        }
+    }
+    let holders = ledger.substate_db().index().get_holders(&resource_address);
+    assert_eq!(holders.len(), 2);
+    assert_eq!(holders, expected_holders);
+}
+
+#[test]
+fn resource_holder_index_tracks_non_fungible_locations() {
+    // Arrange
+    let database = ResourceHolderIndexingDatabase::new(InMemorySubstateDatabase::standard());
+    let mut ledger = LedgerSimulatorBuilder::new()
+        .with_custom_database(database)
+        .without_kernel_trace()
+        .build();
+    let (public_key, _, account) = ledger.new_account(false);
+    let (_, _, other_account) = ledger.new_account(false);
+    let resource_address = ledger.create_non_fungible_resource(account);
+    let moved_id = NonFungibleGlobalId::new(resource_address, NonFungibleLocalId::integer(2));
+    let kept_id = NonFungibleGlobalId::new(resource_address, NonFungibleLocalId::integer(1));
+
+    // Act
+    ledger
+        .execute_manifest(
+            ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .withdraw_non_fungibles_from_account(
+                    account,
+                    resource_address,
+                    [moved_id.local_id().clone()],
+                )
+                .try_deposit_entire_worktop_or_abort(other_account, None)
+                .build(),
+            vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        )
+        .expect_commit_success();
+
+    // Assert
+    let account_vault = ledger.get_component_vaults(account, resource_address)[0];
+    let other_account_vault = ledger.get_component_vaults(other_account, resource_address)[0];
+    let index = ledger.substate_db().index();
+    assert_eq!(index.get_non_fungible_vault(&kept_id), Some(account_vault));
+    assert_eq!(
+        index.get_non_fungible_vault(&moved_id),
+        Some(other_account_vault)
+    );
+    assert_eq!(
+        index.get_global_owner(&other_account_vault),
+        Some(GlobalAddress::from(other_account))
+    );
+}
+
+#[test]
+fn resource_holder_index_built_from_existing_database_matches_incremental_one() {
+    // Arrange
+    let database = ResourceHolderIndexingDatabase::new(InMemorySubstateDatabase::standard());
+    let mut ledger = LedgerSimulatorBuilder::new()
+        .with_custom_database(database)
+        .without_kernel_trace()
+        .build();
+    let (_, _, account) = ledger.new_account(false);
+    let resource_address = ledger.create_non_fungible_resource(account);
+
+    // Act
+    let rebuilt_index = ResourceHolderIndex::from_database(ledger.substate_db());
+
+    // Assert
+    let index = ledger.substate_db().index();
+    for resource_address in [XRD, resource_address] {
+        assert_eq!(
+            rebuilt_index.get_holders(&resource_address),
+            index.get_holders(&resource_address)
+        );
+    }
+}
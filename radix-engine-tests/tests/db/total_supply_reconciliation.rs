@@ -0,0 +1,57 @@
+use radix_substate_store_queries::query::*;
+use scrypto_test::prelude::*;
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_accounting_matches_sequential_accounting() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (_, _, account) = ledger.new_allocated_account();
+    ledger.create_fungible_resource(dec!(100), 18, account);
+    ledger.create_non_fungible_resource(account);
+    let root_node_ids = list_global_node_ids(ledger.substate_db());
+
+    // Act
+    let mut sequential_accounter = ResourceAccounter::new(ledger.substate_db());
+    for node_id in root_node_ids.clone() {
+        sequential_accounter.traverse(node_id);
+    }
+    let sequential_accounting = sequential_accounter.close();
+    let mut parallel_accounter = ResourceAccounter::new(ledger.substate_db());
+    parallel_accounter.par_traverse(root_node_ids);
+    let parallel_accounting = parallel_accounter.close();
+
+    // Assert
+    assert_eq!(parallel_accounting.balances, sequential_accounting.balances);
+    assert_eq!(
+        parallel_accounting.non_fungibles,
+        sequential_accounting.non_fungibles
+    );
+}
+
+#[test]
+fn total_supplies_reconcile_with_vault_balances() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (_, _, account) = ledger.new_allocated_account();
+    let fungible_resource = ledger.create_fungible_resource(dec!(100), 18, account);
+    let non_fungible_resource = ledger.create_non_fungible_resource(account);
+    let mut accounter = ResourceAccounter::new(ledger.substate_db());
+    for node_id in list_global_node_ids(ledger.substate_db()) {
+        accounter.traverse(node_id);
+    }
+    let accounting = accounter.close();
+
+    // Act
+    let report = TotalSupplyReconciliationReport::new(ledger.substate_db(), &accounting);
+
+    // Assert
+    let fungible = report.resources.get(&fungible_resource).unwrap();
+    assert_eq!(fungible.recorded_total_supply, Some(dec!(100)));
+    assert_eq!(fungible.vault_total, dec!(100));
+    let non_fungible = report.resources.get(&non_fungible_resource).unwrap();
+    // The non-fungible resource is created without tracking its total supply.
+    assert_eq!(non_fungible.recorded_total_supply, None);
+    assert_eq!(non_fungible.vault_total, dec!(3));
+    assert!(report.is_consistent());
+}
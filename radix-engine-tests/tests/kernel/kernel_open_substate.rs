@@ -7,15 +7,19 @@ use radix_engine::kernel::kernel_api::KernelSubstateApi;
 use radix_engine::system::bootstrap::Bootstrapper;
 use radix_engine::system::system_callback::{System, SystemLockData};
 use radix_engine::system::system_modules::auth::AuthModule;
-use radix_engine::system::system_modules::costing::{CostingModule, CostingModuleConfig, FeeTable, SystemLoanFeeReserve};
+use radix_engine::system::system_modules::costing::{
+    CostingModule, CostingModuleConfig, FeeTable, SystemLoanFeeReserve,
+};
+use radix_engine::system::system_modules::debugger::DebuggerModule;
 use radix_engine::system::system_modules::execution_trace::ExecutionTraceModule;
+use radix_engine::system::system_modules::instruction_deltas::InstructionDeltasModule;
 use radix_engine::system::system_modules::kernel_trace::KernelTraceModule;
 use radix_engine::system::system_modules::limits::LimitsModule;
 use radix_engine::system::system_modules::transaction_runtime::TransactionRuntimeModule;
 use radix_engine::system::system_modules::{EnabledModules, SystemModuleMixer};
 use radix_engine::track::Track;
 use radix_engine::vm::wasm::DefaultWasmEngine;
-use radix_engine::vm::{DefaultNativeVm, NoExtension, ScryptoVm, Vm, VmInit, VmBoot};
+use radix_engine::vm::{DefaultNativeVm, NoExtension, ScryptoVm, Vm, VmBoot, VmInit};
 use radix_engine_interface::api::LockFlags;
 use radix_engine_interface::prelude::*;
 use radix_substate_store_impls::memory_db::InMemorySubstateDatabase;
@@ -61,7 +65,7 @@ pub fn test_open_substate_of_invisible_package_address() {
         },
         modules: SystemModuleMixer::new(
             EnabledModules::for_test_transaction(),
-            KernelTraceModule,
+            KernelTraceModule::new(),
             TransactionRuntimeModule::new(
                 NetworkDefinition::simulator(),
                 executable.intent_hash().to_hash(),
@@ -78,7 +82,10 @@ pub fn test_open_substate_of_invisible_package_address() {
                 on_apply_cost: Default::default(),
             },
             ExecutionTraceModule::new(MAX_EXECUTION_TRACE_DEPTH),
+            InstructionDeltasModule::new(),
+            DebuggerModule::new(None),
         ),
+        enable_read_write_set: false,
     };
     let mut track = Track::<InMemorySubstateDatabase, SpreadPrefixKeyMapper>::new(&database);
     let mut id_allocator = IdAllocator::new(executable.intent_hash().to_hash());
@@ -0,0 +1,159 @@
+use scrypto_test::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct RecordingDebugger {
+    breaks: Arc<Mutex<Vec<(String, usize)>>>,
+}
+
+impl TransactionDebugger for RecordingDebugger {
+    fn on_break(&mut self, event: &DebugEvent, state: &DebuggerState) -> DebugAction {
+        let description = match event {
+            DebugEvent::Invoke { actor, .. } => format!("Invoke {:?}", actor.blueprint_id()),
+            DebugEvent::Return { actor, .. } => format!("Return {:?}", actor.blueprint_id()),
+            DebugEvent::WriteSubstate { partition_num, .. } => {
+                format!("Write {:?}", partition_num)
+            }
+            _ => "Other".to_string(),
+        };
+        self.breaks
+            .lock()
+            .unwrap()
+            .push((description, state.call_frames.len()));
+        DebugAction::Continue
+    }
+}
+
+#[test]
+fn debugger_pauses_at_method_breakpoint() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let debugger = RecordingDebugger::default();
+    let breaks = debugger.breaks.clone();
+
+    // Act
+    let receipt = ledger.execute_manifest_with_execution_config(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, XRD, dec!(10))
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        ExecutionConfig::for_test_transaction().with_debugger(Some(
+            DebuggerConfig::new(debugger)
+                .with_breakpoint(Breakpoint::Method("withdraw".to_string())),
+        )),
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    let breaks = breaks.lock().unwrap();
+    // Paused before invoking and after returning from `withdraw`, called from the transaction
+    // processor.
+    assert_eq!(breaks.len(), 2);
+    assert!(breaks[0].0.starts_with("Invoke"));
+    assert!(breaks[0].0.contains("Account"));
+    assert!(breaks[1].0.starts_with("Return"));
+    assert_eq!(breaks[0].1, breaks[1].1);
+}
+
+#[test]
+fn debugger_pauses_at_every_event_when_stepping() {
+    // Arrange
+    let mut env = TestEnvironment::new();
+    let debugger = RecordingDebugger::default();
+    let breaks = debugger.breaks.clone();
+
+    // Act
+    env.with_debugger(DebuggerConfig::new(debugger).with_step(true), |env| {
+        let _ = BucketFactory::create_fungible_bucket(XRD, dec!(10), CreationStrategy::Mock, env);
+    });
+
+    // Assert
+    // `Continue` stops the stepping after the first event.
+    assert_eq!(breaks.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn debugger_tracks_call_frames_and_opened_substates() {
+    // Arrange
+    let mut env = TestEnvironment::new();
+    let debugger = RecordingDebugger::default();
+    let breaks = debugger.breaks.clone();
+
+    // Act
+    let bucket = env.with_debugger(
+        DebuggerConfig::new(debugger)
+            .with_breakpoint(Breakpoint::Blueprint(FUNGIBLE_BUCKET_BLUEPRINT.to_string())),
+        |env| {
+            let bucket =
+                BucketFactory::create_fungible_bucket(XRD, dec!(10), CreationStrategy::Mock, env)
+                    .unwrap();
+            bucket.amount(env).unwrap()
+        },
+    );
+
+    // Assert
+    assert_eq!(bucket, dec!(10));
+    let breaks = breaks.lock().unwrap();
+    assert!(breaks
+        .iter()
+        .any(|(description, _)| description.starts_with("Invoke")));
+    assert!(breaks.iter().all(|(_, depth)| *depth >= 1));
+}
+
+#[test]
+fn debugger_pauses_once_in_every_transaction_executed_in_parallel() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let debugger = RecordingDebugger::default();
+    let breaks = debugger.breaks.clone();
+    let executables: Vec<_> = (0..3)
+        .map(|_| {
+            TestTransaction::new_from_nonce(
+                ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .withdraw_from_account(account, XRD, dec!(10))
+                    .try_deposit_entire_worktop_or_abort(account, None)
+                    .build(),
+                ledger.next_transaction_nonce(),
+            )
+            .prepare()
+            .unwrap()
+        })
+        .collect();
+    let executables: Vec<_> = executables
+        .iter()
+        .map(|transaction| {
+            transaction.get_executable(btreeset!(NonFungibleGlobalId::from_public_key(
+                &public_key
+            )))
+        })
+        .collect();
+
+    // Act
+    let result = ledger.execute_transactions_in_parallel(
+        &executables,
+        ExecutionConfig::for_test_transaction().with_debugger(Some(
+            DebuggerConfig::new(debugger)
+                .with_breakpoint(Breakpoint::Method("withdraw".to_string())),
+        )),
+        3,
+    );
+
+    // Assert
+    for receipt in &result.receipts {
+        receipt.expect_commit_success();
+    }
+    let invokes = breaks
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(description, _)| description.starts_with("Invoke"))
+        .count();
+    // The transactions are executed serially, and only once each, when debugging.
+    assert_eq!(invokes, executables.len());
+    assert!(result.re_executed.is_empty());
+}
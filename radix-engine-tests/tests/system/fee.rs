@@ -567,3 +567,35 @@ fn regular_and_contingent_fee_locks_are_correct_in_execution_trace() {
         }
     )
 }
+
+#[test]
+fn call_frame_cost_breakdown_adds_up_to_execution_cost() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+
+    // Act
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .withdraw_from_account(account, XRD, dec!(10))
+        .try_deposit_entire_worktop_or_abort(account, None)
+        .build();
+    let receipt = ledger.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    let fee_details = receipt.fee_details.unwrap();
+    let breakdown = &fee_details.call_frame_cost_breakdown;
+    assert_eq!(breakdown.actor, "Root");
+    assert_eq!(
+        breakdown.total_execution_cost_units,
+        fee_details.execution_cost_breakdown.values().sum::<u32>()
+    );
+    let collapsed_stacks = breakdown.to_collapsed_stacks();
+    assert!(collapsed_stacks
+        .lines()
+        .any(|line| line.starts_with("Root;TransactionProcessor::run;Account::withdraw ")));
+}
@@ -0,0 +1,217 @@
+use radix_engine::system::system_modules::costing::*;
+use radix_engine_tests::common::*;
+use scrypto_test::prelude::*;
+
+fn setup_royalty_component() -> (DefaultLedgerSimulator, PackageAddress, ComponentAddress) {
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let package_address = ledger.publish_package_simple(PackageLoader::get("royalty"));
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_function(
+                package_address,
+                "RoyaltyTest",
+                "create_component_with_royalty_enabled",
+                manifest_args!(),
+            )
+            .build(),
+        vec![],
+    );
+    let component_address: ComponentAddress = receipt.expect_commit(true).output(1);
+    (ledger, package_address, component_address)
+}
+
+fn paid_method_manifest(component_address: ComponentAddress) -> TransactionManifestV1 {
+    ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(component_address, "paid_method", manifest_args!())
+        .build()
+}
+
+#[test]
+fn estimate_without_profile_has_royalties_and_no_upper_bounds() {
+    // Arrange
+    let (ledger, _, component_address) = setup_royalty_component();
+    let manifest = paid_method_manifest(component_address);
+    let profile = ExecutionCostProfile::new();
+
+    // Act
+    let estimate = FeeEstimator::new(
+        ledger.substate_db(),
+        CostingParameters::babylon_genesis(),
+        &profile,
+    )
+    .estimate(&InstructionsV1(manifest.instructions), 1000, 0);
+
+    // Assert
+    assert_eq!(estimate.royalty_cost_in_xrd, dec!("3"));
+    assert_eq!(estimate.unprofiled_instructions, vec![0, 1]);
+    assert_eq!(estimate.execution_cost_units.upper, None);
+    assert_eq!(estimate.total_cost_in_xrd().upper, None);
+    assert_eq!(
+        estimate.execution_cost_units.lower,
+        FeeTable::new().validate_tx_payload_cost(1000)
+    );
+}
+
+#[test]
+fn estimate_bounds_the_executed_costs() {
+    // Arrange
+    let (mut ledger, package_address, component_address) = setup_royalty_component();
+    let manifest = paid_method_manifest(component_address);
+    let mut profile = ExecutionCostProfile::new();
+    profile.insert(
+        ExecutionCostKey::Invocation {
+            blueprint_id: BlueprintId::new(&FAUCET_PACKAGE, FAUCET_BLUEPRINT),
+            ident: "lock_fee".to_string(),
+        },
+        RecordedCosts {
+            execution_cost_units: CostRange::new(0, u32::MAX),
+            finalization_cost_units: CostRange::new(0, u32::MAX),
+            state_storage_bytes: CostRange::new(0, 1_000_000),
+            archive_storage_bytes: CostRange::new(0, 1_000_000),
+        },
+    );
+    profile.insert(
+        ExecutionCostKey::Invocation {
+            blueprint_id: BlueprintId::new(&package_address, "RoyaltyTest"),
+            ident: "paid_method".to_string(),
+        },
+        RecordedCosts {
+            execution_cost_units: CostRange::new(0, u32::MAX),
+            finalization_cost_units: CostRange::new(0, u32::MAX),
+            state_storage_bytes: CostRange::new(0, 1_000_000),
+            archive_storage_bytes: CostRange::new(0, 1_000_000),
+        },
+    );
+    profile.insert(
+        ExecutionCostKey::Overhead,
+        RecordedCosts {
+            execution_cost_units: CostRange::new(0, u32::MAX),
+            finalization_cost_units: CostRange::new(0, u32::MAX),
+            state_storage_bytes: CostRange::new(0, 1_000_000),
+            archive_storage_bytes: CostRange::new(0, 1_000_000),
+        },
+    );
+    let transaction =
+        TestTransaction::new_from_nonce(manifest.clone(), ledger.next_transaction_nonce())
+            .prepare()
+            .unwrap();
+    let executable = transaction.get_executable(btreeset!());
+    let payload_size = executable.payload_size();
+
+    // Act
+    let estimate = FeeEstimator::new(
+        ledger.substate_db(),
+        CostingParameters::babylon_genesis(),
+        &profile,
+    )
+    .estimate(&InstructionsV1(manifest.instructions), payload_size, 0);
+    let receipt = ledger.execute_transaction(executable, ExecutionConfig::for_test_transaction());
+
+    // Assert
+    receipt.expect_commit_success();
+    let fee_summary = &receipt.fee_summary;
+    assert!(estimate.unprofiled_instructions.is_empty());
+    assert_eq!(
+        estimate.royalty_cost_in_xrd,
+        fee_summary.total_royalty_cost_in_xrd
+    );
+    assert!(estimate.execution_cost_units.lower <= fee_summary.total_execution_cost_units_consumed);
+    assert_eq!(
+        estimate.execution_cost_units.upper,
+        Some(CostingParameters::babylon_genesis().execution_cost_unit_limit)
+    );
+    assert!(
+        estimate.finalization_cost_units.upper.unwrap()
+            >= fee_summary.total_finalization_cost_units_consumed
+    );
+    assert!(estimate.storage_cost_in_xrd.lower <= fee_summary.total_storage_cost_in_xrd);
+    assert!(estimate.storage_cost_in_xrd.upper.unwrap() >= fee_summary.total_storage_cost_in_xrd);
+}
+
+fn execute_with_cost_breakdown(
+    ledger: &mut DefaultLedgerSimulator,
+    manifest: TransactionManifestV1,
+    public_key: &Secp256k1PublicKey,
+) -> (TransactionReceipt, usize, usize) {
+    let transaction = TestTransaction::new_from_nonce(manifest, ledger.next_transaction_nonce())
+        .prepare()
+        .unwrap();
+    let executable =
+        transaction.get_executable(btreeset!(NonFungibleGlobalId::from_public_key(public_key)));
+    let payload_size = executable.payload_size();
+    let num_of_signature_validations = executable.num_of_signature_validations();
+    let receipt = ledger.execute_transaction(
+        executable,
+        ExecutionConfig::for_test_transaction().with_cost_breakdown(true),
+    );
+    (receipt, payload_size, num_of_signature_validations)
+}
+
+#[test]
+fn estimate_from_recorded_receipts_tightly_bounds_the_executed_costs() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let (_, _, other_account) = ledger.new_allocated_account();
+    let transfer = |amount: Decimal| {
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, XRD, amount)
+            .try_deposit_entire_worktop_or_abort(other_account, None)
+            .build()
+    };
+    let mut profile = ExecutionCostProfile::new();
+    for amount in [dec!(10), dec!(20)] {
+        let manifest = transfer(amount);
+        let (receipt, _, _) = execute_with_cost_breakdown(&mut ledger, manifest.clone(), &public_key);
+        profile
+            .record_receipt(
+                ledger.substate_db(),
+                &InstructionsV1(manifest.instructions),
+                &receipt,
+            )
+            .unwrap();
+    }
+    let manifest = transfer(dec!(15));
+    let instructions = InstructionsV1(manifest.instructions.clone());
+
+    // Act
+    let (receipt, payload_size, num_of_signature_validations) =
+        execute_with_cost_breakdown(&mut ledger, manifest, &public_key);
+    let estimate = FeeEstimator::new(
+        ledger.substate_db(),
+        CostingParameters::babylon_genesis(),
+        &profile,
+    )
+    .estimate(&instructions, payload_size, num_of_signature_validations);
+
+    // Assert
+    receipt.expect_commit_success();
+    let fee_summary = &receipt.fee_summary;
+    assert!(estimate.unprofiled_instructions.is_empty());
+    assert!(!estimate.unprofiled_overhead);
+    assert_eq!(
+        estimate.execution_cost_units,
+        CostBounds {
+            lower: fee_summary.total_execution_cost_units_consumed,
+            upper: Some(fee_summary.total_execution_cost_units_consumed),
+        }
+    );
+    assert_eq!(
+        estimate.finalization_cost_units,
+        CostBounds {
+            lower: fee_summary.total_finalization_cost_units_consumed,
+            upper: Some(fee_summary.total_finalization_cost_units_consumed),
+        }
+    );
+    assert_eq!(
+        estimate.storage_cost_in_xrd,
+        CostBounds {
+            lower: fee_summary.total_storage_cost_in_xrd,
+            upper: Some(fee_summary.total_storage_cost_in_xrd),
+        }
+    );
+    assert_eq!(estimate.royalty_cost_in_xrd, Decimal::ZERO);
+}
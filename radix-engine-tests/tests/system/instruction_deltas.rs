@@ -0,0 +1,232 @@
+use radix_engine::system::system_modules::execution_trace::ResourceSpecifier;
+use radix_engine::system::system_modules::instruction_deltas::InstructionDeltas;
+use scrypto_test::prelude::*;
+
+fn vault_delta(
+    instruction: &InstructionDeltas,
+    entity: ComponentAddress,
+    resource_address: ResourceAddress,
+) -> Option<Decimal> {
+    instruction
+        .vault_deltas
+        .get(&GlobalAddress::from(entity))
+        .and_then(|deltas| deltas.get(&resource_address))
+        .cloned()
+}
+
+#[test]
+fn instruction_deltas_attribute_transfers_to_instructions() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let (_, _, other_account) = ledger.new_allocated_account();
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, XRD, dec!(10))
+            .try_deposit_entire_worktop_or_abort(other_account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let commit = receipt.expect_commit_success();
+    let instructions = commit.instruction_deltas.as_ref().unwrap();
+    assert_eq!(instructions.len(), 3);
+
+    let lock_fee = &instructions[0];
+    assert!(vault_delta(lock_fee, FAUCET, XRD).unwrap().is_negative());
+    assert_eq!(
+        vault_delta(lock_fee, FAUCET, XRD).unwrap(),
+        commit
+            .fee_source
+            .paying_vaults
+            .values()
+            .fold(Decimal::ZERO, |total, amount| total
+                .checked_add(*amount)
+                .unwrap())
+            .checked_neg()
+            .unwrap()
+    );
+
+    let withdraw = &instructions[1];
+    assert_eq!(withdraw.instruction_index, 1);
+    assert!(withdraw.worktop_before.is_empty());
+    assert_eq!(
+        withdraw.worktop_after,
+        vec![ResourceSpecifier::Amount(XRD, dec!(10))]
+    );
+    assert!(withdraw
+        .created_buckets
+        .values()
+        .any(|bucket| bucket.resource_address() == XRD && bucket.amount() == dec!(10)));
+    assert_eq!(vault_delta(withdraw, account, XRD), Some(dec!(-10)));
+
+    let deposit = &instructions[2];
+    assert_eq!(deposit.worktop_before, withdraw.worktop_after);
+    assert!(deposit.worktop_after.is_empty());
+    assert!(!deposit.consumed_buckets.is_empty());
+    assert_eq!(vault_delta(deposit, other_account, XRD), Some(dec!(10)));
+    assert_eq!(vault_delta(deposit, account, XRD), None);
+}
+
+#[test]
+fn instruction_deltas_of_failed_transaction_only_include_fee_payments() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, XRD, dec!(10))
+            .assert_worktop_contains(XRD, dec!(11))
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let commit = receipt.expect_commit_failure();
+    let instructions = commit.instruction_deltas.as_ref().unwrap();
+    assert_eq!(instructions.len(), 3);
+    assert!(vault_delta(&instructions[0], FAUCET, XRD)
+        .unwrap()
+        .is_negative());
+    assert!(instructions[1].vault_deltas.is_empty());
+    assert_eq!(
+        instructions[1].worktop_after,
+        vec![ResourceSpecifier::Amount(XRD, dec!(10))]
+    );
+}
+
+#[test]
+fn instruction_deltas_are_only_recorded_when_enabled() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+
+    // Act
+    let receipt = ledger.execute_manifest_with_execution_config(
+        ManifestBuilder::new().lock_fee_from_faucet().build(),
+        vec![],
+        ExecutionConfig::for_test_transaction().with_instruction_deltas(false),
+    );
+
+    // Assert
+    assert!(receipt.expect_commit_success().instruction_deltas.is_none());
+}
+
+#[test]
+fn instruction_deltas_are_not_recorded_for_ledger_transactions() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+
+    // Act
+    let receipt = ledger.execute_manifest_with_execution_config(
+        ManifestBuilder::new().lock_fee_from_faucet().build(),
+        vec![],
+        ExecutionConfig::for_notarized_transaction(NetworkDefinition::simulator()),
+    );
+
+    // Assert
+    assert!(receipt.expect_commit_success().instruction_deltas.is_none());
+}
+
+#[test]
+fn instruction_deltas_attribute_recalls_and_burns_to_the_vault_holder() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let resource_address = ledger.create_freezeable_token(account);
+    let vault_id = ledger.get_component_vaults(account, resource_address)[0];
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .recall(InternalAddress::new_or_panic(vault_id.into()), dec!(1))
+            .burn_all_from_worktop(resource_address)
+            .burn_in_account(account, resource_address, dec!(2))
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let commit = receipt.expect_commit_success();
+    let instructions = commit.instruction_deltas.as_ref().unwrap();
+    assert_eq!(instructions.len(), 5);
+    assert_eq!(
+        vault_delta(&instructions[1], account, resource_address),
+        Some(dec!(-1))
+    );
+    assert_eq!(
+        vault_delta(&instructions[4], account, resource_address),
+        Some(dec!(-2))
+    );
+    assert!(instructions
+        .iter()
+        .all(|instruction| instruction.unattributed_vault_deltas.is_empty()));
+}
+
+#[test]
+fn instruction_deltas_keep_recalls_from_vaults_of_unknown_holders_by_vault() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (_, _, account) = ledger.new_allocated_account();
+    let resource_address = ledger.create_freezeable_token(account);
+    let vault_id = ledger.get_component_vaults(account, resource_address)[0];
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .recall(InternalAddress::new_or_panic(vault_id.into()), dec!(1))
+            .burn_all_from_worktop(resource_address)
+            .build(),
+        vec![],
+    );
+
+    // Assert
+    let commit = receipt.expect_commit_success();
+    let recall = &commit.instruction_deltas.as_ref().unwrap()[1];
+    assert_eq!(vault_delta(recall, account, resource_address), None);
+    assert_eq!(
+        recall.unattributed_vault_deltas.get(&vault_id),
+        Some(&(resource_address, dec!(-1)))
+    );
+}
+
+#[test]
+fn instruction_deltas_attribute_puts_under_functions_to_the_new_holder() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (_, _, account) = ledger.new_allocated_account();
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .take_from_worktop(XRD, dec!(100), "controlled_asset")
+            .create_access_controller(
+                "controlled_asset",
+                rule!(allow_all),
+                rule!(allow_all),
+                rule!(allow_all),
+                None,
+            )
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![],
+    );
+
+    // Assert
+    let commit = receipt.expect_commit_success();
+    let access_controller = commit.new_component_addresses()[0];
+    let create = &commit.instruction_deltas.as_ref().unwrap()[3];
+    assert_eq!(vault_delta(create, access_controller, XRD), Some(dec!(100)));
+    assert!(create.unattributed_vault_deltas.is_empty());
+}
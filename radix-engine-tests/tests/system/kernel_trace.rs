@@ -0,0 +1,68 @@
+use scrypto_test::prelude::*;
+
+#[test]
+fn kernel_trace_is_recorded_into_receipt() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().with_kernel_trace().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, XRD, dec!(10))
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    let kernel_trace = receipt.kernel_trace.as_ref().unwrap();
+    assert!(kernel_trace.entries.iter().any(|entry| matches!(
+        &entry.event,
+        KernelTraceEvent::Invoke { actor, .. } if actor.contains("\"withdraw\"")
+    )));
+    assert!(kernel_trace
+        .entries
+        .iter()
+        .any(|entry| matches!(entry.event, KernelTraceEvent::CloseSubstate { .. })));
+    assert!(kernel_trace
+        .to_pretty_string()
+        .contains("Exiting: output size = "));
+    let decoded: KernelTrace = scrypto_decode(&scrypto_encode(kernel_trace).unwrap()).unwrap();
+    assert_eq!(&decoded, kernel_trace);
+}
+
+#[test]
+fn kernel_trace_is_not_recorded_when_disabled() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().without_kernel_trace().build();
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new().lock_fee_from_faucet().build(),
+        vec![],
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert!(receipt.kernel_trace.is_none());
+}
+
+#[test]
+fn kernel_trace_can_be_taken_from_test_environment() {
+    // Arrange
+    let mut env = TestEnvironment::new();
+    env.take_kernel_trace();
+
+    // Act
+    env.with_kernel_trace_module_enabled(|env| {
+        let _ = BucketFactory::create_fungible_bucket(XRD, dec!(10), CreationStrategy::Mock, env);
+    });
+    let kernel_trace = env.take_kernel_trace();
+
+    // Assert
+    assert!(!kernel_trace.entries.is_empty());
+    assert!(env.take_kernel_trace().entries.is_empty());
+}
@@ -0,0 +1,362 @@
+use scrypto_test::prelude::*;
+
+struct ParallelExecutionSetup {
+    ledger: DefaultLedgerSimulator,
+    public_keys: Vec<Secp256k1PublicKey>,
+    accounts: Vec<ComponentAddress>,
+    resources: Vec<ResourceAddress>,
+}
+
+fn setup(count: usize) -> ParallelExecutionSetup {
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let mut public_keys = vec![];
+    let mut accounts = vec![];
+    let mut resources = vec![];
+    for _ in 0..count {
+        let (public_key, _, account) = ledger.new_allocated_account();
+        public_keys.push(public_key);
+        accounts.push(account);
+    }
+    for account in &accounts {
+        resources.push(ledger.create_freely_mintable_fungible_resource(
+            OwnerRole::None,
+            None,
+            DIVISIBILITY_MAXIMUM,
+            *account,
+        ));
+    }
+    ParallelExecutionSetup {
+        ledger,
+        public_keys,
+        accounts,
+        resources,
+    }
+}
+
+/// Costing is disabled so that the transactions don't all conflict on the fee vault.
+fn execution_config() -> ExecutionConfig {
+    let mut execution_config = ExecutionConfig::for_test_transaction();
+    execution_config.system_overrides = Some(SystemOverrides {
+        disable_costing: true,
+        network_definition: Some(NetworkDefinition::simulator()),
+        ..Default::default()
+    });
+    execution_config
+}
+
+fn mint_manifests(setup: &ParallelExecutionSetup) -> Vec<TransactionManifestV1> {
+    setup
+        .accounts
+        .iter()
+        .zip(setup.resources.iter())
+        .map(|(account, resource_address)| {
+            ManifestBuilder::new()
+                .mint_fungible(*resource_address, dec!(10))
+                .try_deposit_entire_worktop_or_abort(*account, None)
+                .build()
+        })
+        .collect()
+}
+
+/// Each transaction pays its fees from the account it mints to.
+fn fee_paying_mint_executables(
+    setup: &mut ParallelExecutionSetup,
+) -> (Vec<PreparedTestTransaction>, Vec<BTreeSet<NonFungibleGlobalId>>) {
+    let manifests = setup
+        .accounts
+        .iter()
+        .zip(setup.resources.iter())
+        .map(|(account, resource_address)| {
+            ManifestBuilder::new()
+                .lock_fee(*account, 500)
+                .mint_fungible(*resource_address, dec!(10))
+                .try_deposit_entire_worktop_or_abort(*account, None)
+                .build()
+        })
+        .collect();
+    let proofs = setup
+        .public_keys
+        .iter()
+        .map(|public_key| btreeset!(NonFungibleGlobalId::from_public_key(public_key)))
+        .collect();
+    (prepare(&mut setup.ledger, manifests), proofs)
+}
+
+fn prepare(
+    ledger: &mut DefaultLedgerSimulator,
+    manifests: Vec<TransactionManifestV1>,
+) -> Vec<PreparedTestTransaction> {
+    manifests
+        .into_iter()
+        .map(|manifest| {
+            TestTransaction::new_from_nonce(manifest, ledger.next_transaction_nonce())
+                .prepare()
+                .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn independent_transactions_are_not_re_executed() {
+    // Arrange
+    let mut setup = setup(4);
+    let manifests = mint_manifests(&setup);
+    let transactions = prepare(&mut setup.ledger, manifests);
+    let executables: Vec<_> = transactions
+        .iter()
+        .map(|transaction| transaction.get_executable(btreeset!()))
+        .collect();
+
+    // Act
+    let result = setup
+        .ledger
+        .execute_transactions_in_parallel(&executables, execution_config(), 4);
+
+    // Assert
+    assert!(result.re_executed.is_empty());
+    for receipt in &result.receipts {
+        receipt.expect_commit_success();
+    }
+    for (account, resource_address) in setup.accounts.iter().zip(setup.resources.iter()) {
+        assert_eq!(
+            setup
+                .ledger
+                .get_component_balance(*account, *resource_address),
+            dec!(10)
+        );
+    }
+}
+
+#[test]
+fn conflicts_are_detected_without_reporting_read_write_sets() {
+    // Arrange
+    let mut setup = setup(2);
+    let mut manifests = mint_manifests(&setup);
+    manifests.push(
+        ManifestBuilder::new()
+            .withdraw_from_account(setup.accounts[0], setup.resources[0], dec!(4))
+            .try_deposit_entire_worktop_or_abort(setup.accounts[1], None)
+            .build(),
+    );
+    let transactions = prepare(&mut setup.ledger, manifests);
+    let executables: Vec<_> = transactions
+        .iter()
+        .map(|transaction| {
+            transaction.get_executable(btreeset!(NonFungibleGlobalId::from_public_key(
+                &setup.public_keys[0]
+            )))
+        })
+        .collect();
+
+    // Act
+    let result = setup.ledger.execute_transactions_in_parallel(
+        &executables,
+        execution_config().with_read_write_set(false),
+        2,
+    );
+
+    // Assert
+    assert_eq!(result.re_executed, vec![2]);
+    for receipt in &result.receipts {
+        assert!(receipt.expect_commit_success().read_write_set.is_none());
+    }
+    assert_eq!(
+        setup
+            .ledger
+            .get_component_balance(setup.accounts[0], setup.resources[0]),
+        dec!(6)
+    );
+}
+
+#[test]
+fn dependent_transactions_are_re_executed_against_previous_state_updates() {
+    // Arrange
+    let mut setup = setup(3);
+    let mut manifests = mint_manifests(&setup);
+    manifests.push(
+        ManifestBuilder::new()
+            .withdraw_from_account(setup.accounts[0], setup.resources[0], dec!(4))
+            .try_deposit_entire_worktop_or_abort(setup.accounts[1], None)
+            .build(),
+    );
+    let transactions = prepare(&mut setup.ledger, manifests);
+    let executables: Vec<_> = transactions
+        .iter()
+        .map(|transaction| {
+            transaction.get_executable(btreeset!(NonFungibleGlobalId::from_public_key(
+                &setup.public_keys[0]
+            )))
+        })
+        .collect();
+
+    // Act
+    let result = setup
+        .ledger
+        .execute_transactions_in_parallel(&executables, execution_config(), 2);
+
+    // Assert
+    assert_eq!(result.re_executed, vec![3]);
+    for receipt in &result.receipts {
+        receipt.expect_commit_success();
+    }
+    assert_eq!(
+        setup
+            .ledger
+            .get_component_balance(setup.accounts[0], setup.resources[0]),
+        dec!(6)
+    );
+    assert_eq!(
+        setup
+            .ledger
+            .get_component_balance(setup.accounts[1], setup.resources[0]),
+        dec!(4)
+    );
+}
+
+#[test]
+fn parallel_execution_matches_sequential_execution() {
+    // Arrange
+    let mut parallel = setup(3);
+    let mut sequential = setup(3);
+    let mut manifests = mint_manifests(&parallel);
+    manifests.push(
+        ManifestBuilder::new()
+            .withdraw_from_account(parallel.accounts[0], parallel.resources[0], dec!(4))
+            .try_deposit_entire_worktop_or_abort(parallel.accounts[2], None)
+            .build(),
+    );
+    let proofs = btreeset!(NonFungibleGlobalId::from_public_key(&parallel.public_keys[0]));
+    let parallel_transactions = prepare(&mut parallel.ledger, manifests.clone());
+    let sequential_transactions = prepare(&mut sequential.ledger, manifests);
+
+    // Act
+    let parallel_executables: Vec<_> = parallel_transactions
+        .iter()
+        .map(|transaction| transaction.get_executable(proofs.clone()))
+        .collect();
+    let result = parallel.ledger.execute_transactions_in_parallel(
+        &parallel_executables,
+        execution_config(),
+        3,
+    );
+    let sequential_receipts: Vec<_> = sequential_transactions
+        .iter()
+        .map(|transaction| {
+            sequential.ledger.execute_transaction(
+                transaction.get_executable(proofs.clone()),
+                execution_config(),
+            )
+        })
+        .collect();
+
+    // Assert
+    assert_eq!(result.receipts.len(), sequential_receipts.len());
+    for (parallel_receipt, sequential_receipt) in
+        result.receipts.iter().zip(sequential_receipts.iter())
+    {
+        assert_eq!(
+            parallel_receipt.expect_commit_success().state_updates,
+            sequential_receipt.expect_commit_success().state_updates
+        );
+    }
+}
+
+#[test]
+fn independent_transactions_paying_fees_are_not_re_executed() {
+    // Arrange
+    let mut parallel = setup(4);
+    let mut sequential = setup(4);
+    let (parallel_transactions, proofs) = fee_paying_mint_executables(&mut parallel);
+    let (sequential_transactions, _) = fee_paying_mint_executables(&mut sequential);
+    let parallel_executables: Vec<_> = parallel_transactions
+        .iter()
+        .zip(proofs.iter())
+        .map(|(transaction, proofs)| transaction.get_executable(proofs.clone()))
+        .collect();
+
+    // Act
+    let result = parallel.ledger.execute_transactions_in_parallel(
+        &parallel_executables,
+        ExecutionConfig::for_test_transaction(),
+        4,
+    );
+    let sequential_receipts: Vec<_> = sequential_transactions
+        .iter()
+        .zip(proofs.iter())
+        .map(|(transaction, proofs)| {
+            sequential.ledger.execute_transaction(
+                transaction.get_executable(proofs.clone()),
+                ExecutionConfig::for_test_transaction(),
+            )
+        })
+        .collect();
+
+    // Assert
+    // All transactions distribute their fees to the same validator rewards, yet none of them
+    // conflicts with the previous ones.
+    assert!(result.re_executed.is_empty());
+    for (parallel_receipt, sequential_receipt) in
+        result.receipts.iter().zip(sequential_receipts.iter())
+    {
+        let parallel_commit = parallel_receipt.expect_commit_success();
+        assert!(parallel_commit.fee_destination.to_proposer.is_positive());
+        assert_eq!(
+            parallel_commit.state_updates,
+            sequential_receipt.expect_commit_success().state_updates
+        );
+    }
+    for (account, resource_address) in parallel.accounts.iter().zip(parallel.resources.iter()) {
+        assert_eq!(
+            parallel.ledger.get_component_balance(*account, XRD),
+            sequential.ledger.get_component_balance(*account, XRD)
+        );
+        assert_eq!(
+            parallel
+                .ledger
+                .get_component_balance(*account, *resource_address),
+            dec!(10)
+        );
+    }
+}
+
+#[test]
+fn transactions_paying_fees_from_a_drained_vault_are_re_executed() {
+    // Arrange
+    let mut setup = setup(2);
+    let (mut transactions, mut proofs) = fee_paying_mint_executables(&mut setup);
+    let (account, other_account) = (setup.accounts[0], setup.accounts[1]);
+    let balance = setup.ledger.get_component_balance(account, XRD);
+    let drain = ManifestBuilder::new()
+        .lock_fee(other_account, 500)
+        .withdraw_from_account(account, XRD, balance - dec!(600))
+        .try_deposit_entire_worktop_or_abort(other_account, None)
+        .build();
+    transactions.insert(0, prepare(&mut setup.ledger, vec![drain]).remove(0));
+    proofs.insert(
+        0,
+        btreeset!(
+            NonFungibleGlobalId::from_public_key(&setup.public_keys[0]),
+            NonFungibleGlobalId::from_public_key(&setup.public_keys[1])
+        ),
+    );
+    let executables: Vec<_> = transactions
+        .iter()
+        .zip(proofs.iter())
+        .map(|(transaction, proofs)| transaction.get_executable(proofs.clone()))
+        .collect();
+
+    // Act
+    let result = setup.ledger.execute_transactions_in_parallel(
+        &executables,
+        ExecutionConfig::for_test_transaction(),
+        3,
+    );
+
+    // Assert
+    // The first mint pays its fees from the drained vault, the second one from the vault the
+    // drained resources have been deposited into.
+    assert_eq!(result.re_executed, vec![1]);
+    for receipt in &result.receipts {
+        receipt.expect_commit_success();
+    }
+}
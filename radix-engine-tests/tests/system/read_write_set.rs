@@ -0,0 +1,168 @@
+use scrypto_test::prelude::*;
+
+fn vault_balance_key(vault_id: NodeId) -> (NodeId, PartitionNumber, SubstateKey) {
+    (
+        vault_id,
+        MAIN_BASE_PARTITION,
+        FungibleVaultField::Balance.into(),
+    )
+}
+
+#[test]
+fn read_write_set_contains_updated_vaults_and_unmodified_reads() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let (_, _, other_account) = ledger.new_allocated_account();
+    let vault_id = ledger.get_component_vaults(account, XRD)[0];
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, XRD, dec!(10))
+            .try_deposit_entire_worktop_or_abort(other_account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let read_write_set = receipt
+        .expect_commit_success()
+        .read_write_set
+        .as_ref()
+        .unwrap();
+    assert!(read_write_set.reads.contains(&vault_balance_key(vault_id)));
+    assert!(read_write_set.writes.contains(&vault_balance_key(vault_id)));
+    assert!(read_write_set
+        .reads
+        .iter()
+        .any(|read| !read_write_set.writes.contains(read)));
+}
+
+#[test]
+fn read_write_set_of_failed_transaction_excludes_reverted_writes() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let (_, _, other_account) = ledger.new_allocated_account();
+    let vault_id = ledger.get_component_vaults(account, XRD)[0];
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, XRD, dec!(10))
+            .try_deposit_entire_worktop_or_abort(other_account, None)
+            .assert_worktop_contains(XRD, dec!(1))
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let read_write_set = receipt
+        .expect_commit_failure()
+        .read_write_set
+        .as_ref()
+        .unwrap();
+    assert!(read_write_set.reads.contains(&vault_balance_key(vault_id)));
+    assert!(!read_write_set.writes.contains(&vault_balance_key(vault_id)));
+}
+
+#[test]
+fn read_write_set_excludes_transient_substates() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let vault_id = ledger.get_component_vaults(account, XRD)[0];
+    let locked_balance_key = (
+        vault_id,
+        MAIN_BASE_PARTITION,
+        FungibleVaultField::LockedBalance.into(),
+    );
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .create_proof_from_account_of_amount(account, XRD, dec!(10))
+            .drop_all_proofs()
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let read_write_set = receipt
+        .expect_commit_success()
+        .read_write_set
+        .as_ref()
+        .unwrap();
+    assert!(read_write_set.reads.contains(&vault_balance_key(vault_id)));
+    assert!(!read_write_set.reads.contains(&locked_balance_key));
+    assert!(!read_write_set.writes.contains(&locked_balance_key));
+}
+
+#[test]
+fn read_write_set_writes_cover_state_updates() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .create_fungible_resource(
+                OwnerRole::None,
+                true,
+                DIVISIBILITY_MAXIMUM,
+                FungibleResourceRoles::default(),
+                metadata!(),
+                Some(dec!(100)),
+            )
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let commit = receipt.expect_commit_success();
+    let read_write_set = commit.read_write_set.as_ref().unwrap();
+    for (node_id, node_updates) in &commit.state_updates.by_node {
+        let NodeStateUpdates::Delta { by_partition } = node_updates;
+        for (partition_num, partition_updates) in by_partition {
+            match partition_updates {
+                PartitionStateUpdates::Delta { by_substate } => {
+                    for substate_key in by_substate.keys() {
+                        assert!(read_write_set.writes.contains(&(
+                            *node_id,
+                            *partition_num,
+                            substate_key.clone()
+                        )));
+                    }
+                }
+                PartitionStateUpdates::Batch(..) => {
+                    assert!(read_write_set
+                        .deleted_partitions
+                        .contains(&(*node_id, *partition_num)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn read_write_set_is_only_recorded_when_enabled() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+
+    // Act
+    let receipt = ledger.execute_manifest_with_execution_config(
+        ManifestBuilder::new().lock_fee_from_faucet().build(),
+        vec![],
+        ExecutionConfig::for_notarized_transaction(NetworkDefinition::simulator()),
+    );
+
+    // Assert
+    assert!(receipt.expect_commit_success().read_write_set.is_none());
+}
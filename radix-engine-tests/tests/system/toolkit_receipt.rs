@@ -694,6 +694,55 @@ fn locked_fees_are_mapped_correctly_in_receipt() {
     assert_eq!(locked_fees.non_contingent, dec!(5000));
 }
 
+#[test]
+fn encoded_v1_receipts_are_converted_like_the_latest_receipts() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().without_kernel_trace().build();
+    let (_, _, account) = ledger.new_account(false);
+    let receipt = ledger.preview_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![],
+        0,
+        PreviewFlags::default(),
+    );
+    let commit = receipt.expect_commit_success().clone();
+    let receipt_v1 = TransactionReceiptV1 {
+        costing_parameters: receipt.costing_parameters.clone(),
+        transaction_costing_parameters: receipt.transaction_costing_parameters.clone(),
+        fee_summary: receipt.fee_summary.clone(),
+        fee_details: None,
+        result: TransactionResultV1::Commit(CommitResultV1 {
+            state_updates: commit.state_updates,
+            state_update_summary: commit.state_update_summary,
+            fee_source: commit.fee_source,
+            fee_destination: commit.fee_destination,
+            outcome: commit.outcome,
+            application_events: commit.application_events,
+            application_logs: commit.application_logs,
+            system_structure: commit.system_structure,
+            execution_trace: commit.execution_trace,
+        }),
+        resources_usage: None,
+    };
+
+    // Act
+    let encoded = scrypto_encode(&VersionedTransactionReceipt::from(
+        TransactionReceiptVersions::V1(receipt_v1),
+    ))
+    .unwrap();
+    let decoded = scrypto_decode::<VersionedTransactionReceipt>(&encoded).unwrap();
+
+    // Assert
+    assert_eq!(
+        RuntimeToolkitTransactionReceipt::try_from(decoded).unwrap(),
+        RuntimeToolkitTransactionReceipt::try_from(receipt).unwrap()
+    );
+}
+
 /// Converts a receipt to a runtime receipt and does the following checks:
 /// * Checks that the receipt can be converted into a runtime receipt.
 /// * Checks that the runtime receipt can be converted into a serializable receipt.
@@ -703,7 +752,7 @@ fn locked_fees_are_mapped_correctly_in_receipt() {
 /// * Checks that the runtime receipt obtained from deserialization equals the runtime receipt
 ///   obtained from direct conversion (roundtrip property)
 fn check_and_convert_receipt_to_runtime_receipt(
-    receipt: TransactionReceiptV1,
+    receipt: TransactionReceipt,
 ) -> RuntimeToolkitTransactionReceipt {
     // Convert to a runtime receipt.
     let runtime_toolkit_receipt = RuntimeToolkitTransactionReceipt::try_from(receipt).unwrap();
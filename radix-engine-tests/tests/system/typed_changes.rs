@@ -0,0 +1,89 @@
+use radix_engine::transaction::BalanceChange;
+use radix_substate_store_queries::typed_changes::*;
+use radix_substate_store_queries::typed_native_events::*;
+use scrypto_test::prelude::*;
+
+#[test]
+fn metadata_update_is_converted_to_typed_change() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .set_metadata(account, "name", "Alice")
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let changes = to_typed_changes(receipt.expect_commit_success(), ledger.substate_db()).unwrap();
+    let metadata_changes = changes
+        .iter()
+        .filter_map(|change| match change {
+            TypedChange::MetadataSet { entity, key, value } => Some((*entity, key, value)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        metadata_changes,
+        vec![(
+            *account.as_node_id(),
+            &"name".to_string(),
+            &MetadataValue::String("Alice".to_string())
+        )]
+    );
+    assert!(changes.iter().any(|change| matches!(
+        change,
+        TypedChange::NativeEvent {
+            event: TypedNativeEvent::Metadata(_),
+            ..
+        }
+    )));
+}
+
+#[test]
+fn vault_balance_changes_are_converted_to_typed_changes_with_owners() {
+    // Arrange
+    let mut ledger = LedgerSimulatorBuilder::new().build();
+    let (public_key, _, account) = ledger.new_allocated_account();
+    let (_, _, other_account) = ledger.new_allocated_account();
+    let resource_address = ledger.create_fungible_resource(dec!(100), 18, account);
+
+    // Act
+    let receipt = ledger.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(account, resource_address, dec!(40))
+            .try_deposit_entire_worktop_or_abort(other_account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+
+    // Assert
+    let changes = to_typed_changes(receipt.expect_commit_success(), ledger.substate_db()).unwrap();
+    let balance_changes = changes
+        .iter()
+        .filter_map(|change| match change {
+            TypedChange::VaultBalanceChanged {
+                owner,
+                resource_address: changed_resource_address,
+                change,
+                ..
+            } if *changed_resource_address == resource_address => Some((*owner, change.clone())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    // The owner of the newly-created vault is known from the commit itself, and the owner of the
+    // pre-existing vault from the substates read by the transaction.
+    assert!(balance_changes.contains(&(
+        Some(GlobalAddress::from(other_account)),
+        BalanceChange::Fungible(dec!(40))
+    )));
+    assert!(balance_changes.contains(&(
+        Some(GlobalAddress::from(account)),
+        BalanceChange::Fungible(dec!(-40))
+    )));
+}
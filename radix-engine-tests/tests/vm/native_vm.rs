@@ -10,7 +10,9 @@ use radix_engine::system::system::*;
 use radix_engine::system::system_callback::*;
 use radix_engine::system::system_modules::auth::AuthModule;
 use radix_engine::system::system_modules::costing::*;
+use radix_engine::system::system_modules::debugger::DebuggerModule;
 use radix_engine::system::system_modules::execution_trace::ExecutionTraceModule;
+use radix_engine::system::system_modules::instruction_deltas::InstructionDeltasModule;
 use radix_engine::system::system_modules::kernel_trace::KernelTraceModule;
 use radix_engine::system::system_modules::limits::LimitsModule;
 use radix_engine::system::system_modules::transaction_runtime::TransactionRuntimeModule;
@@ -83,7 +85,7 @@ fn panics_can_be_caught_in_the_native_vm_and_converted_into_results() {
         },
         modules: SystemModuleMixer::new(
             EnabledModules::for_notarized_transaction(),
-            KernelTraceModule,
+            KernelTraceModule::new(),
             TransactionRuntimeModule::new(NetworkDefinition::simulator(), intent_hash),
             AuthModule::new(AuthZoneParams {
                 initial_proofs: Default::default(),
@@ -100,7 +102,10 @@ fn panics_can_be_caught_in_the_native_vm_and_converted_into_results() {
                 on_apply_cost: Default::default(),
             },
             ExecutionTraceModule::new(MAX_EXECUTION_TRACE_DEPTH),
+            InstructionDeltasModule::new(),
+            DebuggerModule::new(None),
         ),
+        enable_read_write_set: false,
     };
 
     let mut id_allocator = IdAllocator::new(intent_hash);
@@ -162,7 +167,7 @@ fn any_panics_can_be_caught_in_the_native_vm_and_converted_into_results() {
         },
         modules: SystemModuleMixer::new(
             EnabledModules::for_notarized_transaction(),
-            KernelTraceModule,
+            KernelTraceModule::new(),
             TransactionRuntimeModule::new(NetworkDefinition::simulator(), intent_hash),
             AuthModule::new(AuthZoneParams {
                 initial_proofs: Default::default(),
@@ -179,7 +184,10 @@ fn any_panics_can_be_caught_in_the_native_vm_and_converted_into_results() {
                 on_apply_cost: Default::default(),
             },
             ExecutionTraceModule::new(MAX_EXECUTION_TRACE_DEPTH),
+            InstructionDeltasModule::new(),
+            DebuggerModule::new(None),
         ),
+        enable_read_write_set: false,
     };
 
     let mut id_allocator = IdAllocator::new(intent_hash);
@@ -41,7 +41,9 @@ use crate::system::system_modules::costing::{
     CostingModule, FeeReserveFinalizationSummary, FeeTable, FinalizationCostingEntry,
     FinalizingFeeReserve, StorageType, SystemLoanFeeReserve,
 };
+use crate::system::system_modules::debugger::{DebuggerConfig, DebuggerModule};
 use crate::system::system_modules::execution_trace::ExecutionTraceModule;
+use crate::system::system_modules::instruction_deltas::InstructionDeltasModule;
 use crate::system::system_modules::kernel_trace::KernelTraceModule;
 use crate::system::system_modules::limits::LimitsModule;
 use crate::system::system_modules::transaction_runtime::TransactionRuntimeModule;
@@ -49,8 +51,8 @@ use crate::system::system_modules::{EnabledModules, SystemModuleMixer};
 use crate::system::system_substates::KeyValueEntrySubstate;
 use crate::system::system_type_checker::{BlueprintTypeTarget, KVStoreTypeTarget};
 use crate::track::{
-    to_state_updates, BootStore, CanonicalSubstateKey, CommitableSubstateStore, IOAccess,
-    StoreCommitInfo, Track, TrackFinalizeError,
+    to_read_write_set, to_state_updates, BootStore, CanonicalSubstateKey, CommitableSubstateStore,
+    IOAccess, StoreCommitInfo, Track, TrackFinalizeError,
 };
 use crate::transaction::{
     reconcile_resource_state_and_events, AbortResult, CommitResult, CostingParameters,
@@ -148,6 +150,9 @@ pub struct SystemInit<C> {
     pub enable_kernel_trace: bool,
     pub enable_cost_breakdown: bool,
     pub execution_trace: Option<usize>,
+    pub enable_instruction_deltas: bool,
+    pub enable_read_write_set: bool,
+    pub debugger: Option<DebuggerConfig>,
 
     // Higher layer initialization object
     pub callback_init: C,
@@ -162,6 +167,8 @@ pub struct System<C: SystemCallbackObject> {
     pub schema_cache: NonIterMap<SchemaHash, Rc<VersionedScryptoSchema>>,
     pub auth_cache: NonIterMap<CanonicalBlueprintId, AuthConfig>,
     pub modules: SystemModuleMixer,
+    /// Whether to report the substates read and written on the commit result
+    pub enable_read_write_set: bool,
 }
 
 impl<C: SystemCallbackObject> System<C> {
@@ -781,6 +788,12 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
             if init_input.execution_trace.is_some() {
                 enabled_modules |= EnabledModules::EXECUTION_TRACE;
             }
+            if init_input.enable_instruction_deltas {
+                enabled_modules |= EnabledModules::INSTRUCTION_DELTAS;
+            }
+            if init_input.debugger.is_some() {
+                enabled_modules |= EnabledModules::DEBUGGER;
+            }
 
             enabled_modules
         };
@@ -839,12 +852,14 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
 
         let mut modules = SystemModuleMixer::new(
             enabled_modules,
-            KernelTraceModule,
+            KernelTraceModule::new(),
             txn_runtime_module,
             auth_module,
             limits_module,
             costing_module,
             ExecutionTraceModule::new(init_input.execution_trace.unwrap_or(0)),
+            InstructionDeltasModule::new(),
+            DebuggerModule::new(init_input.debugger),
         );
 
         modules.init().map_err(RejectionReason::BootloadingError)?;
@@ -855,6 +870,7 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
             schema_cache: NonIterMap::new(),
             callback,
             modules,
+            enable_read_write_set: init_input.enable_read_write_set,
         };
 
         // Perform runtime validation.
@@ -1051,13 +1067,26 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
             .enabled_modules
             .contains(EnabledModules::EXECUTION_TRACE);
 
-        #[cfg(not(feature = "alloc"))]
+        let instruction_deltas_enabled = self
+            .modules
+            .enabled_modules
+            .contains(EnabledModules::INSTRUCTION_DELTAS);
+
+        let read_write_set_enabled = self.enable_read_write_set;
+
         let kernel_trace_enabled = self
             .modules
             .enabled_modules
             .contains(EnabledModules::KERNEL_TRACE);
 
-        let (mut costing_module, runtime_module, execution_trace_module) = self.modules.unpack();
+        let (
+            kernel_trace_module,
+            mut costing_module,
+            runtime_module,
+            execution_trace_module,
+            instruction_deltas_module,
+        ) = self.modules.unpack();
+        let kernel_trace = kernel_trace_module.finalize();
 
         let costing_parameters = costing_module.fee_reserve.costing_parameters();
 
@@ -1076,6 +1105,8 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
             Some(TransactionFeeDetails {
                 execution_cost_breakdown,
                 finalization_cost_breakdown,
+                call_frame_cost_breakdown: cost_breakdown.call_frame_costs.to_breakdown(),
+                instruction_cost_breakdown: cost_breakdown.instruction_costs.to_breakdown(),
             })
         } else {
             None
@@ -1136,6 +1167,18 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
                     }
                 };
 
+                // Collect the substates read and written before they get pruned below
+                let read_write_set = if read_write_set_enabled {
+                    Some(to_read_write_set(&tracked_substates))
+                } else {
+                    None
+                };
+                let instruction_deltas = instruction_deltas_module.finalize(
+                    &paying_vaults,
+                    is_success,
+                    &tracked_substates,
+                );
+
                 // Generate state updates from tracked substates
                 // Note that this process will prune invalid reads
                 let (new_node_ids, state_updates) =
@@ -1178,6 +1221,12 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
                         } else {
                             None
                         },
+                        instruction_deltas: if instruction_deltas_enabled {
+                            Some(instruction_deltas)
+                        } else {
+                            None
+                        },
+                        read_write_set,
                     }),
                 )
             }
@@ -1200,6 +1249,11 @@ impl<C: SystemCallbackObject> KernelCallbackObject for System<C> {
             fee_details,
             result,
             resources_usage: None,
+            kernel_trace: if kernel_trace_enabled {
+                Some(kernel_trace)
+            } else {
+                None
+            },
         };
 
         // Dump summary
@@ -9,13 +9,16 @@ use crate::kernel::kernel_callback_api::{
     ScanSortedSubstatesEvent, SetSubstateEvent, WriteSubstateEvent,
 };
 use crate::object_modules::royalty::ComponentRoyaltyBlueprint;
-use crate::system::actor::{Actor, FunctionActor, MethodActor, MethodType};
+use crate::system::actor::{Actor, BlueprintHookActor, FunctionActor, MethodActor, MethodType};
 use crate::system::module::{InitSystemModule, SystemModule};
 use crate::system::system_callback::System;
 use crate::system::system_callback_api::SystemCallbackObject;
+use crate::track::interface::IOAccess;
 use crate::{
     errors::{CanBeAbortion, RuntimeError, SystemModuleError},
-    transaction::AbortReason,
+    transaction::{
+        AbortReason, CallFrameCostBreakdown, InstructionCostBreakdown, InstructionCosts,
+    },
 };
 use radix_engine_interface::api::AttachedModuleId;
 use radix_engine_interface::blueprints::package::BlueprintVersionKey;
@@ -104,6 +107,265 @@ pub struct CostBreakdown {
     pub execution_cost_breakdown: IndexMap<String, u32>,
     pub finalization_cost_breakdown: IndexMap<String, u32>,
     pub storage_cost_breakdown: IndexMap<StorageType, usize>,
+    pub call_frame_costs: CallFrameCostTracker,
+    pub instruction_costs: InstructionCostTracker,
+}
+
+/// Attributes the execution costs to the call frames in which they were consumed.
+#[derive(Debug, Clone)]
+pub struct CallFrameCostTracker {
+    frames: Vec<TrackedCallFrame>,
+    current_frame: usize,
+}
+
+#[derive(Debug, Clone)]
+struct TrackedCallFrame {
+    actor: String,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    self_execution_cost_units: u32,
+}
+
+impl Default for CallFrameCostTracker {
+    fn default() -> Self {
+        Self {
+            frames: vec![TrackedCallFrame {
+                actor: "Root".to_string(),
+                parent: None,
+                children: Vec::new(),
+                self_execution_cost_units: 0,
+            }],
+            current_frame: 0,
+        }
+    }
+}
+
+impl CallFrameCostTracker {
+    pub fn enter_call_frame(&mut self, actor: &Actor) {
+        let actor = match actor {
+            Actor::Root => "Root".to_string(),
+            Actor::Method(MethodActor { ident, .. })
+            | Actor::Function(FunctionActor { ident, .. }) => {
+                format!(
+                    "{}::{}",
+                    actor
+                        .blueprint_id()
+                        .map(|id| id.blueprint_name)
+                        .unwrap_or_default(),
+                    ident
+                )
+            }
+            Actor::BlueprintHook(BlueprintHookActor {
+                hook, blueprint_id, ..
+            }) => format!("{}::{:?}", blueprint_id.blueprint_name, hook),
+        };
+        let index = self.frames.len();
+        self.frames.push(TrackedCallFrame {
+            actor,
+            parent: Some(self.current_frame),
+            children: Vec::new(),
+            self_execution_cost_units: 0,
+        });
+        self.frames[self.current_frame].children.push(index);
+        self.current_frame = index;
+    }
+
+    pub fn exit_call_frame(&mut self) {
+        if let Some(parent) = self.frames[self.current_frame].parent {
+            self.current_frame = parent;
+        }
+    }
+
+    pub fn add_execution_cost(&mut self, cost_units: u32) {
+        self.frames[self.current_frame]
+            .self_execution_cost_units
+            .add_assign(cost_units);
+    }
+
+    pub fn to_breakdown(&self) -> CallFrameCostBreakdown {
+        self.to_breakdown_of(0)
+    }
+
+    fn to_breakdown_of(&self, index: usize) -> CallFrameCostBreakdown {
+        let frame = &self.frames[index];
+        let children = frame
+            .children
+            .iter()
+            .map(|child| self.to_breakdown_of(*child))
+            .collect::<Vec<_>>();
+        let total_execution_cost_units = children
+            .iter()
+            .map(|child| child.total_execution_cost_units)
+            .fold(frame.self_execution_cost_units, |total, cost| {
+                total.saturating_add(cost)
+            });
+        CallFrameCostBreakdown {
+            actor: frame.actor.clone(),
+            self_execution_cost_units: frame.self_execution_cost_units,
+            total_execution_cost_units,
+            children,
+        }
+    }
+}
+
+/// Attributes the costs to the manifest instructions which incurred them.
+///
+/// The finalization and state storage costs of a substate are attributed to the instruction which
+/// last updated it, and those of an event or a log to the instruction which emitted it.
+#[derive(Debug, Clone, Default)]
+pub struct InstructionCostTracker {
+    /// The depth of the current call frame
+    depth: usize,
+    /// The current instruction, and the depth of the transaction processor executing it
+    current_instruction: Option<(usize, usize)>,
+    instructions: Vec<InstructionCosts>,
+    overhead: InstructionCosts,
+    /// The number of events and logs emitted before each instruction
+    emitted_before: Vec<(usize, usize)>,
+    /// The instruction which last updated each substate, if any
+    substate_updaters: IndexMap<(NodeId, PartitionNumber, SubstateKey), usize>,
+}
+
+impl InstructionCostTracker {
+    pub fn update_instruction_index(&mut self, new_index: usize, events: usize, logs: usize) {
+        self.current_instruction = Some((new_index, self.depth));
+        if self.instructions.len() <= new_index {
+            self.instructions
+                .resize(new_index + 1, InstructionCosts::default());
+            self.emitted_before.resize(new_index + 1, (events, logs));
+        }
+    }
+
+    pub fn enter_call_frame(&mut self) {
+        self.depth += 1;
+    }
+
+    pub fn exit_call_frame(&mut self) {
+        // Nothing is attributed to the instructions once the transaction processor returns
+        if let Some((_, depth)) = self.current_instruction {
+            if self.depth == depth {
+                self.current_instruction = None;
+            }
+        }
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    pub fn add_execution_cost(&mut self, costing_entry: &ExecutionCostingEntry, cost_units: u32) {
+        let instruction = self.current_instruction.map(|(index, _)| index);
+        if let Some(IOAccess::TrackSubstateUpdated {
+            canonical_substate_key,
+            ..
+        }) = io_access(costing_entry)
+        {
+            let key = (
+                canonical_substate_key.node_id,
+                canonical_substate_key.partition_number,
+                canonical_substate_key.substate_key.clone(),
+            );
+            match instruction {
+                Some(index) => {
+                    self.substate_updaters.insert(key, index);
+                }
+                None => {
+                    self.substate_updaters.swap_remove(&key);
+                }
+            }
+        }
+        self.costs_of(instruction)
+            .execution_cost_units
+            .add_assign(cost_units);
+    }
+
+    pub fn add_finalization_cost(
+        &mut self,
+        costing_entry: &FinalizationCostingEntry,
+        fee_table: &FeeTable,
+    ) {
+        match costing_entry {
+            FinalizationCostingEntry::CommitStateUpdates { store_commit } => {
+                let key = store_commit.canonical_substate_key();
+                let instruction = self
+                    .substate_updaters
+                    .get(&(key.node_id, key.partition_number, key.substate_key.clone()))
+                    .cloned();
+                let costs = self.costs_of(instruction);
+                costs
+                    .finalization_cost_units
+                    .add_assign(fee_table.commit_state_updates_cost(store_commit));
+                costs
+                    .state_storage_bytes
+                    .add_assign(store_commit.len_increase());
+            }
+            FinalizationCostingEntry::CommitEvents { events } => {
+                for (index, event) in events.iter().enumerate() {
+                    let instruction = self.emitter_of(index, |(events, _)| events);
+                    let costs = self.costs_of(instruction);
+                    costs
+                        .finalization_cost_units
+                        .add_assign(fee_table.commit_event_cost(event));
+                    costs.archive_storage_bytes.add_assign(event.len());
+                }
+            }
+            FinalizationCostingEntry::CommitLogs { logs } => {
+                for (index, log) in logs.iter().enumerate() {
+                    let instruction = self.emitter_of(index, |(_, logs)| logs);
+                    let costs = self.costs_of(instruction);
+                    costs
+                        .finalization_cost_units
+                        .add_assign(fee_table.commit_log_cost(log));
+                    costs.archive_storage_bytes.add_assign(log.1.len());
+                }
+            }
+        }
+    }
+
+    pub fn to_breakdown(&self) -> InstructionCostBreakdown {
+        InstructionCostBreakdown {
+            instructions: self.instructions.clone(),
+            overhead: self.overhead,
+        }
+    }
+
+    fn costs_of(&mut self, instruction: Option<usize>) -> &mut InstructionCosts {
+        match instruction {
+            Some(index) => &mut self.instructions[index],
+            None => &mut self.overhead,
+        }
+    }
+
+    /// Returns the instruction which emitted the event or log at the given position.
+    fn emitter_of(&self, index: usize, count: impl Fn((usize, usize)) -> usize) -> Option<usize> {
+        self.emitted_before
+            .iter()
+            .rposition(|emitted_before| count(*emitted_before) <= index)
+    }
+}
+
+fn io_access<'a>(costing_entry: &'a ExecutionCostingEntry) -> Option<&'a IOAccess> {
+    match costing_entry {
+        ExecutionCostingEntry::CreateNode {
+            event: CreateNodeEvent::IOAccess(io_access),
+        }
+        | ExecutionCostingEntry::DropNode {
+            event: DropNodeEvent::IOAccess(io_access),
+        }
+        | ExecutionCostingEntry::MoveModule {
+            event: MoveModuleEvent::IOAccess(io_access),
+        }
+        | ExecutionCostingEntry::WriteSubstate {
+            event: WriteSubstateEvent::IOAccess(io_access),
+        }
+        | ExecutionCostingEntry::SetSubstate {
+            event: SetSubstateEvent::IOAccess(io_access),
+        }
+        | ExecutionCostingEntry::RemoveSubstate {
+            event: RemoveSubstateEvent::IOAccess(io_access),
+        }
+        | ExecutionCostingEntry::DrainSubstates {
+            event: DrainSubstatesEvent::IOAccess(io_access),
+        } => Some(io_access),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -138,6 +400,12 @@ impl CostingModule {
                 .entry(key)
                 .or_default()
                 .add_assign(cost_units);
+            cost_breakdown
+                .call_frame_costs
+                .add_execution_cost(cost_units);
+            cost_breakdown
+                .instruction_costs
+                .add_execution_cost(&costing_entry, cost_units);
         }
 
         Ok(())
@@ -179,6 +447,19 @@ impl CostingModule {
                 .entry(key)
                 .or_default()
                 .add_assign(cost_units);
+            cost_breakdown
+                .call_frame_costs
+                .add_execution_cost(cost_units);
+            // The payload and signatures costs are derived from the transaction alone
+            if !matches!(
+                costing_entry,
+                ExecutionCostingEntry::ValidateTxPayload { .. }
+                    | ExecutionCostingEntry::VerifyTxSignatures { .. }
+            ) {
+                cost_breakdown
+                    .instruction_costs
+                    .add_execution_cost(&costing_entry, cost_units);
+            }
         }
 
         Ok(())
@@ -225,6 +506,9 @@ impl CostingModule {
                 .entry(key)
                 .or_default()
                 .add_assign(cost_units);
+            cost_breakdown
+                .instruction_costs
+                .add_finalization_cost(&costing_entry, &self.fee_table);
         }
 
         Ok(())
@@ -252,6 +536,28 @@ impl CostingModule {
         Ok(())
     }
 
+    pub fn enter_call_frame(&mut self, actor: &Actor) {
+        if let Some(cost_breakdown) = &mut self.cost_breakdown {
+            cost_breakdown.call_frame_costs.enter_call_frame(actor);
+            cost_breakdown.instruction_costs.enter_call_frame();
+        }
+    }
+
+    pub fn exit_call_frame(&mut self) {
+        if let Some(cost_breakdown) = &mut self.cost_breakdown {
+            cost_breakdown.call_frame_costs.exit_call_frame();
+            cost_breakdown.instruction_costs.exit_call_frame();
+        }
+    }
+
+    pub fn update_instruction_index(&mut self, new_index: usize, events: usize, logs: usize) {
+        if let Some(cost_breakdown) = &mut self.cost_breakdown {
+            cost_breakdown
+                .instruction_costs
+                .update_instruction_index(new_index, events, logs);
+        }
+    }
+
     pub fn lock_fee(
         &mut self,
         vault_id: NodeId,
@@ -312,6 +618,10 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for CostingModule {
     ) -> Result<(), RuntimeError> {
         // Skip invocation costing for transaction processor
         if api.kernel_get_current_depth() == 0 {
+            api.kernel_get_system()
+                .modules
+                .costing
+                .enter_call_frame(&invocation.call_frame_data);
             return Ok(());
         }
 
@@ -324,6 +634,12 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for CostingModule {
             })
             .map_err(|e| RuntimeError::SystemModuleError(SystemModuleError::CostingError(e)))?;
 
+        // The invocation's own costs (e.g. the above) are attributed to the caller
+        api.kernel_get_system()
+            .modules
+            .costing
+            .enter_call_frame(&invocation.call_frame_data);
+
         // Identify the function, and optional component address
         let (optional_blueprint_id, ident, maybe_object_royalties) = {
             let (maybe_component, ident) = match &invocation.call_frame_data {
@@ -391,6 +707,8 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for CostingModule {
         api: &mut Y,
         output: &IndexedScryptoValue,
     ) -> Result<(), RuntimeError> {
+        api.kernel_get_system().modules.costing.exit_call_frame();
+
         // Skip invocation costing for transaction processor
         if api.kernel_get_current_depth() == 0 {
             return Ok(());
@@ -0,0 +1,819 @@
+use super::FeeTable;
+use crate::blueprints::package::{
+    PackageBlueprintVersionRoyaltyConfigEntrySubstate, PackageFeature,
+};
+use crate::internal_prelude::*;
+use crate::object_modules::royalty::ComponentRoyaltyMethodAmountEntryPayload;
+use crate::system::system_db_reader::SystemDatabaseReader;
+use crate::transaction::{CostingParameters, InstructionCosts, TransactionReceipt};
+use radix_engine_interface::api::AttachedModuleId;
+use radix_engine_interface::blueprints::package::*;
+use radix_substate_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
+use radix_transactions::model::{
+    DynamicGlobalAddress, DynamicPackageAddress, InstructionV1, InstructionsV1,
+};
+
+/// The unit of work a historical cost is recorded against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExecutionCostKey {
+    /// A function or method invocation, by the blueprint of the callee.
+    Invocation {
+        blueprint_id: BlueprintId,
+        ident: String,
+    },
+    /// An instruction which doesn't invoke a blueprint, by its manifest name.
+    Instruction(String),
+    /// The costs of a transaction not incurred by any of its instructions, e.g. of booting it.
+    Overhead,
+}
+
+/// Inclusive bounds on a cost, in units or bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CostRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl CostRange {
+    pub fn new(min: u32, max: u32) -> Self {
+        Self { min, max }
+    }
+
+    pub fn exactly(cost: u32) -> Self {
+        Self::new(cost, cost)
+    }
+
+    fn record(&mut self, cost: u32) {
+        self.min = self.min.min(cost);
+        self.max = self.max.max(cost);
+    }
+}
+
+/// The historical costs of an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RecordedCosts {
+    pub execution_cost_units: CostRange,
+    pub finalization_cost_units: CostRange,
+    pub state_storage_bytes: CostRange,
+    /// The size of the events and logs
+    pub archive_storage_bytes: CostRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionCostProfileError {
+    InvalidLine {
+        line: usize,
+        reason: String,
+    },
+    /// The receipt has no cost breakdown, i.e. the transaction was executed without
+    /// `ExecutionConfig::with_cost_breakdown`
+    MissingCostBreakdown,
+    /// The transaction wasn't committed successfully, so not all of its instructions were executed
+    UnsuccessfulTransaction,
+}
+
+/// Historical per-instruction costs, used to estimate fees without execution.
+///
+/// Profiles are stored as CSV, one entry per line, in either of these forms:
+/// ```text
+/// invocation,<package address hex>,<blueprint name>,<ident>,<costs>
+/// instruction,<instruction name>,<costs>
+/// overhead,<costs>
+/// ```
+/// where `<costs>` are the minimum and maximum execution cost units, finalization cost units,
+/// state storage bytes and archive storage bytes, i.e. eight comma-separated numbers.
+/// Empty lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExecutionCostProfile {
+    costs: IndexMap<ExecutionCostKey, RecordedCosts>,
+}
+
+impl ExecutionCostProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_csv(csv: &str) -> Result<Self, ExecutionCostProfileError> {
+        let mut profile = Self::new();
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid_line = |reason: &str| ExecutionCostProfileError::InvalidLine {
+                line: index + 1,
+                reason: reason.to_string(),
+            };
+
+            let tokens: Vec<&str> = line.split(',').map(|token| token.trim()).collect();
+            let (key, cost_tokens) = match tokens.as_slice() {
+                ["invocation", package_address, blueprint_name, ident, costs @ ..] => {
+                    let package_address = PackageAddress::try_from_hex(package_address)
+                        .ok_or_else(|| invalid_line("Invalid package address"))?;
+                    (
+                        ExecutionCostKey::Invocation {
+                            blueprint_id: BlueprintId::new(&package_address, *blueprint_name),
+                            ident: ident.to_string(),
+                        },
+                        costs,
+                    )
+                }
+                ["instruction", name, costs @ ..] => {
+                    (ExecutionCostKey::Instruction(name.to_string()), costs)
+                }
+                ["overhead", costs @ ..] => (ExecutionCostKey::Overhead, costs),
+                _ => return Err(invalid_line("Unknown entry kind")),
+            };
+
+            let costs = cost_tokens
+                .iter()
+                .map(|token| u32::from_str(token))
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|_| invalid_line("Invalid cost"))?;
+            let [execution_min, execution_max, finalization_min, finalization_max, state_storage_min, state_storage_max, archive_storage_min, archive_storage_max] =
+                costs.as_slice()
+            else {
+                return Err(invalid_line("Expected eight costs"));
+            };
+            profile.insert(
+                key,
+                RecordedCosts {
+                    execution_cost_units: CostRange::new(*execution_min, *execution_max),
+                    finalization_cost_units: CostRange::new(*finalization_min, *finalization_max),
+                    state_storage_bytes: CostRange::new(*state_storage_min, *state_storage_max),
+                    archive_storage_bytes: CostRange::new(
+                        *archive_storage_min,
+                        *archive_storage_max,
+                    ),
+                },
+            );
+        }
+        Ok(profile)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for (key, costs) in &self.costs {
+            match key {
+                ExecutionCostKey::Invocation {
+                    blueprint_id,
+                    ident,
+                } => csv.push_str(&format!(
+                    "invocation,{},{},{}",
+                    blueprint_id.package_address.to_hex(),
+                    blueprint_id.blueprint_name,
+                    ident
+                )),
+                ExecutionCostKey::Instruction(name) => {
+                    csv.push_str(&format!("instruction,{}", name))
+                }
+                ExecutionCostKey::Overhead => csv.push_str("overhead"),
+            }
+            csv.push_str(&format!(
+                ",{},{},{},{},{},{},{},{}\n",
+                costs.execution_cost_units.min,
+                costs.execution_cost_units.max,
+                costs.finalization_cost_units.min,
+                costs.finalization_cost_units.max,
+                costs.state_storage_bytes.min,
+                costs.state_storage_bytes.max,
+                costs.archive_storage_bytes.min,
+                costs.archive_storage_bytes.max,
+            ));
+        }
+        csv
+    }
+
+    pub fn insert(&mut self, key: ExecutionCostKey, costs: RecordedCosts) {
+        self.costs.insert(key, costs);
+    }
+
+    /// Records an observed cost, widening the bounds of the entry if there is one already.
+    pub fn record(&mut self, key: ExecutionCostKey, costs: &InstructionCosts) {
+        let state_storage_bytes = u32::try_from(costs.state_storage_bytes).unwrap_or(u32::MAX);
+        let archive_storage_bytes = u32::try_from(costs.archive_storage_bytes).unwrap_or(u32::MAX);
+        match self.costs.get_mut(&key) {
+            Some(recorded) => {
+                recorded
+                    .execution_cost_units
+                    .record(costs.execution_cost_units);
+                recorded
+                    .finalization_cost_units
+                    .record(costs.finalization_cost_units);
+                recorded.state_storage_bytes.record(state_storage_bytes);
+                recorded.archive_storage_bytes.record(archive_storage_bytes);
+            }
+            None => {
+                self.costs.insert(
+                    key,
+                    RecordedCosts {
+                        execution_cost_units: CostRange::exactly(costs.execution_cost_units),
+                        finalization_cost_units: CostRange::exactly(costs.finalization_cost_units),
+                        state_storage_bytes: CostRange::exactly(state_storage_bytes),
+                        archive_storage_bytes: CostRange::exactly(archive_storage_bytes),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Records the costs of the instructions of a successfully committed transaction, as
+    /// attributed by the cost breakdown of its receipt.
+    ///
+    /// The callees are resolved against the given database, so it must contain the components
+    /// called by address. Instructions whose callee can't be resolved aren't recorded.
+    pub fn record_receipt<S: SubstateDatabase>(
+        &mut self,
+        substate_db: &S,
+        instructions: &InstructionsV1,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), ExecutionCostProfileError> {
+        if !receipt.is_commit_success() {
+            return Err(ExecutionCostProfileError::UnsuccessfulTransaction);
+        }
+        let breakdown = &receipt
+            .fee_details
+            .as_ref()
+            .ok_or(ExecutionCostProfileError::MissingCostBreakdown)?
+            .instruction_cost_breakdown;
+
+        let reader = SystemDatabaseReader::new(substate_db);
+        for (key, costs) in cost_keys(&reader, instructions)
+            .into_iter()
+            .zip(&breakdown.instructions)
+        {
+            if let Some(key) = key {
+                self.record(key, costs);
+            }
+        }
+        self.record(ExecutionCostKey::Overhead, &breakdown.overhead);
+        Ok(())
+    }
+
+    pub fn get(&self, key: &ExecutionCostKey) -> Option<&RecordedCosts> {
+        self.costs.get(key)
+    }
+}
+
+/// Bounds on a cost. The upper bound is `None` if it can't be determined, i.e. when some of the
+/// instructions, or the overhead of the transaction, have no recorded costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CostBounds<T> {
+    pub lower: T,
+    pub upper: Option<T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FeeEstimate {
+    pub execution_cost_units: CostBounds<u32>,
+    pub finalization_cost_units: CostBounds<u32>,
+
+    pub execution_cost_in_xrd: CostBounds<Decimal>,
+    pub finalization_cost_in_xrd: CostBounds<Decimal>,
+    /// The cost of both the state and the archive storage
+    pub storage_cost_in_xrd: CostBounds<Decimal>,
+    /// The royalties of the functions and methods invoked directly by the manifest. Royalties of
+    /// the invocations made by these can't be derived statically.
+    pub royalty_cost_in_xrd: Decimal,
+
+    /// The indices of the instructions which have no recorded costs
+    pub unprofiled_instructions: Vec<usize>,
+    /// Whether the overhead of the transaction has no recorded costs
+    pub unprofiled_overhead: bool,
+}
+
+impl FeeEstimate {
+    pub fn total_cost_in_xrd(&self) -> CostBounds<Decimal> {
+        let lower = self
+            .execution_cost_in_xrd
+            .lower
+            .checked_add(self.finalization_cost_in_xrd.lower)
+            .and_then(|x| x.checked_add(self.storage_cost_in_xrd.lower))
+            .and_then(|x| x.checked_add(self.royalty_cost_in_xrd))
+            .unwrap_or(Decimal::MAX);
+        let upper = match (
+            self.execution_cost_in_xrd.upper,
+            self.finalization_cost_in_xrd.upper,
+            self.storage_cost_in_xrd.upper,
+        ) {
+            (Some(execution), Some(finalization), Some(storage)) => Some(
+                execution
+                    .checked_add(finalization)
+                    .and_then(|x| x.checked_add(storage))
+                    .and_then(|x| x.checked_add(self.royalty_cost_in_xrd))
+                    .unwrap_or(Decimal::MAX),
+            ),
+            _ => None,
+        };
+        CostBounds { lower, upper }
+    }
+}
+
+/// Estimates the fees of a manifest by walking its instructions, without executing it.
+///
+/// The execution, finalization and state storage costs of each instruction are taken from an
+/// [`ExecutionCostProfile`], while the transaction payload and signature validation costs are
+/// derived from the [`FeeTable`], and the royalties from the royalty configs in the database.
+pub struct FeeEstimator<'a, S: SubstateDatabase> {
+    reader: SystemDatabaseReader<'a, S>,
+    fee_table: FeeTable,
+    costing_parameters: CostingParameters,
+    profile: &'a ExecutionCostProfile,
+}
+
+impl<'a, S: SubstateDatabase> FeeEstimator<'a, S> {
+    pub fn new(
+        substate_db: &'a S,
+        costing_parameters: CostingParameters,
+        profile: &'a ExecutionCostProfile,
+    ) -> Self {
+        Self {
+            reader: SystemDatabaseReader::new(substate_db),
+            fee_table: FeeTable::new(),
+            costing_parameters,
+            profile,
+        }
+    }
+
+    pub fn estimate(
+        &self,
+        instructions: &InstructionsV1,
+        payload_size: usize,
+        num_of_signature_validations: usize,
+    ) -> FeeEstimate {
+        let base_execution_cost_units = self
+            .fee_table
+            .validate_tx_payload_cost(payload_size)
+            .saturating_add(
+                self.fee_table
+                    .verify_tx_signatures_cost(num_of_signature_validations),
+            );
+        let mut costs = RecordedCosts {
+            execution_cost_units: CostRange::exactly(base_execution_cost_units),
+            ..Default::default()
+        };
+        let mut royalty_cost_in_xrd = Decimal::ZERO;
+        let mut unprofiled_instructions = Vec::new();
+
+        let keys = cost_keys(&self.reader, instructions);
+        for (index, (instruction, key)) in instructions.0.iter().zip(keys).enumerate() {
+            let Some(key) = key else {
+                unprofiled_instructions.push(index);
+                continue;
+            };
+
+            if let ExecutionCostKey::Invocation {
+                blueprint_id,
+                ident,
+            } = &key
+            {
+                royalty_cost_in_xrd = royalty_cost_in_xrd
+                    .checked_add(self.royalty_cost(instruction, blueprint_id, ident))
+                    .unwrap_or(Decimal::MAX);
+            }
+
+            match self.profile.get(&key) {
+                Some(recorded) => costs = add_costs(costs, recorded),
+                None => unprofiled_instructions.push(index),
+            }
+        }
+        let unprofiled_overhead = match self.profile.get(&ExecutionCostKey::Overhead) {
+            Some(recorded) => {
+                costs = add_costs(costs, recorded);
+                false
+            }
+            None => true,
+        };
+
+        let is_bounded = unprofiled_instructions.is_empty() && !unprofiled_overhead;
+        let RecordedCosts {
+            execution_cost_units,
+            finalization_cost_units,
+            state_storage_bytes,
+            archive_storage_bytes,
+        } = costs;
+        let execution_cost_units = CostBounds {
+            lower: execution_cost_units.min,
+            upper: is_bounded.then(|| {
+                execution_cost_units
+                    .max
+                    .min(self.costing_parameters.execution_cost_unit_limit)
+            }),
+        };
+        let finalization_cost_units = CostBounds {
+            lower: finalization_cost_units.min,
+            upper: is_bounded.then(|| {
+                finalization_cost_units
+                    .max
+                    .min(self.costing_parameters.finalization_cost_unit_limit)
+            }),
+        };
+        let storage_cost = |state_storage_bytes: u32, archive_storage_bytes: u32| {
+            let archive_storage_bytes =
+                (archive_storage_bytes as usize).saturating_add(payload_size);
+            self.costing_parameters
+                .state_storage_price
+                .checked_mul(state_storage_bytes)
+                .zip(
+                    self.costing_parameters
+                        .archive_storage_price
+                        .checked_mul(archive_storage_bytes),
+                )
+                .and_then(|(state, archive)| state.checked_add(archive))
+                .unwrap_or(Decimal::MAX)
+        };
+
+        FeeEstimate {
+            execution_cost_in_xrd: self.to_xrd(
+                &execution_cost_units,
+                self.costing_parameters.execution_cost_unit_price,
+            ),
+            finalization_cost_in_xrd: self.to_xrd(
+                &finalization_cost_units,
+                self.costing_parameters.finalization_cost_unit_price,
+            ),
+            storage_cost_in_xrd: CostBounds {
+                lower: storage_cost(state_storage_bytes.min, archive_storage_bytes.min),
+                upper: is_bounded
+                    .then(|| storage_cost(state_storage_bytes.max, archive_storage_bytes.max)),
+            },
+            execution_cost_units,
+            finalization_cost_units,
+            royalty_cost_in_xrd,
+            unprofiled_instructions,
+            unprofiled_overhead,
+        }
+    }
+
+    fn to_xrd(
+        &self,
+        cost_units: &CostBounds<u32>,
+        cost_unit_price: Decimal,
+    ) -> CostBounds<Decimal> {
+        let to_xrd = |units: u32| cost_unit_price.checked_mul(units).unwrap_or(Decimal::MAX);
+        CostBounds {
+            lower: to_xrd(cost_units.lower),
+            upper: cost_units.upper.map(to_xrd),
+        }
+    }
+
+    /// Returns the package and component royalties charged for invoking the given function or
+    /// method directly from the manifest.
+    fn royalty_cost(
+        &self,
+        instruction: &InstructionV1,
+        blueprint_id: &BlueprintId,
+        ident: &str,
+    ) -> Decimal {
+        let receiver = match instruction {
+            InstructionV1::CallFunction { .. } => None,
+            InstructionV1::CallMethod {
+                address: DynamicGlobalAddress::Static(address),
+                ..
+            } => Some(*address.as_node_id()),
+            // Newly allocated components have no component royalties configured yet
+            InstructionV1::CallMethod {
+                address: DynamicGlobalAddress::Named(_),
+                ..
+            } => None,
+            InstructionV1::CallDirectVaultMethod { .. } => None,
+            // Module methods aren't charged royalties
+            _ => return Decimal::ZERO,
+        };
+
+        let mut royalty_amounts = Vec::new();
+
+        let package_royalty_enabled = self
+            .reader
+            .get_object_info(*blueprint_id.package_address.as_node_id())
+            .map(|object_info| {
+                object_info
+                    .blueprint_info
+                    .features
+                    .contains(PackageFeature::PackageRoyalty.feature_name())
+            })
+            .unwrap_or(false);
+        if package_royalty_enabled {
+            let royalty_config = self
+                .reader
+                .fetch_substate::<SpreadPrefixKeyMapper, PackageBlueprintVersionRoyaltyConfigEntrySubstate>(
+                    blueprint_id.package_address.as_node_id(),
+                    MAIN_BASE_PARTITION
+                        .at_offset(PACKAGE_ROYALTY_PARTITION_OFFSET)
+                        .unwrap(),
+                    &SubstateKey::Map(
+                        scrypto_encode(&BlueprintVersionKey::new_default(
+                            blueprint_id.blueprint_name.as_str(),
+                        ))
+                        .unwrap(),
+                    ),
+                )
+                .and_then(|substate| substate.into_value());
+            if let Some(royalty_config) = royalty_config {
+                if let PackageRoyaltyConfig::Enabled(amounts) =
+                    royalty_config.fully_update_and_into_latest_version()
+                {
+                    royalty_amounts.extend(amounts.get(ident).cloned());
+                }
+            }
+        }
+
+        if let Some(receiver) = receiver {
+            let has_royalty_module = match self.reader.get_object_info(receiver) {
+                Ok(ObjectInfo {
+                    object_type: ObjectType::Global { modules },
+                    ..
+                }) => modules.contains_key(&AttachedModuleId::Royalty),
+                _ => false,
+            };
+            if has_royalty_module {
+                let royalty_amount = self
+                    .reader
+                    .fetch_substate::<SpreadPrefixKeyMapper, KeyValueEntrySubstate<ComponentRoyaltyMethodAmountEntryPayload>>(
+                        &receiver,
+                        ROYALTY_BASE_PARTITION
+                            .at_offset(ROYALTY_CONFIG_PARTITION_OFFSET)
+                            .unwrap(),
+                        &SubstateKey::Map(scrypto_encode(ident).unwrap()),
+                    )
+                    .and_then(|substate| substate.into_value())
+                    .map(|amount| amount.fully_update_and_into_latest_version());
+                royalty_amounts.extend(royalty_amount);
+            }
+        }
+
+        royalty_amounts
+            .into_iter()
+            .map(|royalty_amount| match royalty_amount {
+                RoyaltyAmount::Xrd(xrd_amount) => xrd_amount,
+                RoyaltyAmount::Usd(usd_amount) => usd_amount
+                    .checked_mul(self.costing_parameters.usd_price)
+                    .unwrap_or(Decimal::MAX),
+                RoyaltyAmount::Free => Decimal::ZERO,
+            })
+            .fold(Decimal::ZERO, |total, amount| {
+                total.checked_add(amount).unwrap_or(Decimal::MAX)
+            })
+    }
+}
+
+/// Returns the keys the costs of the instructions are recorded against, or `None` for those whose
+/// callee can't be resolved statically.
+fn cost_keys<S: SubstateDatabase>(
+    reader: &SystemDatabaseReader<S>,
+    instructions: &InstructionsV1,
+) -> Vec<Option<ExecutionCostKey>> {
+    let mut allocated_blueprints = Vec::new();
+    instructions
+        .0
+        .iter()
+        .map(|instruction| {
+            if let InstructionV1::AllocateGlobalAddress {
+                package_address,
+                blueprint_name,
+            } = instruction
+            {
+                allocated_blueprints.push(BlueprintId::new(package_address, blueprint_name));
+            }
+            cost_key(reader, instruction, &allocated_blueprints)
+        })
+        .collect()
+}
+
+/// Returns the key the costs of the instruction are recorded against, or `None` if the
+/// callee can't be resolved statically.
+fn cost_key<S: SubstateDatabase>(
+    reader: &SystemDatabaseReader<S>,
+    instruction: &InstructionV1,
+    allocated_blueprints: &[BlueprintId],
+) -> Option<ExecutionCostKey> {
+    let (blueprint_id, ident) = match instruction {
+        InstructionV1::CallFunction {
+            package_address: DynamicPackageAddress::Static(package_address),
+            blueprint_name,
+            function_name,
+            ..
+        } => (
+            BlueprintId::new(package_address, blueprint_name),
+            function_name,
+        ),
+        InstructionV1::CallFunction { .. } => return None,
+        InstructionV1::CallMethod {
+            address,
+            method_name,
+            ..
+        } => {
+            let blueprint_id = match address {
+                DynamicGlobalAddress::Static(address) => {
+                    reader
+                        .get_object_info(*address.as_node_id())
+                        .ok()?
+                        .blueprint_info
+                        .blueprint_id
+                }
+                DynamicGlobalAddress::Named(id) => allocated_blueprints.get(*id as usize)?.clone(),
+            };
+            (blueprint_id, method_name)
+        }
+        InstructionV1::CallRoyaltyMethod { method_name, .. } => (
+            BlueprintId::new(&ROYALTY_MODULE_PACKAGE, COMPONENT_ROYALTY_BLUEPRINT),
+            method_name,
+        ),
+        InstructionV1::CallMetadataMethod { method_name, .. } => (
+            BlueprintId::new(&METADATA_MODULE_PACKAGE, METADATA_BLUEPRINT),
+            method_name,
+        ),
+        InstructionV1::CallRoleAssignmentMethod { method_name, .. } => (
+            BlueprintId::new(&ROLE_ASSIGNMENT_MODULE_PACKAGE, ROLE_ASSIGNMENT_BLUEPRINT),
+            method_name,
+        ),
+        InstructionV1::CallDirectVaultMethod {
+            address,
+            method_name,
+            ..
+        } => (
+            reader
+                .get_object_info(*address.as_node_id())
+                .ok()?
+                .blueprint_info
+                .blueprint_id,
+            method_name,
+        ),
+        instruction => {
+            return Some(ExecutionCostKey::Instruction(
+                instruction_name(instruction).to_string(),
+            ))
+        }
+    };
+
+    Some(ExecutionCostKey::Invocation {
+        blueprint_id,
+        ident: ident.clone(),
+    })
+}
+
+fn add_range(a: CostRange, b: CostRange) -> CostRange {
+    CostRange {
+        min: a.min.saturating_add(b.min),
+        max: a.max.saturating_add(b.max),
+    }
+}
+
+fn add_costs(a: RecordedCosts, b: &RecordedCosts) -> RecordedCosts {
+    RecordedCosts {
+        execution_cost_units: add_range(a.execution_cost_units, b.execution_cost_units),
+        finalization_cost_units: add_range(a.finalization_cost_units, b.finalization_cost_units),
+        state_storage_bytes: add_range(a.state_storage_bytes, b.state_storage_bytes),
+        archive_storage_bytes: add_range(a.archive_storage_bytes, b.archive_storage_bytes),
+    }
+}
+
+/// The manifest name of an instruction which doesn't invoke a blueprint.
+fn instruction_name(instruction: &InstructionV1) -> &'static str {
+    match instruction {
+        InstructionV1::TakeAllFromWorktop { .. } => "TAKE_ALL_FROM_WORKTOP",
+        InstructionV1::TakeFromWorktop { .. } => "TAKE_FROM_WORKTOP",
+        InstructionV1::TakeNonFungiblesFromWorktop { .. } => "TAKE_NON_FUNGIBLES_FROM_WORKTOP",
+        InstructionV1::ReturnToWorktop { .. } => "RETURN_TO_WORKTOP",
+        InstructionV1::AssertWorktopContainsAny { .. } => "ASSERT_WORKTOP_CONTAINS_ANY",
+        InstructionV1::AssertWorktopContains { .. } => "ASSERT_WORKTOP_CONTAINS",
+        InstructionV1::AssertWorktopContainsNonFungibles { .. } => {
+            "ASSERT_WORKTOP_CONTAINS_NON_FUNGIBLES"
+        }
+        InstructionV1::PopFromAuthZone => "POP_FROM_AUTH_ZONE",
+        InstructionV1::PushToAuthZone { .. } => "PUSH_TO_AUTH_ZONE",
+        InstructionV1::CreateProofFromAuthZoneOfAmount { .. } => {
+            "CREATE_PROOF_FROM_AUTH_ZONE_OF_AMOUNT"
+        }
+        InstructionV1::CreateProofFromAuthZoneOfNonFungibles { .. } => {
+            "CREATE_PROOF_FROM_AUTH_ZONE_OF_NON_FUNGIBLES"
+        }
+        InstructionV1::CreateProofFromAuthZoneOfAll { .. } => "CREATE_PROOF_FROM_AUTH_ZONE_OF_ALL",
+        InstructionV1::DropAuthZoneProofs => "DROP_AUTH_ZONE_PROOFS",
+        InstructionV1::DropAuthZoneRegularProofs => "DROP_AUTH_ZONE_REGULAR_PROOFS",
+        InstructionV1::DropAuthZoneSignatureProofs => "DROP_AUTH_ZONE_SIGNATURE_PROOFS",
+        InstructionV1::CreateProofFromBucketOfAmount { .. } => "CREATE_PROOF_FROM_BUCKET_OF_AMOUNT",
+        InstructionV1::CreateProofFromBucketOfNonFungibles { .. } => {
+            "CREATE_PROOF_FROM_BUCKET_OF_NON_FUNGIBLES"
+        }
+        InstructionV1::CreateProofFromBucketOfAll { .. } => "CREATE_PROOF_FROM_BUCKET_OF_ALL",
+        InstructionV1::BurnResource { .. } => "BURN_RESOURCE",
+        InstructionV1::CloneProof { .. } => "CLONE_PROOF",
+        InstructionV1::DropProof { .. } => "DROP_PROOF",
+        InstructionV1::DropNamedProofs => "DROP_NAMED_PROOFS",
+        InstructionV1::DropAllProofs => "DROP_ALL_PROOFS",
+        InstructionV1::AllocateGlobalAddress { .. } => "ALLOCATE_GLOBAL_ADDRESS",
+        InstructionV1::CallFunction { .. } => "CALL_FUNCTION",
+        InstructionV1::CallMethod { .. } => "CALL_METHOD",
+        InstructionV1::CallRoyaltyMethod { .. } => "CALL_ROYALTY_METHOD",
+        InstructionV1::CallMetadataMethod { .. } => "CALL_METADATA_METHOD",
+        InstructionV1::CallRoleAssignmentMethod { .. } => "CALL_ROLE_ASSIGNMENT_METHOD",
+        InstructionV1::CallDirectVaultMethod { .. } => "CALL_DIRECT_VAULT_METHOD",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radix_engine_interface::blueprints::account::ACCOUNT_BLUEPRINT;
+
+    #[test]
+    fn test_profile_csv_round_trip() {
+        let csv = format!(
+            "# A comment\n\
+             invocation,{},Account,withdraw,1000,2000,300,400,0,100,50,60\n\
+             \n\
+             instruction,TAKE_ALL_FROM_WORKTOP,10,20,0,0,0,0,0,0\n\
+             overhead,5000,6000,100,200,10,20,0,0\n",
+            ACCOUNT_PACKAGE.to_hex()
+        );
+
+        let profile = ExecutionCostProfile::from_csv(&csv).unwrap();
+
+        assert_eq!(
+            profile.get(&ExecutionCostKey::Invocation {
+                blueprint_id: BlueprintId::new(&ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT),
+                ident: "withdraw".to_string(),
+            }),
+            Some(&RecordedCosts {
+                execution_cost_units: CostRange::new(1000, 2000),
+                finalization_cost_units: CostRange::new(300, 400),
+                state_storage_bytes: CostRange::new(0, 100),
+                archive_storage_bytes: CostRange::new(50, 60),
+            })
+        );
+        assert_eq!(
+            profile
+                .get(&ExecutionCostKey::Instruction(
+                    "TAKE_ALL_FROM_WORKTOP".to_string()
+                ))
+                .unwrap()
+                .execution_cost_units,
+            CostRange::new(10, 20)
+        );
+        assert_eq!(
+            profile
+                .get(&ExecutionCostKey::Overhead)
+                .unwrap()
+                .finalization_cost_units,
+            CostRange::new(100, 200)
+        );
+        assert_eq!(
+            ExecutionCostProfile::from_csv(&profile.to_csv()),
+            Ok(profile)
+        );
+    }
+
+    #[test]
+    fn test_profile_csv_errors() {
+        assert_eq!(
+            ExecutionCostProfile::from_csv("instruction,DROP_ALL_PROOFS,1,2,3,4,5,6"),
+            Err(ExecutionCostProfileError::InvalidLine {
+                line: 1,
+                reason: "Expected eight costs".to_string()
+            })
+        );
+        assert_eq!(
+            ExecutionCostProfile::from_csv("\nunknown,1,2,3,4,5,6,7,8"),
+            Err(ExecutionCostProfileError::InvalidLine {
+                line: 2,
+                reason: "Unknown entry kind".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_widens_bounds() {
+        let key = ExecutionCostKey::Instruction("DROP_ALL_PROOFS".to_string());
+        let mut profile = ExecutionCostProfile::new();
+
+        profile.record(
+            key.clone(),
+            &InstructionCosts {
+                execution_cost_units: 100,
+                finalization_cost_units: 10,
+                state_storage_bytes: 0,
+                archive_storage_bytes: 8,
+            },
+        );
+        profile.record(
+            key.clone(),
+            &InstructionCosts {
+                execution_cost_units: 50,
+                finalization_cost_units: 20,
+                state_storage_bytes: 5,
+                archive_storage_bytes: 4,
+            },
+        );
+
+        assert_eq!(
+            profile.get(&key),
+            Some(&RecordedCosts {
+                execution_cost_units: CostRange::new(50, 100),
+                finalization_cost_units: CostRange::new(10, 20),
+                state_storage_bytes: CostRange::new(0, 5),
+                archive_storage_bytes: CostRange::new(4, 8),
+            })
+        );
+    }
+}
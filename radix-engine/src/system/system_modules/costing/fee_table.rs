@@ -543,19 +543,29 @@ impl FeeTable {
     pub fn commit_events_cost(&self, events: &Vec<Event>) -> u32 {
         let mut sum = 0;
         for event in events {
-            sum += add(cast(event.payload.len()) / 4, 5_000)
+            sum += self.commit_event_cost(event)
         }
         sum
     }
 
+    #[inline]
+    pub fn commit_event_cost(&self, event: &Event) -> u32 {
+        add(cast(event.payload.len()) / 4, 5_000)
+    }
+
     #[inline]
     pub fn commit_logs_cost(&self, logs: &Vec<(Level, String)>) -> u32 {
         let mut sum = 0;
         for log in logs {
-            sum += add(cast(log.1.len()) / 4, 1_000)
+            sum += self.commit_log_cost(log)
         }
         sum
     }
+
+    #[inline]
+    pub fn commit_log_cost(&self, log: &(Level, String)) -> u32 {
+        add(cast(log.1.len()) / 4, 1_000)
+    }
 }
 
 #[inline]
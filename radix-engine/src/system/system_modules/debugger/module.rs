@@ -0,0 +1,453 @@
+use crate::errors::RuntimeError;
+use crate::internal_prelude::*;
+use crate::kernel::kernel_api::{KernelApi, KernelInternalApi, KernelInvocation};
+use crate::kernel::kernel_callback_api::{
+    CloseSubstateEvent, CreateNodeEvent, DropNodeEvent, MoveModuleEvent, OpenSubstateEvent,
+    SetSubstateEvent, WriteSubstateEvent,
+};
+use crate::system::actor::{Actor, FunctionActor, MethodActor};
+use crate::system::module::{InitSystemModule, SystemModule};
+use crate::system::system_callback::System;
+use crate::system::system_callback_api::SystemCallbackObject;
+use crate::track::interface::IOAccess;
+use radix_engine_interface::api::LockFlags;
+use sbor::rust::sync::Arc;
+
+/// A condition under which the execution is paused and the [`TransactionDebugger`] is called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Breaks before invoking (and after returning from) a method or function of the given name.
+    Method(String),
+    /// Breaks before invoking (and after returning from) any method or function of the given
+    /// blueprint.
+    Blueprint(String),
+    /// Breaks before invoking (and after returning from) a method of the given node, or when any
+    /// of its substates is written.
+    Node(NodeId),
+    /// Breaks when a substate of the given partition of the given node is written.
+    SubstateWrite {
+        node_id: NodeId,
+        partition_num: PartitionNumber,
+    },
+}
+
+/// An event at which the execution has been paused.
+#[derive(Debug)]
+pub enum DebugEvent<'a> {
+    Invoke {
+        actor: &'a Actor,
+        input: &'a IndexedScryptoValue,
+    },
+    Return {
+        actor: &'a Actor,
+        output: &'a IndexedScryptoValue,
+    },
+    CreateNode {
+        node_id: &'a NodeId,
+    },
+    DropNode {
+        node_id: &'a NodeId,
+    },
+    OpenSubstate {
+        handle: SubstateHandle,
+        substate: &'a DebugOpenedSubstate,
+    },
+    WriteSubstate {
+        node_id: &'a NodeId,
+        partition_num: &'a PartitionNumber,
+        substate_key: &'a SubstateKey,
+        value: &'a IndexedScryptoValue,
+    },
+    CloseSubstate {
+        handle: SubstateHandle,
+    },
+}
+
+/// What the execution should do after the [`TransactionDebugger`] returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugAction {
+    /// Resumes the execution until the next breakpoint.
+    Continue,
+    /// Resumes the execution until the next event, regardless of the breakpoints.
+    Step,
+}
+
+/// A client of the debugger, called whenever the execution is paused.
+pub trait TransactionDebugger {
+    fn on_break(&mut self, event: &DebugEvent, state: &DebuggerState) -> DebugAction;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugOpenedSubstate {
+    pub node_id: NodeId,
+    pub partition_num: PartitionNumber,
+    pub substate_key: SubstateKey,
+    pub flags: LockFlags,
+    /// The depth of the call frame which opened the substate
+    pub depth: usize,
+}
+
+/// The kernel state observed by the debugger.
+#[derive(Debug, Clone, Default)]
+pub struct DebuggerState {
+    /// The actors of the call frames, from the root to the current one.
+    pub call_frames: Vec<Actor>,
+    /// The nodes which currently live on the heap, in the order of creation.
+    pub heap_nodes: IndexSet<NodeId>,
+    /// The substates which are currently open, by handle.
+    pub opened_substates: IndexMap<SubstateHandle, DebugOpenedSubstate>,
+    pending_open: Option<DebugOpenedSubstate>,
+}
+
+impl DebuggerState {
+    fn observe_io_access(&mut self, io_access: &IOAccess) {
+        match io_access {
+            IOAccess::HeapSubstateUpdated {
+                canonical_substate_key,
+                new_size: Some(_),
+                ..
+            } => {
+                self.heap_nodes.insert(canonical_substate_key.node_id);
+            }
+            IOAccess::TrackSubstateUpdated {
+                canonical_substate_key,
+                ..
+            } => {
+                // The node has been persisted
+                self.heap_nodes
+                    .shift_remove(&canonical_substate_key.node_id);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A [`TransactionDebugger`] shared between the client and any number of executions, which may
+/// run on different threads.
+#[cfg(not(feature = "alloc"))]
+pub type SharedTransactionDebugger = Arc<std::sync::Mutex<dyn TransactionDebugger + Send>>;
+#[cfg(feature = "alloc")]
+pub type SharedTransactionDebugger = Arc<RefCell<dyn TransactionDebugger + Send>>;
+
+/// The configuration of the debugger, shared between the client and the engine.
+#[derive(Clone)]
+pub struct DebuggerConfig {
+    pub breakpoints: Vec<Breakpoint>,
+    /// Whether to pause at every event from the start of the execution
+    pub step: bool,
+    pub debugger: SharedTransactionDebugger,
+}
+
+impl DebuggerConfig {
+    pub fn new<D: TransactionDebugger + Send + 'static>(debugger: D) -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            step: false,
+            #[cfg(not(feature = "alloc"))]
+            debugger: Arc::new(std::sync::Mutex::new(debugger)),
+            #[cfg(feature = "alloc")]
+            debugger: Arc::new(RefCell::new(debugger)),
+        }
+    }
+
+    /// Calls the debugger, one break at a time when executions run concurrently.
+    fn on_break(&self, event: &DebugEvent, state: &DebuggerState) -> DebugAction {
+        #[cfg(not(feature = "alloc"))]
+        let mut debugger = self
+            .debugger
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        #[cfg(feature = "alloc")]
+        let mut debugger = self.debugger.borrow_mut();
+        debugger.on_break(event, state)
+    }
+
+    pub fn with_breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+        self.breakpoints.push(breakpoint);
+        self
+    }
+
+    pub fn with_step(mut self, step: bool) -> Self {
+        self.step = step;
+        self
+    }
+}
+
+impl fmt::Debug for DebuggerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebuggerConfig")
+            .field("breakpoints", &self.breakpoints)
+            .field("step", &self.step)
+            .finish()
+    }
+}
+
+/// Pauses the execution at the configured breakpoints, for a [`TransactionDebugger`] to inspect
+/// the kernel state.
+#[derive(Debug, Clone, Default)]
+pub struct DebuggerModule {
+    config: Option<DebuggerConfig>,
+    stepping: bool,
+    state: DebuggerState,
+}
+
+impl DebuggerModule {
+    pub fn new(config: Option<DebuggerConfig>) -> Self {
+        Self {
+            stepping: config
+                .as_ref()
+                .map(|config| config.step)
+                .unwrap_or_default(),
+            config,
+            state: DebuggerState::default(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: Option<DebuggerConfig>) {
+        *self = Self::new(config);
+    }
+
+    pub fn state(&self) -> &DebuggerState {
+        &self.state
+    }
+
+    fn breaks_on(&self, event: &DebugEvent) -> bool {
+        let Some(config) = &self.config else {
+            return false;
+        };
+        if self.stepping {
+            return true;
+        }
+        config
+            .breakpoints
+            .iter()
+            .any(|breakpoint| match (breakpoint, event) {
+                (
+                    Breakpoint::Method(method),
+                    DebugEvent::Invoke { actor, .. } | DebugEvent::Return { actor, .. },
+                ) => Self::actor_ident(actor) == Some(method.as_str()),
+                (
+                    Breakpoint::Blueprint(blueprint),
+                    DebugEvent::Invoke { actor, .. } | DebugEvent::Return { actor, .. },
+                ) => actor
+                    .blueprint_id()
+                    .is_some_and(|blueprint_id| blueprint_id.blueprint_name.eq(blueprint)),
+                (
+                    Breakpoint::Node(node_id),
+                    DebugEvent::Invoke { actor, .. } | DebugEvent::Return { actor, .. },
+                ) => actor.node_id().as_ref() == Some(node_id),
+                (
+                    Breakpoint::Node(node_id),
+                    DebugEvent::WriteSubstate {
+                        node_id: written, ..
+                    },
+                ) => node_id == *written,
+                (
+                    Breakpoint::SubstateWrite {
+                        node_id,
+                        partition_num,
+                    },
+                    DebugEvent::WriteSubstate {
+                        node_id: written_node_id,
+                        partition_num: written_partition_num,
+                        ..
+                    },
+                ) => node_id == *written_node_id && partition_num == *written_partition_num,
+                _ => false,
+            })
+    }
+
+    fn actor_ident(actor: &Actor) -> Option<&str> {
+        match actor {
+            Actor::Method(MethodActor { ident, .. })
+            | Actor::Function(FunctionActor { ident, .. }) => Some(ident.as_str()),
+            Actor::BlueprintHook(_) | Actor::Root => None,
+        }
+    }
+
+    fn on_event(&mut self, event: DebugEvent) {
+        if !self.breaks_on(&event) {
+            return;
+        }
+        let Some(config) = &self.config else {
+            return;
+        };
+        let action = config.on_break(&event, &self.state);
+        self.stepping = match action {
+            DebugAction::Continue => false,
+            DebugAction::Step => true,
+        };
+    }
+
+    fn debugger<'a, V: SystemCallbackObject + 'a, Y: KernelInternalApi<System<V>>>(
+        api: &'a mut Y,
+    ) -> &'a mut DebuggerModule {
+        &mut api.kernel_get_system().modules.debugger
+    }
+}
+
+impl InitSystemModule for DebuggerModule {}
+
+impl<V: SystemCallbackObject> SystemModule<System<V>> for DebuggerModule {
+    fn before_invoke<Y: KernelApi<System<V>>>(
+        api: &mut Y,
+        invocation: &KernelInvocation<Actor>,
+    ) -> Result<(), RuntimeError> {
+        let debugger = Self::debugger(api);
+        debugger
+            .state
+            .call_frames
+            .push(invocation.call_frame_data.clone());
+        debugger.on_event(DebugEvent::Invoke {
+            actor: &invocation.call_frame_data,
+            input: &invocation.args,
+        });
+        Ok(())
+    }
+
+    fn after_invoke<Y: KernelApi<System<V>>>(
+        api: &mut Y,
+        output: &IndexedScryptoValue,
+    ) -> Result<(), RuntimeError> {
+        let debugger = Self::debugger(api);
+        if let Some(actor) = debugger.state.call_frames.last().cloned() {
+            debugger.on_event(DebugEvent::Return {
+                actor: &actor,
+                output,
+            });
+        }
+        debugger.state.call_frames.pop();
+        Ok(())
+    }
+
+    fn on_create_node<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &CreateNodeEvent,
+    ) -> Result<(), RuntimeError> {
+        let debugger = Self::debugger(api);
+        match event {
+            CreateNodeEvent::Start(..) => {}
+            CreateNodeEvent::IOAccess(io_access) => debugger.state.observe_io_access(io_access),
+            CreateNodeEvent::End(node_id) => debugger.on_event(DebugEvent::CreateNode { node_id }),
+        }
+        Ok(())
+    }
+
+    fn on_move_module<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &MoveModuleEvent,
+    ) -> Result<(), RuntimeError> {
+        let debugger = Self::debugger(api);
+        match event {
+            MoveModuleEvent::IOAccess(io_access) => debugger.state.observe_io_access(io_access),
+        }
+        Ok(())
+    }
+
+    fn on_drop_node<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &DropNodeEvent,
+    ) -> Result<(), RuntimeError> {
+        let debugger = Self::debugger(api);
+        match event {
+            DropNodeEvent::Start(node_id) => debugger.on_event(DebugEvent::DropNode { node_id }),
+            DropNodeEvent::IOAccess(io_access) => debugger.state.observe_io_access(io_access),
+            DropNodeEvent::End(node_id, _) => {
+                debugger.state.heap_nodes.shift_remove(*node_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn on_open_substate<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &OpenSubstateEvent,
+    ) -> Result<(), RuntimeError> {
+        let depth = api.kernel_get_current_depth();
+        let debugger = Self::debugger(api);
+        match event {
+            OpenSubstateEvent::Start {
+                node_id,
+                partition_num,
+                substate_key,
+                flags,
+            } => {
+                debugger.state.pending_open = Some(DebugOpenedSubstate {
+                    node_id: **node_id,
+                    partition_num: **partition_num,
+                    substate_key: (*substate_key).clone(),
+                    flags: **flags,
+                    depth,
+                });
+            }
+            OpenSubstateEvent::IOAccess(io_access) => debugger.state.observe_io_access(io_access),
+            OpenSubstateEvent::End { handle, .. } => {
+                if let Some(substate) = debugger.state.pending_open.take() {
+                    debugger
+                        .state
+                        .opened_substates
+                        .insert(*handle, substate.clone());
+                    debugger.on_event(DebugEvent::OpenSubstate {
+                        handle: *handle,
+                        substate: &substate,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn on_write_substate<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &WriteSubstateEvent,
+    ) -> Result<(), RuntimeError> {
+        let debugger = Self::debugger(api);
+        match event {
+            WriteSubstateEvent::Start { handle, value } => {
+                if let Some(substate) = debugger.state.opened_substates.get(handle).cloned() {
+                    debugger.on_event(DebugEvent::WriteSubstate {
+                        node_id: &substate.node_id,
+                        partition_num: &substate.partition_num,
+                        substate_key: &substate.substate_key,
+                        value,
+                    });
+                }
+            }
+            WriteSubstateEvent::IOAccess(io_access) => debugger.state.observe_io_access(io_access),
+        }
+        Ok(())
+    }
+
+    fn on_close_substate<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &CloseSubstateEvent,
+    ) -> Result<(), RuntimeError> {
+        let debugger = Self::debugger(api);
+        match event {
+            CloseSubstateEvent::Start(handle) => {
+                debugger.on_event(DebugEvent::CloseSubstate { handle: *handle });
+                debugger.state.opened_substates.shift_remove(handle);
+            }
+        }
+        Ok(())
+    }
+
+    fn on_set_substate(
+        system: &mut System<V>,
+        event: &SetSubstateEvent,
+    ) -> Result<(), RuntimeError> {
+        let debugger = &mut system.modules.debugger;
+        match event {
+            SetSubstateEvent::Start(node_id, partition_num, substate_key, value) => {
+                debugger.on_event(DebugEvent::WriteSubstate {
+                    node_id,
+                    partition_num,
+                    substate_key,
+                    value,
+                });
+            }
+            SetSubstateEvent::IOAccess(io_access) => debugger.state.observe_io_access(io_access),
+        }
+        Ok(())
+    }
+}
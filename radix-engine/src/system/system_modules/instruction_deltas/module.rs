@@ -0,0 +1,524 @@
+use crate::blueprints::resource::VaultUtil;
+use crate::errors::*;
+use crate::internal_prelude::*;
+use crate::kernel::call_frame::CallFrameMessage;
+use crate::kernel::kernel_api::{KernelApi, KernelInternalApi, KernelInvocation};
+use crate::kernel::kernel_callback_api::{CreateNodeEvent, DropNodeEvent};
+use crate::system::actor::{Actor, MethodActor};
+use crate::system::module::{InitSystemModule, SystemModule};
+use crate::system::system_callback::System;
+use crate::system::system_callback_api::SystemCallbackObject;
+use crate::system::system_modules::execution_trace::{
+    BucketSnapshot, ProofSnapshot, ResourceSpecifier, ResourceSummary,
+};
+use crate::track::TrackedSubstates;
+use radix_common::math::Decimal;
+use radix_engine_interface::blueprints::resource::*;
+
+//===================================================================================
+// Note: like ExecutionTrace, this module must not produce any error or transactional
+// side effect!
+//===================================================================================
+
+/// The resource movements attributed to a single manifest instruction.
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub struct InstructionDeltas {
+    pub instruction_index: usize,
+    /// The worktop contents before the instruction is executed
+    pub worktop_before: Vec<ResourceSpecifier>,
+    /// The worktop contents after the instruction is executed
+    pub worktop_after: Vec<ResourceSpecifier>,
+    /// The buckets created while executing the instruction, with their contents at creation
+    pub created_buckets: IndexMap<NodeId, BucketSnapshot>,
+    /// The buckets dropped while executing the instruction, with their contents when dropped
+    pub consumed_buckets: IndexMap<NodeId, BucketSnapshot>,
+    /// The proofs created while executing the instruction
+    pub created_proofs: IndexMap<NodeId, ProofSnapshot>,
+    /// The proofs dropped while executing the instruction
+    pub dropped_proofs: IndexMap<NodeId, ProofSnapshot>,
+    /// The net changes of vault balances, by the global entity holding the vaults, including the
+    /// fees paid from the vaults locked by the instruction.
+    pub vault_deltas: IndexMap<GlobalAddress, IndexMap<ResourceAddress, Decimal>>,
+    /// The net changes of the balances of the vaults whose holding global entity isn't known to
+    /// the transaction (e.g. a vault recalled from by direct access), by vault.
+    pub unattributed_vault_deltas: IndexMap<NodeId, (ResourceAddress, Decimal)>,
+}
+
+impl InstructionDeltas {
+    fn new(instruction_index: usize, worktop: Vec<ResourceSpecifier>) -> Self {
+        Self {
+            instruction_index,
+            worktop_before: worktop.clone(),
+            worktop_after: worktop,
+            created_buckets: index_map_new(),
+            consumed_buckets: index_map_new(),
+            created_proofs: index_map_new(),
+            dropped_proofs: index_map_new(),
+            vault_deltas: index_map_new(),
+            unattributed_vault_deltas: index_map_new(),
+        }
+    }
+}
+
+/// Records the [`InstructionDeltas`] of every manifest instruction.
+///
+/// Unlike the [`super::execution_trace::ExecutionTraceModule`], this doesn't trace the kernel
+/// calls, and only inspects the invocations of worktop and vault methods.
+#[derive(Debug, Clone, Default)]
+pub struct InstructionDeltasModule {
+    /// The current worktop contents
+    worktop: IndexMap<ResourceAddress, ResourceSpecifier>,
+    /// The deltas of the instructions executed so far, the last one being the current one
+    instructions: Vec<InstructionDeltas>,
+    /// The vaults used to lock fees: (Vault ID, (position of the instruction, entity))
+    fee_vaults: IndexMap<NodeId, (usize, GlobalAddress)>,
+    /// The vault operations not performed by a method of the global entity holding the vault
+    /// (e.g. within a blueprint function, or a recall): (position of the instruction, Vault ID,
+    /// resource, delta)
+    unresolved_vault_ops: Vec<(usize, NodeId, ResourceAddress, Decimal)>,
+}
+
+impl InstructionDeltasModule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update_instruction_index(&mut self, new_index: usize) {
+        self.close_current_instruction();
+        self.instructions
+            .push(InstructionDeltas::new(new_index, self.worktop_contents()));
+    }
+
+    /// Returns the deltas of all instructions. Only the fee payments are kept in the vault deltas
+    /// of a failed transaction, since the other vault updates are reverted.
+    ///
+    /// The vaults operated on outside of their holders' methods are attributed to the global
+    /// entities owning them in the given tracked substates.
+    pub fn finalize(
+        mut self,
+        fee_payments: &IndexMap<NodeId, Decimal>,
+        is_success: bool,
+        tracked_substates: &TrackedSubstates,
+    ) -> Vec<InstructionDeltas> {
+        self.close_current_instruction();
+
+        if !is_success {
+            for instruction in self.instructions.iter_mut() {
+                instruction.vault_deltas.clear();
+            }
+            self.unresolved_vault_ops.clear();
+        }
+
+        if !self.unresolved_vault_ops.is_empty() {
+            let vault_holders = Self::resolve_global_owners(tracked_substates);
+            for (position, vault_id, resource_address, delta) in self.unresolved_vault_ops {
+                let instruction = &mut self.instructions[position];
+                match vault_holders.get(&vault_id) {
+                    Some(entity) => {
+                        Self::apply_vault_delta(instruction, *entity, resource_address, delta)
+                    }
+                    None => {
+                        let entry = &mut instruction
+                            .unattributed_vault_deltas
+                            .entry(vault_id)
+                            .or_insert((resource_address, Decimal::ZERO))
+                            .1;
+                        *entry = entry.checked_add(delta).unwrap();
+                    }
+                }
+            }
+        }
+
+        for (vault_id, (position, entity)) in self.fee_vaults {
+            if let Some(fee_payment) = fee_payments.get(&vault_id) {
+                Self::apply_vault_delta(
+                    &mut self.instructions[position],
+                    entity,
+                    XRD,
+                    fee_payment.checked_neg().unwrap(),
+                );
+            }
+        }
+
+        for instruction in self.instructions.iter_mut() {
+            for deltas in instruction.vault_deltas.values_mut() {
+                deltas.retain(|_, delta| !delta.is_zero());
+            }
+            instruction
+                .vault_deltas
+                .retain(|_, deltas| !deltas.is_empty());
+            instruction
+                .unattributed_vault_deltas
+                .retain(|_, (_, delta)| !delta.is_zero());
+        }
+
+        self.instructions
+    }
+
+    fn close_current_instruction(&mut self) {
+        let worktop = self.worktop_contents();
+        if let Some(current) = self.instructions.last_mut() {
+            current.worktop_after = worktop;
+        }
+    }
+
+    fn worktop_contents(&self) -> Vec<ResourceSpecifier> {
+        self.worktop.values().cloned().collect()
+    }
+
+    fn handle_created_node(&mut self, resource_summary: ResourceSummary) {
+        if let Some(current) = self.instructions.last_mut() {
+            current.created_buckets.extend(resource_summary.buckets);
+            current.created_proofs.extend(resource_summary.proofs);
+        }
+    }
+
+    fn handle_dropped_node(&mut self, resource_summary: ResourceSummary) {
+        if let Some(current) = self.instructions.last_mut() {
+            current.consumed_buckets.extend(resource_summary.buckets);
+            current.dropped_proofs.extend(resource_summary.proofs);
+        }
+    }
+
+    fn handle_worktop_put(&mut self, resource_summary: &ResourceSummary) {
+        for bucket in resource_summary.buckets.values() {
+            let resource_address = bucket.resource_address();
+            let contents = match (self.worktop.swap_remove(&resource_address), bucket) {
+                (
+                    Some(ResourceSpecifier::Amount(_, amount)),
+                    BucketSnapshot::Fungible { liquid, .. },
+                ) => ResourceSpecifier::Amount(
+                    resource_address,
+                    amount.checked_add(*liquid).unwrap(),
+                ),
+                (
+                    Some(ResourceSpecifier::Ids(_, mut ids)),
+                    BucketSnapshot::NonFungible { liquid, .. },
+                ) => {
+                    ids.extend(liquid.iter().cloned());
+                    ResourceSpecifier::Ids(resource_address, ids)
+                }
+                (_, bucket) => bucket.into(),
+            };
+            self.insert_worktop_contents(contents);
+        }
+    }
+
+    fn handle_worktop_take(&mut self, resource_summary: &ResourceSummary) {
+        for bucket in resource_summary.buckets.values() {
+            let resource_address = bucket.resource_address();
+            let contents = match (self.worktop.swap_remove(&resource_address), bucket) {
+                (
+                    Some(ResourceSpecifier::Amount(_, amount)),
+                    BucketSnapshot::Fungible { liquid, .. },
+                ) => ResourceSpecifier::Amount(
+                    resource_address,
+                    amount.checked_sub(*liquid).unwrap(),
+                ),
+                (
+                    Some(ResourceSpecifier::Ids(_, mut ids)),
+                    BucketSnapshot::NonFungible { liquid, .. },
+                ) => {
+                    ids.retain(|id| !liquid.contains(id));
+                    ResourceSpecifier::Ids(resource_address, ids)
+                }
+                (Some(contents), _) => contents,
+                (None, _) => continue,
+            };
+            self.insert_worktop_contents(contents);
+        }
+    }
+
+    fn insert_worktop_contents(&mut self, contents: ResourceSpecifier) {
+        let (resource_address, is_empty) = match &contents {
+            ResourceSpecifier::Amount(resource_address, amount) => {
+                (*resource_address, !amount.is_positive())
+            }
+            ResourceSpecifier::Ids(resource_address, ids) => (*resource_address, ids.is_empty()),
+        };
+        if !is_empty {
+            self.worktop.insert(resource_address, contents);
+        }
+    }
+
+    fn handle_vault_op(
+        &mut self,
+        entity: Option<GlobalAddress>,
+        vault_id: &NodeId,
+        resource_summary: &ResourceSummary,
+        is_put: bool,
+    ) {
+        for bucket in resource_summary.buckets.values() {
+            let delta = if is_put {
+                bucket.amount()
+            } else {
+                bucket.amount().checked_neg().unwrap()
+            };
+            self.handle_vault_delta(entity, vault_id, bucket.resource_address(), delta);
+        }
+    }
+
+    /// Records a vault balance change, attributed to the given entity if the change was made by
+    /// one of its methods, or else to the vault's holder once known.
+    fn handle_vault_delta(
+        &mut self,
+        entity: Option<GlobalAddress>,
+        vault_id: &NodeId,
+        resource_address: ResourceAddress,
+        delta: Decimal,
+    ) {
+        let Some(position) = self.instructions.len().checked_sub(1) else {
+            return;
+        };
+        match entity {
+            Some(entity) => Self::apply_vault_delta(
+                &mut self.instructions[position],
+                entity,
+                resource_address,
+                delta,
+            ),
+            None => self
+                .unresolved_vault_ops
+                .push((position, *vault_id, resource_address, delta)),
+        }
+    }
+
+    fn handle_vault_lock_fee(&mut self, entity: Option<GlobalAddress>, vault_id: &NodeId) {
+        let (Some(entity), Some(position)) = (entity, self.instructions.len().checked_sub(1))
+        else {
+            return;
+        };
+        // Fees are only paid once per vault, so the payment is attributed to the first lock
+        self.fee_vaults
+            .entry(*vault_id)
+            .or_insert((position, entity));
+    }
+
+    fn apply_vault_delta(
+        instruction: &mut InstructionDeltas,
+        entity: GlobalAddress,
+        resource_address: ResourceAddress,
+        delta: Decimal,
+    ) {
+        let entry = instruction
+            .vault_deltas
+            .entry(entity)
+            .or_default()
+            .entry(resource_address)
+            .or_default();
+        *entry = entry.checked_add(delta).unwrap();
+    }
+
+    /// Returns the global entity (directly, or through a chain of internal nodes) owning each of
+    /// the nodes referenced by the given tracked substates.
+    fn resolve_global_owners(
+        tracked_substates: &TrackedSubstates,
+    ) -> IndexMap<NodeId, GlobalAddress> {
+        let mut owners = index_map_new::<NodeId, NodeId>();
+        for (node_id, tracked_node) in &tracked_substates.tracked_nodes {
+            for tracked_partition in tracked_node.tracked_partitions.values() {
+                for tracked_substate in tracked_partition.substates.values() {
+                    if let Some(value) = tracked_substate.substate_value.get() {
+                        for owned_node_id in value.owned_nodes() {
+                            owners.insert(*owned_node_id, *node_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut global_owners = index_map_new();
+        for node_id in owners.keys() {
+            let mut current = node_id;
+            // The length of any owner chain is bounded by the number of owned nodes.
+            for _ in 0..owners.len() {
+                let Some(owner) = owners.get(current) else {
+                    break;
+                };
+                if owner.is_global() {
+                    global_owners.insert(*node_id, GlobalAddress::new_or_panic(owner.0));
+                    break;
+                }
+                current = owner;
+            }
+        }
+        global_owners
+    }
+
+    /// The global entity on behalf of which the given actor operates on vaults, if any.
+    fn global_entity(actor: &Actor) -> Option<GlobalAddress> {
+        match actor {
+            Actor::Method(MethodActor { node_id, .. }) if node_id.is_global() => {
+                Some(GlobalAddress::new_or_panic(node_id.0))
+            }
+            _ => actor
+                .instance_context()
+                .map(|instance_context| instance_context.outer_object),
+        }
+    }
+
+    fn is_worktop_method(actor: &MethodActor) -> bool {
+        actor.get_blueprint_id() == BlueprintId::new(&RESOURCE_PACKAGE, WORKTOP_BLUEPRINT)
+    }
+}
+
+impl InitSystemModule for InstructionDeltasModule {}
+
+impl<V: SystemCallbackObject> SystemModule<System<V>> for InstructionDeltasModule {
+    fn on_create_node<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &CreateNodeEvent,
+    ) -> Result<(), RuntimeError> {
+        if let CreateNodeEvent::End(node_id) = event {
+            let resource_summary = ResourceSummary::from_node_id(api, node_id);
+            if !resource_summary.is_empty() {
+                api.kernel_get_system_state()
+                    .system
+                    .modules
+                    .instruction_deltas
+                    .handle_created_node(resource_summary);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_drop_node<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &DropNodeEvent,
+    ) -> Result<(), RuntimeError> {
+        if let DropNodeEvent::Start(node_id) = event {
+            let resource_summary = ResourceSummary::from_node_id(api, node_id);
+            if !resource_summary.is_empty() {
+                api.kernel_get_system_state()
+                    .system
+                    .modules
+                    .instruction_deltas
+                    .handle_dropped_node(resource_summary);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn before_invoke<Y: KernelApi<System<V>>>(
+        api: &mut Y,
+        invocation: &KernelInvocation<Actor>,
+    ) -> Result<(), RuntimeError> {
+        let Actor::Method(callee @ MethodActor { node_id, ident, .. }) =
+            &invocation.call_frame_data
+        else {
+            return Ok(());
+        };
+
+        let is_worktop_put = Self::is_worktop_method(callee) && ident.eq(WORKTOP_PUT_IDENT);
+        let is_vault_put =
+            VaultUtil::is_vault_blueprint(&callee.get_blueprint_id()) && ident.eq(VAULT_PUT_IDENT);
+        let is_vault_lock_fee = VaultUtil::is_vault_blueprint(&callee.get_blueprint_id())
+            && ident.eq(FUNGIBLE_VAULT_LOCK_FEE_IDENT);
+        let is_vault_burn = VaultUtil::is_vault_blueprint(&callee.get_blueprint_id())
+            && (ident.eq(VAULT_BURN_IDENT)
+                || ident.eq(NON_FUNGIBLE_VAULT_BURN_NON_FUNGIBLES_IDENT));
+        if !(is_worktop_put || is_vault_put || is_vault_lock_fee || is_vault_burn) {
+            return Ok(());
+        }
+
+        if is_vault_burn {
+            // The burnt resources are taken from the vault internally, so only the input tells
+            // the amount (a failing burn fails the whole transaction anyway).
+            let amount = if ident.eq(VAULT_BURN_IDENT) {
+                invocation
+                    .args
+                    .as_typed::<VaultBurnInput>()
+                    .map(|input| input.amount)
+            } else {
+                invocation
+                    .args
+                    .as_typed::<NonFungibleVaultBurnNonFungiblesInput>()
+                    .map(|input| Decimal::from(input.non_fungible_local_ids.len()))
+            };
+            let Ok(amount) = amount else {
+                return Ok(());
+            };
+            let resource_address =
+                ResourceAddress::new_or_panic(callee.object_info.get_outer_object().into());
+            let system_state = api.kernel_get_system_state();
+            let entity = Self::global_entity(system_state.current_call_frame);
+            system_state
+                .system
+                .modules
+                .instruction_deltas
+                .handle_vault_delta(
+                    entity,
+                    node_id,
+                    resource_address,
+                    amount.checked_neg().unwrap(),
+                );
+            return Ok(());
+        }
+
+        let message = CallFrameMessage::from_input(&invocation.args, &invocation.call_frame_data);
+        let resource_summary = ResourceSummary::from_message(api, &message);
+        let system_state = api.kernel_get_system_state();
+        let entity = Self::global_entity(system_state.current_call_frame);
+        let module = &mut system_state.system.modules.instruction_deltas;
+        if is_worktop_put {
+            module.handle_worktop_put(&resource_summary);
+        } else if is_vault_put {
+            module.handle_vault_op(entity, node_id, &resource_summary, true);
+        } else {
+            module.handle_vault_lock_fee(entity, node_id);
+        }
+
+        Ok(())
+    }
+
+    fn on_execution_finish<Y: KernelApi<System<V>>>(
+        api: &mut Y,
+        message: &CallFrameMessage,
+    ) -> Result<(), RuntimeError> {
+        let system_state = api.kernel_get_system_state();
+        let Actor::Method(actor @ MethodActor { node_id, ident, .. }) =
+            system_state.current_call_frame
+        else {
+            return Ok(());
+        };
+
+        let is_worktop_take = Self::is_worktop_method(actor)
+            && (ident.eq(WORKTOP_TAKE_IDENT)
+                || ident.eq(WORKTOP_TAKE_ALL_IDENT)
+                || ident.eq(WORKTOP_TAKE_NON_FUNGIBLES_IDENT)
+                || ident.eq(WORKTOP_DRAIN_IDENT));
+        let is_vault_take = VaultUtil::is_vault_blueprint(&actor.get_blueprint_id())
+            && (ident.eq(VAULT_TAKE_IDENT)
+                || ident.eq(VAULT_TAKE_ADVANCED_IDENT)
+                || ident.eq(NON_FUNGIBLE_VAULT_TAKE_NON_FUNGIBLES_IDENT));
+        let is_vault_recall = VaultUtil::is_vault_blueprint(&actor.get_blueprint_id())
+            && (ident.eq(VAULT_RECALL_IDENT)
+                || ident.eq(NON_FUNGIBLE_VAULT_RECALL_NON_FUNGIBLES_IDENT));
+        if !(is_worktop_take || is_vault_take || is_vault_recall) {
+            return Ok(());
+        }
+        let vault_id = *node_id;
+        // The caller of a recall isn't the vault's holder, which is resolved on finalization
+        let entity = if is_vault_recall {
+            None
+        } else {
+            Self::global_entity(system_state.caller_call_frame)
+        };
+
+        let resource_summary = ResourceSummary::from_message(api, message);
+        let module = &mut api
+            .kernel_get_system_state()
+            .system
+            .modules
+            .instruction_deltas;
+        if is_worktop_take {
+            module.handle_worktop_take(&resource_summary);
+        } else {
+            module.handle_vault_op(entity, &vault_id, &resource_summary, false);
+        }
+
+        Ok(())
+    }
+}
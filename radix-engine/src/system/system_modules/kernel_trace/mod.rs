@@ -1,2 +1,4 @@
 mod module;
+mod trace;
 pub use module::*;
+pub use trace::*;
@@ -1,28 +1,53 @@
+use super::{KernelTrace, KernelTraceEvent};
 use crate::internal_prelude::*;
 use crate::kernel::call_frame::CallFrameMessage;
 use crate::kernel::kernel_api::{KernelInternalApi, KernelInvocation};
 use crate::kernel::kernel_callback_api::{
-    CloseSubstateEvent, CreateNodeEvent, DropNodeEvent, OpenSubstateEvent, ReadSubstateEvent,
-    WriteSubstateEvent,
+    CloseSubstateEvent, CreateNodeEvent, DropNodeEvent, MoveModuleEvent, OpenSubstateEvent,
+    ReadSubstateEvent, WriteSubstateEvent,
 };
 use crate::system::actor::Actor;
 use crate::system::module::{InitSystemModule, SystemModule};
 use crate::system::system_callback::System;
 use crate::system::system_callback_api::SystemCallbackObject;
+use crate::track::interface::IOAccess;
 use crate::{errors::RuntimeError, kernel::kernel_api::KernelApi};
-use colored::Colorize;
-use radix_engine_interface::types::SubstateKey;
-use sbor::rust::collections::BTreeMap;
 
-#[derive(Debug, Clone)]
-pub struct KernelTraceModule;
+/// Records the kernel operations into a [`KernelTrace`], for debugging, and prints them as they
+/// happen (unless `alloc`).
+#[derive(Debug, Clone, Default)]
+pub struct KernelTraceModule {
+    trace: KernelTrace,
+}
+
+impl KernelTraceModule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn take_trace(&mut self) -> KernelTrace {
+        core::mem::take(&mut self.trace)
+    }
+
+    pub fn finalize(self) -> KernelTrace {
+        self.trace
+    }
 
-#[macro_export]
-macro_rules! log {
-    ( $api: expr, $msg: expr $( , $arg:expr )* ) => {
+    fn record<V: SystemCallbackObject, Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: KernelTraceEvent,
+    ) {
+        let depth = api.kernel_get_current_depth();
+        let _entry = api
+            .kernel_get_system()
+            .modules
+            .kernel_trace
+            .trace
+            .record(depth, event);
+        // Printed as it happens, so that the trace is available even if the execution panics
         #[cfg(not(feature = "alloc"))]
-        println!("{}[{}] {}", "    ".repeat($api.kernel_get_current_depth()), $api.kernel_get_current_depth(), sbor::rust::format!($msg, $( $arg ),*));
-    };
+        print!("{}", _entry.to_pretty_string());
+    }
 }
 
 impl InitSystemModule for KernelTraceModule {
@@ -32,22 +57,18 @@ impl InitSystemModule for KernelTraceModule {
     }
 }
 
-#[allow(unused_variables)] // for no_std
 impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
     fn before_invoke<Y: KernelApi<System<V>>>(
         api: &mut Y,
         invocation: &KernelInvocation<Actor>,
     ) -> Result<(), RuntimeError> {
-        let message = format!(
-            "Invoking: fn = {:?}, input size = {}",
-            invocation.call_frame_data,
-            invocation.len(),
-        )
-        .green();
-
-        log!(api, "{}", message);
-        log!(api, "Sending nodes: {:?}", invocation.args.owned_nodes());
-        log!(api, "Sending refs: {:?}", invocation.args.references());
+        let event = KernelTraceEvent::Invoke {
+            actor: format!("{:?}", invocation.call_frame_data),
+            input_size: invocation.len(),
+            sent_nodes: invocation.args.owned_nodes().clone(),
+            sent_references: invocation.args.references().clone(),
+        };
+        Self::record(api, event);
         Ok(())
     }
 
@@ -55,8 +76,11 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
         api: &mut Y,
         message: &CallFrameMessage,
     ) -> Result<(), RuntimeError> {
-        log!(api, "Returning nodes: {:?}", message.move_nodes);
-        log!(api, "Returning refs: {:?}", message.copy_global_references);
+        let event = KernelTraceEvent::Return {
+            returned_nodes: message.move_nodes.clone(),
+            returned_references: message.copy_global_references.clone(),
+        };
+        Self::record(api, event);
         Ok(())
     }
 
@@ -64,7 +88,10 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
         api: &mut Y,
         output: &IndexedScryptoValue,
     ) -> Result<(), RuntimeError> {
-        log!(api, "Exiting: output size = {}", output.len());
+        let event = KernelTraceEvent::Exit {
+            output_size: output.len(),
+        };
+        Self::record(api, event);
         Ok(())
     }
 
@@ -72,7 +99,7 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
         api: &mut Y,
         entity_type: EntityType,
     ) -> Result<(), RuntimeError> {
-        log!(api, "Allocating node id: entity_type = {:?}", entity_type);
+        Self::record(api, KernelTraceEvent::AllocateNodeId { entity_type });
         Ok(())
     }
 
@@ -82,25 +109,23 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
     ) -> Result<(), RuntimeError> {
         match event {
             CreateNodeEvent::Start(node_id, node_module_init) => {
-                let mut module_substate_keys =
-                    BTreeMap::<&PartitionNumber, Vec<&SubstateKey>>::new();
-                for (module_id, m) in *node_module_init {
-                    for (substate_key, _) in m {
-                        module_substate_keys
-                            .entry(module_id)
-                            .or_default()
-                            .push(substate_key);
-                    }
-                }
-                let message = format!(
-                    "Creating node: id = {:?}, type = {:?}, substates = {:?}, module 0 = {:?}",
-                    node_id,
-                    node_id.entity_type(),
-                    module_substate_keys,
-                    node_module_init.get(&PartitionNumber(0))
-                )
-                .red();
-                log!(api, "{}", message);
+                let substates = node_module_init
+                    .iter()
+                    .map(|(partition_num, substates)| {
+                        let substates = substates
+                            .iter()
+                            .map(|(substate_key, value)| {
+                                (substate_key.clone(), value.as_slice().to_vec())
+                            })
+                            .collect();
+                        (*partition_num, substates)
+                    })
+                    .collect();
+                let event = KernelTraceEvent::CreateNode {
+                    node_id: **node_id,
+                    substates,
+                };
+                Self::record(api, event);
             }
             _ => {}
         }
@@ -108,13 +133,42 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
         Ok(())
     }
 
+    fn on_move_module<Y: KernelInternalApi<System<V>>>(
+        api: &mut Y,
+        event: &MoveModuleEvent,
+    ) -> Result<(), RuntimeError> {
+        match event {
+            MoveModuleEvent::IOAccess(io_access) => {
+                let key = match io_access {
+                    IOAccess::ReadFromDb(key, _)
+                    | IOAccess::ReadFromDbNotFound(key)
+                    | IOAccess::TrackSubstateUpdated {
+                        canonical_substate_key: key,
+                        ..
+                    }
+                    | IOAccess::HeapSubstateUpdated {
+                        canonical_substate_key: key,
+                        ..
+                    } => key,
+                };
+                let event = KernelTraceEvent::MoveModule {
+                    node_id: key.node_id,
+                    partition_num: key.partition_number,
+                    substate_key: key.substate_key.clone(),
+                };
+                Self::record(api, event);
+            }
+        }
+        Ok(())
+    }
+
     fn on_drop_node<Y: KernelInternalApi<System<V>>>(
         api: &mut Y,
         event: &DropNodeEvent,
     ) -> Result<(), RuntimeError> {
         match event {
             DropNodeEvent::Start(node_id) => {
-                log!(api, "Dropping node: id = {:?}", node_id);
+                Self::record(api, KernelTraceEvent::DropNode { node_id: **node_id });
             }
             _ => {}
         }
@@ -132,27 +186,23 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
                 substate_key,
                 flags,
             } => {
-                log!(
-                    api,
-                    "Locking substate: node id = {:?}, partition_num = {:?}, substate_key = {:?}, flags = {:?}",
-                    node_id,
-                    partition_num,
-                    substate_key,
-                    flags
-                );
+                let event = KernelTraceEvent::OpenSubstate {
+                    node_id: **node_id,
+                    partition_num: **partition_num,
+                    substate_key: (*substate_key).clone(),
+                    flags: **flags,
+                };
+                Self::record(api, event);
             }
             OpenSubstateEvent::IOAccess(..) => {}
             OpenSubstateEvent::End {
-                handle,
-                node_id,
-                size,
+                handle, node_id, ..
             } => {
-                log!(
-                    api,
-                    "Substate locked: node id = {:?}, handle = {:?}",
-                    node_id,
-                    handle
-                );
+                let event = KernelTraceEvent::SubstateOpened {
+                    node_id: **node_id,
+                    handle: *handle,
+                };
+                Self::record(api, event);
             }
         }
 
@@ -169,13 +219,12 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
                 value,
                 device,
             } => {
-                log!(
-                    api,
-                    "Reading substate: handle = {}, size = {}, device = {:?}",
-                    handle,
-                    value.len(),
-                    device
-                );
+                let event = KernelTraceEvent::ReadSubstate {
+                    handle: *handle,
+                    size: value.len(),
+                    device: *device,
+                };
+                Self::record(api, event);
             }
             ReadSubstateEvent::IOAccess(_) => {}
         }
@@ -189,12 +238,11 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
     ) -> Result<(), RuntimeError> {
         match event {
             WriteSubstateEvent::Start { handle, value } => {
-                log!(
-                    api,
-                    "Writing substate: handle = {}, size = {}",
-                    handle,
-                    value.len()
-                );
+                let event = KernelTraceEvent::WriteSubstate {
+                    handle: *handle,
+                    size: value.len(),
+                };
+                Self::record(api, event);
             }
             _ => {}
         }
@@ -208,7 +256,12 @@ impl<V: SystemCallbackObject> SystemModule<System<V>> for KernelTraceModule {
     ) -> Result<(), RuntimeError> {
         match event {
             CloseSubstateEvent::Start(lock_handle) => {
-                log!(api, "Substate close: handle = {} ", lock_handle);
+                Self::record(
+                    api,
+                    KernelTraceEvent::CloseSubstate {
+                        handle: *lock_handle,
+                    },
+                );
             }
         }
         Ok(())
@@ -0,0 +1,229 @@
+use crate::internal_prelude::*;
+use crate::kernel::substate_io::SubstateDevice;
+use colored::Colorize;
+use radix_engine_interface::api::LockFlags;
+use radix_engine_interface::types::{IndexedScryptoValue, SubstateHandle, SubstateKey};
+#[cfg(feature = "serde")]
+use sbor::representations::{SerializationMode, SerializationParameters};
+use sbor::rust::collections::BTreeMap;
+
+/// The kernel operations recorded by the [`super::KernelTraceModule`], in the order of execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq, ScryptoSbor)]
+pub struct KernelTrace {
+    pub entries: Vec<KernelTraceEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub struct KernelTraceEntry {
+    /// The depth of the call frame in which the event happened
+    pub depth: usize,
+    pub event: KernelTraceEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub enum KernelTraceEvent {
+    Invoke {
+        actor: String,
+        input_size: usize,
+        sent_nodes: Vec<NodeId>,
+        sent_references: Vec<NodeId>,
+    },
+    Return {
+        returned_nodes: Vec<NodeId>,
+        returned_references: Vec<NodeId>,
+    },
+    Exit {
+        output_size: usize,
+    },
+    AllocateNodeId {
+        entity_type: EntityType,
+    },
+    /// A node has been created, with the given (encoded) substates.
+    CreateNode {
+        node_id: NodeId,
+        substates: BTreeMap<PartitionNumber, BTreeMap<SubstateKey, Vec<u8>>>,
+    },
+    DropNode {
+        node_id: NodeId,
+    },
+    /// A substate has been accessed while moving a module's partitions into a new node.
+    MoveModule {
+        node_id: NodeId,
+        partition_num: PartitionNumber,
+        substate_key: SubstateKey,
+    },
+    OpenSubstate {
+        node_id: NodeId,
+        partition_num: PartitionNumber,
+        substate_key: SubstateKey,
+        flags: LockFlags,
+    },
+    SubstateOpened {
+        node_id: NodeId,
+        handle: SubstateHandle,
+    },
+    ReadSubstate {
+        handle: SubstateHandle,
+        size: usize,
+        device: SubstateDevice,
+    },
+    WriteSubstate {
+        handle: SubstateHandle,
+        size: usize,
+    },
+    CloseSubstate {
+        handle: SubstateHandle,
+    },
+}
+
+impl KernelTrace {
+    pub fn record(&mut self, depth: usize, event: KernelTraceEvent) -> &KernelTraceEntry {
+        self.entries.push(KernelTraceEntry { depth, event });
+        self.entries.last().unwrap()
+    }
+
+    /// Renders the trace as a human-readable, indented log, one line per message.
+    pub fn to_pretty_string(&self) -> String {
+        self.entries
+            .iter()
+            .map(KernelTraceEntry::to_pretty_string)
+            .collect()
+    }
+
+    /// Encodes the trace as JSON, in the programmatic SBOR JSON format.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let (type_id, schema) =
+            generate_full_schema_from_single_type::<KernelTrace, ScryptoCustomSchema>();
+        let payload = scrypto_encode(self).expect("Kernel trace should be encodable");
+        serde_json::to_string(
+            &ScryptoRawPayload::new_from_valid_slice(&payload).serializable(
+                SerializationParameters::WithSchema {
+                    mode: SerializationMode::Programmatic,
+                    custom_context: ScryptoValueDisplayContext::no_context(),
+                    schema: schema.v1(),
+                    type_id,
+                    depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+                },
+            ),
+        )
+        .expect("Kernel trace should be serializable")
+    }
+}
+
+impl KernelTraceEntry {
+    /// Renders the entry as indented log lines, one per message.
+    pub fn to_pretty_string(&self) -> String {
+        let mut buffer = String::new();
+        for message in self.event.to_messages() {
+            buffer.push_str(&format!(
+                "{}[{}] {}\n",
+                "    ".repeat(self.depth),
+                self.depth,
+                message
+            ));
+        }
+        buffer
+    }
+}
+
+impl KernelTraceEvent {
+    fn to_messages(&self) -> Vec<String> {
+        match self {
+            KernelTraceEvent::Invoke {
+                actor,
+                input_size,
+                sent_nodes,
+                sent_references,
+            } => vec![
+                format!("Invoking: fn = {}, input size = {}", actor, input_size)
+                    .green()
+                    .to_string(),
+                format!("Sending nodes: {:?}", sent_nodes),
+                format!("Sending refs: {:?}", sent_references),
+            ],
+            KernelTraceEvent::Return {
+                returned_nodes,
+                returned_references,
+            } => vec![
+                format!("Returning nodes: {:?}", returned_nodes),
+                format!("Returning refs: {:?}", returned_references),
+            ],
+            KernelTraceEvent::Exit { output_size } => {
+                vec![format!("Exiting: output size = {}", output_size)]
+            }
+            KernelTraceEvent::AllocateNodeId { entity_type } => {
+                vec![format!(
+                    "Allocating node id: entity_type = {:?}",
+                    entity_type
+                )]
+            }
+            KernelTraceEvent::CreateNode { node_id, substates } => {
+                let module_substate_keys = substates
+                    .iter()
+                    .map(|(partition_num, substates)| {
+                        (partition_num, substates.keys().collect::<Vec<_>>())
+                    })
+                    .collect::<BTreeMap<_, _>>();
+                let module_0 = substates.get(&PartitionNumber(0)).map(|substates| {
+                    substates
+                        .iter()
+                        .filter_map(|(substate_key, value)| {
+                            IndexedScryptoValue::from_slice(value)
+                                .ok()
+                                .map(|value| (substate_key, value))
+                        })
+                        .collect::<BTreeMap<_, _>>()
+                });
+                vec![format!(
+                    "Creating node: id = {:?}, type = {:?}, substates = {:?}, module 0 = {:?}",
+                    node_id,
+                    node_id.entity_type(),
+                    module_substate_keys,
+                    module_0
+                )
+                .red()
+                .to_string()]
+            }
+            KernelTraceEvent::DropNode { node_id } => {
+                vec![format!("Dropping node: id = {:?}", node_id)]
+            }
+            KernelTraceEvent::MoveModule {
+                node_id,
+                partition_num,
+                substate_key,
+            } => vec![format!(
+                "Moving module substate: node id = {:?}, partition_num = {:?}, substate_key = {:?}",
+                node_id, partition_num, substate_key
+            )],
+            KernelTraceEvent::OpenSubstate {
+                node_id,
+                partition_num,
+                substate_key,
+                flags,
+            } => vec![format!(
+                "Locking substate: node id = {:?}, partition_num = {:?}, substate_key = {:?}, flags = {:?}",
+                node_id, partition_num, substate_key, flags
+            )],
+            KernelTraceEvent::SubstateOpened { node_id, handle } => vec![format!(
+                "Substate locked: node id = {:?}, handle = {:?}",
+                node_id, handle
+            )],
+            KernelTraceEvent::ReadSubstate {
+                handle,
+                size,
+                device,
+            } => vec![format!(
+                "Reading substate: handle = {}, size = {}, device = {:?}",
+                handle, size, device
+            )],
+            KernelTraceEvent::WriteSubstate { handle, size } => vec![format!(
+                "Writing substate: handle = {}, size = {}",
+                handle, size
+            )],
+            KernelTraceEvent::CloseSubstate { handle } => {
+                vec![format!("Substate close: handle = {} ", handle)]
+            }
+        }
+    }
+}
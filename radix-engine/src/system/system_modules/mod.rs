@@ -1,6 +1,8 @@
 pub mod auth;
 pub mod costing;
+pub mod debugger;
 pub mod execution_trace;
+pub mod instruction_deltas;
 pub mod kernel_trace;
 pub mod limits;
 pub mod transaction_runtime;
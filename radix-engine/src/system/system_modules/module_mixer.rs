@@ -18,7 +18,9 @@ use crate::system::system_callback_api::SystemCallbackObject;
 use crate::system::system_modules::auth::AuthModule;
 use crate::system::system_modules::costing::CostingModule;
 use crate::system::system_modules::costing::SystemLoanFeeReserve;
+use crate::system::system_modules::debugger::DebuggerModule;
 use crate::system::system_modules::execution_trace::ExecutionTraceModule;
+use crate::system::system_modules::instruction_deltas::InstructionDeltasModule;
 use crate::system::system_modules::kernel_trace::KernelTraceModule;
 use crate::system::system_modules::limits::LimitsModule;
 use crate::system::system_modules::transaction_runtime::{Event, TransactionRuntimeModule};
@@ -41,6 +43,12 @@ bitflags! {
 
         // Execution trace, for preview only
         const EXECUTION_TRACE = 0x01 << 6;
+
+        // Debugger, for interactive debugging only
+        const DEBUGGER = 0x01 << 7;
+
+        // Per-instruction resource movements, for preview and wallet review
+        const INSTRUCTION_DELTAS = 0x01 << 8;
     }
 }
 
@@ -87,6 +95,8 @@ pub struct SystemModuleMixer {
     pub(super) auth: AuthModule,
     pub(crate) transaction_runtime: TransactionRuntimeModule,
     pub(super) execution_trace: ExecutionTraceModule,
+    pub(super) instruction_deltas: InstructionDeltasModule,
+    pub(super) debugger: DebuggerModule,
 }
 
 // Macro generates default modules dispatches call based on passed function name and arguments.
@@ -113,6 +123,12 @@ macro_rules! internal_call_dispatch {
             if modules.contains(EnabledModules::EXECUTION_TRACE) {
                 ExecutionTraceModule::[< $fn >]($($param, )*)?;
             }
+            if modules.contains(EnabledModules::INSTRUCTION_DELTAS) {
+                InstructionDeltasModule::[< $fn >]($($param, )*)?;
+            }
+            if modules.contains(EnabledModules::DEBUGGER) {
+                DebuggerModule::[< $fn >]($($param, )*)?;
+            }
             Ok(())
         }
     }};
@@ -127,6 +143,8 @@ impl SystemModuleMixer {
         limits: LimitsModule,
         costing: CostingModule,
         execution_trace: ExecutionTraceModule,
+        instruction_deltas: InstructionDeltasModule,
+        debugger: DebuggerModule,
     ) -> Self {
         Self {
             enabled_modules,
@@ -136,17 +154,27 @@ impl SystemModuleMixer {
             costing,
             limits,
             execution_trace,
+            instruction_deltas,
+            debugger,
         }
     }
 
     pub fn unpack(
         self,
     ) -> (
+        KernelTraceModule,
         CostingModule,
         TransactionRuntimeModule,
         ExecutionTraceModule,
+        InstructionDeltasModule,
     ) {
-        (self.costing, self.transaction_runtime, self.execution_trace)
+        (
+            self.kernel_trace,
+            self.costing,
+            self.transaction_runtime,
+            self.execution_trace,
+            self.instruction_deltas,
+        )
     }
 }
 
@@ -160,6 +188,16 @@ impl InitSystemModule for SystemModuleMixer {
     fn init(&mut self) -> Result<(), BootloadingError> {
         let modules: EnabledModules = self.enabled_modules;
 
+        // Enable debugger
+        if modules.contains(EnabledModules::DEBUGGER) {
+            self.debugger.init()?;
+        }
+
+        // Enable instruction deltas
+        if modules.contains(EnabledModules::INSTRUCTION_DELTAS) {
+            self.instruction_deltas.init()?;
+        }
+
         // Enable execution trace
         if modules.contains(EnabledModules::EXECUTION_TRACE) {
             self.execution_trace.init()?;
@@ -214,6 +252,12 @@ impl InitSystemModule for SystemModuleMixer {
         if modules.contains(EnabledModules::EXECUTION_TRACE) {
             self.execution_trace.on_teardown()?;
         }
+        if modules.contains(EnabledModules::INSTRUCTION_DELTAS) {
+            self.instruction_deltas.on_teardown()?;
+        }
+        if modules.contains(EnabledModules::DEBUGGER) {
+            self.debugger.on_teardown()?;
+        }
 
         Ok(())
     }
@@ -562,6 +606,15 @@ impl SystemModuleMixer {
         }
     }
 
+    /// Returns the kernel trace module, which retains the recorded trace even when disabled.
+    pub fn kernel_trace_mut(&mut self) -> &mut KernelTraceModule {
+        &mut self.kernel_trace
+    }
+
+    pub fn debugger_mut(&mut self) -> &mut DebuggerModule {
+        &mut self.debugger
+    }
+
     pub fn costing(&self) -> Option<&CostingModule> {
         if self.enabled_modules.contains(EnabledModules::COSTING) {
             Some(&self.costing)
@@ -620,12 +673,25 @@ impl SystemModuleMixer {
     }
 
     pub fn update_instruction_index(&mut self, new_index: usize) {
+        if self.enabled_modules.contains(EnabledModules::COSTING) {
+            self.costing.update_instruction_index(
+                new_index,
+                self.transaction_runtime.events.len(),
+                self.transaction_runtime.logs.len(),
+            )
+        }
         if self
             .enabled_modules
             .contains(EnabledModules::EXECUTION_TRACE)
         {
             self.execution_trace.update_instruction_index(new_index)
         }
+        if self
+            .enabled_modules
+            .contains(EnabledModules::INSTRUCTION_DELTAS)
+        {
+            self.instruction_deltas.update_instruction_index(new_index)
+        }
     }
 
     pub fn apply_execution_cost(
@@ -291,6 +291,23 @@ pub enum StoreCommit {
 }
 
 impl StoreCommit {
+    pub fn canonical_substate_key(&self) -> &CanonicalSubstateKey {
+        match self {
+            StoreCommit::Insert {
+                canonical_substate_key,
+                ..
+            }
+            | StoreCommit::Update {
+                canonical_substate_key,
+                ..
+            }
+            | StoreCommit::Delete {
+                canonical_substate_key,
+                ..
+            } => canonical_substate_key,
+        }
+    }
+
     pub fn node_id(&self) -> NodeId {
         match self {
             StoreCommit::Insert {
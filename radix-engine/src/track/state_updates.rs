@@ -583,6 +583,59 @@ pub fn to_state_updates<M: DatabaseKeyMapper + 'static>(
     )
 }
 
+/// The substates read and written by a transaction, as tracked by the
+/// [`Track`](crate::track::Track).
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor, Default)]
+pub struct SubstateReadWriteSet {
+    /// The substates read, whether they exist or not, including the ones subsequently written
+    /// and the ones read while booting.
+    pub reads: IndexSet<(NodeId, PartitionNumber, SubstateKey)>,
+    /// The substates created, updated or deleted.
+    pub writes: IndexSet<(NodeId, PartitionNumber, SubstateKey)>,
+    /// The partitions whose entries have been iterated over, in full or in part.
+    pub scanned_partitions: IndexSet<(NodeId, PartitionNumber)>,
+    /// The partitions deleted as a whole.
+    pub deleted_partitions: IndexSet<(NodeId, PartitionNumber)>,
+}
+
+pub fn to_read_write_set(tracked: &TrackedSubstates) -> SubstateReadWriteSet {
+    let mut read_write_set = SubstateReadWriteSet {
+        reads: tracked.boot_reads.clone(),
+        writes: index_set_new(),
+        scanned_partitions: tracked.scanned_partitions.clone(),
+        deleted_partitions: tracked.deleted_partitions.clone(),
+    };
+    let transient_substates = &tracked.transient_substates;
+    for (node_id, tracked_node) in &tracked.tracked_nodes {
+        for (partition_num, tracked_partition) in &tracked_node.tracked_partitions {
+            for tracked in tracked_partition.substates.values() {
+                // Transient substates are never committed, so can't conflict with other transactions
+                if transient_substates.is_transient(node_id, *partition_num, &tracked.substate_key)
+                {
+                    continue;
+                }
+                let (is_read, is_write) = match &tracked.substate_value {
+                    TrackedSubstateValue::ReadOnly(..) => (true, false),
+                    TrackedSubstateValue::ReadExistAndWrite(..)
+                    | TrackedSubstateValue::ReadNonExistAndWrite(..) => (true, true),
+                    TrackedSubstateValue::New(..) | TrackedSubstateValue::WriteOnly(..) => {
+                        (false, true)
+                    }
+                    TrackedSubstateValue::Garbage => (false, false),
+                };
+                let key = (*node_id, *partition_num, tracked.substate_key.clone());
+                if is_read {
+                    read_write_set.reads.insert(key.clone());
+                }
+                if is_write {
+                    read_write_set.writes.insert(key);
+                }
+            }
+        }
+    }
+    read_write_set
+}
+
 pub struct IterationCountedIter<'a, E> {
     pub iter:
         Box<dyn Iterator<Item = Result<(DbSortKey, (SubstateKey, IndexedScryptoValue)), E>> + 'a>,
@@ -12,6 +12,7 @@ use radix_substate_store_interface::{
     db_key_mapper::DatabaseKeyMapper,
     interface::{DbSortKey, PartitionEntry, SubstateDatabase},
 };
+use sbor::rust::cell::RefCell;
 use sbor::rust::collections::btree_map::Entry;
 use sbor::rust::iter::empty;
 use sbor::rust::mem;
@@ -35,6 +36,11 @@ pub struct Track<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> {
 
     transient_substates: TransientSubstates,
 
+    /// The substates read through [`BootStore`], which bypasses the tracked nodes
+    boot_reads: RefCell<IndexSet<(NodeId, PartitionNumber, SubstateKey)>>,
+    /// The partitions whose entries have been listed from the substate database
+    scanned_partitions: IndexSet<(NodeId, PartitionNumber)>,
+
     phantom_data: PhantomData<M>,
 }
 
@@ -48,6 +54,9 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> BootStore for Trac
         let db_partition_key = M::to_db_partition_key(node_id, partition_num);
         let db_sort_key = M::to_db_sort_key(&substate_key);
 
+        self.boot_reads
+            .borrow_mut()
+            .insert((*node_id, partition_num, substate_key.clone()));
         self.substate_db
             .get_substate(&db_partition_key, &db_sort_key)
             .map(|e| IndexedScryptoValue::from_vec(e).expect("Failed to decode substate"))
@@ -60,6 +69,10 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> BootStore for Trac
 pub struct TrackedSubstates {
     pub tracked_nodes: IndexMap<NodeId, TrackedNode>,
     pub deleted_partitions: IndexSet<(NodeId, PartitionNumber)>,
+    pub boot_reads: IndexSet<(NodeId, PartitionNumber, SubstateKey)>,
+    pub scanned_partitions: IndexSet<(NodeId, PartitionNumber)>,
+    /// The substates which are never committed, and so have been pruned from the tracked nodes
+    pub transient_substates: TransientSubstates,
 }
 
 impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
@@ -70,6 +83,8 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
             tracked_nodes: index_map_new(),
             deleted_partitions: index_set_new(),
             transient_substates: TransientSubstates::new(),
+            boot_reads: RefCell::new(index_set_new()),
+            scanned_partitions: index_set_new(),
             phantom_data: PhantomData::default(),
         }
     }
@@ -204,14 +219,14 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
     ///
     ///  Returns the state changes and dependencies.
     pub fn finalize(mut self) -> Result<(TrackedSubstates, &'s S), TrackFinalizeError> {
-        for (node_id, transient_substates) in self.transient_substates.transient_substates {
+        for (node_id, transient_substates) in &self.transient_substates.transient_substates {
             for (partition, substate_key) in transient_substates {
                 if let Some(tracked_partition) = self
                     .tracked_nodes
-                    .get_mut(&node_id)
-                    .and_then(|tracked_node| tracked_node.tracked_partitions.get_mut(&partition))
+                    .get_mut(node_id)
+                    .and_then(|tracked_node| tracked_node.tracked_partitions.get_mut(partition))
                 {
-                    let db_sort_key = M::to_db_sort_key(&substate_key);
+                    let db_sort_key = M::to_db_sort_key(substate_key);
                     let tracked_substate = tracked_partition.substates.remove(&db_sort_key);
                     if let Some(substate) =
                         tracked_substate.and_then(|s| s.substate_value.into_value())
@@ -228,6 +243,9 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
             TrackedSubstates {
                 tracked_nodes: self.tracked_nodes,
                 deleted_partitions: self.deleted_partitions,
+                boot_reads: self.boot_reads.into_inner(),
+                scanned_partitions: self.scanned_partitions,
+                transient_substates: self.transient_substates,
             },
             self.substate_db,
         ))
@@ -606,6 +624,7 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstate
             return Ok(items);
         }
 
+        self.scanned_partitions.insert((*node_id, partition_number));
         let db_partition_key = M::to_db_partition_key(node_id, partition_number);
         let mut tracked_iter = IterationCountedIter::new(Self::list_entries_from_db::<E, F, K>(
             self.substate_db,
@@ -694,6 +713,7 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstate
         }
 
         // Read from database
+        self.scanned_partitions.insert((*node_id, partition_number));
         let db_partition_key = M::to_db_partition_key(node_id, partition_number);
 
         let (new_updates, num_iterations) = {
@@ -798,6 +818,7 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstate
         > = if tracked_node.is_new {
             Box::new(empty()) // optimization: avoid touching the database altogether
         } else {
+            self.scanned_partitions.insert((*node_id, partition_number));
             let partition_key = M::to_db_partition_key(node_id, partition_number);
             Box::new(Self::list_entries_from_db::<E, F, SortedKey>(
                 self.substate_db,
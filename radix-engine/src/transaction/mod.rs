@@ -1,3 +1,5 @@
+#[cfg(not(feature = "alloc"))]
+mod parallel_executor;
 mod preview_executor;
 mod state_update_summary;
 mod system_structure;
@@ -5,6 +7,8 @@ mod transaction_executor;
 mod transaction_receipt;
 mod transaction_reconciler;
 
+#[cfg(not(feature = "alloc"))]
+pub use parallel_executor::*;
 pub use preview_executor::*;
 pub use state_update_summary::*;
 pub use system_structure::*;
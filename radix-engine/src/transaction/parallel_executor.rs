@@ -0,0 +1,405 @@
+use crate::blueprints::consensus_manager::{
+    ConsensusManagerField, ConsensusManagerValidatorRewardsFieldPayload, ValidatorRewardsSubstate,
+};
+use crate::blueprints::resource::{
+    FungibleVaultBalanceFieldPayload, FungibleVaultBalanceFieldSubstate, FungibleVaultField,
+};
+use crate::internal_prelude::*;
+use crate::track::*;
+use crate::transaction::*;
+use crate::vm::wasm::WasmEngine;
+use crate::vm::{NativeVmExtension, VmInit};
+use radix_substate_store_impls::substate_database_overlay::SubstateDatabaseOverlay;
+use radix_substate_store_interface::db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper};
+use radix_substate_store_interface::interface::*;
+use radix_transactions::model::Executable;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The substates written by the transactions of a batch, as recorded by the track.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchWriteSet {
+    /// The substates created, updated or deleted individually
+    pub substates: IndexSet<(NodeId, PartitionNumber, SubstateKey)>,
+    /// The partitions deleted as a whole
+    pub deleted_partitions: IndexSet<(NodeId, PartitionNumber)>,
+    /// All the partitions in which any substate has been written, or which have been deleted
+    pub partitions: IndexSet<(NodeId, PartitionNumber)>,
+}
+
+impl BatchWriteSet {
+    pub fn extend(&mut self, read_write_set: &SubstateReadWriteSet) {
+        for (node_id, partition_num, substate_key) in &read_write_set.writes {
+            self.substates
+                .insert((*node_id, *partition_num, substate_key.clone()));
+            self.partitions.insert((*node_id, *partition_num));
+        }
+        for partition in &read_write_set.deleted_partitions {
+            self.deleted_partitions.insert(*partition);
+            self.partitions.insert(*partition);
+        }
+    }
+
+    /// Whether the given substate may have been changed by these writes.
+    pub fn may_have_changed(&self, substate: &(NodeId, PartitionNumber, SubstateKey)) -> bool {
+        self.substates.contains(substate)
+            || self.deleted_partitions.contains(&(substate.0, substate.1))
+    }
+}
+
+/// The outcome of executing a batch of transactions in parallel.
+#[derive(Debug, Clone)]
+pub struct ParallelExecutionResult {
+    /// The receipts of all transactions, in the order of the batch. Each of them is identical to
+    /// the receipt of executing the transaction after committing all the previous ones.
+    pub receipts: Vec<TransactionReceipt>,
+    /// The indices of the transactions which conflicted with previous ones and have hence been
+    /// executed again, serially.
+    pub re_executed: Vec<usize>,
+    /// The state updates of the whole batch, in terms of the database.
+    pub database_updates: DatabaseUpdates,
+}
+
+/// Executes a batch of transactions, with results identical to executing and committing them one
+/// after another, without committing anything to the substate database.
+///
+/// The read/write sets are recorded regardless of `ExecutionConfig::enable_read_write_set`, which
+/// only determines whether they are kept on the returned receipts.
+///
+/// All transactions are first executed speculatively, on up to `parallelism` threads, against
+/// the substate database as is. The receipts are then accepted in order, based on the read/write
+/// sets recorded by the track: a transaction conflicts with the previous ones if any substate it
+/// has read has since been changed, or if any partition it has scanned has since been written, in
+/// which case it is executed again against the database overlaid with the previous state updates.
+///
+/// The substates which every transaction updates while paying its fees are excluded from the
+/// conflict detection, as these updates commute. Instead, their speculative updates are applied
+/// as deltas on top of the previous state updates:
+/// - the balances of the vaults the transaction only pays fees from, provided that these haven't
+///   decreased since, so that the fee locks would have succeeded too;
+/// - the validator rewards and the rewards vault, which the fees are distributed to, unless the
+///   transaction updates the consensus manager state, e.g. changes the epoch.
+///
+/// The transaction tracker never conflicts either, as it is only ever written with the same
+/// value within an epoch.
+///
+/// Note that if the execution config has a debugger, the speculative execution is skipped, and
+/// all transactions are executed serially with the debugger attached, so that it breaks exactly
+/// once in the execution of each transaction, in the order of the batch.
+pub fn execute_transactions_in_parallel<
+    's,
+    S: SubstateDatabase + Sync,
+    W: WasmEngine + Sync,
+    E: NativeVmExtension + Send + Sync,
+>(
+    substate_db: &S,
+    vm_init: VmInit<'s, W, E>,
+    execution_config: &ExecutionConfig,
+    transactions: &[Executable],
+    parallelism: usize,
+) -> ParallelExecutionResult {
+    let keep_read_write_sets = execution_config.enable_read_write_set;
+    let execution_config = &execution_config.clone().with_read_write_set(true);
+    let speculative_receipts = Mutex::new(
+        (0..transactions.len())
+            .map(|_| None)
+            .collect::<Vec<Option<TransactionReceipt>>>(),
+    );
+    let next_transaction = AtomicUsize::new(0);
+    let scrypto_vm = vm_init.scrypto_vm;
+    let native_vm_extension = &vm_init.native_vm_extension;
+    let speculative_threads = if execution_config.debugger.is_some() {
+        0
+    } else {
+        parallelism.max(1).min(transactions.len())
+    };
+
+    std::thread::scope(|scope| {
+        for _ in 0..speculative_threads {
+            scope.spawn(|| loop {
+                let index = next_transaction.fetch_add(1, Ordering::Relaxed);
+                let Some(transaction) = transactions.get(index) else {
+                    break;
+                };
+                let receipt = execute_transaction(
+                    substate_db,
+                    VmInit::new(scrypto_vm, native_vm_extension.clone()),
+                    execution_config,
+                    transaction,
+                );
+                speculative_receipts.lock().unwrap()[index] = Some(receipt);
+            });
+        }
+    });
+
+    let mut overlay = SubstateDatabaseOverlay::new_unmergeable(substate_db);
+    let mut write_set = BatchWriteSet::default();
+    let mut receipts = Vec::with_capacity(transactions.len());
+    let mut re_executed = Vec::new();
+    let speculative_receipts = speculative_receipts.into_inner().unwrap();
+    for (index, (transaction, speculative_receipt)) in
+        transactions.iter().zip(speculative_receipts).enumerate()
+    {
+        let accepted_receipt = match speculative_receipt {
+            Some(mut receipt) => {
+                let accepted = match &mut receipt.result {
+                    TransactionResult::Commit(commit) => {
+                        rebase_fee_updates(substate_db, &overlay, &write_set, commit)
+                    }
+                    // Rejections and aborts don't record what they have read
+                    TransactionResult::Reject(..) | TransactionResult::Abort(..) => {
+                        write_set.partitions.is_empty()
+                    }
+                };
+                if !accepted {
+                    re_executed.push(index);
+                }
+                accepted.then_some(receipt)
+            }
+            // Not executed speculatively, because of the debugger
+            None => None,
+        };
+        let mut receipt = accepted_receipt.unwrap_or_else(|| {
+            execute_transaction(
+                &overlay,
+                VmInit::new(scrypto_vm, native_vm_extension.clone()),
+                execution_config,
+                transaction,
+            )
+        });
+
+        if let TransactionResult::Commit(commit) = &mut receipt.result {
+            write_set.extend(
+                commit
+                    .read_write_set
+                    .as_ref()
+                    .expect("Read/write set should be recorded"),
+            );
+            overlay.commit(
+                &commit
+                    .state_updates
+                    .create_database_updates::<SpreadPrefixKeyMapper>(),
+            );
+            if !keep_read_write_sets {
+                commit.read_write_set = None;
+            }
+        }
+        receipts.push(receipt);
+    }
+
+    ParallelExecutionResult {
+        receipts,
+        re_executed,
+        database_updates: overlay.into_database_updates(),
+    }
+}
+
+/// Checks whether a speculatively executed transaction conflicts with the previous ones, and if
+/// not, applies its updates of the fee substates as deltas on top of the previous state updates.
+///
+/// Returns whether the transaction can be accepted as is.
+fn rebase_fee_updates<S: SubstateDatabase, O: SubstateDatabase>(
+    substate_db: &S,
+    overlay: &O,
+    write_set: &BatchWriteSet,
+    commit: &mut CommitResult,
+) -> bool {
+    let read_write_set = commit
+        .read_write_set
+        .as_ref()
+        .expect("Read/write set should be recorded");
+    let changed = |substate: &(NodeId, PartitionNumber, SubstateKey)| {
+        write_set.may_have_changed(substate)
+            && read_substate(substate_db, substate) != read_substate(overlay, substate)
+    };
+
+    let mut fee_substates = index_map_new();
+    for (vault_id, paid) in &commit.fee_source.paying_vaults {
+        fee_substates.insert(
+            vault_balance_key(*vault_id),
+            FeeSubstate::PayingVault(*paid),
+        );
+    }
+    let consensus_manager_state = (
+        CONSENSUS_MANAGER.into_node_id(),
+        MAIN_BASE_PARTITION,
+        ConsensusManagerField::State.into(),
+    );
+    let validator_rewards = (
+        CONSENSUS_MANAGER.into_node_id(),
+        MAIN_BASE_PARTITION,
+        ConsensusManagerField::ValidatorRewards.into(),
+    );
+    if !read_write_set.writes.contains(&consensus_manager_state)
+        && read_write_set.writes.contains(&validator_rewards)
+    {
+        let rewards_vault_id = read_substate(substate_db, &validator_rewards)
+            .map(|value| decode_validator_rewards(&value).rewards_vault.0 .0)
+            .expect("Validator rewards should exist");
+        fee_substates.insert(
+            vault_balance_key(rewards_vault_id),
+            FeeSubstate::RewardsVault,
+        );
+        fee_substates.insert(validator_rewards, FeeSubstate::ValidatorRewards);
+    }
+    // Only the fee substates which the transaction has updated, and which have been changed by
+    // the previous transactions, need to be rebased
+    fee_substates
+        .retain(|substate, _| read_write_set.writes.contains(substate) && changed(substate));
+
+    if read_write_set
+        .reads
+        .iter()
+        .any(|substate| !fee_substates.contains_key(substate) && changed(substate))
+        || read_write_set
+            .scanned_partitions
+            .iter()
+            .any(|partition| write_set.partitions.contains(partition))
+    {
+        return false;
+    }
+
+    let mut rebased_values = Vec::new();
+    for (substate, fee_substate) in &fee_substates {
+        let (Some(read), Some(current)) = (
+            read_substate(substate_db, substate),
+            read_substate(overlay, substate),
+        ) else {
+            return false;
+        };
+        let Some(written) = updated_value(&commit.state_updates, substate) else {
+            return false;
+        };
+        let rebased = match fee_substate {
+            FeeSubstate::PayingVault(paid) => {
+                let read = decode_vault_balance(&read)
+                    .payload()
+                    .as_unique_version()
+                    .amount();
+                let current_balance = decode_vault_balance(&current);
+                let current = current_balance.payload().as_unique_version().amount();
+                let written = decode_vault_balance(written);
+                let written_amount = written.payload().as_unique_version().amount();
+                // The vault must have been used to pay fees only, and must hold at least as much
+                // as it did when the fees were locked
+                let delta = written_amount.checked_sub(read).unwrap();
+                if delta != paid.checked_neg().unwrap() || current < read {
+                    return false;
+                }
+                encode_vault_balance(current.checked_add(delta).unwrap(), written.lock_status())
+            }
+            FeeSubstate::RewardsVault => {
+                let read = decode_vault_balance(&read)
+                    .payload()
+                    .as_unique_version()
+                    .amount();
+                let current = decode_vault_balance(&current)
+                    .payload()
+                    .as_unique_version()
+                    .amount();
+                let written = decode_vault_balance(written);
+                let delta = written
+                    .payload()
+                    .as_unique_version()
+                    .amount()
+                    .checked_sub(read)
+                    .unwrap();
+                encode_vault_balance(current.checked_add(delta).unwrap(), written.lock_status())
+            }
+            FeeSubstate::ValidatorRewards => {
+                let read = decode_validator_rewards(&read);
+                let mut rebased = decode_validator_rewards(&current);
+                for (validator_index, written) in decode_validator_rewards(written).proposer_rewards
+                {
+                    let delta = written
+                        .checked_sub(
+                            read.proposer_rewards
+                                .get(&validator_index)
+                                .cloned()
+                                .unwrap_or_default(),
+                        )
+                        .unwrap();
+                    let entry = rebased.proposer_rewards.entry(validator_index).or_default();
+                    *entry = entry.checked_add(delta).unwrap();
+                }
+                scrypto_encode(&FieldSubstate::new_unlocked_field(
+                    ConsensusManagerValidatorRewardsFieldPayload::from_content_source(rebased),
+                ))
+                .unwrap()
+            }
+        };
+        rebased_values.push((substate.clone(), rebased));
+    }
+
+    for ((node_id, partition_num, substate_key), value) in rebased_values {
+        commit
+            .state_updates
+            .of_node(node_id)
+            .of_partition(partition_num)
+            .update_substates([(substate_key, DatabaseUpdate::Set(value))]);
+    }
+    true
+}
+
+/// A substate which transactions update while paying fees.
+enum FeeSubstate {
+    /// The balance of a vault the transaction has paid the given amount of fees from
+    PayingVault(Decimal),
+    /// The balance of the vault which the validator rewards are deposited into
+    RewardsVault,
+    /// The rewards of the proposers
+    ValidatorRewards,
+}
+
+fn vault_balance_key(vault_id: NodeId) -> (NodeId, PartitionNumber, SubstateKey) {
+    (
+        vault_id,
+        MAIN_BASE_PARTITION,
+        FungibleVaultField::Balance.into(),
+    )
+}
+
+fn read_substate<S: SubstateDatabase>(
+    substate_db: &S,
+    (node_id, partition_num, substate_key): &(NodeId, PartitionNumber, SubstateKey),
+) -> Option<DbSubstateValue> {
+    substate_db.get_substate(
+        &SpreadPrefixKeyMapper::to_db_partition_key(node_id, *partition_num),
+        &SpreadPrefixKeyMapper::to_db_sort_key(substate_key),
+    )
+}
+
+fn updated_value<'a>(
+    state_updates: &'a StateUpdates,
+    (node_id, partition_num, substate_key): &(NodeId, PartitionNumber, SubstateKey),
+) -> Option<&'a DbSubstateValue> {
+    let NodeStateUpdates::Delta { by_partition } = state_updates.by_node.get(node_id)?;
+    match by_partition.get(partition_num)? {
+        PartitionStateUpdates::Delta { by_substate } => match by_substate.get(substate_key)? {
+            DatabaseUpdate::Set(value) => Some(value),
+            DatabaseUpdate::Delete => None,
+        },
+        PartitionStateUpdates::Batch(BatchPartitionStateUpdate::Reset {
+            new_substate_values,
+        }) => new_substate_values.get(substate_key),
+    }
+}
+
+fn decode_vault_balance(value: &[u8]) -> FungibleVaultBalanceFieldSubstate {
+    scrypto_decode(value).expect("Fee vault balance should decode")
+}
+
+fn encode_vault_balance(amount: Decimal, lock_status: LockStatus) -> Vec<u8> {
+    scrypto_encode(&FieldSubstate::new_field(
+        FungibleVaultBalanceFieldPayload::from_content_source(LiquidFungibleResource::new(amount)),
+        lock_status,
+    ))
+    .unwrap()
+}
+
+fn decode_validator_rewards(value: &[u8]) -> ValidatorRewardsSubstate {
+    scrypto_decode::<FieldSubstate<ConsensusManagerValidatorRewardsFieldPayload>>(value)
+        .expect("Validator rewards should decode")
+        .into_payload()
+        .into_unique_version()
+}
@@ -1,8 +1,11 @@
+use crate::internal_prelude::*;
 use crate::transaction::TransactionReceipt;
 use crate::transaction::*;
 use crate::vm::wasm::WasmEngine;
 use crate::vm::{NativeVmExtension, VmInit};
 use radix_common::network::NetworkDefinition;
+use radix_substate_store_impls::substate_database_overlay::SubstateDatabaseOverlay;
+use radix_substate_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
 use radix_substate_store_interface::interface::*;
 use radix_transactions::errors::TransactionValidationError;
 use radix_transactions::model::PreviewIntentV1;
@@ -43,3 +46,74 @@ pub fn execute_preview<'s, S: SubstateDatabase, W: WasmEngine, E: NativeVmExtens
         &validated.get_executable(),
     ))
 }
+
+/// Executes a preview intent as if the given database updates had been committed on top of the
+/// substate database, e.g. to simulate a transaction whose preconditions don't hold yet.
+///
+/// The updates are layered over the database through a [`SubstateDatabaseOverlay`] and are never
+/// written to the database itself. State updates, e.g. taken from the receipt of another preview,
+/// can be turned into database updates through [`StateUpdates::create_database_updates`].
+///
+/// [`StateUpdates::create_database_updates`]: crate::track::StateUpdates::create_database_updates
+pub fn execute_preview_with_state_overrides<
+    's,
+    S: SubstateDatabase,
+    W: WasmEngine,
+    E: NativeVmExtension,
+>(
+    substate_db: &S,
+    vm_init: VmInit<'s, W, E>,
+    network: &NetworkDefinition,
+    preview_intent: PreviewIntentV1,
+    with_kernel_trace: bool,
+    state_overrides: &DatabaseUpdates,
+) -> Result<TransactionReceipt, PreviewError> {
+    let mut overlay = SubstateDatabaseOverlay::new_unmergeable(substate_db);
+    overlay.commit(state_overrides);
+
+    execute_preview(
+        &overlay,
+        vm_init,
+        network,
+        preview_intent,
+        with_kernel_trace,
+    )
+}
+
+/// Executes an ordered sequence of preview intents, each of them seeing the state updates of the
+/// previous ones as if they had been committed, and returns the receipts of all of them.
+///
+/// The state updates are accumulated in a [`SubstateDatabaseOverlay`], so nothing is written to
+/// the substate database itself. The state updates of failed transactions (i.e. fee payments) are
+/// carried over too, like they would be on ledger, whereas rejected or aborted transactions have
+/// no effect on the subsequent ones.
+pub fn execute_preview_batch<'s, S: SubstateDatabase, W: WasmEngine, E: NativeVmExtension>(
+    substate_db: &S,
+    vm_init: VmInit<'s, W, E>,
+    network: &NetworkDefinition,
+    preview_intents: Vec<PreviewIntentV1>,
+    with_kernel_trace: bool,
+) -> Result<Vec<TransactionReceipt>, PreviewError> {
+    let mut overlay = SubstateDatabaseOverlay::new_unmergeable(substate_db);
+
+    let mut receipts = Vec::with_capacity(preview_intents.len());
+    for preview_intent in preview_intents {
+        let receipt = execute_preview(
+            &overlay,
+            VmInit::new(vm_init.scrypto_vm, vm_init.native_vm_extension.clone()),
+            network,
+            preview_intent,
+            with_kernel_trace,
+        )?;
+        if let TransactionResult::Commit(commit) = &receipt.result {
+            overlay.commit(
+                &commit
+                    .state_updates
+                    .create_database_updates::<SpreadPrefixKeyMapper>(),
+            );
+        }
+        receipts.push(receipt);
+    }
+
+    Ok(receipts)
+}
@@ -5,6 +5,7 @@ use crate::kernel::kernel::BootLoader;
 use crate::kernel::kernel_callback_api::*;
 use crate::system::system_callback::{System, SystemInit};
 use crate::system::system_callback_api::SystemCallbackObject;
+use crate::system::system_modules::debugger::DebuggerConfig;
 use crate::track::{BootStore, Track};
 use crate::transaction::*;
 use crate::vm::wasm::WasmEngine;
@@ -159,6 +160,9 @@ pub struct ExecutionConfig {
     pub enable_kernel_trace: bool,
     pub enable_cost_breakdown: bool,
     pub execution_trace: Option<usize>,
+    pub enable_instruction_deltas: bool,
+    pub enable_read_write_set: bool,
+    pub debugger: Option<DebuggerConfig>,
 
     pub system_overrides: Option<SystemOverrides>,
 }
@@ -169,6 +173,9 @@ impl Default for ExecutionConfig {
             enable_kernel_trace: false,
             enable_cost_breakdown: false,
             execution_trace: None,
+            enable_instruction_deltas: false,
+            enable_read_write_set: false,
+            debugger: None,
             system_overrides: None,
         }
     }
@@ -219,6 +226,8 @@ impl ExecutionConfig {
         Self {
             enable_kernel_trace: true,
             enable_cost_breakdown: true,
+            enable_instruction_deltas: true,
+            enable_read_write_set: true,
             ..Self::with_network(NetworkDefinition::simulator())
         }
     }
@@ -227,6 +236,7 @@ impl ExecutionConfig {
         Self {
             enable_cost_breakdown: true,
             execution_trace: Some(MAX_EXECUTION_TRACE_DEPTH),
+            enable_instruction_deltas: true,
             ..Self::with_network(network_definition)
         }
     }
@@ -235,6 +245,7 @@ impl ExecutionConfig {
         Self {
             enable_cost_breakdown: true,
             execution_trace: Some(MAX_EXECUTION_TRACE_DEPTH),
+            enable_instruction_deltas: true,
             system_overrides: Some(SystemOverrides {
                 disable_auth: true,
                 network_definition: Some(network_definition),
@@ -253,6 +264,21 @@ impl ExecutionConfig {
         self.enable_cost_breakdown = enabled;
         self
     }
+
+    pub fn with_instruction_deltas(mut self, enabled: bool) -> Self {
+        self.enable_instruction_deltas = enabled;
+        self
+    }
+
+    pub fn with_read_write_set(mut self, enabled: bool) -> Self {
+        self.enable_read_write_set = enabled;
+        self
+    }
+
+    pub fn with_debugger(mut self, debugger: Option<DebuggerConfig>) -> Self {
+        self.debugger = debugger;
+        self
+    }
 }
 
 pub struct SubstateBootStore<'a, S: SubstateDatabase> {
@@ -320,6 +346,9 @@ pub fn execute_transaction_with_configuration<S: SubstateDatabase, V: SystemCall
             enable_kernel_trace: execution_config.enable_kernel_trace,
             enable_cost_breakdown: execution_config.enable_cost_breakdown,
             execution_trace: execution_config.execution_trace,
+            enable_instruction_deltas: execution_config.enable_instruction_deltas,
+            enable_read_write_set: execution_config.enable_read_write_set,
+            debugger: execution_config.debugger.clone(),
             callback_init: vms,
             system_overrides: execution_config.system_overrides.clone(),
         },
@@ -6,11 +6,14 @@ use crate::kernel::kernel_callback_api::ExecutionReceipt;
 use crate::system::system_db_reader::SystemDatabaseReader;
 use crate::system::system_modules::costing::*;
 use crate::system::system_modules::execution_trace::*;
+use crate::system::system_modules::instruction_deltas::InstructionDeltas;
+use crate::system::system_modules::kernel_trace::KernelTrace;
 use crate::system::system_substate_schemas::*;
 use crate::track::BatchPartitionStateUpdate;
 use crate::track::NodeStateUpdates;
 use crate::track::PartitionStateUpdates;
 use crate::track::StateUpdates;
+use crate::track::SubstateReadWriteSet;
 use crate::transaction::SystemStructure;
 use colored::*;
 use radix_engine_interface::blueprints::transaction_processor::InstructionOutput;
@@ -19,17 +22,140 @@ use radix_transactions::model::Executable;
 use radix_transactions::prelude::TransactionCostingParametersReceipt;
 use sbor::representations::*;
 
-define_single_versioned! {
+define_versioned! {
     /// We define a versioned transaction receipt for encoding in the preview API.
     /// This allows a new toolkit build to be able to handle both current and future
     /// receipt versions, allowing us to release a wallet ahead-of-time which is forward
     /// compatible with a new version of the engine (and so a new transaction receipt).
     #[derive(Clone, ScryptoSbor)]
-    pub VersionedTransactionReceipt(TransactionReceiptVersions) => TransactionReceipt = TransactionReceiptV1
+    pub VersionedTransactionReceipt(TransactionReceiptVersions) {
+        previous_versions: [
+            1 => TransactionReceiptV1: { updates_to: 2 },
+        ],
+        latest_version: {
+            2 => TransactionReceipt = TransactionReceiptV2,
+        },
+    }
 }
 
+/// The receipt before the kernel trace, the call frame and instruction cost breakdowns, the
+/// instruction deltas and the read/write set were added.
+///
+/// Its encoding must not change, so that receipts encoded by older engines can still be decoded.
 #[derive(Clone, ScryptoSbor, PartialEq, Eq)]
 pub struct TransactionReceiptV1 {
+    /// Costing parameters
+    pub costing_parameters: CostingParameters,
+    /// Transaction costing parameters
+    pub transaction_costing_parameters: TransactionCostingParametersReceipt,
+    /// Transaction fee summary
+    pub fee_summary: TransactionFeeSummary,
+    /// Transaction fee detail
+    /// Available if `ExecutionConfig::enable_cost_breakdown` is enabled
+    pub fee_details: Option<TransactionFeeDetailsV1>,
+    /// Transaction result
+    pub result: TransactionResultV1,
+    /// Hardware resources usage report
+    /// Available if `resources_usage` feature flag is enabled
+    pub resources_usage: Option<ResourcesUsage>,
+}
+
+#[derive(Default, Debug, Clone, ScryptoSbor, PartialEq, Eq)]
+#[sbor(type_name = "TransactionFeeDetails")]
+pub struct TransactionFeeDetailsV1 {
+    /// Execution cost breakdown
+    pub execution_cost_breakdown: BTreeMap<String, u32>,
+    /// Finalization cost breakdown
+    pub finalization_cost_breakdown: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, ScryptoSbor, PartialEq, Eq)]
+#[sbor(type_name = "TransactionResult")]
+pub enum TransactionResultV1 {
+    Commit(CommitResultV1),
+    Reject(RejectResult),
+    Abort(AbortResult),
+}
+
+#[derive(Debug, Clone, ScryptoSbor, PartialEq, Eq)]
+#[sbor(type_name = "CommitResult")]
+pub struct CommitResultV1 {
+    /// Substate updates
+    pub state_updates: StateUpdates,
+    /// Information extracted from the substate updates
+    pub state_update_summary: StateUpdateSummary,
+    /// The source of transaction fee
+    pub fee_source: FeeSource,
+    /// The destination of transaction fee
+    pub fee_destination: FeeDestination,
+    /// Transaction execution outcome
+    pub outcome: TransactionOutcome,
+    /// Events emitted
+    pub application_events: Vec<(EventTypeIdentifier, Vec<u8>)>,
+    /// Logs emitted
+    pub application_logs: Vec<(Level, String)>,
+    /// Additional annotation on substates and events
+    pub system_structure: SystemStructure,
+    /// Transaction execution traces
+    /// Available if `ExecutionTrace` module is enabled
+    pub execution_trace: Option<TransactionExecutionTrace>,
+}
+
+impl From<TransactionReceiptV1> for TransactionReceiptV2 {
+    fn from(value: TransactionReceiptV1) -> Self {
+        Self {
+            costing_parameters: value.costing_parameters,
+            transaction_costing_parameters: value.transaction_costing_parameters,
+            fee_summary: value.fee_summary,
+            fee_details: value.fee_details.map(Into::into),
+            result: value.result.into(),
+            resources_usage: value.resources_usage,
+            kernel_trace: None,
+        }
+    }
+}
+
+impl From<TransactionFeeDetailsV1> for TransactionFeeDetails {
+    fn from(value: TransactionFeeDetailsV1) -> Self {
+        Self {
+            execution_cost_breakdown: value.execution_cost_breakdown,
+            finalization_cost_breakdown: value.finalization_cost_breakdown,
+            call_frame_cost_breakdown: Default::default(),
+            instruction_cost_breakdown: Default::default(),
+        }
+    }
+}
+
+impl From<TransactionResultV1> for TransactionResult {
+    fn from(value: TransactionResultV1) -> Self {
+        match value {
+            TransactionResultV1::Commit(commit) => TransactionResult::Commit(commit.into()),
+            TransactionResultV1::Reject(reject) => TransactionResult::Reject(reject),
+            TransactionResultV1::Abort(abort) => TransactionResult::Abort(abort),
+        }
+    }
+}
+
+impl From<CommitResultV1> for CommitResult {
+    fn from(value: CommitResultV1) -> Self {
+        Self {
+            state_updates: value.state_updates,
+            state_update_summary: value.state_update_summary,
+            fee_source: value.fee_source,
+            fee_destination: value.fee_destination,
+            outcome: value.outcome,
+            application_events: value.application_events,
+            application_logs: value.application_logs,
+            system_structure: value.system_structure,
+            execution_trace: value.execution_trace,
+            instruction_deltas: None,
+            read_write_set: None,
+        }
+    }
+}
+
+#[derive(Clone, ScryptoSbor, PartialEq, Eq)]
+pub struct TransactionReceiptV2 {
     /// Costing parameters
     pub costing_parameters: CostingParameters,
     /// Transaction costing parameters
@@ -44,6 +170,9 @@ pub struct TransactionReceiptV1 {
     /// Hardware resources usage report
     /// Available if `resources_usage` feature flag is enabled
     pub resources_usage: Option<ResourcesUsage>,
+    /// Kernel operations performed during execution
+    /// Available if `KernelTrace` module is enabled
+    pub kernel_trace: Option<KernelTrace>,
 }
 
 impl ExecutionReceipt for TransactionReceipt {
@@ -55,6 +184,7 @@ impl ExecutionReceipt for TransactionReceipt {
             fee_details: None,
             result: TransactionResult::Reject(RejectResult { reason }),
             resources_usage: None,
+            kernel_trace: None,
         }
     }
 
@@ -88,6 +218,69 @@ pub struct TransactionFeeDetails {
     pub execution_cost_breakdown: BTreeMap<String, u32>,
     /// Finalization cost breakdown
     pub finalization_cost_breakdown: BTreeMap<String, u32>,
+    /// Execution cost breakdown by call frame
+    pub call_frame_cost_breakdown: CallFrameCostBreakdown,
+    /// Cost breakdown by manifest instruction
+    pub instruction_cost_breakdown: InstructionCostBreakdown,
+}
+
+/// The execution costs consumed by a call frame and by the call frames invoked from it.
+#[derive(Default, Debug, Clone, ScryptoSbor, PartialEq, Eq)]
+pub struct CallFrameCostBreakdown {
+    /// The actor of the call frame, e.g. `Account::withdraw`.
+    pub actor: String,
+    /// Execution cost units consumed directly by this call frame.
+    pub self_execution_cost_units: u32,
+    /// Execution cost units consumed by this call frame and all the call frames invoked from it.
+    pub total_execution_cost_units: u32,
+    /// The call frames invoked from this call frame, in the order of invocation.
+    pub children: Vec<CallFrameCostBreakdown>,
+}
+
+impl CallFrameCostBreakdown {
+    /// Renders the tree in the "collapsed stacks" format consumed by the flame graph tools, i.e. a
+    /// `Root;Caller;Callee <self execution cost units>` line for every call frame.
+    pub fn to_collapsed_stacks(&self) -> String {
+        let mut buffer = String::new();
+        self.write_collapsed_stacks("", &mut buffer);
+        buffer
+    }
+
+    fn write_collapsed_stacks(&self, parent_stack: &str, buffer: &mut String) {
+        let stack = if parent_stack.is_empty() {
+            self.actor.clone()
+        } else {
+            format!("{};{}", parent_stack, self.actor)
+        };
+        if self.self_execution_cost_units > 0 {
+            buffer.push_str(&format!("{} {}\n", stack, self.self_execution_cost_units));
+        }
+        for child in &self.children {
+            child.write_collapsed_stacks(&stack, buffer);
+        }
+    }
+}
+
+/// The costs attributed to the instructions of a manifest.
+#[derive(Default, Debug, Clone, ScryptoSbor, PartialEq, Eq)]
+pub struct InstructionCostBreakdown {
+    /// The costs of each executed instruction, by position in the manifest.
+    pub instructions: Vec<InstructionCosts>,
+    /// The costs not incurred by any instruction, e.g. of booting the transaction, excluding the
+    /// costs of validating the payload and verifying the signatures.
+    pub overhead: InstructionCosts,
+}
+
+/// The costs incurred by a manifest instruction, including those of committing the substates it
+/// last updated and the events and logs it emitted.
+#[derive(Default, Debug, Clone, Copy, ScryptoSbor, PartialEq, Eq)]
+pub struct InstructionCosts {
+    pub execution_cost_units: u32,
+    pub finalization_cost_units: u32,
+    /// The increase of the state size, in bytes
+    pub state_storage_bytes: usize,
+    /// The size of the events and logs, in bytes
+    pub archive_storage_bytes: usize,
 }
 
 /// Captures whether a transaction should be committed, and its other results
@@ -119,6 +312,12 @@ pub struct CommitResult {
     /// Transaction execution traces
     /// Available if `ExecutionTrace` module is enabled
     pub execution_trace: Option<TransactionExecutionTrace>,
+    /// The resource movements of each manifest instruction
+    /// Available if `InstructionDeltas` module is enabled
+    pub instruction_deltas: Option<Vec<InstructionDeltas>>,
+    /// The substates read and written, including the reads which did not lead to any update
+    /// Available if `ExecutionConfig::enable_read_write_set` is enabled
+    pub read_write_set: Option<SubstateReadWriteSet>,
 }
 
 #[derive(Debug, Clone, Default, ScryptoSbor, PartialEq, Eq)]
@@ -207,6 +406,8 @@ impl CommitResult {
             application_logs: Default::default(),
             system_structure: Default::default(),
             execution_trace: Default::default(),
+            instruction_deltas: Default::default(),
+            read_write_set: None,
         }
     }
 
@@ -385,6 +586,7 @@ impl TransactionReceipt {
             fee_details: Default::default(),
             result: TransactionResult::Commit(commit_result),
             resources_usage: Default::default(),
+            kernel_trace: Default::default(),
         }
     }
 
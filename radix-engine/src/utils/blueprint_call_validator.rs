@@ -0,0 +1,199 @@
+use super::native_blueprint_call_validator::*;
+use crate::internal_prelude::*;
+use crate::system::system_db_reader::{SystemDatabaseReader, SystemReaderError};
+use radix_common::data::manifest::*;
+use radix_engine_interface::api::ModuleId;
+use radix_engine_interface::blueprints::account::ACCOUNT_BLUEPRINT;
+use radix_engine_interface::blueprints::identity::IDENTITY_BLUEPRINT;
+use radix_engine_interface::blueprints::package::*;
+use radix_transactions::manifest::compiler::CompileErrorDiagnosticsStyle;
+use radix_transactions::manifest::diagnostic_snippets::create_snippet;
+use radix_transactions::manifest::token::Span;
+use radix_transactions::prelude::*;
+
+/// Validates the arguments of the invocations in the manifest against the input schemas of the
+/// invoked blueprints, as found in the given database.
+///
+/// Unlike [`validate_call_arguments_to_native_components`], this resolves the target of each
+/// invocation through the database, and so covers calls to Scrypto packages and components too.
+/// Invocations whose target isn't known statically (e.g. those to named addresses) and functions
+/// with generic inputs are skipped.
+pub fn validate_call_arguments_to_blueprints<S: SubstateDatabase + ?Sized>(
+    instructions: &[InstructionV1],
+    substate_db: &S,
+) -> Result<(), LocatedInstructionSchemaValidationError> {
+    let reader = SystemDatabaseReader::new(substate_db);
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        let Some((invocation, args)) = get_invocation(instruction) else {
+            continue;
+        };
+
+        validate_invocation(&reader, &invocation, args).map_err(|cause| {
+            LocatedInstructionSchemaValidationError {
+                instruction_index: index,
+                cause,
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+fn validate_invocation<S: SubstateDatabase + ?Sized>(
+    reader: &SystemDatabaseReader<S>,
+    invocation: &Invocation,
+    args: &ManifestValue,
+) -> Result<(), InstructionSchemaValidationError> {
+    let blueprint_id = match invocation {
+        Invocation::Function(package_address, blueprint_name, _) => {
+            BlueprintId::new(package_address, blueprint_name)
+        }
+        Invocation::Method(address, module_id, _) => {
+            match reader.get_blueprint_id(address.as_node_id(), *module_id) {
+                Ok(blueprint_id) => blueprint_id,
+                Err(SystemReaderError::NodeIdDoesNotExist) if is_virtual(address) => {
+                    virtual_blueprint_id(address, *module_id).ok_or(
+                        InstructionSchemaValidationError::ModuleNotAttached(*address, *module_id),
+                    )?
+                }
+                Err(SystemReaderError::ModuleDoesNotExist) => {
+                    return Err(InstructionSchemaValidationError::ModuleNotAttached(
+                        *address, *module_id,
+                    ))
+                }
+                Err(_) => return Err(InstructionSchemaValidationError::InvalidAddress(*address)),
+            }
+        }
+        Invocation::DirectMethod(address, _) => reader
+            .get_blueprint_id(address.as_node_id(), ModuleId::Main)
+            .map_err(|_| InstructionSchemaValidationError::InvalidInternalAddress(*address))?,
+    };
+
+    let definition = reader
+        .get_blueprint_definition(&blueprint_id)
+        .map_err(|_| {
+            InstructionSchemaValidationError::InvalidBlueprint(
+                blueprint_id.package_address,
+                blueprint_id.blueprint_name.clone(),
+            )
+        })?;
+    let function_schema = definition
+        .interface
+        .functions
+        .get(invocation.method())
+        .ok_or_else(|| {
+            InstructionSchemaValidationError::MethodNotFound(invocation.method().to_owned())
+        })?;
+    if !is_valid_receiver(&function_schema.receiver, invocation) {
+        return Err(InstructionSchemaValidationError::InvalidReceiver);
+    }
+
+    let BlueprintPayloadDef::Static(ScopedTypeId(schema_hash, local_type_id)) =
+        function_schema.input
+    else {
+        return Ok(());
+    };
+    let schema = reader
+        .get_schema(blueprint_id.package_address.as_node_id(), &schema_hash)
+        .map_err(|error| {
+            InstructionSchemaValidationError::SchemaValidationError(format!("{:?}", error))
+        })?;
+
+    validate_payload_against_schema::<ManifestCustomExtension, _>(
+        &manifest_encode(args).unwrap(),
+        schema.v1(),
+        local_type_id,
+        &(),
+        MANIFEST_SBOR_V1_MAX_DEPTH,
+    )
+    .map_err(|error| {
+        InstructionSchemaValidationError::SchemaValidationError(format!("{:?}", error))
+    })
+}
+
+fn is_virtual(address: &GlobalAddress) -> bool {
+    matches!(
+        address.as_node_id().entity_type(),
+        Some(
+            EntityType::GlobalVirtualSecp256k1Account
+                | EntityType::GlobalVirtualEd25519Account
+                | EntityType::GlobalVirtualSecp256k1Identity
+                | EntityType::GlobalVirtualEd25519Identity
+        )
+    )
+}
+
+/// Returns the blueprint of the module of a virtual account or identity, as implied by its entity
+/// type, since these are valid targets before they are instantiated on the first call.
+fn virtual_blueprint_id(address: &GlobalAddress, module_id: ModuleId) -> Option<BlueprintId> {
+    let main_blueprint_id = match address.as_node_id().entity_type()? {
+        EntityType::GlobalVirtualSecp256k1Account | EntityType::GlobalVirtualEd25519Account => {
+            BlueprintId::new(&ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT)
+        }
+        EntityType::GlobalVirtualSecp256k1Identity | EntityType::GlobalVirtualEd25519Identity => {
+            BlueprintId::new(&IDENTITY_PACKAGE, IDENTITY_BLUEPRINT)
+        }
+        _ => return None,
+    };
+    match module_id {
+        ModuleId::Main => Some(main_blueprint_id),
+        // Virtual entities are instantiated with metadata and role assignment, but no royalty
+        ModuleId::Metadata | ModuleId::RoleAssignment => module_id.static_blueprint(),
+        ModuleId::Royalty => None,
+    }
+}
+
+/// Renders a validation error as a snippet of the manifest source, given the spans of its
+/// instructions as returned by [`compile_with_spans`].
+///
+/// [`compile_with_spans`]: radix_transactions::manifest::compiler::compile_with_spans
+pub fn instruction_schema_validation_error_diagnostics(
+    s: &str,
+    instruction_spans: &[Span],
+    err: &LocatedInstructionSchemaValidationError,
+    style: CompileErrorDiagnosticsStyle,
+) -> String {
+    let (title, label) = instruction_schema_validation_error_title_and_label(&err.cause);
+    match instruction_spans.get(err.instruction_index) {
+        Some(span) => create_snippet(s, span, &title, &label, style),
+        None => format!("error at instruction {}: {}", err.instruction_index, title),
+    }
+}
+
+/// Returns the title and the label of the diagnostics of a validation error, describing the error
+/// and annotating the instruction respectively.
+pub fn instruction_schema_validation_error_title_and_label(
+    cause: &InstructionSchemaValidationError,
+) -> (String, String) {
+    match cause {
+        InstructionSchemaValidationError::MethodNotFound(method_name) => (
+            format!("function or method `{}` not found", method_name),
+            "unknown function or method".to_string(),
+        ),
+        InstructionSchemaValidationError::SchemaValidationError(error) => (
+            format!("arguments don't match the input schema: {}", error),
+            "invalid arguments".to_string(),
+        ),
+        InstructionSchemaValidationError::InvalidAddress(_) => (
+            "global address not found".to_string(),
+            "unknown address".to_string(),
+        ),
+        InstructionSchemaValidationError::InvalidInternalAddress(_) => (
+            "internal address not found".to_string(),
+            "unknown address".to_string(),
+        ),
+        InstructionSchemaValidationError::InvalidBlueprint(_, blueprint_name) => (
+            format!("blueprint `{}` not found", blueprint_name),
+            "unknown blueprint".to_string(),
+        ),
+        InstructionSchemaValidationError::InvalidReceiver => (
+            "invalid receiver".to_string(),
+            "function called as a method, or method called as a function".to_string(),
+        ),
+        InstructionSchemaValidationError::ModuleNotAttached(_, module_id) => (
+            format!("{:?} module not attached", module_id),
+            "unknown module".to_string(),
+        ),
+    }
+}
@@ -1,3 +1,4 @@
+mod blueprint_call_validator;
 #[cfg(not(feature = "alloc"))]
 mod costing_formatting;
 #[cfg(feature = "coverage")]
@@ -7,6 +8,7 @@ mod native_blueprint_call_validator;
 mod package_extractor;
 mod panics;
 
+pub use blueprint_call_validator::*;
 #[cfg(not(feature = "alloc"))]
 pub use costing_formatting::*;
 #[cfg(feature = "coverage")]
@@ -20,61 +20,8 @@ pub fn validate_call_arguments_to_native_components(
     instructions: &[InstructionV1],
 ) -> Result<(), LocatedInstructionSchemaValidationError> {
     for (index, instruction) in instructions.iter().enumerate() {
-        let (invocation, args) = match instruction {
-            InstructionV1::CallFunction {
-                package_address: DynamicPackageAddress::Static(address),
-                blueprint_name,
-                function_name,
-                args,
-            } => (
-                Invocation::Function(
-                    *address,
-                    blueprint_name.to_owned(),
-                    function_name.to_owned(),
-                ),
-                args,
-            ),
-            InstructionV1::CallMethod {
-                address: DynamicGlobalAddress::Static(address),
-                method_name,
-                args,
-            } => (
-                Invocation::Method(*address, ModuleId::Main, method_name.to_owned()),
-                args,
-            ),
-            InstructionV1::CallMetadataMethod {
-                address: DynamicGlobalAddress::Static(address),
-                method_name,
-                args,
-            } => (
-                Invocation::Method(*address, ModuleId::Metadata, method_name.to_owned()),
-                args,
-            ),
-            InstructionV1::CallRoyaltyMethod {
-                address: DynamicGlobalAddress::Static(address),
-                method_name,
-                args,
-            } => (
-                Invocation::Method(*address, ModuleId::Royalty, method_name.to_owned()),
-                args,
-            ),
-            InstructionV1::CallRoleAssignmentMethod {
-                address: DynamicGlobalAddress::Static(address),
-                method_name,
-                args,
-            } => (
-                Invocation::Method(*address, ModuleId::RoleAssignment, method_name.to_owned()),
-                args,
-            ),
-            InstructionV1::CallDirectVaultMethod {
-                address,
-                method_name,
-                args,
-            } => (
-                Invocation::DirectMethod(*address, method_name.to_owned()),
-                args,
-            ),
-            _ => continue,
+        let Some((invocation, args)) = get_invocation(instruction) else {
+            continue;
         };
 
         let schema = get_arguments_schema(invocation).map_err(|cause| {
@@ -107,6 +54,68 @@ pub fn validate_call_arguments_to_native_components(
     Ok(())
 }
 
+/// Returns the invocation made by the instruction, or `None` if the instruction doesn't invoke a
+/// blueprint or its target isn't known statically.
+pub(super) fn get_invocation(instruction: &InstructionV1) -> Option<(Invocation, &ManifestValue)> {
+    let invocation = match instruction {
+        InstructionV1::CallFunction {
+            package_address: DynamicPackageAddress::Static(address),
+            blueprint_name,
+            function_name,
+            args,
+        } => (
+            Invocation::Function(
+                *address,
+                blueprint_name.to_owned(),
+                function_name.to_owned(),
+            ),
+            args,
+        ),
+        InstructionV1::CallMethod {
+            address: DynamicGlobalAddress::Static(address),
+            method_name,
+            args,
+        } => (
+            Invocation::Method(*address, ModuleId::Main, method_name.to_owned()),
+            args,
+        ),
+        InstructionV1::CallMetadataMethod {
+            address: DynamicGlobalAddress::Static(address),
+            method_name,
+            args,
+        } => (
+            Invocation::Method(*address, ModuleId::Metadata, method_name.to_owned()),
+            args,
+        ),
+        InstructionV1::CallRoyaltyMethod {
+            address: DynamicGlobalAddress::Static(address),
+            method_name,
+            args,
+        } => (
+            Invocation::Method(*address, ModuleId::Royalty, method_name.to_owned()),
+            args,
+        ),
+        InstructionV1::CallRoleAssignmentMethod {
+            address: DynamicGlobalAddress::Static(address),
+            method_name,
+            args,
+        } => (
+            Invocation::Method(*address, ModuleId::RoleAssignment, method_name.to_owned()),
+            args,
+        ),
+        InstructionV1::CallDirectVaultMethod {
+            address,
+            method_name,
+            args,
+        } => (
+            Invocation::DirectMethod(*address, method_name.to_owned()),
+            args,
+        ),
+        _ => return None,
+    };
+    Some(invocation)
+}
+
 fn get_blueprint_schema<'p>(
     package_definition: &'p PackageDefinition,
     package_address: PackageAddress,
@@ -274,11 +283,7 @@ fn get_arguments_schema<'s>(
             .functions
             .get(invocation.method())
         {
-            if is_self_or_mut_self_receiver(&function_schema.receiver) && invocation.is_method()
-                || is_direct_access_receiver(&function_schema.receiver)
-                    && invocation.is_direct_access_method()
-                || is_function_receiver(&function_schema.receiver) && invocation.is_function()
-            {
+            if is_valid_receiver(&function_schema.receiver, &invocation) {
                 Ok(Some((
                     function_schema.input.clone(),
                     blueprint_schema.schema.schema.v1(),
@@ -296,6 +301,12 @@ fn get_arguments_schema<'s>(
     }
 }
 
+pub(super) fn is_valid_receiver(receiver: &Option<ReceiverInfo>, invocation: &Invocation) -> bool {
+    is_self_or_mut_self_receiver(receiver) && invocation.is_method()
+        || is_direct_access_receiver(receiver) && invocation.is_direct_access_method()
+        || is_function_receiver(receiver) && invocation.is_function()
+}
+
 fn is_self_or_mut_self_receiver(receiver: &Option<ReceiverInfo>) -> bool {
     if let Some(ref receiver) = receiver {
         match (&receiver.receiver, receiver.ref_types) {
@@ -323,14 +334,14 @@ fn is_function_receiver(receiver: &Option<ReceiverInfo>) -> bool {
 }
 
 #[derive(Clone, Debug)]
-enum Invocation {
+pub(super) enum Invocation {
     DirectMethod(InternalAddress, String),
     Method(GlobalAddress, ModuleId, String),
     Function(PackageAddress, String, String),
 }
 
 impl Invocation {
-    fn method(&self) -> &str {
+    pub(super) fn method(&self) -> &str {
         match self {
             Self::DirectMethod(_, method) => method,
             Self::Method(_, _, method) => method,
@@ -380,6 +391,8 @@ pub enum InstructionSchemaValidationError {
     SchemaValidationError(String),
 
     InvalidAddress(GlobalAddress),
+    InvalidInternalAddress(InternalAddress),
     InvalidBlueprint(PackageAddress, String),
     InvalidReceiver,
+    ModuleNotAttached(GlobalAddress, ModuleId),
 }
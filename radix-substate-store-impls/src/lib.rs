@@ -10,6 +10,10 @@ pub mod memory_db;
 pub mod rocks_db;
 #[cfg(feature = "rocksdb")]
 pub mod rocks_db_with_merkle_tree;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(all(feature = "std", feature = "sqlite"))]
+pub mod sqlite_db;
 pub mod state_tree;
 pub mod substate_database_overlay;
 
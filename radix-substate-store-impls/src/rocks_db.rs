@@ -37,6 +37,28 @@ impl RocksdbSubstateStore {
         Self { db }
     }
 
+    /// Opens the database at `root` as a secondary instance, which doesn't lock it, and so can be
+    /// read while a primary instance writes to it. The secondary instance keeps its own logs at
+    /// `secondary_root`, and sees the primary's writes as of its last
+    /// [`Self::try_catch_up_with_primary`].
+    pub fn secondary(root: PathBuf, secondary_root: PathBuf) -> Result<Self, rocksdb::Error> {
+        let mut options = Options::default();
+        // Required by secondary instances, which must keep all the primary's files open
+        options.set_max_open_files(-1);
+        let db = DB::open_cf_as_secondary(
+            &options,
+            root.as_path(),
+            secondary_root.as_path(),
+            vec![Self::THE_ONLY_CF],
+        )?;
+        Ok(Self { db })
+    }
+
+    /// Catches up a secondary instance with the writes of the primary one.
+    pub fn try_catch_up_with_primary(&self) -> Result<(), rocksdb::Error> {
+        self.db.try_catch_up_with_primary()
+    }
+
     fn cf(&self) -> &ColumnFamily {
         self.db.cf_handle(Self::THE_ONLY_CF).unwrap()
     }
@@ -1,4 +1,6 @@
+use crate::state_tree::proofs::*;
 use crate::state_tree::tree_store::*;
+use crate::state_tree::{get_substate_at_version, list_entries_from_at_version};
 use itertools::Itertools;
 use radix_common::constants::MAX_SUBSTATE_KEY_SIZE;
 use radix_common::data::scrypto::{scrypto_decode, scrypto_encode};
@@ -21,10 +23,20 @@ const META_CF: &str = "meta";
 const SUBSTATES_CF: &str = "substates";
 const MERKLE_NODES_CF: &str = "merkle_nodes";
 const STALE_MERKLE_TREE_PARTS_CF: &str = "stale_merkle_tree_parts";
+const ASSOCIATED_SUBSTATES_CF: &str = "associated_substates";
+const ROLLBACK_RECORDS_CF: &str = "rollback_records";
+
+/// The key (within [`META_CF`]) marking that the Substate values associated with the state tree
+/// leaves were stored since the very first version, and so historical reads are possible.
+///
+/// Databases populated by earlier versions of this store (or with pruning enabled at any time)
+/// lack some associated values, which are needed to resolve the leaves of past versions.
+const ASSOCIATED_SUBSTATES_SINCE_GENESIS_KEY: &[u8] = b"associated_substates_since_genesis";
 
 pub struct RocksDBWithMerkleTreeSubstateStore {
     db: DBWithThreadMode<SingleThreaded>,
     pruning_enabled: bool,
+    retained_rollback_versions: u64,
 }
 
 impl RocksDBWithMerkleTreeSubstateStore {
@@ -53,6 +65,8 @@ impl RocksDBWithMerkleTreeSubstateStore {
                 SUBSTATES_CF,
                 MERKLE_NODES_CF,
                 STALE_MERKLE_TREE_PARTS_CF,
+                ASSOCIATED_SUBSTATES_CF,
+                ROLLBACK_RECORDS_CF,
             ]
             .into_iter()
             .map(|name| ColumnFamilyDescriptor::new(name, Options::default()))
@@ -62,9 +76,18 @@ impl RocksDBWithMerkleTreeSubstateStore {
         Self {
             db,
             pruning_enabled,
+            retained_rollback_versions: 0,
         }
     }
 
+    /// Makes every subsequent commit also store the information needed to revert it (i.e. the
+    /// reverse [`DatabaseUpdates`] and any pruned tree nodes), so that the store can be rolled back
+    /// by up to the given number of most recent versions (see [`Self::rollback_to()`]).
+    pub fn with_rollback_history(mut self, retained_versions: u64) -> Self {
+        self.retained_rollback_versions = retained_versions;
+        self
+    }
+
     fn cf(&self, cf: &str) -> &ColumnFamily {
         self.db.cf_handle(cf).unwrap()
     }
@@ -92,6 +115,143 @@ impl RocksDBWithMerkleTreeSubstateStore {
             })
             .unwrap_or(Hash([0u8; Hash::LENGTH]))
     }
+
+    /// Returns `true` if the store can be rolled back to the given (past or current) version,
+    /// i.e. if rollback records of all the versions after it are retained.
+    pub fn can_rollback_to(&self, state_version: u64) -> bool {
+        let current_version = self.get_current_version();
+        state_version <= current_version
+            && ((state_version + 1)..=current_version).all(|version| {
+                self.get_retained_rollback_record(version, current_version)
+                    .is_some()
+            })
+    }
+
+    /// Atomically reverts the store to the given past version, restoring both the Substates and
+    /// the state tree (including its root hash), as if the later versions were never committed.
+    ///
+    /// This is only possible for the versions committed with the rollback history enabled (see
+    /// [`Self::with_rollback_history()`]), and still retained.
+    pub fn rollback_to(&mut self, state_version: u64) -> Result<(), RollbackError> {
+        let current_version = self.get_current_version();
+        if state_version > current_version {
+            return Err(RollbackError::VersionNotReached {
+                state_version,
+                current_version,
+            });
+        }
+        if state_version == current_version {
+            return Ok(());
+        }
+
+        let mut batch = WriteBatch::default();
+        let mut state_root_hash = self.get_current_root_hash();
+        for version in ((state_version + 1)..=current_version).rev() {
+            let record = self
+                .get_retained_rollback_record(version, current_version)
+                .ok_or(RollbackError::RollbackRecordMissing { version })?;
+            self.put_substate_updates(&mut batch, &record.reverse_database_updates);
+            for (key, node_bytes) in record.pruned_tree_nodes {
+                batch.put_cf(self.cf(MERKLE_NODES_CF), key, node_bytes);
+            }
+            state_root_hash = record.parent_state_root_hash;
+        }
+
+        // All the tree-related column families are keyed by the version first, so the data of the
+        // rolled-back versions forms a single range (which also covers any pruned nodes restored
+        // above, if they were only created in the rolled-back versions):
+        let rolled_back_versions_start = (state_version + 1).to_be_bytes();
+        let rolled_back_versions_end = (current_version + 1).to_be_bytes();
+        for cf in [
+            MERKLE_NODES_CF,
+            STALE_MERKLE_TREE_PARTS_CF,
+            ASSOCIATED_SUBSTATES_CF,
+            ROLLBACK_RECORDS_CF,
+        ] {
+            batch.delete_range_cf(
+                self.cf(cf),
+                rolled_back_versions_start,
+                rolled_back_versions_end,
+            );
+        }
+
+        batch.put_cf(
+            self.cf(META_CF),
+            [],
+            scrypto_encode(&Metadata {
+                current_state_version: state_version,
+                current_state_root_hash: state_root_hash,
+            })
+            .unwrap(),
+        );
+        self.db.write(batch).unwrap();
+        Ok(())
+    }
+
+    /// Generates a proof of inclusion of the Substate under the given key, in the state at the
+    /// given version (see [`get_substate_inclusion_proof()`]).
+    /// Note: if pruning is enabled, only the current version can be proven; an error is returned
+    /// for versions whose tree nodes are not stored.
+    pub fn get_substate_inclusion_proof(
+        &self,
+        state_version: Version,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Result<Option<SubstateInclusionProof>, ProofGenerationError> {
+        self.check_provable(state_version)?;
+        Ok(get_substate_inclusion_proof(
+            self,
+            state_version,
+            partition_key,
+            sort_key,
+        ))
+    }
+
+    /// Generates a proof that the Substate under the given key does not exist in the state at the
+    /// given version (see [`get_substate_exclusion_proof()`]).
+    /// Note: the same version restrictions apply as for [`Self::get_substate_inclusion_proof()`].
+    pub fn get_substate_exclusion_proof(
+        &self,
+        state_version: Version,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Result<Option<ExclusionProof>, ProofGenerationError> {
+        self.check_provable(state_version)?;
+        Ok(get_substate_exclusion_proof(
+            self,
+            state_version,
+            partition_key,
+            sort_key,
+        ))
+    }
+
+    /// Generates a proof that the given partition does not exist in the state at the given version
+    /// (see [`get_partition_exclusion_proof()`]).
+    /// Note: the same version restrictions apply as for [`Self::get_substate_inclusion_proof()`].
+    pub fn get_partition_exclusion_proof(
+        &self,
+        state_version: Version,
+        partition_key: &DbPartitionKey,
+    ) -> Result<Option<ExclusionProof>, ProofGenerationError> {
+        self.check_provable(state_version)?;
+        Ok(get_partition_exclusion_proof(
+            self,
+            state_version,
+            partition_key,
+        ))
+    }
+
+    /// Generates a proof that the given entity does not exist in the state at the given version
+    /// (see [`get_entity_exclusion_proof()`]).
+    /// Note: the same version restrictions apply as for [`Self::get_substate_inclusion_proof()`].
+    pub fn get_entity_exclusion_proof(
+        &self,
+        state_version: Version,
+        node_key: &DbNodeKey,
+    ) -> Result<Option<ExclusionProof>, ProofGenerationError> {
+        self.check_provable(state_version)?;
+        Ok(get_entity_exclusion_proof(self, state_version, node_key))
+    }
 }
 
 impl SubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
@@ -151,54 +311,15 @@ impl CommittableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
         // prepare a batch write (we use the same approach in the actual Node)
         let mut batch = WriteBatch::default();
 
+        // capture the reverse changes (before they are overwritten), if rollbacks are supported
+        let reverse_database_updates = if self.retained_rollback_versions > 0 {
+            Some(self.compute_reverse_updates(database_updates))
+        } else {
+            None
+        };
+
         // put regular substate changes
-        for (node_key, node_updates) in &database_updates.node_updates {
-            for (partition_num, partition_updates) in &node_updates.partition_updates {
-                let partition_key = DbPartitionKey {
-                    node_key: node_key.clone(),
-                    partition_num: *partition_num,
-                };
-                match partition_updates {
-                    PartitionDatabaseUpdates::Delta { substate_updates } => {
-                        for (sort_key, update) in substate_updates {
-                            let key_bytes = encode_to_rocksdb_bytes(&partition_key, sort_key);
-                            match update {
-                                DatabaseUpdate::Set(value_bytes) => {
-                                    self.db
-                                        .put_cf(self.cf(SUBSTATES_CF), key_bytes, value_bytes)
-                                }
-                                DatabaseUpdate::Delete => {
-                                    self.db.delete_cf(self.cf(SUBSTATES_CF), key_bytes)
-                                }
-                            }
-                            .expect("IO error");
-                        }
-                    }
-                    PartitionDatabaseUpdates::Reset {
-                        new_substate_values,
-                    } => {
-                        // Note: a plain `delete_range()` is missing from rocksdb's API, and
-                        // (at the moment of writing) this is the only reason of having CF.
-                        self.db
-                            .delete_range_cf(
-                                self.cf(SUBSTATES_CF),
-                                encode_to_rocksdb_bytes(&partition_key, &DbSortKey(vec![])),
-                                encode_to_rocksdb_bytes(
-                                    &partition_key,
-                                    &DbSortKey(vec![u8::MAX; 2 * MAX_SUBSTATE_KEY_SIZE]),
-                                ),
-                            )
-                            .expect("IO error");
-                        for (sort_key, value_bytes) in new_substate_values {
-                            let key_bytes = encode_to_rocksdb_bytes(&partition_key, sort_key);
-                            self.db
-                                .put_cf(self.cf(SUBSTATES_CF), key_bytes, value_bytes)
-                                .expect("IO error");
-                        }
-                    }
-                }
-            }
-        }
+        self.put_substate_updates(&mut batch, database_updates);
 
         // derive and put new JMT nodes (also record references to stale parts, for later amortized background GC [not implemented here!])
         let (state_tree_diff, new_root_hash) =
@@ -210,13 +331,23 @@ impl CommittableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
                 scrypto_encode(&VersionedTreeNode::from_latest_version(node)).unwrap(),
             );
         }
-        if !self.pruning_enabled {
+        if self.pruning_enabled {
+            batch.delete_cf(self.cf(META_CF), ASSOCIATED_SUBSTATES_SINCE_GENESIS_KEY);
+        } else {
+            if parent_state_version == 0 {
+                batch.put_cf(self.cf(META_CF), ASSOCIATED_SUBSTATES_SINCE_GENESIS_KEY, []);
+            }
             // If pruning is not enabled, we store the stale nodes in DB.
             batch.put_cf(
                 self.cf(STALE_MERKLE_TREE_PARTS_CF),
                 next_state_version.to_be_bytes(),
                 scrypto_encode(&state_tree_diff.stale_tree_parts).unwrap(),
             );
+            // We also store the Substate values associated with new leaves, to serve historical
+            // reads (see `VersionedSubstateDatabase`).
+            for (key, value) in state_tree_diff.new_associated_substates.take() {
+                batch.put_cf(self.cf(ASSOCIATED_SUBSTATES_CF), encode_key(&key), value);
+            }
         }
 
         // update the metadata
@@ -230,52 +361,218 @@ impl CommittableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
             .unwrap(),
         );
 
+        // delete the stale tree nodes, if pruning is enabled
+        let pruned_tree_nodes = if self.pruning_enabled {
+            self.collect_stale_tree_nodes(state_tree_diff.stale_tree_parts.take())
+        } else {
+            Vec::new()
+        };
+        for (key, _) in &pruned_tree_nodes {
+            batch.delete_cf(self.cf(MERKLE_NODES_CF), key);
+        }
+
+        // put the information needed to revert this commit, and forget the no-longer-retained ones
+        if let Some(reverse_database_updates) = reverse_database_updates {
+            batch.put_cf(
+                self.cf(ROLLBACK_RECORDS_CF),
+                next_state_version.to_be_bytes(),
+                scrypto_encode(&RollbackRecord {
+                    parent_state_root_hash: metadata.current_state_root_hash,
+                    reverse_database_updates,
+                    pruned_tree_nodes,
+                })
+                .unwrap(),
+            );
+        }
+        // the stored records may start well before the retention boundary, e.g. if the store was
+        // previously opened with a longer rollback history
+        let first_retained_version =
+            (next_state_version + 1).saturating_sub(self.retained_rollback_versions);
+        let oldest_stored_version = self
+            .db
+            .iterator_cf(self.cf(ROLLBACK_RECORDS_CF), IteratorMode::Start)
+            .next()
+            .map(|kv| u64::from_be_bytes(kv.unwrap().0.as_ref().try_into().unwrap()));
+        if let Some(oldest_stored_version) = oldest_stored_version {
+            if oldest_stored_version < first_retained_version {
+                batch.delete_range_cf(
+                    self.cf(ROLLBACK_RECORDS_CF),
+                    oldest_stored_version.to_be_bytes(),
+                    first_retained_version.to_be_bytes(),
+                );
+            }
+        }
+
         // flush the batch
         self.db.write(batch).unwrap();
+    }
+}
 
-        if self.pruning_enabled {
-            for part in state_tree_diff.stale_tree_parts.take() {
-                match part {
-                    StaleTreePart::Node(node_key) => {
-                        self.db
-                            .delete_cf(self.cf(MERKLE_NODES_CF), encode_key(&node_key))
-                            .unwrap();
-                    }
-                    StaleTreePart::Subtree(node_key) => {
-                        let mut queue = VecDeque::new();
-                        queue.push_back(node_key);
-
-                        while let Some(node_key) = queue.pop_front() {
-                            if let Some(bytes) = self
-                                .db
-                                .get_cf(self.cf(MERKLE_NODES_CF), encode_key(&node_key))
-                                .unwrap()
-                            {
-                                self.db
-                                    .delete_cf(self.cf(MERKLE_NODES_CF), encode_key(&node_key))
-                                    .unwrap();
-                                let value: VersionedTreeNode = scrypto_decode(&bytes).unwrap();
-                                match value.fully_update_and_into_latest_version() {
-                                    TreeNodeV1::Internal(x) => {
-                                        for child in x.children {
-                                            queue.push_back(
-                                                node_key.gen_child_node_key(
-                                                    child.version,
-                                                    child.nibble,
-                                                ),
-                                            )
-                                        }
-                                    }
-                                    TreeNodeV1::Leaf(_) => {}
-                                    TreeNodeV1::Null => {}
+impl RocksDBWithMerkleTreeSubstateStore {
+    fn put_substate_updates(&self, batch: &mut WriteBatch, database_updates: &DatabaseUpdates) {
+        for (node_key, node_updates) in &database_updates.node_updates {
+            for (partition_num, partition_updates) in &node_updates.partition_updates {
+                let partition_key = DbPartitionKey {
+                    node_key: node_key.clone(),
+                    partition_num: *partition_num,
+                };
+                match partition_updates {
+                    PartitionDatabaseUpdates::Delta { substate_updates } => {
+                        for (sort_key, update) in substate_updates {
+                            let key_bytes = encode_to_rocksdb_bytes(&partition_key, sort_key);
+                            match update {
+                                DatabaseUpdate::Set(value_bytes) => {
+                                    batch.put_cf(self.cf(SUBSTATES_CF), key_bytes, value_bytes)
+                                }
+                                DatabaseUpdate::Delete => {
+                                    batch.delete_cf(self.cf(SUBSTATES_CF), key_bytes)
                                 }
                             }
                         }
                     }
+                    PartitionDatabaseUpdates::Reset {
+                        new_substate_values,
+                    } => {
+                        // Note: a plain `delete_range()` is missing from rocksdb's API, and
+                        // (at the moment of writing) this is the only reason of having CF.
+                        batch.delete_range_cf(
+                            self.cf(SUBSTATES_CF),
+                            encode_to_rocksdb_bytes(&partition_key, &DbSortKey(vec![])),
+                            encode_to_rocksdb_bytes(
+                                &partition_key,
+                                &DbSortKey(vec![u8::MAX; 2 * MAX_SUBSTATE_KEY_SIZE]),
+                            ),
+                        );
+                        for (sort_key, value_bytes) in new_substate_values {
+                            let key_bytes = encode_to_rocksdb_bytes(&partition_key, sort_key);
+                            batch.put_cf(self.cf(SUBSTATES_CF), key_bytes, value_bytes);
+                        }
+                    }
                 }
             }
         }
     }
+
+    /// Computes the updates which, applied after the given ones, restore the current Substates.
+    fn compute_reverse_updates(&self, database_updates: &DatabaseUpdates) -> DatabaseUpdates {
+        let mut reverse_database_updates = DatabaseUpdates::default();
+        for (node_key, node_updates) in &database_updates.node_updates {
+            let reverse_node_updates = reverse_database_updates
+                .node_updates
+                .entry(node_key.clone())
+                .or_default();
+            for (partition_num, partition_updates) in &node_updates.partition_updates {
+                let partition_key = DbPartitionKey {
+                    node_key: node_key.clone(),
+                    partition_num: *partition_num,
+                };
+                let reverse_partition_updates = match partition_updates {
+                    PartitionDatabaseUpdates::Delta { substate_updates } => {
+                        PartitionDatabaseUpdates::Delta {
+                            substate_updates: substate_updates
+                                .keys()
+                                .map(|sort_key| {
+                                    let reverse_update =
+                                        match self.get_substate(&partition_key, sort_key) {
+                                            Some(value) => DatabaseUpdate::Set(value),
+                                            None => DatabaseUpdate::Delete,
+                                        };
+                                    (sort_key.clone(), reverse_update)
+                                })
+                                .collect(),
+                        }
+                    }
+                    PartitionDatabaseUpdates::Reset { .. } => PartitionDatabaseUpdates::Reset {
+                        new_substate_values: self.list_entries(&partition_key).collect(),
+                    },
+                };
+                reverse_node_updates
+                    .partition_updates
+                    .insert(*partition_num, reverse_partition_updates);
+            }
+        }
+        reverse_database_updates
+    }
+
+    /// Resolves the given stale tree parts into the individual tree nodes (as their encoded keys
+    /// and values).
+    fn collect_stale_tree_nodes(
+        &self,
+        stale_tree_parts: Vec<StaleTreePart>,
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut stale_tree_nodes = Vec::new();
+        for part in stale_tree_parts {
+            let mut queue = VecDeque::new();
+            let is_subtree = match part {
+                StaleTreePart::Node(node_key) => {
+                    queue.push_back(node_key);
+                    false
+                }
+                StaleTreePart::Subtree(node_key) => {
+                    queue.push_back(node_key);
+                    true
+                }
+            };
+            while let Some(node_key) = queue.pop_front() {
+                let key = encode_key(&node_key);
+                let Some(bytes) = self.db.get_cf(self.cf(MERKLE_NODES_CF), &key).unwrap() else {
+                    continue;
+                };
+                if is_subtree {
+                    let value: VersionedTreeNode = scrypto_decode(&bytes).unwrap();
+                    match value.fully_update_and_into_latest_version() {
+                        TreeNodeV1::Internal(x) => {
+                            for child in x.children {
+                                queue.push_back(
+                                    node_key.gen_child_node_key(child.version, child.nibble),
+                                )
+                            }
+                        }
+                        TreeNodeV1::Leaf(_) => {}
+                        TreeNodeV1::Null => {}
+                    }
+                }
+                stale_tree_nodes.push((key, bytes));
+            }
+        }
+        stale_tree_nodes
+    }
+
+    /// Checks that the tree nodes of the given version are stored, so that proofs of its state can
+    /// be generated.
+    fn check_provable(&self, state_version: Version) -> Result<(), ProofGenerationError> {
+        if state_version > 0 && self.is_version_available(state_version) {
+            Ok(())
+        } else {
+            Err(ProofGenerationError::VersionNotAvailable {
+                state_version,
+                current_version: self.get_current_version(),
+            })
+        }
+    }
+
+    fn has_associated_substates_since_genesis(&self) -> bool {
+        self.db
+            .get_cf(self.cf(META_CF), ASSOCIATED_SUBSTATES_SINCE_GENESIS_KEY)
+            .unwrap()
+            .is_some()
+    }
+
+    /// Returns the rollback record of the given version, unless it is past the retention boundary
+    /// (such records are only deleted by the next commit).
+    fn get_retained_rollback_record(
+        &self,
+        state_version: u64,
+        current_version: u64,
+    ) -> Option<RollbackRecord> {
+        if current_version - state_version >= self.retained_rollback_versions {
+            return None;
+        }
+        self.db
+            .get_cf(self.cf(ROLLBACK_RECORDS_CF), state_version.to_be_bytes())
+            .unwrap()
+            .map(|bytes| scrypto_decode::<RollbackRecord>(&bytes).unwrap())
+    }
 }
 
 impl ListableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
@@ -305,12 +602,106 @@ impl ReadableTreeStore for RocksDBWithMerkleTreeSubstateStore {
     }
 }
 
+impl ReadableAssociatedSubstateStore for RocksDBWithMerkleTreeSubstateStore {
+    fn get_associated_substate(
+        &self,
+        state_tree_leaf_key: &StoredTreeNodeKey,
+    ) -> Option<DbSubstateValue> {
+        self.db
+            .get_cf(
+                self.cf(ASSOCIATED_SUBSTATES_CF),
+                &encode_key(state_tree_leaf_key),
+            )
+            .unwrap()
+    }
+}
+
+impl VersionedSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
+    fn is_version_available(&self, state_version: u64) -> bool {
+        let current_version = self.get_current_version();
+        state_version == current_version
+            || (state_version < current_version
+                && !self.pruning_enabled
+                && self.has_associated_substates_since_genesis())
+    }
+
+    fn get_substate_at_version(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        assert!(
+            self.is_version_available(state_version),
+            "state version {} is not available",
+            state_version
+        );
+        if state_version == self.get_current_version() {
+            return self.get_substate(partition_key, sort_key);
+        }
+        if state_version == 0 {
+            return None;
+        }
+        get_substate_at_version(self, state_version, partition_key, sort_key)
+    }
+
+    fn list_entries_from_at_version(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        assert!(
+            self.is_version_available(state_version),
+            "state version {} is not available",
+            state_version
+        );
+        if state_version == self.get_current_version() {
+            return self.list_entries_from(partition_key, from_sort_key);
+        }
+        if state_version == 0 {
+            return Box::new(std::iter::empty());
+        }
+        list_entries_from_at_version(self, state_version, partition_key, from_sort_key)
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq, ScryptoSbor)]
 struct Metadata {
     current_state_version: u64,
     current_state_root_hash: Hash,
 }
 
+/// The information needed to revert a single committed version.
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+struct RollbackRecord {
+    /// The state root hash before the version was committed.
+    parent_state_root_hash: Hash,
+    /// The Substate updates restoring the state before the version was committed.
+    reverse_database_updates: DatabaseUpdates,
+    /// The tree nodes deleted by pruning, as their encoded keys and values.
+    pruned_tree_nodes: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RollbackError {
+    VersionNotReached {
+        state_version: u64,
+        current_version: u64,
+    },
+    RollbackRecordMissing {
+        version: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofGenerationError {
+    VersionNotAvailable {
+        state_version: u64,
+        current_version: u64,
+    },
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -365,4 +756,376 @@ mod tests {
         });
         assert_eq!(db.list_partition_keys().count(), 8);
     }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_rollback_restores_substates_and_state_tree() {
+        for pruning_enabled in [true, false] {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let mut options = Options::default();
+            options.create_if_missing(true);
+            options.create_missing_column_families(true);
+            let mut db = RocksDBWithMerkleTreeSubstateStore::with_options(
+                &options,
+                temp_dir.into_path(),
+                pruning_enabled,
+            )
+            .with_rollback_history(10);
+
+            let first_updates = DatabaseUpdates {
+                node_updates: indexmap! {
+                    vec![0] => NodeDatabaseUpdates {
+                        partition_updates: indexmap! {
+                            0 => PartitionDatabaseUpdates::Reset {
+                                new_substate_values: indexmap! {
+                                    DbSortKey(vec![5]) => vec![6],
+                                    DbSortKey(vec![7]) => vec![8],
+                                }
+                            },
+                        }
+                    },
+                },
+            };
+            let second_updates = DatabaseUpdates {
+                node_updates: indexmap! {
+                    vec![0] => NodeDatabaseUpdates {
+                        partition_updates: indexmap! {
+                            0 => PartitionDatabaseUpdates::Delta {
+                                substate_updates: indexmap! {
+                                    DbSortKey(vec![5]) => DatabaseUpdate::Set(vec![9]),
+                                    DbSortKey(vec![7]) => DatabaseUpdate::Delete,
+                                    DbSortKey(vec![1]) => DatabaseUpdate::Set(vec![2]),
+                                }
+                            },
+                        }
+                    },
+                },
+            };
+            let third_updates = DatabaseUpdates {
+                node_updates: indexmap! {
+                    vec![0] => NodeDatabaseUpdates {
+                        partition_updates: indexmap! {
+                            0 => PartitionDatabaseUpdates::Reset {
+                                new_substate_values: indexmap! {}
+                            },
+                            1 => PartitionDatabaseUpdates::Reset {
+                                new_substate_values: indexmap! {
+                                    DbSortKey(vec![3]) => vec![4],
+                                }
+                            },
+                        }
+                    },
+                },
+            };
+
+            db.commit(&first_updates);
+            let first_root_hash = db.get_current_root_hash();
+            let first_entries = db.list_entries(&partition_key(0)).collect::<Vec<_>>();
+            db.commit(&second_updates);
+            let second_root_hash = db.get_current_root_hash();
+            db.commit(&third_updates);
+
+            db.rollback_to(1).unwrap();
+            assert_eq!(db.get_current_version(), 1);
+            assert_eq!(db.get_current_root_hash(), first_root_hash);
+            assert_eq!(
+                db.list_entries(&partition_key(0)).collect::<Vec<_>>(),
+                first_entries
+            );
+            assert_eq!(db.list_partition_keys().count(), 1);
+
+            // The restored state tree must be fully usable for further commits:
+            db.commit(&second_updates);
+            assert_eq!(db.get_current_root_hash(), second_root_hash);
+
+            db.rollback_to(0).unwrap();
+            assert_eq!(db.get_current_version(), 0);
+            assert_eq!(db.list_partition_keys().count(), 0);
+            db.commit(&first_updates);
+            assert_eq!(db.get_current_root_hash(), first_root_hash);
+        }
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_rollback_is_limited_to_retained_versions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db = RocksDBWithMerkleTreeSubstateStore::standard(temp_dir.into_path())
+            .with_rollback_history(2);
+        for value in 0..4 {
+            db.commit(&DatabaseUpdates::from_delta_maps(indexmap! {
+                partition_key(0) => indexmap! {
+                    DbSortKey(vec![0]) => DatabaseUpdate::Set(vec![value])
+                }
+            }));
+        }
+
+        assert!(db.can_rollback_to(2));
+        assert!(!db.can_rollback_to(1));
+        assert_eq!(
+            db.rollback_to(1),
+            Err(RollbackError::RollbackRecordMissing { version: 2 })
+        );
+        assert_eq!(
+            db.rollback_to(5),
+            Err(RollbackError::VersionNotReached {
+                state_version: 5,
+                current_version: 4
+            })
+        );
+        assert_eq!(db.get_current_version(), 4);
+
+        db.rollback_to(2).unwrap();
+        assert_eq!(
+            db.get_substate(&partition_key(0), &DbSortKey(vec![0])),
+            Some(vec![1])
+        );
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_rollback_records_are_expired_when_reopened_with_shorter_history() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.into_path();
+        let updates = |value: u8| {
+            DatabaseUpdates::from_delta_maps(indexmap! {
+                partition_key(0) => indexmap! {
+                    DbSortKey(vec![0]) => DatabaseUpdate::Set(vec![value])
+                }
+            })
+        };
+        let mut db =
+            RocksDBWithMerkleTreeSubstateStore::standard(path.clone()).with_rollback_history(3);
+        for value in 1..=4 {
+            db.commit(&updates(value));
+        }
+        assert!(db.can_rollback_to(1));
+        drop(db);
+
+        let mut db = RocksDBWithMerkleTreeSubstateStore::standard(path).with_rollback_history(1);
+        assert!(db.can_rollback_to(3));
+        assert!(!db.can_rollback_to(2));
+        assert_eq!(
+            db.rollback_to(1),
+            Err(RollbackError::RollbackRecordMissing { version: 3 })
+        );
+
+        db.commit(&updates(5));
+        for version in 1..=4 {
+            assert!(db
+                .db
+                .get_cf(db.cf(ROLLBACK_RECORDS_CF), u64::to_be_bytes(version))
+                .unwrap()
+                .is_none());
+        }
+        assert!(db.can_rollback_to(4));
+        assert!(!db.can_rollback_to(3));
+        db.rollback_to(4).unwrap();
+        assert_eq!(
+            db.get_substate(&partition_key(0), &DbSortKey(vec![0])),
+            Some(vec![4])
+        );
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_versions_without_associated_substates_are_not_available() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.into_path();
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let updates = |value: u8| {
+            DatabaseUpdates::from_delta_maps(indexmap! {
+                partition_key(0) => indexmap! {
+                    DbSortKey(vec![0]) => DatabaseUpdate::Set(vec![value])
+                }
+            })
+        };
+
+        // A legacy database, whose first version was committed without associated substates
+        let mut db = RocksDBWithMerkleTreeSubstateStore::with_options(&options, path.clone(), true);
+        db.commit(&updates(1));
+        drop(db);
+        let mut db = RocksDBWithMerkleTreeSubstateStore::with_options(&options, path, false);
+        db.commit(&updates(2));
+        assert!(!db.is_version_available(1));
+        assert!(db.is_version_available(2));
+
+        // A database storing associated substates since the first version
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut db =
+            RocksDBWithMerkleTreeSubstateStore::with_options(&options, temp_dir.into_path(), false);
+        db.commit(&updates(1));
+        db.commit(&updates(2));
+        assert!(db.is_version_available(1));
+        assert_eq!(
+            db.get_substate_at_version(1, &partition_key(0), &DbSortKey(vec![0])),
+            Some(vec![1])
+        );
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_inclusion_proofs_of_unavailable_versions_are_refused() {
+        for pruning_enabled in [true, false] {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let mut options = Options::default();
+            options.create_if_missing(true);
+            options.create_missing_column_families(true);
+            let mut db = RocksDBWithMerkleTreeSubstateStore::with_options(
+                &options,
+                temp_dir.into_path(),
+                pruning_enabled,
+            );
+            let sort_key = DbSortKey(vec![0]);
+            let mut root_hashes = vec![];
+            for value in 1..=3 {
+                db.commit(&DatabaseUpdates::from_delta_maps(indexmap! {
+                    partition_key(0) => indexmap! {
+                        sort_key.clone() => DatabaseUpdate::Set(vec![value])
+                    }
+                }));
+                root_hashes.push(db.get_current_root_hash());
+            }
+
+            for state_version in [0, 4] {
+                assert_eq!(
+                    db.get_substate_inclusion_proof(state_version, &partition_key(0), &sort_key),
+                    Err(ProofGenerationError::VersionNotAvailable {
+                        state_version,
+                        current_version: 3
+                    })
+                );
+            }
+            let proof = db
+                .get_substate_inclusion_proof(3, &partition_key(0), &sort_key)
+                .unwrap()
+                .unwrap();
+            assert!(proof
+                .verify(root_hashes[2], &partition_key(0), &sort_key, &vec![3])
+                .is_ok());
+
+            let past_proof = db.get_substate_inclusion_proof(2, &partition_key(0), &sort_key);
+            if pruning_enabled {
+                assert_eq!(
+                    past_proof,
+                    Err(ProofGenerationError::VersionNotAvailable {
+                        state_version: 2,
+                        current_version: 3
+                    })
+                );
+            } else {
+                assert!(past_proof
+                    .unwrap()
+                    .unwrap()
+                    .verify(root_hashes[1], &partition_key(0), &sort_key, &vec![2])
+                    .is_ok());
+            }
+        }
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_exclusion_proofs_of_unavailable_versions_are_refused() {
+        for pruning_enabled in [true, false] {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let mut options = Options::default();
+            options.create_if_missing(true);
+            options.create_missing_column_families(true);
+            let mut db = RocksDBWithMerkleTreeSubstateStore::with_options(
+                &options,
+                temp_dir.into_path(),
+                pruning_enabled,
+            );
+            let mut root_hashes = vec![];
+            for value in 1..=3 {
+                db.commit(&DatabaseUpdates::from_delta_maps(indexmap! {
+                    partition_key(0) => indexmap! {
+                        DbSortKey(vec![0]) => DatabaseUpdate::Set(vec![value])
+                    }
+                }));
+                root_hashes.push(db.get_current_root_hash());
+            }
+            let missing_sort_key = DbSortKey(vec![1]);
+            let missing_node_key = vec![1];
+
+            for state_version in [0, 4] {
+                let expected_error = Err(ProofGenerationError::VersionNotAvailable {
+                    state_version,
+                    current_version: 3,
+                });
+                assert_eq!(
+                    db.get_substate_exclusion_proof(
+                        state_version,
+                        &partition_key(0),
+                        &missing_sort_key
+                    ),
+                    expected_error
+                );
+                assert_eq!(
+                    db.get_partition_exclusion_proof(state_version, &partition_key(1)),
+                    expected_error
+                );
+                assert_eq!(
+                    db.get_entity_exclusion_proof(state_version, &missing_node_key),
+                    expected_error
+                );
+            }
+
+            let mut provable_versions = vec![3];
+            if pruning_enabled {
+                let expected_error = Err(ProofGenerationError::VersionNotAvailable {
+                    state_version: 2,
+                    current_version: 3,
+                });
+                assert_eq!(
+                    db.get_substate_exclusion_proof(2, &partition_key(0), &missing_sort_key),
+                    expected_error
+                );
+                assert_eq!(
+                    db.get_partition_exclusion_proof(2, &partition_key(1)),
+                    expected_error
+                );
+                assert_eq!(
+                    db.get_entity_exclusion_proof(2, &missing_node_key),
+                    expected_error
+                );
+            } else {
+                provable_versions.push(2);
+            }
+            for state_version in provable_versions {
+                let root_hash = root_hashes[state_version as usize - 1];
+                assert!(db
+                    .get_substate_exclusion_proof(
+                        state_version,
+                        &partition_key(0),
+                        &missing_sort_key
+                    )
+                    .unwrap()
+                    .unwrap()
+                    .verify_substate_absent(root_hash, &partition_key(0), &missing_sort_key)
+                    .is_ok());
+                assert!(db
+                    .get_partition_exclusion_proof(state_version, &partition_key(1))
+                    .unwrap()
+                    .unwrap()
+                    .verify_partition_absent(root_hash, &partition_key(1))
+                    .is_ok());
+                assert!(db
+                    .get_entity_exclusion_proof(state_version, &missing_node_key)
+                    .unwrap()
+                    .unwrap()
+                    .verify_entity_absent(root_hash, &missing_node_key)
+                    .is_ok());
+            }
+        }
+    }
+
+    fn partition_key(partition_num: u8) -> DbPartitionKey {
+        DbPartitionKey {
+            node_key: vec![0],
+            partition_num,
+        }
+    }
 }
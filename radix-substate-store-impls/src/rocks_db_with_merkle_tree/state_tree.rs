@@ -1,7 +1,9 @@
 use crate::state_tree::put_at_next_version;
 use crate::state_tree::tree_store::*;
 use radix_common::prelude::Hash;
-use radix_substate_store_interface::interface::{DatabaseUpdates, DbPartitionKey, DbSortKey};
+use radix_substate_store_interface::interface::{
+    DatabaseUpdates, DbPartitionKey, DbSortKey, DbSubstateValue,
+};
 use std::cell::RefCell;
 
 struct CollectingTreeStore<'s, S> {
@@ -35,12 +37,19 @@ impl<'s, S> WriteableTreeStore for CollectingTreeStore<'s, S> {
 
     fn associate_substate(
         &self,
-        _state_tree_leaf_key: &StoredTreeNodeKey,
+        state_tree_leaf_key: &StoredTreeNodeKey,
         _partition_key: &DbPartitionKey,
         _sort_key: &DbSortKey,
-        _substate_value: AssociatedSubstateValue,
+        substate_value: AssociatedSubstateValue,
     ) {
-        // intentionally empty
+        // Note: the "unchanged" associations are not collected, since they can be resolved from
+        // the leaf created at the Substate's most recent upsert.
+        if let AssociatedSubstateValue::Upserted(value) = substate_value {
+            self.diff
+                .new_associated_substates
+                .borrow_mut()
+                .push((state_tree_leaf_key.clone(), value.clone()));
+        }
     }
 
     fn record_stale_tree_part(&self, part: StaleTreePart) {
@@ -52,6 +61,7 @@ impl<'s, S> WriteableTreeStore for CollectingTreeStore<'s, S> {
 pub struct StateTreeDiff {
     pub new_nodes: RefCell<Vec<(StoredTreeNodeKey, TreeNode)>>,
     pub stale_tree_parts: RefCell<Vec<StaleTreePart>>,
+    pub new_associated_substates: RefCell<Vec<(StoredTreeNodeKey, DbSubstateValue)>>,
 }
 
 impl StateTreeDiff {
@@ -59,6 +69,7 @@ impl StateTreeDiff {
         Self {
             new_nodes: RefCell::new(Vec::new()),
             stale_tree_parts: RefCell::new(Vec::new()),
+            new_associated_substates: RefCell::new(Vec::new()),
         }
     }
 }
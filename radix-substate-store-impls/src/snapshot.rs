@@ -0,0 +1,658 @@
+//! A portable, streaming file format for the entire contents of a Substate database.
+//!
+//! A snapshot consists of:
+//! - the [`SNAPSHOT_MAGIC`] bytes,
+//! - a frame holding the [`VersionedSnapshotHeader`] (which includes the state root hash),
+//! - a sequence of frames holding [`SnapshotRecord::Partition`] records (each optionally followed by
+//!   [`SnapshotRecord::PartitionContinuation`] records, for large partitions),
+//! - a frame holding the [`SnapshotRecord::End`] record, which carries a checksum of all the
+//!   preceding bytes.
+//!
+//! Each frame is a big-endian `u32` length (at most [`MAX_FRAME_LENGTH`]) followed by that many
+//! bytes of an SBOR-encoded value. This way, a snapshot can be written and read
+//! partition-by-partition, without ever holding all the Substate values in memory.
+//!
+//! Note: verifying the state root hash of a snapshot (and computing it, for export) requires a
+//! state tree of all the Substates, which is held in memory. While it only holds the hashes of the
+//! Substates (and not their values), its size is still proportional to the number of Substates.
+//! Moreover, [`import_snapshot()`] holds all the Substate values in memory, so that none of them
+//! is committed before the entire snapshot is verified.
+
+use crate::state_tree::put_at_next_version;
+use crate::state_tree::tree_store::{TypedInMemoryTreeStore, Version};
+use radix_common::constants::{MAX_SUBSTATE_KEY_SIZE, MAX_SUBSTATE_VALUE_SIZE};
+use radix_common::crypto::{Hash, HashAccumulator};
+use radix_common::data::scrypto::{scrypto_decode, scrypto_encode};
+use radix_common::prelude::*;
+use radix_substate_store_interface::interface::*;
+use std::io::{ErrorKind, Read, Write};
+use std::iter::Peekable;
+
+/// The bytes opening every snapshot.
+pub const SNAPSHOT_MAGIC: [u8; 8] = *b"RDXSNAP\0";
+
+/// The maximum number of Substates written in a single record.
+/// Larger partitions are split into a [`SnapshotRecord::Partition`] followed by as many
+/// [`SnapshotRecord::PartitionContinuation`]s as needed.
+const MAX_ENTRIES_PER_RECORD: usize = 1000;
+
+/// The total size of the keys and values of Substates after which no more Substates are added to
+/// a record (i.e. a record exceeds it by at most a single Substate).
+const MAX_ENTRIES_SIZE_PER_RECORD: usize = 16 * 1024 * 1024;
+
+/// The maximum length of a frame's payload, allowing for the largest possible Substate on top of
+/// [`MAX_ENTRIES_SIZE_PER_RECORD`], plus the encoding overhead.
+///
+/// This bounds the memory allocated when reading a (potentially corrupted) snapshot.
+pub const MAX_FRAME_LENGTH: usize =
+    MAX_ENTRIES_SIZE_PER_RECORD + MAX_SUBSTATE_KEY_SIZE + MAX_SUBSTATE_VALUE_SIZE + 1024 * 1024;
+
+/// The size of a frame's length prefix.
+const FRAME_LENGTH_BYTES: usize = 4;
+
+define_single_versioned! {
+    #[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+    pub VersionedSnapshotHeader(SnapshotHeaderVersions) => SnapshotHeader = SnapshotHeaderV1
+}
+
+/// Global information about the snapshotted state.
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub struct SnapshotHeaderV1 {
+    /// The root hash of the state tree representing the snapshotted Substates (as computed by
+    /// [`compute_state_root_hash()`]).
+    pub state_root_hash: Hash,
+}
+
+/// A single record of a snapshot, following its header.
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub enum SnapshotRecord {
+    /// Starts a new partition, with its first Substates.
+    Partition {
+        partition_key: DbPartitionKey,
+        entries: Vec<PartitionEntry>,
+    },
+    /// More Substates of the partition started by the most recent [`SnapshotRecord::Partition`].
+    PartitionContinuation { entries: Vec<PartitionEntry> },
+    /// The end of the snapshot.
+    End {
+        /// The number of [`SnapshotRecord::Partition`] records in the snapshot.
+        partition_count: u64,
+        /// A hash of all the snapshot's bytes preceding this record's frame.
+        checksum: Hash,
+    },
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    IoError(std::io::Error),
+    NotASnapshot,
+    DecodeError(DecodeError),
+    UnexpectedContinuation,
+    UnexpectedEndOfSnapshot,
+    FrameTooLarge { length: usize },
+    ChecksumMismatch { expected: Hash, actual: Hash },
+    PartitionCountMismatch { expected: u64, actual: u64 },
+    StateRootHashMismatch { expected: Hash, actual: Hash },
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            ErrorKind::UnexpectedEof => SnapshotError::UnexpectedEndOfSnapshot,
+            _ => SnapshotError::IoError(error),
+        }
+    }
+}
+
+impl From<DecodeError> for SnapshotError {
+    fn from(error: DecodeError) -> Self {
+        SnapshotError::DecodeError(error)
+    }
+}
+
+/// Writes the entire contents of the given database as a snapshot.
+///
+/// Note: this reads the database twice - first to compute its state root hash (for the header),
+/// and then to write the actual Substates. If the state root hash is already known (e.g. when
+/// snapshotting a database which maintains a state tree), a [`SnapshotWriter`] can be used
+/// directly instead.
+pub fn export_snapshot<D: SubstateDatabase + ListableSubstateDatabase, W: Write>(
+    database: &D,
+    writer: W,
+) -> Result<SnapshotHeader, SnapshotError> {
+    let header = SnapshotHeaderV1 {
+        state_root_hash: compute_state_root_hash(database),
+    };
+    let mut snapshot_writer = SnapshotWriter::new(writer, header.clone())?;
+    for partition_key in database.list_partition_keys() {
+        let entries = database.list_entries(&partition_key);
+        snapshot_writer.write_partition(&partition_key, entries)?;
+    }
+    snapshot_writer.finish()?;
+    Ok(header)
+}
+
+/// Reads a snapshot and commits all its Substates into the given database, verifying the
+/// snapshot's checksum and state root hash.
+///
+/// The Substates are only committed (in a single commit) once the entire snapshot is read and
+/// verified. Hence, if an error is returned, the database is left untouched. For snapshots too
+/// large to be held in memory, a [`SnapshotReader`] can be used directly instead.
+pub fn import_snapshot<R: Read, D: CommittableSubstateDatabase>(
+    reader: R,
+    database: &mut D,
+) -> Result<SnapshotHeader, SnapshotError> {
+    let mut snapshot_reader = SnapshotReader::new(reader)?;
+    let mut root_hash_calculator = StateRootHashCalculator::new();
+    let mut all_database_updates = DatabaseUpdates::default();
+    while let Some(database_updates) = snapshot_reader.read_next_updates()? {
+        root_hash_calculator.apply(&database_updates);
+        merge_database_updates(&mut all_database_updates, database_updates);
+    }
+    let header = snapshot_reader.into_header();
+    let actual_root_hash = root_hash_calculator.root_hash();
+    if actual_root_hash != header.state_root_hash {
+        return Err(SnapshotError::StateRootHashMismatch {
+            expected: header.state_root_hash,
+            actual: actual_root_hash,
+        });
+    }
+    database.commit(&all_database_updates);
+    Ok(header)
+}
+
+/// Computes the root hash of a state tree holding exactly the Substates of the given database.
+///
+/// Note: the root hash of a state tree only depends on its contents (and not on the history of
+/// changes which led to them), so the result is equal to the current root hash of any state tree
+/// maintained alongside the given database (with the exception of an empty database, which is
+/// represented by a zero hash).
+pub fn compute_state_root_hash<D: SubstateDatabase + ListableSubstateDatabase>(
+    database: &D,
+) -> Hash {
+    let mut root_hash_calculator = StateRootHashCalculator::new();
+    for partition_key in database.list_partition_keys() {
+        let mut entries = database.list_entries(&partition_key).peekable();
+        let mut is_first_chunk = true;
+        while entries.peek().is_some() {
+            let chunk = next_chunk(&mut entries);
+            root_hash_calculator.apply(&to_database_updates(&partition_key, is_first_chunk, chunk));
+            is_first_chunk = false;
+        }
+    }
+    root_hash_calculator.root_hash()
+}
+
+/// A low-level, streaming writer of a snapshot.
+pub struct SnapshotWriter<W: Write> {
+    writer: W,
+    checksum: HashAccumulator,
+    partition_count: u64,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Writes the magic bytes and the given header.
+    pub fn new(writer: W, header: SnapshotHeader) -> Result<Self, SnapshotError> {
+        let mut snapshot_writer = Self {
+            writer,
+            checksum: HashAccumulator::new(),
+            partition_count: 0,
+        };
+        snapshot_writer.write_bytes(&SNAPSHOT_MAGIC)?;
+        snapshot_writer.write_frame(&VersionedSnapshotHeader::from_latest_version(header))?;
+        Ok(snapshot_writer)
+    }
+
+    /// Writes all Substates of the given partition (which must not be written before).
+    /// An empty partition is skipped.
+    pub fn write_partition(
+        &mut self,
+        partition_key: &DbPartitionKey,
+        entries: impl Iterator<Item = PartitionEntry>,
+    ) -> Result<(), SnapshotError> {
+        let mut entries = entries.peekable();
+        let mut is_first_chunk = true;
+        while entries.peek().is_some() {
+            let chunk = next_chunk(&mut entries);
+            let record = if is_first_chunk {
+                SnapshotRecord::Partition {
+                    partition_key: partition_key.clone(),
+                    entries: chunk,
+                }
+            } else {
+                SnapshotRecord::PartitionContinuation { entries: chunk }
+            };
+            self.write_frame(&record)?;
+            is_first_chunk = false;
+        }
+        if !is_first_chunk {
+            self.partition_count += 1;
+        }
+        Ok(())
+    }
+
+    /// Writes the [`SnapshotRecord::End`] record, flushes and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, SnapshotError> {
+        let end = SnapshotRecord::End {
+            partition_count: self.partition_count,
+            checksum: self.checksum.finalize(),
+        };
+        self.writer.write_all(&encode_frame(&end)?)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_frame<T: ScryptoEncode>(&mut self, value: &T) -> Result<(), SnapshotError> {
+        self.write_bytes(&encode_frame(value)?)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+        self.checksum.update_no_chain(bytes);
+        self.writer.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+/// A low-level, streaming reader of a snapshot.
+pub struct SnapshotReader<R: Read> {
+    reader: R,
+    checksum: HashAccumulator,
+    header: SnapshotHeader,
+    current_partition_key: Option<DbPartitionKey>,
+    partition_count: u64,
+    finished: bool,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    /// Reads and verifies the magic bytes, and reads the header.
+    pub fn new(reader: R) -> Result<Self, SnapshotError> {
+        let mut snapshot_reader = Self {
+            reader,
+            checksum: HashAccumulator::new(),
+            header: SnapshotHeaderV1 {
+                state_root_hash: Hash([0; Hash::LENGTH]),
+            },
+            current_partition_key: None,
+            partition_count: 0,
+            finished: false,
+        };
+        let mut magic = [0; SNAPSHOT_MAGIC.len()];
+        snapshot_reader
+            .reader
+            .read_exact(&mut magic)
+            .map_err(|error| match error.kind() {
+                ErrorKind::UnexpectedEof => SnapshotError::NotASnapshot,
+                _ => SnapshotError::IoError(error),
+            })?;
+        if magic != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        snapshot_reader.checksum.update_no_chain(magic);
+        let header_frame = snapshot_reader.read_frame()?;
+        snapshot_reader.checksum.update_no_chain(&header_frame);
+        snapshot_reader.header =
+            scrypto_decode::<VersionedSnapshotHeader>(&header_frame[FRAME_LENGTH_BYTES..])?
+                .fully_update_and_into_latest_version();
+        Ok(snapshot_reader)
+    }
+
+    pub fn header(&self) -> &SnapshotHeader {
+        &self.header
+    }
+
+    pub fn into_header(self) -> SnapshotHeader {
+        self.header
+    }
+
+    /// Reads the next record and returns it as [`DatabaseUpdates`] to be committed (in order) into
+    /// an initially-empty database.
+    /// Returns `None` once the [`SnapshotRecord::End`] record is read and verified.
+    pub fn read_next_updates(&mut self) -> Result<Option<DatabaseUpdates>, SnapshotError> {
+        if self.finished {
+            return Ok(None);
+        }
+        let frame = self.read_frame()?;
+        let record = scrypto_decode::<SnapshotRecord>(&frame[FRAME_LENGTH_BYTES..])?;
+        if !matches!(record, SnapshotRecord::End { .. }) {
+            self.checksum.update_no_chain(&frame);
+        }
+        match record {
+            SnapshotRecord::Partition {
+                partition_key,
+                entries,
+            } => {
+                let database_updates = to_database_updates(&partition_key, true, entries);
+                self.current_partition_key = Some(partition_key);
+                self.partition_count += 1;
+                Ok(Some(database_updates))
+            }
+            SnapshotRecord::PartitionContinuation { entries } => {
+                let Some(partition_key) = &self.current_partition_key else {
+                    return Err(SnapshotError::UnexpectedContinuation);
+                };
+                Ok(Some(to_database_updates(partition_key, false, entries)))
+            }
+            SnapshotRecord::End {
+                partition_count,
+                checksum,
+            } => {
+                let actual_checksum =
+                    mem::replace(&mut self.checksum, HashAccumulator::new()).finalize();
+                if checksum != actual_checksum {
+                    return Err(SnapshotError::ChecksumMismatch {
+                        expected: checksum,
+                        actual: actual_checksum,
+                    });
+                }
+                if partition_count != self.partition_count {
+                    return Err(SnapshotError::PartitionCountMismatch {
+                        expected: partition_count,
+                        actual: self.partition_count,
+                    });
+                }
+                self.finished = true;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Reads an entire frame (i.e. including its length prefix), without updating the checksum.
+    fn read_frame(&mut self) -> Result<Vec<u8>, SnapshotError> {
+        let mut frame = vec![0; FRAME_LENGTH_BYTES];
+        self.reader.read_exact(&mut frame)?;
+        let length = u32::from_be_bytes(frame.as_slice().try_into().unwrap()) as usize;
+        if length > MAX_FRAME_LENGTH {
+            return Err(SnapshotError::FrameTooLarge { length });
+        }
+        frame.resize(FRAME_LENGTH_BYTES + length, 0);
+        self.reader.read_exact(&mut frame[FRAME_LENGTH_BYTES..])?;
+        Ok(frame)
+    }
+}
+
+/// Maintains a pruned, in-memory state tree, only to compute its root hash.
+struct StateRootHashCalculator {
+    tree_store: TypedInMemoryTreeStore,
+    current_version: Version,
+    current_hash: Hash,
+}
+
+impl StateRootHashCalculator {
+    fn new() -> Self {
+        Self {
+            tree_store: TypedInMemoryTreeStore::new().with_pruning_enabled(),
+            current_version: 0,
+            current_hash: Hash([0; Hash::LENGTH]),
+        }
+    }
+
+    fn apply(&mut self, database_updates: &DatabaseUpdates) {
+        self.current_hash = put_at_next_version(
+            &self.tree_store,
+            Some(self.current_version).filter(|version| *version > 0),
+            database_updates,
+        );
+        self.current_version += 1;
+    }
+
+    fn root_hash(&self) -> Hash {
+        self.current_hash
+    }
+}
+
+fn encode_frame<T: ScryptoEncode>(value: &T) -> Result<Vec<u8>, SnapshotError> {
+    let payload = scrypto_encode(value).unwrap();
+    if payload.len() > MAX_FRAME_LENGTH {
+        return Err(SnapshotError::FrameTooLarge {
+            length: payload.len(),
+        });
+    }
+    let mut frame = (payload.len() as u32).to_be_bytes().to_vec();
+    frame.extend(payload);
+    Ok(frame)
+}
+
+/// Takes the entries of the next record of a partition, bounded by both
+/// [`MAX_ENTRIES_PER_RECORD`] and [`MAX_ENTRIES_SIZE_PER_RECORD`].
+fn next_chunk(entries: &mut Peekable<impl Iterator<Item = PartitionEntry>>) -> Vec<PartitionEntry> {
+    let mut chunk = Vec::new();
+    let mut size = 0;
+    while chunk.len() < MAX_ENTRIES_PER_RECORD && size < MAX_ENTRIES_SIZE_PER_RECORD {
+        let Some((sort_key, value)) = entries.next() else {
+            break;
+        };
+        size += sort_key.0.len() + value.len();
+        chunk.push((sort_key, value));
+    }
+    chunk
+}
+
+/// Merges the given updates into the ones preceding them, with the same effect as committing both
+/// in order (e.g. the Substates of a partition's continuation are added to its reset).
+fn merge_database_updates(merged: &mut DatabaseUpdates, next: DatabaseUpdates) {
+    for (node_key, node_updates) in next.node_updates {
+        let merged_partition_updates = &mut merged
+            .node_updates
+            .entry(node_key)
+            .or_default()
+            .partition_updates;
+        for (partition_num, partition_updates) in node_updates.partition_updates {
+            let Some(PartitionDatabaseUpdates::Reset {
+                new_substate_values,
+            }) = merged_partition_updates.get_mut(&partition_num)
+            else {
+                merged_partition_updates.insert(partition_num, partition_updates);
+                continue;
+            };
+            match partition_updates {
+                PartitionDatabaseUpdates::Delta { substate_updates } => {
+                    for (sort_key, update) in substate_updates {
+                        match update {
+                            DatabaseUpdate::Set(value) => {
+                                new_substate_values.insert(sort_key, value);
+                            }
+                            DatabaseUpdate::Delete => {
+                                new_substate_values.swap_remove(&sort_key);
+                            }
+                        }
+                    }
+                }
+                PartitionDatabaseUpdates::Reset { .. } => {
+                    merged_partition_updates.insert(partition_num, partition_updates);
+                }
+            }
+        }
+    }
+}
+
+fn to_database_updates(
+    partition_key: &DbPartitionKey,
+    is_first_chunk: bool,
+    entries: Vec<PartitionEntry>,
+) -> DatabaseUpdates {
+    let partition_updates = if is_first_chunk {
+        PartitionDatabaseUpdates::Reset {
+            new_substate_values: entries.into_iter().collect(),
+        }
+    } else {
+        PartitionDatabaseUpdates::Delta {
+            substate_updates: entries
+                .into_iter()
+                .map(|(sort_key, value)| (sort_key, DatabaseUpdate::Set(value)))
+                .collect(),
+        }
+    };
+    DatabaseUpdates {
+        node_updates: indexmap!(
+            partition_key.node_key.clone() => NodeDatabaseUpdates {
+                partition_updates: indexmap!(
+                    partition_key.partition_num => partition_updates
+                ),
+            }
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_db::InMemorySubstateDatabase;
+    use crate::state_tree_support::StateTreeUpdatingDatabase;
+
+    #[test]
+    fn snapshot_round_trip_restores_all_substates() {
+        let source = populated_database();
+
+        let mut bytes = Vec::new();
+        let exported_header = export_snapshot(&source, &mut bytes).unwrap();
+
+        let mut target = InMemorySubstateDatabase::standard();
+        let imported_header = import_snapshot(bytes.as_slice(), &mut target).unwrap();
+
+        assert_eq!(imported_header, exported_header);
+        assert_eq!(target, source);
+    }
+
+    #[test]
+    fn snapshot_header_holds_state_tree_root_hash() {
+        let mut tracked = StateTreeUpdatingDatabase::new(InMemorySubstateDatabase::standard());
+        tracked.commit(&populating_updates());
+        tracked.commit(&DatabaseUpdates::from_delta_maps(indexmap!(
+            partition_key(1, 0) => indexmap!(
+                DbSortKey(vec![3]) => DatabaseUpdate::Delete,
+            ),
+        )));
+
+        let mut bytes = Vec::new();
+        let header = export_snapshot(&tracked, &mut bytes).unwrap();
+
+        assert_eq!(header.state_root_hash, tracked.get_current_root_hash());
+    }
+
+    #[test]
+    fn snapshot_with_corrupted_byte_is_rejected() {
+        let mut bytes = Vec::new();
+        export_snapshot(&populated_database(), &mut bytes).unwrap();
+        let last_substate_byte_index = bytes.len() - 60;
+        bytes[last_substate_byte_index] ^= 0xFF;
+
+        let result = import_snapshot(bytes.as_slice(), &mut InMemorySubstateDatabase::standard());
+
+        assert!(matches!(
+            result,
+            Err(SnapshotError::ChecksumMismatch { .. } | SnapshotError::DecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_snapshot_is_rejected_without_committing_anything() {
+        let mut bytes = Vec::new();
+        export_snapshot(&populated_database(), &mut bytes).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        let mut target = InMemorySubstateDatabase::standard();
+        let result = import_snapshot(bytes.as_slice(), &mut target);
+
+        assert!(matches!(
+            result,
+            Err(SnapshotError::UnexpectedEndOfSnapshot)
+        ));
+        assert_eq!(target, InMemorySubstateDatabase::standard());
+    }
+
+    #[test]
+    fn snapshot_with_oversized_frame_is_rejected_before_allocating() {
+        let mut bytes = SNAPSHOT_MAGIC.to_vec();
+        bytes.extend(u32::MAX.to_be_bytes());
+
+        let result = import_snapshot(bytes.as_slice(), &mut InMemorySubstateDatabase::standard());
+
+        assert!(matches!(
+            result,
+            Err(SnapshotError::FrameTooLarge { length }) if length == u32::MAX as usize
+        ));
+    }
+
+    #[test]
+    fn non_snapshot_bytes_are_rejected() {
+        let result = import_snapshot(
+            b"not a snapshot".as_slice(),
+            &mut InMemorySubstateDatabase::standard(),
+        );
+
+        assert!(matches!(result, Err(SnapshotError::NotASnapshot)));
+    }
+
+    #[test]
+    fn snapshot_with_mismatched_state_root_hash_is_rejected() {
+        let source = populated_database();
+        let mut bytes = Vec::new();
+        let mut writer = SnapshotWriter::new(
+            &mut bytes,
+            SnapshotHeaderV1 {
+                state_root_hash: Hash([7; Hash::LENGTH]),
+            },
+        )
+        .unwrap();
+        for partition_key in source.list_partition_keys() {
+            writer
+                .write_partition(&partition_key, source.list_entries(&partition_key))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut target = InMemorySubstateDatabase::standard();
+        let result = import_snapshot(bytes.as_slice(), &mut target);
+
+        assert!(matches!(
+            result,
+            Err(SnapshotError::StateRootHashMismatch { .. })
+        ));
+        assert_eq!(target, InMemorySubstateDatabase::standard());
+    }
+
+    fn populated_database() -> InMemorySubstateDatabase {
+        let mut database = InMemorySubstateDatabase::standard();
+        database.commit(&populating_updates());
+        database
+    }
+
+    /// Creates a few small partitions, plus one spanning multiple snapshot records.
+    fn populating_updates() -> DatabaseUpdates {
+        let mut maps = index_map_new();
+        for node_seed in 1..4 {
+            for partition_num in 0..3 {
+                maps.insert(
+                    partition_key(node_seed, partition_num),
+                    (0..5u8)
+                        .map(|sort_seed| {
+                            (
+                                DbSortKey(vec![sort_seed]),
+                                DatabaseUpdate::Set(vec![node_seed, partition_num, sort_seed]),
+                            )
+                        })
+                        .collect(),
+                );
+            }
+        }
+        maps.insert(
+            partition_key(9, 9),
+            (0..(2 * MAX_ENTRIES_PER_RECORD as u32 + 1))
+                .map(|sort_seed| {
+                    (
+                        DbSortKey(sort_seed.to_be_bytes().to_vec()),
+                        DatabaseUpdate::Set(sort_seed.to_le_bytes().to_vec()),
+                    )
+                })
+                .collect(),
+        );
+        DatabaseUpdates::from_delta_maps(maps)
+    }
+
+    fn partition_key(node_seed: u8, partition_num: u8) -> DbPartitionKey {
+        DbPartitionKey {
+            node_key: vec![node_seed; 3],
+            partition_num,
+        }
+    }
+}
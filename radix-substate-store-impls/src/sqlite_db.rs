@@ -0,0 +1,365 @@
+use radix_substate_store_interface::interface::*;
+use rusqlite::{params, Connection, OptionalExtension};
+use sbor::rust::prelude::*;
+use std::path::PathBuf;
+
+/// A Substate database stored in a single SQLite file.
+///
+/// All Substates live in a single `substates` table, keyed by the `(node_key, partition_num,
+/// sort_key)` columns. SQLite compares BLOBs using `memcmp()`, so the entries are listed in the same
+/// order as the [`DbSortKey`]s.
+pub struct SqliteSubstateStore {
+    connection: Connection,
+}
+
+impl SqliteSubstateStore {
+    /// The maximum number of rows fetched by a single query, when lazily listing entries.
+    const PAGE_SIZE: usize = 1000;
+
+    pub fn standard(path: PathBuf) -> Self {
+        Self::with_connection(Connection::open(path).expect("IO error"))
+    }
+
+    pub fn in_memory() -> Self {
+        Self::with_connection(Connection::open_in_memory().expect("IO error"))
+    }
+
+    pub fn with_connection(connection: Connection) -> Self {
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS substates (
+                    node_key BLOB NOT NULL,
+                    partition_num INTEGER NOT NULL,
+                    sort_key BLOB NOT NULL,
+                    value BLOB NOT NULL,
+                    PRIMARY KEY (node_key, partition_num, sort_key)
+                ) WITHOUT ROWID;",
+            )
+            .expect("IO error");
+        Self { connection }
+    }
+}
+
+impl SubstateDatabase for SqliteSubstateStore {
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        self.connection
+            .prepare_cached(
+                "SELECT value FROM substates
+                WHERE node_key = ?1 AND partition_num = ?2 AND sort_key = ?3",
+            )
+            .expect("IO error")
+            .query_row(
+                params![
+                    partition_key.node_key,
+                    partition_key.partition_num,
+                    sort_key.0
+                ],
+                |row| row.get(0),
+            )
+            .optional()
+            .expect("IO error")
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        let partition_key = partition_key.clone();
+        let from_sort_key = from_sort_key.cloned().unwrap_or(DbSortKey(vec![]));
+        Box::new(paged(
+            |(sort_key, _value): &PartitionEntry| sort_key.clone(),
+            move |last_sort_key| {
+                let (sql, sort_key_bound) = match last_sort_key {
+                    None => (
+                        "SELECT sort_key, value FROM substates
+                        WHERE node_key = ?1 AND partition_num = ?2 AND sort_key >= ?3
+                        ORDER BY sort_key LIMIT ?4",
+                        &from_sort_key,
+                    ),
+                    Some(last_sort_key) => (
+                        "SELECT sort_key, value FROM substates
+                        WHERE node_key = ?1 AND partition_num = ?2 AND sort_key > ?3
+                        ORDER BY sort_key LIMIT ?4",
+                        last_sort_key,
+                    ),
+                };
+                self.connection
+                    .prepare_cached(sql)
+                    .expect("IO error")
+                    .query_map(
+                        params![
+                            partition_key.node_key,
+                            partition_key.partition_num,
+                            sort_key_bound.0,
+                            Self::PAGE_SIZE
+                        ],
+                        |row| Ok((DbSortKey(row.get(0)?), row.get(1)?)),
+                    )
+                    .expect("IO error")
+                    .collect::<Result<Vec<_>, _>>()
+                    .expect("IO error")
+            },
+        ))
+    }
+}
+
+impl CommittableSubstateDatabase for SqliteSubstateStore {
+    fn commit(&mut self, database_updates: &DatabaseUpdates) {
+        let transaction = self.connection.transaction().expect("IO error");
+        {
+            let mut upsert = transaction
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO substates (node_key, partition_num, sort_key, value)
+                    VALUES (?1, ?2, ?3, ?4)",
+                )
+                .expect("IO error");
+            let mut delete = transaction
+                .prepare_cached(
+                    "DELETE FROM substates
+                    WHERE node_key = ?1 AND partition_num = ?2 AND sort_key = ?3",
+                )
+                .expect("IO error");
+            let mut delete_partition = transaction
+                .prepare_cached("DELETE FROM substates WHERE node_key = ?1 AND partition_num = ?2")
+                .expect("IO error");
+            for (node_key, node_updates) in &database_updates.node_updates {
+                for (partition_num, partition_updates) in &node_updates.partition_updates {
+                    match partition_updates {
+                        PartitionDatabaseUpdates::Delta { substate_updates } => {
+                            for (sort_key, update) in substate_updates {
+                                match update {
+                                    DatabaseUpdate::Set(value_bytes) => upsert.execute(params![
+                                        node_key,
+                                        partition_num,
+                                        sort_key.0,
+                                        value_bytes
+                                    ]),
+                                    DatabaseUpdate::Delete => {
+                                        delete.execute(params![node_key, partition_num, sort_key.0])
+                                    }
+                                }
+                                .expect("IO error");
+                            }
+                        }
+                        PartitionDatabaseUpdates::Reset {
+                            new_substate_values,
+                        } => {
+                            delete_partition
+                                .execute(params![node_key, partition_num])
+                                .expect("IO error");
+                            for (sort_key, value_bytes) in new_substate_values {
+                                upsert
+                                    .execute(params![
+                                        node_key,
+                                        partition_num,
+                                        sort_key.0,
+                                        value_bytes
+                                    ])
+                                    .expect("IO error");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        transaction.commit().expect("IO error");
+    }
+}
+
+impl ListableSubstateDatabase for SqliteSubstateStore {
+    fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
+        Box::new(paged(
+            |partition_key: &DbPartitionKey| partition_key.clone(),
+            move |last_partition_key| {
+                let mut statement = match last_partition_key {
+                    None => self.connection.prepare_cached(
+                        "SELECT DISTINCT node_key, partition_num FROM substates
+                        ORDER BY node_key, partition_num LIMIT ?1",
+                    ),
+                    Some(_) => self.connection.prepare_cached(
+                        "SELECT DISTINCT node_key, partition_num FROM substates
+                        WHERE (node_key, partition_num) > (?2, ?3)
+                        ORDER BY node_key, partition_num LIMIT ?1",
+                    ),
+                }
+                .expect("IO error");
+                let map_row = |row: &rusqlite::Row| {
+                    Ok(DbPartitionKey {
+                        node_key: row.get(0)?,
+                        partition_num: row.get(1)?,
+                    })
+                };
+                match last_partition_key {
+                    None => statement.query_map(params![Self::PAGE_SIZE], map_row),
+                    Some(last_partition_key) => statement.query_map(
+                        params![
+                            Self::PAGE_SIZE,
+                            last_partition_key.node_key,
+                            last_partition_key.partition_num
+                        ],
+                        map_row,
+                    ),
+                }
+                .expect("IO error")
+                .collect::<Result<Vec<_>, _>>()
+                .expect("IO error")
+            },
+        ))
+    }
+}
+
+/// Lazily iterates over the items of consecutive pages, each fetched on demand by the given
+/// function, based on the key of the last item of the previous page (or `None` for the first
+/// page).
+fn paged<'a, T: 'a, K: 'a>(
+    key_of: impl Fn(&T) -> K + 'a,
+    mut fetch_page: impl FnMut(Option<&K>) -> Vec<T> + 'a,
+) -> impl Iterator<Item = T> + 'a {
+    let mut page = Vec::new().into_iter();
+    let mut last_key = None;
+    let mut exhausted = false;
+    core::iter::from_fn(move || {
+        if page.len() == 0 {
+            if exhausted {
+                return None;
+            }
+            let items = fetch_page(last_key.as_ref());
+            exhausted = items.len() < SqliteSubstateStore::PAGE_SIZE;
+            last_key = items.last().map(&key_of);
+            page = items.into_iter();
+        }
+        page.next()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radix_substate_store_interface::interface::{
+        CommittableSubstateDatabase, DatabaseUpdates, DbSortKey, NodeDatabaseUpdates,
+        PartitionDatabaseUpdates,
+    };
+
+    #[test]
+    fn test_partition_deletion() {
+        let mut db = SqliteSubstateStore::in_memory();
+
+        let node_updates = NodeDatabaseUpdates {
+            partition_updates: indexmap! {
+                0 => PartitionDatabaseUpdates::Reset {
+                    new_substate_values: indexmap! {
+                        DbSortKey(vec![5]) => vec![6]
+                    }
+                },
+                1 => PartitionDatabaseUpdates::Reset {
+                    new_substate_values: indexmap! {
+                        DbSortKey(vec![7]) => vec![8]
+                    }
+                },
+                255 => PartitionDatabaseUpdates::Reset {
+                    new_substate_values: indexmap! {
+                        DbSortKey(vec![9]) => vec![10]
+                    }
+                }
+            },
+        };
+        let updates = DatabaseUpdates {
+            node_updates: indexmap! {
+                vec![0] => node_updates.clone(),
+                vec![1] => node_updates.clone(),
+                vec![255] => node_updates.clone(),
+            },
+        };
+        db.commit(&updates);
+
+        assert_eq!(db.list_partition_keys().count(), 9);
+        db.commit(&DatabaseUpdates {
+            node_updates: indexmap! {
+                vec![0] => NodeDatabaseUpdates {
+                    partition_updates: indexmap!{
+                        255 => PartitionDatabaseUpdates::Reset { new_substate_values: indexmap!{} }
+                    }
+                }
+            },
+        });
+        assert_eq!(db.list_partition_keys().count(), 8);
+    }
+
+    #[test]
+    fn test_entries_are_listed_in_sort_key_order_across_pages() {
+        let mut db = SqliteSubstateStore::in_memory();
+        let partition_key = DbPartitionKey {
+            node_key: vec![1, 2, 3],
+            partition_num: 4,
+        };
+        let mut sort_keys = (0..(2 * SqliteSubstateStore::PAGE_SIZE as u16 + 1))
+            .map(|i| {
+                DbSortKey(
+                    i.to_be_bytes()
+                        .into_iter()
+                        .skip_while(|b| *b == 0)
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        db.commit(&DatabaseUpdates::from_delta_maps(indexmap! {
+            partition_key.clone() => sort_keys
+                .iter()
+                .rev()
+                .map(|sort_key| (sort_key.clone(), DatabaseUpdate::Set(sort_key.0.clone())))
+                .collect(),
+        }));
+        sort_keys.sort();
+
+        assert_eq!(
+            db.list_entries(&partition_key)
+                .map(|(sort_key, _value)| sort_key)
+                .collect::<Vec<_>>(),
+            sort_keys
+        );
+        assert_eq!(
+            db.list_entries_from(&partition_key, Some(&sort_keys[1500]))
+                .map(|(sort_key, _value)| sort_key)
+                .collect::<Vec<_>>(),
+            sort_keys[1500..].to_vec()
+        );
+        assert_eq!(
+            db.get_substate(&partition_key, &sort_keys[7]),
+            Some(sort_keys[7].0.clone())
+        );
+    }
+
+    #[test]
+    fn test_partition_keys_are_listed_across_pages() {
+        let mut db = SqliteSubstateStore::in_memory();
+        let partition_keys = (0..(SqliteSubstateStore::PAGE_SIZE as u16 + 1))
+            .flat_map(|i| {
+                [0, u8::MAX].map(|partition_num| DbPartitionKey {
+                    node_key: i.to_be_bytes().to_vec(),
+                    partition_num,
+                })
+            })
+            .collect::<Vec<_>>();
+        db.commit(&DatabaseUpdates::from_delta_maps(
+            partition_keys
+                .iter()
+                .map(|partition_key| {
+                    (
+                        partition_key.clone(),
+                        indexmap! {
+                            DbSortKey(vec![0]) => DatabaseUpdate::Set(vec![0]),
+                            DbSortKey(vec![1]) => DatabaseUpdate::Set(vec![1]),
+                        },
+                    )
+                })
+                .collect(),
+        ));
+
+        assert_eq!(db.list_partition_keys().collect::<Vec<_>>(), partition_keys);
+    }
+}
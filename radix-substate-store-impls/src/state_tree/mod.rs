@@ -3,6 +3,7 @@ pub mod tree_store;
 
 pub mod entity_tier;
 pub mod partition_tier;
+pub mod proofs;
 pub mod substate_tier;
 pub mod tier_framework;
 
@@ -10,12 +11,13 @@ use entity_tier::EntityTier;
 use radix_common::crypto::Hash;
 use radix_rust::prelude::*;
 use radix_substate_store_interface::interface::*;
+use substate_tier::SubstateSummary;
 use tree_store::*;
 use types::*;
 
 // The sources copied from Aptos (the `jellyfish` and `types` modules) contain support for
-// generating proofs, which we plan to use in near future. Hence, we do not delete that code, but
-// suppress warnings.
+// generating various proofs, out of which we only use some (see the `proofs` module). Hence, we do
+// not delete that code, but suppress warnings.
 
 #[allow(dead_code)]
 mod jellyfish;
@@ -68,3 +70,70 @@ pub fn list_substate_hashes_at_version<S: ReadableTreeStore>(
         })
         .collect()
 }
+
+/// Reads the value of the Substate under the given key, as of the given state version, from the
+/// Substate values associated with the "3-Tier JMT" leaves (see
+/// [`WriteableTreeStore::associate_substate()`]).
+///
+/// # Panics
+/// Panics if the tree nodes (or the associated Substate values) of the given version are not
+/// available in the `TreeStore`.
+pub fn get_substate_at_version<S: ReadableTreeStore + ReadableAssociatedSubstateStore>(
+    tree_store: &S,
+    state_version: Version,
+    partition_key: &DbPartitionKey,
+    sort_key: &DbSortKey,
+) -> Option<DbSubstateValue> {
+    EntityTier::new(tree_store, Some(state_version))
+        .get_entity_partition_tier(partition_key.node_key.clone())
+        .get_partition_substate_tier(partition_key.partition_num)
+        .get_substate_summary(sort_key)
+        .map(|summary| resolve_associated_substate(tree_store, partition_key, summary))
+}
+
+/// Iterates over the entries of the given partition, as of the given state version (see
+/// [`get_substate_at_version()`] for details).
+///
+/// # Panics
+/// Panics if the tree nodes (or the associated Substate values) of the given version are not
+/// available in the `TreeStore`.
+pub fn list_entries_from_at_version<'s, S: ReadableTreeStore + ReadableAssociatedSubstateStore>(
+    tree_store: &'s S,
+    state_version: Version,
+    partition_key: &DbPartitionKey,
+    from_sort_key: Option<&DbSortKey>,
+) -> Box<dyn Iterator<Item = PartitionEntry> + 's> {
+    let partition_key = partition_key.clone();
+    let substate_tier = EntityTier::new(tree_store, Some(state_version))
+        .get_entity_partition_tier(partition_key.node_key.clone())
+        .get_partition_substate_tier(partition_key.partition_num);
+    Box::new(
+        substate_tier
+            .into_iter_substate_summaries_from(from_sort_key)
+            .map(move |summary| {
+                let sort_key = summary.sort_key.clone();
+                let value = resolve_associated_substate(tree_store, &partition_key, summary);
+                (sort_key, value)
+            }),
+    )
+}
+
+fn resolve_associated_substate<S: ReadableTreeStore + ReadableAssociatedSubstateStore>(
+    tree_store: &S,
+    partition_key: &DbPartitionKey,
+    summary: SubstateSummary,
+) -> DbSubstateValue {
+    if let Some(value) = tree_store.get_associated_substate(&summary.state_tree_leaf_key) {
+        return value;
+    }
+    // The leaf was re-created by a tree restructuring (i.e. associated with an "unchanged" value).
+    // However, the leaf created at the Substate's most recent upsert holds exactly that value:
+    let upserted_summary = EntityTier::new(tree_store, Some(summary.upsert_version))
+        .get_entity_partition_tier(partition_key.node_key.clone())
+        .get_partition_substate_tier(partition_key.partition_num)
+        .get_substate_summary(&summary.sort_key)
+        .expect("leaf of the most recent upsert must exist");
+    tree_store
+        .get_associated_substate(&upserted_summary.state_tree_leaf_key)
+        .expect("value of the most recent upsert must be associated")
+}
@@ -0,0 +1,302 @@
+use super::entity_tier::EntityTier;
+use super::partition_tier::PartitionTier;
+use super::substate_tier::SubstateTier;
+use super::tier_framework::*;
+use super::tree_store::*;
+use radix_common::crypto::{hash, Hash};
+use radix_substate_store_interface::interface::*;
+use sbor::*;
+
+// Re-exports
+pub use super::types::{LeafKey, ProofVerificationError, SparseMerkleLeafNode, SparseMerkleProof};
+
+/// A proof of a Substate's inclusion in the "3-Tier JMT", composed of individual proofs from each
+/// of the tiers.
+///
+/// The proofs are verified bottom-up: the Substate-Tier proof leads from the Substate's value hash
+/// to the partition's root hash, which is the value hash of the Partition-Tier leaf; the
+/// Partition-Tier proof leads from it to the entity's root hash, which is the value hash of the
+/// Entity-Tier leaf; and finally, the Entity-Tier proof leads from it to the state root hash.
+#[derive(Clone, Debug, PartialEq, Eq, Sbor)]
+pub struct SubstateInclusionProof {
+    /// A proof of the entity's leaf within the (top) Entity-Tier.
+    pub entity_tier_proof: SparseMerkleProof,
+    /// A proof of the partition's leaf within the entity's Partition-Tier.
+    pub partition_tier_proof: SparseMerkleProof,
+    /// A proof of the Substate's leaf within the partition's Substate-Tier.
+    pub substate_tier_proof: SparseMerkleProof,
+}
+
+impl SubstateInclusionProof {
+    /// Verifies that the given Substate value exists under the given key in the state represented
+    /// by the given root hash.
+    pub fn verify(
+        &self,
+        expected_root_hash: Hash,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+        substate_value: &DbSubstateValue,
+    ) -> Result<(), ProofVerificationError> {
+        let partition_root_hash = self.substate_tier_proof.compute_root_hash(
+            &SubstateTier::<()>::to_leaf_key(sort_key),
+            Some(hash(substate_value)),
+        )?;
+        let entity_root_hash = self.partition_tier_proof.compute_root_hash(
+            &PartitionTier::<()>::to_leaf_key(&partition_key.partition_num),
+            Some(partition_root_hash),
+        )?;
+        self.entity_tier_proof.verify(
+            expected_root_hash,
+            &EntityTier::<()>::to_leaf_key(&partition_key.node_key),
+            Some(entity_root_hash),
+        )
+    }
+}
+
+/// A proof that a Substate, a partition or an entire entity does not exist in the "3-Tier JMT".
+///
+/// The absence is proven by a non-inclusion proof at the highest tier at which the Substate's key
+/// is missing, preceded by inclusion proofs of the tiers above it (see [`SubstateInclusionProof`]
+/// for the bottom-up verification logic).
+#[derive(Clone, Debug, PartialEq, Eq, Sbor)]
+pub enum ExclusionProof {
+    /// The entity is absent from the Entity-Tier.
+    EntityAbsent {
+        entity_tier_proof: SparseMerkleProof,
+    },
+    /// The entity exists, but the partition is absent from its Partition-Tier.
+    PartitionAbsent {
+        entity_tier_proof: SparseMerkleProof,
+        partition_tier_proof: SparseMerkleProof,
+    },
+    /// The partition exists, but the Substate is absent from its Substate-Tier.
+    SubstateAbsent {
+        entity_tier_proof: SparseMerkleProof,
+        partition_tier_proof: SparseMerkleProof,
+        substate_tier_proof: SparseMerkleProof,
+    },
+}
+
+impl ExclusionProof {
+    /// Verifies that the given entity does not exist in the state represented by the given root
+    /// hash.
+    pub fn verify_entity_absent(
+        &self,
+        expected_root_hash: Hash,
+        node_key: &DbNodeKey,
+    ) -> Result<(), ProofVerificationError> {
+        match self {
+            ExclusionProof::EntityAbsent { entity_tier_proof } => entity_tier_proof.verify(
+                expected_root_hash,
+                &EntityTier::<()>::to_leaf_key(node_key),
+                None,
+            ),
+            ExclusionProof::PartitionAbsent { .. } | ExclusionProof::SubstateAbsent { .. } => {
+                Err(ProofVerificationError::ExpectedNonInclusionProof)
+            }
+        }
+    }
+
+    /// Verifies that the given partition does not exist in the state represented by the given root
+    /// hash (either because its entire entity does not exist, or because the entity does not have
+    /// this partition).
+    pub fn verify_partition_absent(
+        &self,
+        expected_root_hash: Hash,
+        partition_key: &DbPartitionKey,
+    ) -> Result<(), ProofVerificationError> {
+        match self {
+            ExclusionProof::EntityAbsent { .. } => {
+                self.verify_entity_absent(expected_root_hash, &partition_key.node_key)
+            }
+            ExclusionProof::PartitionAbsent {
+                entity_tier_proof,
+                partition_tier_proof,
+            } => {
+                let entity_root_hash = partition_tier_proof.compute_root_hash(
+                    &PartitionTier::<()>::to_leaf_key(&partition_key.partition_num),
+                    None,
+                )?;
+                entity_tier_proof.verify(
+                    expected_root_hash,
+                    &EntityTier::<()>::to_leaf_key(&partition_key.node_key),
+                    Some(entity_root_hash),
+                )
+            }
+            ExclusionProof::SubstateAbsent { .. } => {
+                Err(ProofVerificationError::ExpectedNonInclusionProof)
+            }
+        }
+    }
+
+    /// Verifies that the given Substate does not exist in the state represented by the given root
+    /// hash (either because its partition or entity does not exist, or because the partition does
+    /// not contain this sort key).
+    pub fn verify_substate_absent(
+        &self,
+        expected_root_hash: Hash,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Result<(), ProofVerificationError> {
+        match self {
+            ExclusionProof::EntityAbsent { .. } | ExclusionProof::PartitionAbsent { .. } => {
+                self.verify_partition_absent(expected_root_hash, partition_key)
+            }
+            ExclusionProof::SubstateAbsent {
+                entity_tier_proof,
+                partition_tier_proof,
+                substate_tier_proof,
+            } => {
+                let partition_root_hash = substate_tier_proof
+                    .compute_root_hash(&SubstateTier::<()>::to_leaf_key(sort_key), None)?;
+                let entity_root_hash = partition_tier_proof.compute_root_hash(
+                    &PartitionTier::<()>::to_leaf_key(&partition_key.partition_num),
+                    Some(partition_root_hash),
+                )?;
+                entity_tier_proof.verify(
+                    expected_root_hash,
+                    &EntityTier::<()>::to_leaf_key(&partition_key.node_key),
+                    Some(entity_root_hash),
+                )
+            }
+        }
+    }
+}
+
+/// Generates a proof of inclusion of the Substate under the given key, in the state at the given
+/// version.
+/// Returns `None` if the Substate does not exist at that version.
+///
+/// # Panics
+/// Panics if the tree nodes of the given version are not available in the `TreeStore` (e.g. they
+/// were pruned).
+pub fn get_substate_inclusion_proof<S: ReadableTreeStore>(
+    tree_store: &S,
+    state_version: Version,
+    partition_key: &DbPartitionKey,
+    sort_key: &DbSortKey,
+) -> Option<SubstateInclusionProof> {
+    let (entity_leaf, entity_tier_proof) = EntityTier::new(tree_store, Some(state_version))
+        .get_persisted_leaf_with_proof(&partition_key.node_key)?;
+    let (_entity_root_hash, entity_root_version) = entity_leaf?;
+
+    let (partition_leaf, partition_tier_proof) = PartitionTier::new(
+        tree_store,
+        Some(entity_root_version),
+        partition_key.node_key.clone(),
+    )
+    .get_persisted_leaf_with_proof(&partition_key.partition_num)?;
+    let (_partition_root_hash, partition_root_version) = partition_leaf?;
+
+    let (substate_leaf, substate_tier_proof) = SubstateTier::new(
+        tree_store,
+        Some(partition_root_version),
+        partition_key.node_key.clone(),
+        partition_key.partition_num,
+    )
+    .get_persisted_leaf_with_proof(sort_key)?;
+    substate_leaf?;
+
+    Some(SubstateInclusionProof {
+        entity_tier_proof,
+        partition_tier_proof,
+        substate_tier_proof,
+    })
+}
+
+/// Generates a proof that the given entity does not exist in the state at the given version.
+/// Returns `None` if the entity exists at that version.
+///
+/// # Panics
+/// Panics if the tree nodes of the given version are not available in the `TreeStore`.
+pub fn get_entity_exclusion_proof<S: ReadableTreeStore>(
+    tree_store: &S,
+    state_version: Version,
+    node_key: &DbNodeKey,
+) -> Option<ExclusionProof> {
+    let (entity_leaf, entity_tier_proof) =
+        EntityTier::new(tree_store, Some(state_version)).get_persisted_leaf_with_proof(node_key)?;
+    match entity_leaf {
+        Some(_) => None,
+        None => Some(ExclusionProof::EntityAbsent { entity_tier_proof }),
+    }
+}
+
+/// Generates a proof that the given partition does not exist in the state at the given version.
+/// Returns `None` if the partition exists at that version.
+///
+/// # Panics
+/// Panics if the tree nodes of the given version are not available in the `TreeStore`.
+pub fn get_partition_exclusion_proof<S: ReadableTreeStore>(
+    tree_store: &S,
+    state_version: Version,
+    partition_key: &DbPartitionKey,
+) -> Option<ExclusionProof> {
+    let (entity_leaf, entity_tier_proof) = EntityTier::new(tree_store, Some(state_version))
+        .get_persisted_leaf_with_proof(&partition_key.node_key)?;
+    let Some((_entity_root_hash, entity_root_version)) = entity_leaf else {
+        return Some(ExclusionProof::EntityAbsent { entity_tier_proof });
+    };
+
+    let (partition_leaf, partition_tier_proof) = PartitionTier::new(
+        tree_store,
+        Some(entity_root_version),
+        partition_key.node_key.clone(),
+    )
+    .get_persisted_leaf_with_proof(&partition_key.partition_num)?;
+    match partition_leaf {
+        Some(_) => None,
+        None => Some(ExclusionProof::PartitionAbsent {
+            entity_tier_proof,
+            partition_tier_proof,
+        }),
+    }
+}
+
+/// Generates a proof that the Substate under the given key does not exist in the state at the
+/// given version.
+/// Returns `None` if the Substate exists at that version.
+///
+/// # Panics
+/// Panics if the tree nodes of the given version are not available in the `TreeStore`.
+pub fn get_substate_exclusion_proof<S: ReadableTreeStore>(
+    tree_store: &S,
+    state_version: Version,
+    partition_key: &DbPartitionKey,
+    sort_key: &DbSortKey,
+) -> Option<ExclusionProof> {
+    let (entity_leaf, entity_tier_proof) = EntityTier::new(tree_store, Some(state_version))
+        .get_persisted_leaf_with_proof(&partition_key.node_key)?;
+    let Some((_entity_root_hash, entity_root_version)) = entity_leaf else {
+        return Some(ExclusionProof::EntityAbsent { entity_tier_proof });
+    };
+
+    let (partition_leaf, partition_tier_proof) = PartitionTier::new(
+        tree_store,
+        Some(entity_root_version),
+        partition_key.node_key.clone(),
+    )
+    .get_persisted_leaf_with_proof(&partition_key.partition_num)?;
+    let Some((_partition_root_hash, partition_root_version)) = partition_leaf else {
+        return Some(ExclusionProof::PartitionAbsent {
+            entity_tier_proof,
+            partition_tier_proof,
+        });
+    };
+
+    let (substate_leaf, substate_tier_proof) = SubstateTier::new(
+        tree_store,
+        Some(partition_root_version),
+        partition_key.node_key.clone(),
+        partition_key.partition_num,
+    )
+    .get_persisted_leaf_with_proof(sort_key)?;
+    match substate_leaf {
+        Some(_) => None,
+        None => Some(ExclusionProof::SubstateAbsent {
+            entity_tier_proof,
+            partition_tier_proof,
+            substate_tier_proof,
+        }),
+    }
+}
@@ -2,8 +2,12 @@ use super::jellyfish::JellyfishMerkleTree;
 use super::tier_framework::{StateTreeTier, TIER_SEPARATOR};
 use super::tree_store::*;
 use super::types::*;
+use crate::memory_db::InMemorySubstateDatabase;
 use crate::state_tree::entity_tier::EntityTier;
+use crate::state_tree::proofs::*;
 use crate::state_tree::substate_tier::SubstateSummary;
+use crate::state_tree::{get_substate_at_version, list_entries_from_at_version};
+use crate::state_tree_support::StateTreeUpdatingDatabase;
 use itertools::Itertools;
 use radix_common::crypto::{hash, Hash};
 use radix_common::data::scrypto::{scrypto_decode, scrypto_encode};
@@ -864,6 +868,389 @@ fn lists_historical_substate_summaries() {
     );
 }
 
+#[test]
+fn substate_inclusion_proof_verifies_against_root_hash() {
+    let mut tester = StateTreeTester::new_empty();
+    tester.put_substate_changes(vec![
+        change(1, 9, 1, Some(196)),
+        change(3, 2, 6, Some(36)),
+        change(3, 2, 8, Some(38)),
+    ]);
+    let root_hash = tester
+        .put_substate_changes(vec![change(3, 4, 7, Some(237)), change(4, 2, 6, Some(46))])
+        .unwrap();
+
+    let proof = get_substate_inclusion_proof(
+        &tester.tree_store,
+        2,
+        &partition_key(from_seed(3), 2),
+        &DbSortKey(from_seed(8)),
+    )
+    .unwrap();
+
+    assert_eq!(
+        proof.verify(
+            root_hash,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(8)),
+            &from_seed(38)
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn substate_inclusion_proof_rejects_different_value_or_key() {
+    let mut tester = StateTreeTester::new_empty();
+    let root_hash = tester
+        .put_substate_changes(vec![
+            change(1, 9, 1, Some(196)),
+            change(3, 2, 6, Some(36)),
+            change(3, 2, 8, Some(38)),
+        ])
+        .unwrap();
+
+    let proof = get_substate_inclusion_proof(
+        &tester.tree_store,
+        1,
+        &partition_key(from_seed(3), 2),
+        &DbSortKey(from_seed(6)),
+    )
+    .unwrap();
+
+    assert_eq!(
+        proof.verify(
+            root_hash,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(6)),
+            &from_seed(37)
+        ),
+        Err(ProofVerificationError::ValueHashMismatch)
+    );
+    assert_eq!(
+        proof.verify(
+            root_hash,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(8)),
+            &from_seed(36)
+        ),
+        Err(ProofVerificationError::LeafKeyMismatch)
+    );
+    assert!(proof
+        .verify(
+            Hash([7; Hash::LENGTH]),
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(6)),
+            &from_seed(36)
+        )
+        .is_err());
+}
+
+#[test]
+fn historical_substate_inclusion_proof_verifies_against_historical_root_hash() {
+    let mut tester = StateTreeTester::new_empty();
+    let root_hash_v1 = tester
+        .put_substate_changes(vec![change(1, 9, 1, Some(196)), change(3, 2, 6, Some(16))])
+        .unwrap();
+    let root_hash_v2 = tester
+        .put_substate_changes(vec![change(3, 2, 6, Some(36))])
+        .unwrap();
+
+    let historical_proof = get_substate_inclusion_proof(
+        &tester.tree_store,
+        1,
+        &partition_key(from_seed(3), 2),
+        &DbSortKey(from_seed(6)),
+    )
+    .unwrap();
+
+    assert_eq!(
+        historical_proof.verify(
+            root_hash_v1,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(6)),
+            &from_seed(16)
+        ),
+        Ok(())
+    );
+    assert!(historical_proof
+        .verify(
+            root_hash_v2,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(6)),
+            &from_seed(16)
+        )
+        .is_err());
+}
+
+#[test]
+fn substate_inclusion_proof_not_generated_for_missing_substate() {
+    let mut tester = StateTreeTester::new_empty();
+    tester.put_substate_changes(vec![change(1, 9, 1, Some(196)), change(3, 2, 6, Some(36))]);
+
+    let proof = get_substate_inclusion_proof(
+        &tester.tree_store,
+        1,
+        &partition_key(from_seed(3), 2),
+        &DbSortKey(from_seed(7)),
+    );
+
+    assert_eq!(proof, None);
+}
+
+#[test]
+fn substate_exclusion_proof_verifies_missing_sort_key() {
+    let mut tester = StateTreeTester::new_empty();
+    let root_hash = tester
+        .put_substate_changes(vec![
+            change(1, 9, 1, Some(196)),
+            change(3, 2, 6, Some(36)),
+            change(3, 2, 8, Some(38)),
+        ])
+        .unwrap();
+
+    let proof = get_substate_exclusion_proof(
+        &tester.tree_store,
+        1,
+        &partition_key(from_seed(3), 2),
+        &DbSortKey(from_seed(7)),
+    )
+    .unwrap();
+
+    assert!(matches!(proof, ExclusionProof::SubstateAbsent { .. }));
+    assert_eq!(
+        proof.verify_substate_absent(
+            root_hash,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(7))
+        ),
+        Ok(())
+    );
+    assert!(proof
+        .verify_substate_absent(
+            root_hash,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(6))
+        )
+        .is_err());
+    assert_eq!(
+        proof.verify_partition_absent(root_hash, &partition_key(from_seed(3), 2)),
+        Err(ProofVerificationError::ExpectedNonInclusionProof)
+    );
+}
+
+#[test]
+fn substate_exclusion_proof_verifies_deleted_substate() {
+    let mut tester = StateTreeTester::new_empty();
+    tester.put_substate_changes(vec![change(3, 2, 6, Some(36)), change(3, 2, 8, Some(38))]);
+    let root_hash = tester
+        .put_substate_changes(vec![change(3, 2, 8, None)])
+        .unwrap();
+
+    let proof = get_substate_exclusion_proof(
+        &tester.tree_store,
+        2,
+        &partition_key(from_seed(3), 2),
+        &DbSortKey(from_seed(8)),
+    )
+    .unwrap();
+
+    assert_eq!(
+        proof.verify_substate_absent(
+            root_hash,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(8))
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        get_substate_exclusion_proof(
+            &tester.tree_store,
+            1,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(8)),
+        ),
+        None
+    );
+}
+
+#[test]
+fn partition_exclusion_proof_verifies_missing_partition() {
+    let mut tester = StateTreeTester::new_empty();
+    let root_hash = tester
+        .put_substate_changes(vec![change(1, 9, 1, Some(196)), change(3, 2, 6, Some(36))])
+        .unwrap();
+
+    let proof =
+        get_partition_exclusion_proof(&tester.tree_store, 1, &partition_key(from_seed(3), 4))
+            .unwrap();
+
+    assert!(matches!(proof, ExclusionProof::PartitionAbsent { .. }));
+    assert_eq!(
+        proof.verify_partition_absent(root_hash, &partition_key(from_seed(3), 4)),
+        Ok(())
+    );
+    assert_eq!(
+        proof.verify_substate_absent(
+            root_hash,
+            &partition_key(from_seed(3), 4),
+            &DbSortKey(from_seed(1))
+        ),
+        Ok(())
+    );
+    assert!(proof
+        .verify_partition_absent(root_hash, &partition_key(from_seed(3), 2))
+        .is_err());
+    assert_eq!(
+        get_partition_exclusion_proof(&tester.tree_store, 1, &partition_key(from_seed(3), 2)),
+        None
+    );
+}
+
+#[test]
+fn entity_exclusion_proof_verifies_missing_entity() {
+    let mut tester = StateTreeTester::new_empty();
+    let root_hash = tester
+        .put_substate_changes(vec![
+            change(1, 9, 1, Some(196)),
+            change(3, 2, 6, Some(36)),
+            change(4, 2, 6, Some(46)),
+        ])
+        .unwrap();
+
+    let proof = get_entity_exclusion_proof(&tester.tree_store, 1, &from_seed(2)).unwrap();
+
+    assert_eq!(proof.verify_entity_absent(root_hash, &from_seed(2)), Ok(()));
+    assert_eq!(
+        proof.verify_substate_absent(
+            root_hash,
+            &partition_key(from_seed(2), 7),
+            &DbSortKey(from_seed(1))
+        ),
+        Ok(())
+    );
+    assert!(proof
+        .verify_entity_absent(root_hash, &from_seed(3))
+        .is_err());
+    assert_eq!(
+        get_entity_exclusion_proof(&tester.tree_store, 1, &from_seed(3)),
+        None
+    );
+}
+
+#[test]
+fn reads_historical_substate_values() {
+    let mut tester =
+        StateTreeTester::new(TypedInMemoryTreeStore::new().storing_associated_substates());
+    tester.put_substate_changes(vec![
+        change(1, 9, 1, Some(196)),
+        change(3, 2, 6, Some(16)),
+        change(3, 2, 7, Some(27)),
+    ]);
+    tester.put_substate_changes(vec![change(3, 2, 6, Some(36)), change(3, 2, 7, None)]);
+
+    let read = |version: Version, sort_key_seed: u8| {
+        get_substate_at_version(
+            &tester.tree_store,
+            version,
+            &partition_key(from_seed(3), 2),
+            &DbSortKey(from_seed(sort_key_seed)),
+        )
+    };
+    assert_eq!(read(1, 6), Some(from_seed(16)));
+    assert_eq!(read(1, 7), Some(from_seed(27)));
+    assert_eq!(read(2, 6), Some(from_seed(36)));
+    assert_eq!(read(2, 7), None);
+}
+
+#[test]
+fn reads_historical_substate_values_after_tree_restructuring() {
+    let mut tester =
+        StateTreeTester::new(TypedInMemoryTreeStore::new().storing_associated_substates());
+    tester.put_substate_changes(vec![change_exact(
+        vec![123, 12, 1],
+        8,
+        vec![6, 7, 5, 9],
+        Some(vec![1, 2]),
+    )]);
+    // This "sibling" substate forces a re-creation of the leaf of the unchanged `vec![6, 7, 5, 9]`:
+    tester.put_substate_changes(vec![change_exact(
+        vec![123, 12, 1],
+        8,
+        vec![6, 7, 3],
+        Some(vec![3]),
+    )]);
+
+    let entries = list_entries_from_at_version(
+        &tester.tree_store,
+        2,
+        &partition_key(vec![123, 12, 1], 8),
+        None,
+    )
+    .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![
+            (DbSortKey(vec![6, 7, 3]), vec![3]),
+            (DbSortKey(vec![6, 7, 5, 9]), vec![1, 2]),
+        ]
+    );
+}
+
+#[test]
+fn state_tree_updating_database_serves_historical_versions() {
+    let mut database =
+        StateTreeUpdatingDatabase::new_retaining_history(InMemorySubstateDatabase::standard());
+    let substate_key = |sort_key_seed: u8| {
+        (
+            partition_key(from_seed(3), 2),
+            DbSortKey(from_seed(sort_key_seed)),
+        )
+    };
+    database.commit(&DatabaseUpdates::from_delta_maps(indexmap!(
+        partition_key(from_seed(3), 2) => indexmap!(
+            DbSortKey(from_seed(6)) => DatabaseUpdate::Set(from_seed(16)),
+            DbSortKey(from_seed(7)) => DatabaseUpdate::Set(from_seed(17)),
+        )
+    )));
+    database.commit(&DatabaseUpdates::from_delta_maps(indexmap!(
+        partition_key(from_seed(3), 2) => indexmap!(
+            DbSortKey(from_seed(6)) => DatabaseUpdate::Set(from_seed(26)),
+            DbSortKey(from_seed(7)) => DatabaseUpdate::Delete,
+        )
+    )));
+
+    let (partition_key, sort_key_6) = substate_key(6);
+    let historical = SubstateDatabaseAtVersion::new(&database, 1).unwrap();
+    assert_eq!(
+        historical.get_substate(&partition_key, &sort_key_6),
+        Some(from_seed(16))
+    );
+    assert_eq!(
+        historical.list_entries(&partition_key).collect::<Vec<_>>(),
+        vec![
+            (DbSortKey(from_seed(6)), from_seed(16)),
+            (DbSortKey(from_seed(7)), from_seed(17)),
+        ]
+    );
+    assert_eq!(
+        database.get_substate_at_version(2, &partition_key, &sort_key_6),
+        Some(from_seed(26))
+    );
+    assert_eq!(
+        database
+            .list_entries_from_at_version(0, &partition_key, None)
+            .count(),
+        0
+    );
+    assert!(SubstateDatabaseAtVersion::new(&database, 3).is_none());
+
+    let mut pruning_database = StateTreeUpdatingDatabase::new(InMemorySubstateDatabase::standard());
+    pruning_database.commit(&DatabaseUpdates::default());
+    assert!(!pruning_database.is_version_available(0));
+    assert!(pruning_database.is_version_available(1));
+}
+
 type SingleSubstateChange = (DbSubstateKey, DatabaseUpdate);
 
 fn change(
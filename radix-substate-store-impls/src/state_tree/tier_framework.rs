@@ -40,17 +40,32 @@ pub trait ReadableTier: StateTreeTier {
     }
 
     fn get_persisted_leaf_payload(&self, key: &Self::TypedLeafKey) -> Option<Self::Payload> {
-        let Some(root_version) = self.root_version() else {
-            return None;
-        };
+        self.get_persisted_leaf_with_proof(key)
+            .and_then(|(leaf, _proof)| leaf)
+            .map(|(_value_hash, payload)| payload)
+    }
 
-        let leaf_key = Self::to_leaf_key(&key);
+    /// Gets the leaf's value hash and payload (if the leaf exists), together with a proof of its
+    /// inclusion in (or, if it does not exist, its absence from) this tier's tree.
+    /// Returns `None` if this tier is empty.
+    fn get_persisted_leaf_with_proof(
+        &self,
+        key: &Self::TypedLeafKey,
+    ) -> Option<TierLeafWithProof<Self::Payload>> {
+        let root_version = self.root_version()?;
 
-        let (leaf_node_data, _proof) = self.jmt().get_with_proof(&leaf_key, root_version).unwrap();
-        leaf_node_data.map(|(_hash, payload, _version)| payload)
+        let leaf_key = Self::to_leaf_key(key);
+
+        let (leaf_node_data, proof) = self.jmt().get_with_proof(&leaf_key, root_version).unwrap();
+        let leaf = leaf_node_data.map(|(value_hash, payload, _version)| (value_hash, payload));
+        Some((leaf, proof))
     }
 }
 
+/// A leaf's value hash and payload (or `None`, if the leaf does not exist), together with a proof
+/// of its inclusion (or absence).
+pub type TierLeafWithProof<P> = (Option<(Hash, P)>, SparseMerkleProof);
+
 pub struct TierLeaf<T: StateTreeTier> {
     pub key: T::TypedLeafKey,
     pub value_hash: Hash,
@@ -181,6 +181,18 @@ pub enum AssociatedSubstateValue<'v> {
     Unchanged,
 }
 
+/// The "read" counterpart of [`WriteableTreeStore::associate_substate()`], implemented by the
+/// stores which keep the historical Substate values.
+pub trait ReadableAssociatedSubstateStore {
+    /// Gets the Substate value which was upserted together with the given Substate-Tier leaf.
+    /// Returns `None` if the leaf was associated with an [`AssociatedSubstateValue::Unchanged`]
+    /// Substate (or if the association is unknown).
+    fn get_associated_substate(
+        &self,
+        state_tree_leaf_key: &StoredTreeNodeKey,
+    ) -> Option<DbSubstateValue>;
+}
+
 /// A complete tree node storage SPI.
 pub trait TreeStore: ReadableTreeStore + WriteableTreeStore {}
 impl<S: ReadableTreeStore + WriteableTreeStore> TreeStore for S {}
@@ -300,6 +312,18 @@ impl WriteableTreeStore for TypedInMemoryTreeStore {
     }
 }
 
+impl ReadableAssociatedSubstateStore for TypedInMemoryTreeStore {
+    fn get_associated_substate(
+        &self,
+        state_tree_leaf_key: &StoredTreeNodeKey,
+    ) -> Option<DbSubstateValue> {
+        self.associated_substates
+            .borrow()
+            .get(state_tree_leaf_key)
+            .and_then(|(_substate_key, substate_value)| substate_value.clone())
+    }
+}
+
 /// A `TreeStore` based on serialized payloads stored in memory.
 #[derive(Debug, PartialEq, Eq)]
 pub struct SerializedInMemoryTreeStore {
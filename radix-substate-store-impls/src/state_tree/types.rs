@@ -146,11 +146,133 @@ impl SparseMerkleProof {
     pub fn siblings(&self) -> &[Hash] {
         &self.siblings
     }
+
+    // SOURCE: https://github.com/aptos-labs/aptos-core/blob/1.0.4/types/src/proof/definition.rs#L172
+    /// If `element_hash` is present, verifies an element whose key is `element_key` and value is
+    /// authenticated by `element_hash` exists in the Sparse Merkle Tree using the provided proof.
+    /// Otherwise verifies the proof is a valid non-inclusion proof that shows this key doesn't
+    /// exist in the tree.
+    pub fn verify(
+        &self,
+        expected_root_hash: Hash,
+        element_key: &LeafKey,
+        element_hash: Option<Hash>,
+    ) -> Result<(), ProofVerificationError> {
+        let actual_root_hash = self.compute_root_hash(element_key, element_hash)?;
+        if actual_root_hash != expected_root_hash {
+            return Err(ProofVerificationError::RootHashMismatch {
+                expected: expected_root_hash,
+                actual: actual_root_hash,
+            });
+        }
+        Ok(())
+    }
+
+    // INITIAL-MODIFICATION: we split the Aptos' `verify_by_hash()` into a root-computing part, so
+    // that the root of a nested tree may be fed as an element hash into a proof of its parent tree.
+    /// Checks the internal consistency of this proof (against the given element) and computes the
+    /// root hash that it leads to.
+    pub fn compute_root_hash(
+        &self,
+        element_key: &LeafKey,
+        element_hash: Option<Hash>,
+    ) -> Result<Hash, ProofVerificationError> {
+        let key_bits_len = element_key.bytes.len() * 8;
+        if self.siblings.len() > key_bits_len {
+            return Err(ProofVerificationError::TooManySiblings {
+                num_siblings: self.siblings.len(),
+                key_bits_len,
+            });
+        }
+
+        match (element_hash, &self.leaf) {
+            (Some(hash), Some(leaf)) => {
+                // This is an inclusion proof, so the key and value hash provided in the proof
+                // should match element_key and element_value_hash. `siblings` should prove the
+                // route from the leaf node to the root.
+                if element_key != leaf.key() {
+                    return Err(ProofVerificationError::LeafKeyMismatch);
+                }
+                if hash != *leaf.value_hash() {
+                    return Err(ProofVerificationError::ValueHashMismatch);
+                }
+            }
+            (Some(_), None) => return Err(ProofVerificationError::ExpectedInclusionProof),
+            (None, Some(leaf)) => {
+                // This is a non-inclusion proof. The proof intends to show that if a leaf node
+                // representing `element_key` is inserted, it will break a currently existing leaf
+                // node represented by `proof_key` into a branch. `siblings` should prove the
+                // route from that leaf node to the root.
+                if element_key == leaf.key() {
+                    return Err(ProofVerificationError::ExpectedNonInclusionProof);
+                }
+                let common_prefix_bits_len = element_key
+                    .iter_bits()
+                    .zip(leaf.key().iter_bits())
+                    .take_while(|(element_bit, leaf_bit)| element_bit == leaf_bit)
+                    .count();
+                if common_prefix_bits_len < self.siblings.len() {
+                    return Err(ProofVerificationError::LeafNotInSubtree);
+                }
+            }
+            (None, None) => {
+                // This is a non-inclusion proof. The proof intends to show that if a leaf node
+                // representing `element_key` is inserted, it will show up at a currently empty
+                // position. `sibling` should prove the route from this empty position to the root.
+            }
+        }
+
+        let current_hash = self
+            .leaf
+            .as_ref()
+            .map_or(SPARSE_MERKLE_PLACEHOLDER_HASH, |leaf| leaf.hash());
+        let actual_root_hash = self
+            .siblings
+            .iter()
+            .zip(
+                element_key
+                    .iter_bits()
+                    .rev()
+                    .skip(key_bits_len - self.siblings.len()),
+            )
+            .fold(current_hash, |hash, (sibling_hash, bit)| {
+                if bit {
+                    SparseMerkleInternalNode::new(*sibling_hash, hash).hash()
+                } else {
+                    SparseMerkleInternalNode::new(hash, *sibling_hash).hash()
+                }
+            });
+        Ok(actual_root_hash)
+    }
+}
+
+// INITIAL-MODIFICATION: we propagate usage of our own error enum (instead of `anyhow` errors used
+// by Aptos) to allow for no-std build.
+/// An error returned when a [`SparseMerkleProof`] does not authenticate the given element.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProofVerificationError {
+    /// The proof has more siblings than the number of bits in the element's key.
+    TooManySiblings {
+        num_siblings: usize,
+        key_bits_len: usize,
+    },
+    /// An inclusion proof was given for a different key.
+    LeafKeyMismatch,
+    /// An inclusion proof was given for a different value.
+    ValueHashMismatch,
+    /// A non-inclusion proof was given where an inclusion proof was expected.
+    ExpectedInclusionProof,
+    /// An inclusion proof was given where a non-inclusion proof was expected.
+    ExpectedNonInclusionProof,
+    /// The leaf of a non-inclusion proof is not located in the subtree of the element's key.
+    LeafNotInSubtree,
+    /// The proof leads to a different root hash than expected.
+    RootHashMismatch { expected: Hash, actual: Hash },
 }
 
 /// A proof that can be used to authenticate an element in a Sparse Merkle Tree given trusted root
 /// hash. For example, `TransactionInfoToAccountProof` can be constructed on top of this structure.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Sbor)]
 pub struct SparseMerkleProof {
     /// This proof can be used to authenticate whether a given leaf exists in the tree or not.
     ///     - If this is `Some(leaf_node)`
@@ -239,7 +361,7 @@ impl SparseMerkleRangeProof {
 }
 
 // SOURCE: https://github.com/aptos-labs/aptos-core/blob/1.0.4/types/src/proof/mod.rs#L97
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Sbor)]
 pub struct SparseMerkleLeafNode {
     key: LeafKey,
     value_hash: Hash,
@@ -672,7 +794,7 @@ impl<'a> NibbleIterator<'a> {
 
 // INITIAL-MODIFICATION: We will use this type (instead of `Hash`) to allow for arbitrary key length
 /// A leaf key (i.e. a complete nibble path).
-#[derive(Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, Sbor)]
 pub struct LeafKey {
     /// The underlying bytes.
     /// All leaf keys of the same tree must be of the same length - otherwise the tree's behavior
@@ -1,9 +1,14 @@
+use crate::state_tree::proofs::*;
 use crate::state_tree::tree_store::{TypedInMemoryTreeStore, Version};
-use crate::state_tree::{list_substate_hashes_at_version, put_at_next_version};
+use crate::state_tree::{
+    get_substate_at_version, list_entries_from_at_version, list_substate_hashes_at_version,
+    put_at_next_version,
+};
 use radix_common::prelude::*;
 use radix_substate_store_interface::interface::{
-    CommittableSubstateDatabase, DatabaseUpdates, DbPartitionKey, DbSortKey, DbSubstateValue,
-    ListableSubstateDatabase, PartitionEntry, SubstateDatabase,
+    CommittableSubstateDatabase, DatabaseUpdates, DbNodeKey, DbPartitionKey, DbSortKey,
+    DbSubstateValue, ListableSubstateDatabase, PartitionEntry, SubstateDatabase,
+    VersionedSubstateDatabase,
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -24,6 +29,17 @@ impl<D> StateTreeUpdatingDatabase<D> {
         }
     }
 
+    /// Creates a database which retains all historical tree nodes and Substate values, and thus
+    /// can serve reads (and proofs) at any past version (see [`VersionedSubstateDatabase`]).
+    pub fn new_retaining_history(underlying: D) -> Self {
+        StateTreeUpdatingDatabase {
+            underlying,
+            tree_store: TypedInMemoryTreeStore::new().storing_associated_substates(),
+            current_version: 0,
+            current_hash: Hash([0; Hash::LENGTH]),
+        }
+    }
+
     pub fn get_current_root_hash(&self) -> Hash {
         self.current_hash
     }
@@ -36,6 +52,50 @@ impl<D> StateTreeUpdatingDatabase<D> {
         list_substate_hashes_at_version(&self.tree_store, self.current_version)
     }
 
+    /// Generates a proof of inclusion of the Substate under the given key, in the state at the
+    /// given version (see [`get_substate_inclusion_proof()`]).
+    /// Note: unless created as [`Self::new_retaining_history()`], this database prunes stale tree
+    /// nodes, so only the current version can be proven.
+    pub fn get_substate_inclusion_proof(
+        &self,
+        state_version: Version,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<SubstateInclusionProof> {
+        get_substate_inclusion_proof(&self.tree_store, state_version, partition_key, sort_key)
+    }
+
+    /// Generates a proof that the Substate under the given key does not exist in the state at the
+    /// given version (see [`get_substate_exclusion_proof()`]).
+    pub fn get_substate_exclusion_proof(
+        &self,
+        state_version: Version,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<ExclusionProof> {
+        get_substate_exclusion_proof(&self.tree_store, state_version, partition_key, sort_key)
+    }
+
+    /// Generates a proof that the given partition does not exist in the state at the given version
+    /// (see [`get_partition_exclusion_proof()`]).
+    pub fn get_partition_exclusion_proof(
+        &self,
+        state_version: Version,
+        partition_key: &DbPartitionKey,
+    ) -> Option<ExclusionProof> {
+        get_partition_exclusion_proof(&self.tree_store, state_version, partition_key)
+    }
+
+    /// Generates a proof that the given entity does not exist in the state at the given version
+    /// (see [`get_entity_exclusion_proof()`]).
+    pub fn get_entity_exclusion_proof(
+        &self,
+        state_version: Version,
+        node_key: &DbNodeKey,
+    ) -> Option<ExclusionProof> {
+        get_entity_exclusion_proof(&self.tree_store, state_version, node_key)
+    }
+
     fn update_with(&mut self, db_updates: &DatabaseUpdates) {
         self.current_hash = put_at_next_version(
             &mut self.tree_store,
@@ -65,6 +125,60 @@ impl<D: SubstateDatabase> SubstateDatabase for StateTreeUpdatingDatabase<D> {
     }
 }
 
+impl<D: SubstateDatabase> VersionedSubstateDatabase for StateTreeUpdatingDatabase<D> {
+    fn is_version_available(&self, state_version: u64) -> bool {
+        state_version == self.current_version
+            || (state_version < self.current_version
+                && !self.tree_store.pruning_enabled
+                && self.tree_store.store_associated_substates)
+    }
+
+    fn get_substate_at_version(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        assert!(
+            self.is_version_available(state_version),
+            "state version {} is not available",
+            state_version
+        );
+        if state_version == self.current_version {
+            return self.get_substate(partition_key, sort_key);
+        }
+        if state_version == 0 {
+            return None;
+        }
+        get_substate_at_version(&self.tree_store, state_version, partition_key, sort_key)
+    }
+
+    fn list_entries_from_at_version(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        assert!(
+            self.is_version_available(state_version),
+            "state version {} is not available",
+            state_version
+        );
+        if state_version == self.current_version {
+            return self.list_entries_from(partition_key, from_sort_key);
+        }
+        if state_version == 0 {
+            return Box::new(core::iter::empty());
+        }
+        list_entries_from_at_version(
+            &self.tree_store,
+            state_version,
+            partition_key,
+            from_sort_key,
+        )
+    }
+}
+
 impl<D: ListableSubstateDatabase> ListableSubstateDatabase for StateTreeUpdatingDatabase<D> {
     fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
         self.underlying.list_partition_keys()
@@ -203,3 +203,87 @@ pub trait ListableSubstateDatabase {
     /// Iterates over all partition keys, in an arbitrary order.
     fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_>;
 }
+
+/// A historical read interface of a database vendor which retains past states (i.e. allows reading
+/// the state as it was right after committing a specific state version).
+///
+/// Note: state version `0` denotes the empty, initial state (i.e. before the first commit).
+pub trait VersionedSubstateDatabase {
+    /// Returns `true` if the state at the given version can be read, i.e. it was already reached
+    /// and its historical data was not pruned.
+    fn is_version_available(&self, state_version: u64) -> bool;
+
+    /// Reads a substate value by its partition and sort key, as of the given state version, or
+    /// [`Option::None`] if missing.
+    ///
+    /// # Panics
+    /// Panics if the given version is not available (see [`Self::is_version_available()`]).
+    fn get_substate_at_version(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue>;
+
+    /// Iterates over all entries of the given partition as of the given state version - see
+    /// [`SubstateDatabase::list_entries_from()`] for the ordering guarantees.
+    ///
+    /// # Panics
+    /// Panics if the given version is not available (see [`Self::is_version_available()`]).
+    fn list_entries_from_at_version(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_>;
+}
+
+/// A [`SubstateDatabase`] view of a single historical state of a [`VersionedSubstateDatabase`].
+///
+/// This allows to run any regular read-only logic (e.g. a transaction preview) against a past
+/// state.
+pub struct SubstateDatabaseAtVersion<'d, D: ?Sized> {
+    database: &'d D,
+    state_version: u64,
+}
+
+impl<'d, D: VersionedSubstateDatabase + ?Sized> SubstateDatabaseAtVersion<'d, D> {
+    /// Creates a view of the given database at the given state version, or returns
+    /// [`Option::None`] if that version is not available.
+    pub fn new(database: &'d D, state_version: u64) -> Option<Self> {
+        if database.is_version_available(state_version) {
+            Some(Self {
+                database,
+                state_version,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn state_version(&self) -> u64 {
+        self.state_version
+    }
+}
+
+impl<'d, D: VersionedSubstateDatabase + ?Sized> SubstateDatabase
+    for SubstateDatabaseAtVersion<'d, D>
+{
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        self.database
+            .get_substate_at_version(self.state_version, partition_key, sort_key)
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.database
+            .list_entries_from_at_version(self.state_version, partition_key, from_sort_key)
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "rayon")]
+use super::par_traverse_subtrees;
 use super::{StateTreeTraverser, StateTreeVisitor};
 use radix_common::prelude::*;
 use radix_engine_interface::prelude::*;
@@ -9,6 +11,8 @@ pub struct ResourceAccounter<'s, S: SubstateDatabase> {
 }
 
 impl<'s, S: SubstateDatabase> ResourceAccounter<'s, S> {
+    const MAX_DEPTH: u32 = 100;
+
     pub fn new(substate_db: &'s S) -> Self {
         ResourceAccounter {
             substate_db,
@@ -18,10 +22,29 @@ impl<'s, S: SubstateDatabase> ResourceAccounter<'s, S> {
 
     pub fn traverse(&mut self, node_id: NodeId) {
         let mut state_tree_visitor =
-            StateTreeTraverser::new(self.substate_db, &mut self.accounting, 100);
+            StateTreeTraverser::new(self.substate_db, &mut self.accounting, Self::MAX_DEPTH);
         state_tree_visitor.traverse_subtree(None, node_id)
     }
 
+    /// Traverses the subtrees of all the given root nodes, fanning out across multiple threads.
+    #[cfg(feature = "rayon")]
+    pub fn par_traverse(&mut self, root_node_ids: Vec<NodeId>)
+    where
+        S: Sync,
+    {
+        let accounting = par_traverse_subtrees(
+            self.substate_db,
+            root_node_ids,
+            Self::MAX_DEPTH,
+            Accounting::new,
+            |mut accounting, other| {
+                accounting.merge(other);
+                accounting
+            },
+        );
+        self.accounting.merge(accounting);
+    }
+
     pub fn close(self) -> Accounting {
         self.accounting
     }
@@ -62,6 +85,21 @@ impl Accounting {
         *entry = entry.checked_add(resource.amount).unwrap()
     }
 
+    /// Adds all the balances and non-fungibles of the other accounting to this one.
+    pub fn merge(&mut self, other: Accounting) {
+        for (address, amount) in other.balances {
+            let entry = self.balances.entry(address).or_default();
+            // NOTE: Decimal arithmetic operation safe unwrap.
+            //       Resources have a mint limit below the Decimal max
+            *entry = entry
+                .checked_add(amount)
+                .expect("Resource overflow despite mint limit")
+        }
+        for (address, ids) in other.non_fungibles {
+            self.non_fungibles.entry(address).or_default().extend(ids);
+        }
+    }
+
     pub fn add_non_fungible(&mut self, address: &ResourceAddress, id: &NonFungibleLocalId) {
         self.non_fungibles
             .entry(*address)
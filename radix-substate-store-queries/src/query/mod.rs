@@ -1,7 +1,11 @@
 mod accounter;
+mod resource_holder_index;
+mod supply_reconciliation;
 mod traverse;
 mod vault_finder;
 
 pub use accounter::*;
+pub use resource_holder_index::*;
+pub use supply_reconciliation::*;
 pub use traverse::*;
 pub use vault_finder::*;
@@ -0,0 +1,334 @@
+use radix_common::constants::RESOURCE_PACKAGE;
+use radix_common::prelude::*;
+use radix_engine::system::type_info::TypeInfoSubstate;
+use radix_engine_interface::blueprints::resource::{
+    FUNGIBLE_VAULT_BLUEPRINT, NON_FUNGIBLE_VAULT_BLUEPRINT,
+};
+use radix_engine_interface::prelude::*;
+use radix_engine_interface::types::{IndexedScryptoValue, NonFungibleVaultPartitionOffset};
+use radix_substate_store_interface::db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper};
+use radix_substate_store_interface::interface::*;
+
+/// An incrementally-maintained index of resource holders, answering:
+/// - which vaults hold a given resource (and which global entities own these vaults),
+/// - which vault holds a given non-fungible.
+///
+/// The index is fed with the [`DatabaseUpdates`] of every commit (see [`Self::update()`], or the
+/// [`ResourceHolderIndexingDatabase`] wrapper doing it automatically).
+///
+/// Note: in order to resolve the owning global entity of a vault, the index tracks the owner of
+/// every owned (i.e. internal) node, by decoding all upserted Substate values.
+pub struct ResourceHolderIndex {
+    resource_vaults: IndexMap<ResourceAddress, IndexSet<NodeId>>,
+    vault_resources: IndexMap<NodeId, ResourceAddress>,
+    vault_non_fungibles: IndexMap<NodeId, IndexSet<NonFungibleLocalId>>,
+    non_fungible_vaults: IndexMap<NonFungibleGlobalId, NodeId>,
+    owners: IndexMap<NodeId, NodeId>,
+}
+
+impl Default for ResourceHolderIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceHolderIndex {
+    pub fn new() -> Self {
+        ResourceHolderIndex {
+            resource_vaults: index_map_new(),
+            vault_resources: index_map_new(),
+            vault_non_fungibles: index_map_new(),
+            non_fungible_vaults: index_map_new(),
+            owners: index_map_new(),
+        }
+    }
+
+    /// Builds the index from the entire current contents of the given database (i.e. performs
+    /// the single full scan needed before the index can be maintained incrementally).
+    pub fn from_database<D: SubstateDatabase + ListableSubstateDatabase>(database: &D) -> Self {
+        let mut index = Self::new();
+        // Similarly to `update()`, all Type Info partitions have to be processed first.
+        let type_info_partition_num =
+            SpreadPrefixKeyMapper::to_db_partition_num(TYPE_INFO_FIELD_PARTITION);
+        let (type_info_partition_keys, other_partition_keys): (Vec<_>, Vec<_>) = database
+            .list_partition_keys()
+            .partition(|partition_key| partition_key.partition_num == type_info_partition_num);
+        for partition_key in type_info_partition_keys
+            .into_iter()
+            .chain(other_partition_keys)
+        {
+            let partition_updates = PartitionDatabaseUpdates::Reset {
+                new_substate_values: database.list_entries(&partition_key).collect(),
+            };
+            index.update(&DatabaseUpdates {
+                node_updates: indexmap!(
+                    partition_key.node_key.clone() => NodeDatabaseUpdates {
+                        partition_updates: indexmap!(
+                            partition_key.partition_num => partition_updates
+                        ),
+                    }
+                ),
+            });
+        }
+        index
+    }
+
+    /// Updates the index with the changes committed to the database.
+    pub fn update(&mut self, database_updates: &DatabaseUpdates) {
+        // Vaults' Type Info is processed first, so that the resource of a newly-created vault is
+        // known when its non-fungible contents are processed.
+        let type_info_partition_num =
+            SpreadPrefixKeyMapper::to_db_partition_num(TYPE_INFO_FIELD_PARTITION);
+        let non_fungible_index_partition_num = SpreadPrefixKeyMapper::to_db_partition_num(
+            NonFungibleVaultPartitionOffset::NonFungibleIndex.as_partition(MAIN_BASE_PARTITION),
+        );
+        for (db_node_key, node_updates) in &database_updates.node_updates {
+            let node_id = SpreadPrefixKeyMapper::from_db_node_key(db_node_key);
+            if let Some(partition_updates) =
+                node_updates.partition_updates.get(&type_info_partition_num)
+            {
+                self.update_type_info(node_id, partition_updates);
+            }
+        }
+        for (db_node_key, node_updates) in &database_updates.node_updates {
+            let node_id = SpreadPrefixKeyMapper::from_db_node_key(db_node_key);
+            for (partition_num, partition_updates) in &node_updates.partition_updates {
+                if *partition_num == non_fungible_index_partition_num
+                    && self.vault_resources.contains_key(&node_id)
+                {
+                    self.update_non_fungible_index(node_id, partition_updates);
+                }
+                for value in Self::upserted_values(partition_updates) {
+                    self.update_owners(node_id, value);
+                }
+            }
+        }
+    }
+
+    /// Returns the vaults holding the given resource.
+    pub fn get_vaults(&self, resource_address: &ResourceAddress) -> Vec<NodeId> {
+        self.resource_vaults
+            .get(resource_address)
+            .map(|vaults| vaults.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the vaults holding the given resource, each with its owning global entity (or
+    /// `None` if the vault's owner chain is not fully known to the index).
+    pub fn get_holders(
+        &self,
+        resource_address: &ResourceAddress,
+    ) -> IndexMap<NodeId, Option<GlobalAddress>> {
+        self.get_vaults(resource_address)
+            .into_iter()
+            .map(|vault_id| (vault_id, self.get_global_owner(&vault_id)))
+            .collect()
+    }
+
+    /// Returns the vault holding the given non-fungible, if any.
+    pub fn get_non_fungible_vault(&self, non_fungible_id: &NonFungibleGlobalId) -> Option<NodeId> {
+        self.non_fungible_vaults.get(non_fungible_id).cloned()
+    }
+
+    /// Returns the global entity which (directly, or through a chain of internal nodes) owns the
+    /// given node.
+    pub fn get_global_owner(&self, node_id: &NodeId) -> Option<GlobalAddress> {
+        let mut current = *node_id;
+        // The length of any owner chain is bounded by the number of tracked nodes.
+        for _ in 0..=self.owners.len() {
+            let owner = self.owners.get(&current)?;
+            if owner.is_global() {
+                return GlobalAddress::try_from(owner.0).ok();
+            }
+            current = *owner;
+        }
+        None
+    }
+
+    fn update_type_info(&mut self, node_id: NodeId, partition_updates: &PartitionDatabaseUpdates) {
+        let type_info_sort_key =
+            SpreadPrefixKeyMapper::to_db_sort_key(&TypeInfoField::TypeInfo.into());
+        match partition_updates.get_substate_change(&type_info_sort_key) {
+            Some(SubstateChange::Upsert(value)) => {
+                let Ok(TypeInfoSubstate::Object(info)) = scrypto_decode::<TypeInfoSubstate>(value)
+                else {
+                    return;
+                };
+                let blueprint_id = &info.blueprint_info.blueprint_id;
+                if blueprint_id.package_address == RESOURCE_PACKAGE
+                    && (blueprint_id.blueprint_name == FUNGIBLE_VAULT_BLUEPRINT
+                        || blueprint_id.blueprint_name == NON_FUNGIBLE_VAULT_BLUEPRINT)
+                {
+                    let resource_address =
+                        ResourceAddress::new_or_panic(info.get_outer_object().into());
+                    self.resource_vaults
+                        .entry(resource_address)
+                        .or_default()
+                        .insert(node_id);
+                    self.vault_resources.insert(node_id, resource_address);
+                }
+            }
+            Some(SubstateChange::Delete) => self.remove_node(&node_id),
+            None => {}
+        }
+    }
+
+    fn update_non_fungible_index(
+        &mut self,
+        vault_id: NodeId,
+        partition_updates: &PartitionDatabaseUpdates,
+    ) {
+        let resource_address = self.vault_resources[&vault_id];
+        if let PartitionDatabaseUpdates::Reset { .. } = partition_updates {
+            self.remove_vault_non_fungibles(&vault_id);
+        }
+        let changes: Vec<(&DbSortKey, bool)> = match partition_updates {
+            PartitionDatabaseUpdates::Delta { substate_updates } => substate_updates
+                .iter()
+                .map(|(sort_key, update)| (sort_key, matches!(update, DatabaseUpdate::Set(_))))
+                .collect(),
+            PartitionDatabaseUpdates::Reset {
+                new_substate_values,
+            } => new_substate_values
+                .keys()
+                .map(|sort_key| (sort_key, true))
+                .collect(),
+        };
+        for (sort_key, is_present) in changes {
+            let map_key = SpreadPrefixKeyMapper::map_from_db_sort_key(sort_key);
+            let Ok(local_id) = scrypto_decode::<NonFungibleLocalId>(&map_key) else {
+                continue;
+            };
+            let global_id = NonFungibleGlobalId::new(resource_address, local_id.clone());
+            let vault_non_fungibles = self.vault_non_fungibles.entry(vault_id).or_default();
+            if is_present {
+                vault_non_fungibles.insert(local_id);
+                self.non_fungible_vaults.insert(global_id, vault_id);
+            } else {
+                vault_non_fungibles.swap_remove(&local_id);
+                // The non-fungible may have already been moved to another vault within the same
+                // commit - such newer entry must not be removed.
+                if self.non_fungible_vaults.get(&global_id) == Some(&vault_id) {
+                    self.non_fungible_vaults.swap_remove(&global_id);
+                }
+            }
+        }
+    }
+
+    fn update_owners(&mut self, node_id: NodeId, value: &DbSubstateValue) {
+        let Ok(indexed_value) = IndexedScryptoValue::from_slice(value) else {
+            return;
+        };
+        for owned_node_id in indexed_value.owned_nodes() {
+            self.owners.insert(*owned_node_id, node_id);
+        }
+    }
+
+    fn remove_node(&mut self, node_id: &NodeId) {
+        self.owners.swap_remove(node_id);
+        if let Some(resource_address) = self.vault_resources.swap_remove(node_id) {
+            if let Some(vaults) = self.resource_vaults.get_mut(&resource_address) {
+                vaults.swap_remove(node_id);
+            }
+            self.remove_vault_non_fungibles(node_id);
+        }
+    }
+
+    fn remove_vault_non_fungibles(&mut self, vault_id: &NodeId) {
+        let Some(resource_address) = self.vault_resources.get(vault_id).cloned() else {
+            return;
+        };
+        for local_id in self
+            .vault_non_fungibles
+            .swap_remove(vault_id)
+            .unwrap_or_default()
+        {
+            let global_id = NonFungibleGlobalId::new(resource_address, local_id);
+            if self.non_fungible_vaults.get(&global_id) == Some(vault_id) {
+                self.non_fungible_vaults.swap_remove(&global_id);
+            }
+        }
+    }
+
+    fn upserted_values(
+        partition_updates: &PartitionDatabaseUpdates,
+    ) -> Box<dyn Iterator<Item = &DbSubstateValue> + '_> {
+        match partition_updates {
+            PartitionDatabaseUpdates::Delta { substate_updates } => {
+                Box::new(substate_updates.values().filter_map(|update| match update {
+                    DatabaseUpdate::Set(value) => Some(value),
+                    DatabaseUpdate::Delete => None,
+                }))
+            }
+            PartitionDatabaseUpdates::Reset {
+                new_substate_values,
+            } => Box::new(new_substate_values.values()),
+        }
+    }
+}
+
+/// A database wrapper which keeps a [`ResourceHolderIndex`] up-to-date with every commit.
+pub struct ResourceHolderIndexingDatabase<D> {
+    underlying: D,
+    index: ResourceHolderIndex,
+}
+
+impl<D> ResourceHolderIndexingDatabase<D> {
+    /// Wraps a database, which must be empty (see [`Self::new_indexing_existing()`] otherwise).
+    pub fn new(underlying: D) -> Self {
+        ResourceHolderIndexingDatabase {
+            underlying,
+            index: ResourceHolderIndex::new(),
+        }
+    }
+
+    pub fn index(&self) -> &ResourceHolderIndex {
+        &self.index
+    }
+
+    pub fn into_underlying(self) -> D {
+        self.underlying
+    }
+}
+
+impl<D: SubstateDatabase + ListableSubstateDatabase> ResourceHolderIndexingDatabase<D> {
+    /// Wraps a non-empty database, building the index from its current contents first.
+    pub fn new_indexing_existing(underlying: D) -> Self {
+        let index = ResourceHolderIndex::from_database(&underlying);
+        ResourceHolderIndexingDatabase { underlying, index }
+    }
+}
+
+impl<D: SubstateDatabase> SubstateDatabase for ResourceHolderIndexingDatabase<D> {
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        self.underlying.get_substate(partition_key, sort_key)
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.underlying
+            .list_entries_from(partition_key, from_sort_key)
+    }
+}
+
+impl<D: ListableSubstateDatabase> ListableSubstateDatabase for ResourceHolderIndexingDatabase<D> {
+    fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
+        self.underlying.list_partition_keys()
+    }
+}
+
+impl<D: CommittableSubstateDatabase> CommittableSubstateDatabase
+    for ResourceHolderIndexingDatabase<D>
+{
+    fn commit(&mut self, database_updates: &DatabaseUpdates) {
+        self.underlying.commit(database_updates);
+        self.index.update(database_updates);
+    }
+}
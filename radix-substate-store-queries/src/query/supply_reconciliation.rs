@@ -0,0 +1,118 @@
+use super::{list_global_node_ids, Accounting};
+use radix_common::prelude::*;
+use radix_engine::blueprints::resource::*;
+use radix_engine_interface::prelude::*;
+use radix_substate_store_interface::db_key_mapper::{
+    MappedSubstateDatabase, SpreadPrefixKeyMapper,
+};
+use radix_substate_store_interface::interface::{ListableSubstateDatabase, SubstateDatabase};
+
+/// A comparison of the total supplies recorded by resource managers against the amounts actually
+/// held in vaults (as summed up by an [`Accounting`]).
+pub struct TotalSupplyReconciliationReport {
+    pub resources: IndexMap<ResourceAddress, ResourceSupplyReconciliation>,
+}
+
+pub struct ResourceSupplyReconciliation {
+    /// The total supply recorded by the resource manager, or `None` if it does not track it.
+    pub recorded_total_supply: Option<Decimal>,
+    /// The sum of all vaults' balances (or the number of non-fungibles held in vaults).
+    pub vault_total: Decimal,
+}
+
+impl ResourceSupplyReconciliation {
+    /// Returns the recorded total supply minus the vault total, if the supply is tracked and the
+    /// values do not match.
+    pub fn discrepancy(&self) -> Option<Decimal> {
+        self.recorded_total_supply
+            .filter(|recorded_total_supply| *recorded_total_supply != self.vault_total)
+            .map(|recorded_total_supply| {
+                recorded_total_supply
+                    .checked_sub(self.vault_total)
+                    .expect("Resource overflow despite mint limit")
+            })
+    }
+}
+
+impl TotalSupplyReconciliationReport {
+    /// Reconciles all resources existing in the database with the given accounting, which should
+    /// be a result of traversing all global entities of the same database (see
+    /// [`list_global_node_ids()`]).
+    pub fn new<S: SubstateDatabase + ListableSubstateDatabase>(
+        substate_db: &S,
+        accounting: &Accounting,
+    ) -> Self {
+        let resource_addresses = list_global_node_ids(substate_db)
+            .into_iter()
+            .filter(|node_id| node_id.is_global_resource_manager())
+            .map(|node_id| ResourceAddress::new_or_panic(node_id.0))
+            .chain(accounting.balances.keys().cloned())
+            .chain(accounting.non_fungibles.keys().cloned())
+            .collect::<IndexSet<_>>();
+        let resources = resource_addresses
+            .into_iter()
+            .map(|resource_address| {
+                let reconciliation = ResourceSupplyReconciliation {
+                    recorded_total_supply: Self::read_total_supply(substate_db, &resource_address),
+                    vault_total: Self::get_vault_total(accounting, &resource_address),
+                };
+                (resource_address, reconciliation)
+            })
+            .collect();
+        Self { resources }
+    }
+
+    /// Returns the resources for which the recorded total supply does not match the vault total.
+    pub fn mismatches(&self) -> impl Iterator<Item = (&ResourceAddress, Decimal)> {
+        self.resources
+            .iter()
+            .filter_map(|(resource_address, reconciliation)| {
+                reconciliation
+                    .discrepancy()
+                    .map(|discrepancy| (resource_address, discrepancy))
+            })
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches().next().is_none()
+    }
+
+    fn read_total_supply<S: SubstateDatabase>(
+        substate_db: &S,
+        resource_address: &ResourceAddress,
+    ) -> Option<Decimal> {
+        if resource_address.is_fungible() {
+            substate_db
+                .get_mapped::<SpreadPrefixKeyMapper, FungibleResourceManagerTotalSupplyFieldSubstate>(
+                    resource_address.as_node_id(),
+                    MAIN_BASE_PARTITION,
+                    &FungibleResourceManagerField::TotalSupply.into(),
+                )
+                .map(|substate| substate.into_payload().fully_update_and_into_latest_version())
+        } else {
+            substate_db
+                .get_mapped::<SpreadPrefixKeyMapper, NonFungibleResourceManagerTotalSupplyFieldSubstate>(
+                    resource_address.as_node_id(),
+                    MAIN_BASE_PARTITION,
+                    &NonFungibleResourceManagerField::TotalSupply.into(),
+                )
+                .map(|substate| substate.into_payload().fully_update_and_into_latest_version())
+        }
+    }
+
+    fn get_vault_total(accounting: &Accounting, resource_address: &ResourceAddress) -> Decimal {
+        if resource_address.is_fungible() {
+            accounting
+                .balances
+                .get(resource_address)
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            accounting
+                .non_fungibles
+                .get(resource_address)
+                .map(|ids| Decimal::from(ids.len()))
+                .unwrap_or_default()
+        }
+    }
+}
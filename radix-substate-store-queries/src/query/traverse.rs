@@ -16,7 +16,8 @@ use radix_engine_interface::types::{
     BlueprintId, IndexedScryptoValue, ObjectType, ResourceAddress,
 };
 use radix_engine_interface::{blueprints::resource::LiquidFungibleResource, types::NodeId};
-use radix_substate_store_interface::interface::SubstateDatabase;
+use radix_substate_store_interface::db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper};
+use radix_substate_store_interface::interface::{ListableSubstateDatabase, SubstateDatabase};
 
 pub struct StateTreeTraverser<'s, 'v, S: SubstateDatabase, V: StateTreeVisitor + 'v> {
     system_db_reader: SystemDatabaseReader<'s, S>,
@@ -272,3 +273,41 @@ impl<'s, 'v, S: SubstateDatabase, V: StateTreeVisitor + 'v> StateTreeTraverser<'
         }
     }
 }
+
+/// Lists the global entities of the given database, i.e. the roots of the state tree which can be
+/// traversed independently.
+pub fn list_global_node_ids<S: ListableSubstateDatabase>(substate_db: &S) -> Vec<NodeId> {
+    substate_db
+        .list_partition_keys()
+        .map(|partition_key| SpreadPrefixKeyMapper::from_db_partition_key(&partition_key).0)
+        .filter(|node_id| node_id.is_global())
+        .collect::<IndexSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Traverses the subtrees of the given root nodes in parallel.
+/// Each rayon job visits its subtrees using a new visitor (created by `new_visitor`), and all these
+/// visitors are eventually combined into a single result (using `merge`).
+#[cfg(feature = "rayon")]
+pub fn par_traverse_subtrees<S, V>(
+    substate_db: &S,
+    root_node_ids: Vec<NodeId>,
+    max_depth: u32,
+    new_visitor: impl Fn() -> V + Sync + Send,
+    merge: impl Fn(V, V) -> V + Sync + Send,
+) -> V
+where
+    S: SubstateDatabase + Sync,
+    V: StateTreeVisitor + Send,
+{
+    use rayon::prelude::*;
+    root_node_ids
+        .into_par_iter()
+        .fold(&new_visitor, |mut visitor, node_id| {
+            StateTreeTraverser::new(substate_db, &mut visitor, max_depth)
+                .traverse_subtree(None, node_id);
+            visitor
+        })
+        .reduce(&new_visitor, &merge)
+}
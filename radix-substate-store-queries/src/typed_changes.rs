@@ -0,0 +1,340 @@
+//! This module contains the code and models that are required to convert the result of a committed
+//! transaction (i.e. its state updates and application events) into a stream of strongly typed
+//! domain-level changes, so that the consumers do not have to interpret the raw [`StateUpdates`]
+//! themselves.
+
+use crate::query::ResourceHolderIndex;
+use crate::typed_native_events::*;
+use crate::typed_substate_layout::*;
+use radix_common::prelude::*;
+use radix_engine::track::{
+    BatchPartitionStateUpdate, NodeStateUpdates, PartitionStateUpdates, StateUpdates,
+};
+use radix_engine::transaction::{BalanceChange, CommitResult};
+use radix_engine_interface::prelude::*;
+use radix_substate_store_interface::db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper};
+use radix_substate_store_interface::interface::*;
+
+/// A strongly typed change, resulting from a committed transaction.
+#[derive(Debug)]
+pub enum TypedChange {
+    /// The balance of the given vault has changed.
+    /// The `owner` is the global entity owning the vault (e.g. an account), if it could be resolved.
+    VaultBalanceChanged {
+        vault_id: NodeId,
+        owner: Option<GlobalAddress>,
+        resource_address: ResourceAddress,
+        change: BalanceChange,
+    },
+    MetadataSet {
+        entity: NodeId,
+        key: String,
+        value: MetadataValue,
+    },
+    MetadataRemoved {
+        entity: NodeId,
+        key: String,
+    },
+    RoleSet {
+        entity: NodeId,
+        role_key: ModuleRoleKey,
+        rule: AccessRule,
+    },
+    RoleRemoved {
+        entity: NodeId,
+        role_key: ModuleRoleKey,
+    },
+    OwnerRoleSet {
+        entity: NodeId,
+        owner_role: OwnerRoleEntry,
+    },
+    /// An event emitted by a native blueprint or module.
+    NativeEvent {
+        emitter: Emitter,
+        event: TypedNativeEvent,
+    },
+}
+
+#[derive(Debug)]
+pub enum TypedChangeError {
+    SubstateDecodeError(String),
+    EventDecodeError(TypedNativeEventError),
+    /// The owners of pre-existing vaults can't be resolved from the database, as the commit
+    /// doesn't report its read/write set.
+    MissingReadWriteSet,
+}
+
+/// Converts the given [`CommitResult`] into [`TypedChange`]s, in the following order:
+/// - vault balance changes (in the order of [`CommitResult::vault_balance_changes()`]),
+/// - metadata and role changes (in the order of the state updates),
+/// - native events (in the order of emission; the events of non-native blueprints are skipped).
+///
+/// The owners of the vaults are resolved from the commit's own state updates and from the
+/// substates read by the transaction, as found in the given database (which is expected to
+/// already contain the commit). This covers the vaults created by the transaction as well as the
+/// pre-existing vaults reached through their owners (e.g. the vaults of an account).
+pub fn to_typed_changes<D: SubstateDatabase>(
+    commit_result: &CommitResult,
+    database: &D,
+) -> Result<Vec<TypedChange>, TypedChangeError> {
+    TypedChangeExtractor::new()
+        .with_database(database)
+        .extract(commit_result)
+}
+
+/// A configurable converter of [`CommitResult`]s into [`TypedChange`]s.
+pub struct TypedChangeExtractor<'i> {
+    holder_index: Option<&'i ResourceHolderIndex>,
+    database: Option<&'i dyn SubstateDatabase>,
+}
+
+impl<'i> Default for TypedChangeExtractor<'i> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'i> TypedChangeExtractor<'i> {
+    /// Creates an extractor resolving the owners of vaults from the commit's state updates only.
+    pub fn new() -> Self {
+        Self {
+            holder_index: None,
+            database: None,
+        }
+    }
+
+    /// Uses the given index to resolve the owners of vaults.
+    /// Note: the index is expected to already contain the changes of the extracted commit.
+    pub fn with_holder_index(self, holder_index: &'i ResourceHolderIndex) -> Self {
+        Self {
+            holder_index: Some(holder_index),
+            ..self
+        }
+    }
+
+    /// Resolves the owners of pre-existing vaults by looking up the substates read by the
+    /// transaction in the given database (ignored if a holder index is given).
+    /// Note: the database is expected to already contain the changes of the extracted commit, and
+    /// the commit to have been executed with `ExecutionConfig::enable_read_write_set`.
+    pub fn with_database<D: SubstateDatabase>(self, database: &'i D) -> Self {
+        Self {
+            database: Some(database),
+            ..self
+        }
+    }
+
+    /// See [`to_typed_changes()`].
+    pub fn extract(
+        &self,
+        commit_result: &CommitResult,
+    ) -> Result<Vec<TypedChange>, TypedChangeError> {
+        let mut changes = Vec::new();
+        self.extract_vault_balance_changes(commit_result, &mut changes)?;
+        Self::extract_substate_changes(&commit_result.state_updates, &mut changes)?;
+        Self::extract_native_events(&commit_result.application_events, &mut changes)?;
+        Ok(changes)
+    }
+
+    fn extract_vault_balance_changes(
+        &self,
+        commit_result: &CommitResult,
+        changes: &mut Vec<TypedChange>,
+    ) -> Result<(), TypedChangeError> {
+        let commit_index;
+        let holder_index = match self.holder_index {
+            Some(holder_index) => holder_index,
+            None => {
+                let mut index = ResourceHolderIndex::new();
+                if let Some(database) = self.database {
+                    index.update(&Self::read_substate_values(commit_result, database)?);
+                }
+                index.update(
+                    &commit_result
+                        .state_updates
+                        .create_database_updates::<SpreadPrefixKeyMapper>(),
+                );
+                commit_index = index;
+                &commit_index
+            }
+        };
+        for (vault_id, (resource_address, change)) in commit_result.vault_balance_changes() {
+            changes.push(TypedChange::VaultBalanceChanged {
+                vault_id: *vault_id,
+                owner: holder_index.get_global_owner(vault_id),
+                resource_address: *resource_address,
+                change: change.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the current values of the substates read by the transaction, as database updates,
+    /// so that the ownership they record can be indexed.
+    fn read_substate_values(
+        commit_result: &CommitResult,
+        database: &dyn SubstateDatabase,
+    ) -> Result<DatabaseUpdates, TypedChangeError> {
+        let read_write_set = commit_result
+            .read_write_set
+            .as_ref()
+            .ok_or(TypedChangeError::MissingReadWriteSet)?;
+        let mut database_updates = DatabaseUpdates::default();
+        for (node_id, partition_num, substate_key) in &read_write_set.reads {
+            let partition_key = SpreadPrefixKeyMapper::to_db_partition_key(node_id, *partition_num);
+            let sort_key = SpreadPrefixKeyMapper::to_db_sort_key(substate_key);
+            let Some(value) = database.get_substate(&partition_key, &sort_key) else {
+                continue;
+            };
+            let partition_updates = database_updates
+                .node_updates
+                .entry(partition_key.node_key)
+                .or_default()
+                .partition_updates
+                .entry(partition_key.partition_num)
+                .or_insert_with(|| PartitionDatabaseUpdates::Delta {
+                    substate_updates: index_map_new(),
+                });
+            if let PartitionDatabaseUpdates::Delta { substate_updates } = partition_updates {
+                substate_updates.insert(sort_key, DatabaseUpdate::Set(value));
+            }
+        }
+        Ok(database_updates)
+    }
+
+    fn extract_substate_changes(
+        state_updates: &StateUpdates,
+        changes: &mut Vec<TypedChange>,
+    ) -> Result<(), TypedChangeError> {
+        for (node_id, node_updates) in &state_updates.by_node {
+            let Some(entity_type) = node_id.entity_type() else {
+                continue;
+            };
+            let NodeStateUpdates::Delta { by_partition } = node_updates;
+            for (partition_num, partition_updates) in by_partition {
+                if !matches!(
+                    *partition_num,
+                    METADATA_BASE_PARTITION
+                        | ROLE_ASSIGNMENT_FIELDS_PARTITION
+                        | ROLE_ASSIGNMENT_ROLE_DEF_PARTITION
+                ) {
+                    continue;
+                }
+                for (substate_key, value) in Self::iter_substate_updates(partition_updates) {
+                    let typed_key =
+                        to_typed_substate_key(entity_type, *partition_num, substate_key)
+                            .map_err(TypedChangeError::SubstateDecodeError)?;
+                    let typed_value = value
+                        .map(|value| to_typed_substate_value(&typed_key, value))
+                        .transpose()
+                        .map_err(TypedChangeError::SubstateDecodeError)?;
+                    if let Some(change) = Self::to_typed_change(*node_id, typed_key, typed_value) {
+                        changes.push(change);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn to_typed_change(
+        entity: NodeId,
+        typed_key: TypedSubstateKey,
+        typed_value: Option<TypedSubstateValue>,
+    ) -> Option<TypedChange> {
+        match (typed_key, typed_value) {
+            (
+                TypedSubstateKey::MetadataModule(TypedMetadataModuleSubstateKey::MetadataEntryKey(
+                    key,
+                )),
+                value,
+            ) => {
+                let value = match value {
+                    Some(TypedSubstateValue::MetadataModule(
+                        TypedMetadataModuleSubstateValue::MetadataEntry(entry),
+                    )) => entry
+                        .into_value()
+                        .map(|payload| payload.fully_update_and_into_latest_version()),
+                    _ => None,
+                };
+                Some(match value {
+                    Some(value) => TypedChange::MetadataSet { entity, key, value },
+                    None => TypedChange::MetadataRemoved { entity, key },
+                })
+            }
+            (
+                TypedSubstateKey::RoleAssignmentModule(TypedRoleAssignmentSubstateKey::Rule(
+                    role_key,
+                )),
+                value,
+            ) => {
+                let rule = match value {
+                    Some(TypedSubstateValue::RoleAssignmentModule(
+                        TypedRoleAssignmentModuleSubstateValue::Rule(entry),
+                    )) => entry
+                        .into_value()
+                        .map(|payload| payload.fully_update_and_into_latest_version()),
+                    _ => None,
+                };
+                Some(match rule {
+                    Some(rule) => TypedChange::RoleSet {
+                        entity,
+                        role_key,
+                        rule,
+                    },
+                    None => TypedChange::RoleRemoved { entity, role_key },
+                })
+            }
+            (
+                TypedSubstateKey::RoleAssignmentModule(_),
+                Some(TypedSubstateValue::RoleAssignmentModule(
+                    TypedRoleAssignmentModuleSubstateValue::OwnerRole(field),
+                )),
+            ) => Some(TypedChange::OwnerRoleSet {
+                entity,
+                owner_role: field
+                    .into_payload()
+                    .fully_update_and_into_latest_version()
+                    .owner_role_entry,
+            }),
+            _ => None,
+        }
+    }
+
+    fn extract_native_events(
+        application_events: &[(EventTypeIdentifier, Vec<u8>)],
+        changes: &mut Vec<TypedChange>,
+    ) -> Result<(), TypedChangeError> {
+        for (event_type_identifier, event_data) in application_events {
+            match to_typed_native_event(event_type_identifier, event_data) {
+                Ok(event) => changes.push(TypedChange::NativeEvent {
+                    emitter: event_type_identifier.0.clone(),
+                    event,
+                }),
+                Err(TypedNativeEventError::NotANativeBlueprint(_)) => {}
+                Err(error) => return Err(TypedChangeError::EventDecodeError(error)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the Substate-level updates of the given partition, with `None` meaning deletion.
+    fn iter_substate_updates(
+        partition_updates: &PartitionStateUpdates,
+    ) -> Box<dyn Iterator<Item = (&SubstateKey, Option<&[u8]>)> + '_> {
+        match partition_updates {
+            PartitionStateUpdates::Delta { by_substate } => {
+                Box::new(by_substate.iter().map(|(key, update)| match update {
+                    DatabaseUpdate::Set(value) => (key, Some(value.as_slice())),
+                    DatabaseUpdate::Delete => (key, None),
+                }))
+            }
+            PartitionStateUpdates::Batch(BatchPartitionStateUpdate::Reset {
+                new_substate_values,
+            }) => Box::new(
+                new_substate_values
+                    .iter()
+                    .map(|(key, value)| (key, Some(value.as_slice()))),
+            ),
+        }
+    }
+}
@@ -59,7 +59,7 @@ where
     /* Callbacks */
     /// A callback that is called when a scenario transaction is executed.
     on_transaction_executed:
-        Box<dyn FnMut(&ScenarioMetadata, &NextTransaction, &TransactionReceiptV1, &D) + 'a>,
+        Box<dyn FnMut(&ScenarioMetadata, &NextTransaction, &TransactionReceipt, &D) + 'a>,
     /// A callback that is called when a new scenario is started.
     on_scenario_started: Box<dyn FnMut(&ScenarioMetadata) + 'a>,
     on_scenario_ended: Box<dyn FnMut(&ScenarioMetadata, &EndState, &D) + 'a>,
@@ -176,7 +176,7 @@ where
 
     /// Sets the callback to call after executing a scenario transaction.
     pub fn on_transaction_executed<
-        F: FnMut(&ScenarioMetadata, &NextTransaction, &TransactionReceiptV1, &D) + 'a,
+        F: FnMut(&ScenarioMetadata, &NextTransaction, &TransactionReceipt, &D) + 'a,
     >(
         mut self,
         callback: F,
@@ -367,7 +367,7 @@ where
     fn execute_transaction(
         &mut self,
         transaction: &RawNotarizedTransaction,
-    ) -> Result<TransactionReceiptV1, ScenarioExecutorError> {
+    ) -> Result<TransactionReceipt, ScenarioExecutorError> {
         let validator = NotarizedTransactionValidator::new(ValidationConfig::default(
             self.network_definition.id,
         ));
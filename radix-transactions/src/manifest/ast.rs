@@ -510,3 +510,11 @@ pub struct InstructionWithSpan {
     pub instruction: Instruction,
     pub span: Span,
 }
+
+/// The declaration of a parameter of a manifest template, e.g. `PARAMETER $amount: Decimal;`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterDeclaration {
+    pub name: String,
+    pub value_kind: ValueKindWithSpan,
+    pub span: Span,
+}
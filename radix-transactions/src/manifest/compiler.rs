@@ -1,10 +1,23 @@
 use crate::internal_prelude::*;
+use crate::manifest::token::{Span, TokenWithSpan};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CompileError {
     LexerError(lexer::LexerError),
     ParserError(parser::ParserError),
     GeneratorError(generator::GeneratorError),
+    TemplateError(template::TemplateError),
+}
+
+impl CompileError {
+    pub fn span(&self) -> Span {
+        match self {
+            CompileError::LexerError(err) => err.span,
+            CompileError::ParserError(err) => err.span,
+            CompileError::GeneratorError(err) => err.span,
+            CompileError::TemplateError(err) => err.span,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -22,6 +35,22 @@ pub fn compile_error_diagnostics(
         CompileError::LexerError(err) => lexer::lexer_error_diagnostics(s, err, style),
         CompileError::ParserError(err) => parser::parser_error_diagnostics(s, err, style),
         CompileError::GeneratorError(err) => generator::generator_error_diagnostics(s, err, style),
+        CompileError::TemplateError(err) => template::template_error_diagnostics(s, err, style),
+    }
+}
+
+/// Returns the title and the label of the diagnostics of an error, for tools rendering the
+/// diagnostics themselves.
+pub fn compile_error_title_and_label(err: CompileError) -> (String, String) {
+    match err {
+        CompileError::LexerError(err) => lexer::lexer_error_title_and_label(err.error_kind),
+        CompileError::ParserError(err) => parser::parser_error_title_and_label(err.error_kind),
+        CompileError::GeneratorError(err) => {
+            generator::generator_error_title_and_label(err.error_kind)
+        }
+        CompileError::TemplateError(err) => {
+            template::template_error_title_and_label(err.error_kind)
+        }
     }
 }
 
@@ -33,13 +62,42 @@ pub fn compile<B>(
 where
     B: IsBlobProvider,
 {
-    let address_bech32_decoder = AddressBech32Decoder::new(network);
+    compile_with_spans(s, network, blobs).map(|(manifest, _)| manifest)
+}
 
+/// Compiles the manifest, also returning the span of each of the compiled instructions in the
+/// source, which can be used to locate errors found after compilation.
+pub fn compile_with_spans<B>(
+    s: &str,
+    network: &NetworkDefinition,
+    blobs: B,
+) -> Result<(TransactionManifestV1, Vec<Span>), CompileError>
+where
+    B: IsBlobProvider,
+{
     let tokens = lexer::tokenize(s).map_err(CompileError::LexerError)?;
+    compile_tokens_with_spans(tokens, network, blobs)
+}
+
+pub(crate) fn compile_tokens_with_spans<B>(
+    tokens: Vec<TokenWithSpan>,
+    network: &NetworkDefinition,
+    blobs: B,
+) -> Result<(TransactionManifestV1, Vec<Span>), CompileError>
+where
+    B: IsBlobProvider,
+{
+    let address_bech32_decoder = AddressBech32Decoder::new(network);
+
     let instructions = parser::Parser::new(tokens, parser::PARSER_MAX_DEPTH)
         .map_err(CompileError::ParserError)?
         .parse_manifest()
         .map_err(CompileError::ParserError)?;
-    generator::generate_manifest(&instructions, &address_bech32_decoder, blobs)
-        .map_err(CompileError::GeneratorError)
+    let manifest = generator::generate_manifest(&instructions, &address_bech32_decoder, blobs)
+        .map_err(CompileError::GeneratorError)?;
+    let spans = instructions
+        .iter()
+        .map(|instruction| instruction.span)
+        .collect();
+    Ok((manifest, spans))
 }
@@ -0,0 +1,432 @@
+//! Formatting of manifests in the canonical style, i.e. that of decompiled manifests.
+//!
+//! Each argument of an instruction goes on its own line, as does each element of a composite
+//! value, with an indent of four spaces per level. Unlike decompilation, formatting keeps the
+//! manifest as written otherwise: the names of buckets, proofs and addresses, the literals, the
+//! comments, and single empty lines between instructions. Instructions, and so the named buckets
+//! and proofs they create, are never reordered, as their order is part of the meaning of the
+//! manifest.
+//!
+//! Manifest templates are formatted too, with their parameter declarations and placeholders.
+
+use crate::internal_prelude::*;
+use crate::manifest::ast::{Value, ValueWithSpan};
+use crate::manifest::lexer::{tokenize_with_comments, Comment};
+use crate::manifest::parser::{Parser, ParserError, PARAMETER_DECLARATION_IDENT, PARSER_MAX_DEPTH};
+use crate::manifest::token::{Position, Span, Token, TokenWithSpan};
+use sbor::rust::iter::Peekable;
+use sbor::rust::slice;
+
+const INDENT: &str = "    ";
+
+/// Formats the manifest in the canonical style.
+pub fn format_manifest(s: &str) -> Result<String, CompileError> {
+    let (tokens, comments) = tokenize_with_comments(s).map_err(CompileError::LexerError)?;
+
+    // The placeholders of templates are parsed as strings, except in their declarations. This
+    // doesn't show in the output, as values are written as they are in the source.
+    let mut tokens = tokens;
+    for i in 0..tokens.len() {
+        if let Token::Placeholder(name) = &tokens[i].token {
+            let is_declared = i > 0
+                && tokens[i - 1].token == Token::Ident(PARAMETER_DECLARATION_IDENT.to_string());
+            if !is_declared {
+                tokens[i].token = Token::StringLiteral(name.clone());
+            }
+        }
+    }
+
+    let mut formatter = Formatter {
+        text: s.chars().collect(),
+        tokens: &tokens,
+        comments: comments.into_iter().peekable(),
+        output: String::new(),
+        last_line: None,
+    };
+    if !tokens.is_empty() {
+        // The instructions are checked up front, as they are formatted from their tokens.
+        let mut parser =
+            Parser::new(tokens.clone(), PARSER_MAX_DEPTH).map_err(CompileError::ParserError)?;
+        parser
+            .parse_parameter_declarations()
+            .map_err(CompileError::ParserError)?;
+        parser.parse_manifest().map_err(CompileError::ParserError)?;
+
+        formatter
+            .write_manifest(Parser::new(tokens.clone(), PARSER_MAX_DEPTH).unwrap())
+            .map_err(CompileError::ParserError)?;
+    }
+    formatter.write_comments_before(s.chars().count(), 0);
+    if !formatter.output.is_empty() {
+        formatter.output.push('\n');
+    }
+    Ok(formatter.output)
+}
+
+struct Formatter<'a> {
+    /// The source text chars
+    text: Vec<char>,
+    tokens: &'a [TokenWithSpan],
+    /// The comments which are yet to be written
+    comments: Peekable<vec::IntoIter<Comment>>,
+    output: String,
+    /// The line in the source of the last written token or comment
+    last_line: Option<usize>,
+}
+
+impl<'a> Formatter<'a> {
+    fn write_manifest(&mut self, mut parser: Parser) -> Result<(), ParserError> {
+        for declaration in parser.parse_parameter_declarations()? {
+            let line = format!(
+                "{} ${}: {};",
+                PARAMETER_DECLARATION_IDENT,
+                declaration.name,
+                self.source(declaration.value_kind.span)
+            );
+            self.write_comments_before(declaration.span.start.full_index, 0);
+            self.start_line(declaration.span.start, 0);
+            self.write(declaration.span.end, &line);
+        }
+
+        while !parser.is_eof() {
+            let instruction = parser.advance()?;
+            self.write_comments_before(instruction.span.start.full_index, 0);
+            self.start_line(instruction.span.start, 0);
+            self.write(instruction.span.end, &self.source(instruction.span));
+
+            let mut has_arguments = false;
+            while parser.peek()?.token != Token::Semicolon {
+                let argument = parser.parse_value()?;
+                self.write_value(&argument, 1, true);
+                has_arguments = true;
+            }
+
+            let semicolon = parser.advance()?;
+            if has_arguments {
+                self.write_comments_before(semicolon.span.start.full_index, 1);
+                self.start_closing_line(0);
+            }
+            self.write(semicolon.span.end, ";");
+        }
+        Ok(())
+    }
+
+    /// Writes the value, on a new line or else after the current output.
+    fn write_value(&mut self, value: &ValueWithSpan, depth: usize, on_new_line: bool) {
+        let index = self.token_index(value.span.start);
+        let head = match &value.value {
+            Value::Enum(..) => format!("Enum<{}>", self.source(self.tokens[index + 2].span)),
+            Value::Array(element_value_kind, _) => {
+                format!("Array<{}>", self.source(element_value_kind.span))
+            }
+            Value::Map(key_value_kind, value_value_kind, _) => format!(
+                "Map<{}, {}>",
+                self.source(key_value_kind.span),
+                self.source(value_value_kind.span)
+            ),
+            _ => self.source(value.span),
+        };
+        if on_new_line {
+            self.write_comments_before(value.span.start.full_index, depth);
+            self.start_line(value.span.start, depth);
+        }
+        self.write(value.span.end, &head);
+
+        match &value.value {
+            Value::Enum(_, elements) | Value::Array(_, elements) | Value::Tuple(elements) => {
+                let close = self.closing_parenthesis(index);
+                self.write_elements(elements, close, depth);
+            }
+            Value::Map(_, _, entries) => {
+                let close = self.closing_parenthesis(index);
+                if entries.is_empty() && !self.has_comments_before(close.start) {
+                    self.write(close.end, "()");
+                    return;
+                }
+                self.output.push('(');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    self.write_value(key, depth + 1, true);
+                    self.output.push_str(" => ");
+                    self.write_value(value, depth + 1, false);
+                    if i < entries.len() - 1 {
+                        self.output.push(',');
+                    }
+                }
+                self.write_comments_before(close.start.full_index, depth + 1);
+                self.start_closing_line(depth);
+                self.write(close.end, ")");
+            }
+            Value::Some(element)
+            | Value::Ok(element)
+            | Value::Err(element)
+            | Value::Bytes(element)
+            | Value::NonFungibleGlobalId(element)
+            | Value::Address(element)
+            | Value::NamedAddress(element)
+            | Value::Bucket(element)
+            | Value::Proof(element)
+            | Value::Expression(element)
+            | Value::Blob(element)
+            | Value::Decimal(element)
+            | Value::PreciseDecimal(element)
+            | Value::NonFungibleLocalId(element)
+            | Value::AddressReservation(element) => {
+                let close = self.closing_parenthesis(index);
+                if is_simple(element) && !self.has_comments_before(close.start) {
+                    self.output.push('(');
+                    self.write_value(element, depth, false);
+                    self.write(close.end, ")");
+                } else {
+                    self.write_elements(slice::from_ref(element.as_ref()), close, depth);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes the elements of a composite value, each on its own line.
+    fn write_elements(&mut self, elements: &[ValueWithSpan], close: Span, depth: usize) {
+        if elements.is_empty() && !self.has_comments_before(close.start) {
+            self.write(close.end, "()");
+            return;
+        }
+        self.output.push('(');
+        for (i, element) in elements.iter().enumerate() {
+            self.write_value(element, depth + 1, true);
+            if i < elements.len() - 1 {
+                self.output.push(',');
+            }
+        }
+        self.write_comments_before(close.start.full_index, depth + 1);
+        self.start_closing_line(depth);
+        self.write(close.end, ")");
+    }
+
+    /// Writes the comments before the given index in the source, each on its own line unless it
+    /// follows the last written token on its line.
+    fn write_comments_before(&mut self, full_index: usize, depth: usize) {
+        while let Some(comment) = self
+            .comments
+            .next_if(|comment| comment.span.start.full_index < full_index)
+        {
+            if self.last_line == Some(comment.span.start.line_idx) {
+                self.output.push(' ');
+            } else {
+                self.start_line(comment.span.start, depth);
+            }
+            self.write(comment.span.end, &comment.text);
+        }
+    }
+
+    fn has_comments_before(&mut self, position: Position) -> bool {
+        self.comments
+            .peek()
+            .is_some_and(|comment| comment.span.start.full_index < position.full_index)
+    }
+
+    /// Starts a line for a token or comment at the given position in the source, keeping an
+    /// empty line between top-level items which are apart in the source.
+    fn start_line(&mut self, position: Position, depth: usize) {
+        if let Some(last_line) = self.last_line {
+            self.output.push('\n');
+            if depth == 0 && position.line_idx > last_line + 1 {
+                self.output.push('\n');
+            }
+        }
+        self.output.push_str(&INDENT.repeat(depth));
+    }
+
+    /// Starts a line for a closing `)` or `;`, which is never preceded by an empty line.
+    fn start_closing_line(&mut self, depth: usize) {
+        self.output.push('\n');
+        self.output.push_str(&INDENT.repeat(depth));
+    }
+
+    fn write(&mut self, end: Position, s: &str) {
+        self.output.push_str(s);
+        self.last_line = Some(end.line_idx);
+    }
+
+    fn source(&self, span: Span) -> String {
+        self.text[span.start.full_index..span.end.full_index]
+            .iter()
+            .collect()
+    }
+
+    fn token_index(&self, position: Position) -> usize {
+        self.tokens
+            .binary_search_by_key(&position.full_index, |token| token.span.start.full_index)
+            .expect("Values start at a token")
+    }
+
+    /// Returns the span of the `)` closing the value starting at the given token.
+    fn closing_parenthesis(&self, index: usize) -> Span {
+        let mut depth = 0;
+        for token in &self.tokens[index..] {
+            match token.token {
+                Token::OpenParenthesis => depth += 1,
+                Token::CloseParenthesis => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return token.span;
+                    }
+                }
+                _ => {}
+            }
+        }
+        panic!("Composite values are closed, as checked by the parser")
+    }
+}
+
+/// Returns whether the value is written on a single line, even as the element of another value.
+fn is_simple(value: &ValueWithSpan) -> bool {
+    match &value.value {
+        Value::Enum(..) | Value::Array(..) | Value::Tuple(..) | Value::Map(..) => false,
+        Value::Some(element)
+        | Value::Ok(element)
+        | Value::Err(element)
+        | Value::Bytes(element)
+        | Value::NonFungibleGlobalId(element)
+        | Value::Address(element)
+        | Value::NamedAddress(element)
+        | Value::Bucket(element)
+        | Value::Proof(element)
+        | Value::Expression(element)
+        | Value::Blob(element)
+        | Value::Decimal(element)
+        | Value::PreciseDecimal(element)
+        | Value::NonFungibleLocalId(element)
+        | Value::AddressReservation(element) => is_simple(element),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::*;
+
+    #[test]
+    fn test_manifest_is_formatted_keeping_comments() {
+        let manifest = r##"
+# Pays the fee
+CALL_METHOD Address("component_sim1cptxxxxxxxxxfaucetxxxxxxxxx000527798379xxxxxxxxxhkrefh")   "lock_fee" Decimal("10") ;
+
+
+TAKE_ALL_FROM_WORKTOP Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3")
+    Bucket("xrd"); # Named after the resource
+CALL_METHOD Address("component_sim1cptxxxxxxxxxfaucetxxxxxxxxx000527798379xxxxxxxxxhkrefh") "call"
+    Tuple( Bucket("xrd"), Enum<Metadata::String>( "a\"b" ),
+        # Nothing
+        Array<String>(), Some(Decimal("1")),
+        Map<U8, Tuple>(1u8 => Tuple(true)), Some(Tuple(-5i32,)))
+    # Arguments end here
+;
+DROP_ALL_PROOFS;
+"##;
+        let expected = r##"# Pays the fee
+CALL_METHOD
+    Address("component_sim1cptxxxxxxxxxfaucetxxxxxxxxx000527798379xxxxxxxxxhkrefh")
+    "lock_fee"
+    Decimal("10")
+;
+
+TAKE_ALL_FROM_WORKTOP
+    Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3")
+    Bucket("xrd")
+; # Named after the resource
+CALL_METHOD
+    Address("component_sim1cptxxxxxxxxxfaucetxxxxxxxxx000527798379xxxxxxxxxhkrefh")
+    "call"
+    Tuple(
+        Bucket("xrd"),
+        Enum<Metadata::String>(
+            "a\"b"
+        ),
+        # Nothing
+        Array<String>(),
+        Some(Decimal("1")),
+        Map<U8, Tuple>(
+            1u8 => Tuple(
+                true
+            )
+        ),
+        Some(
+            Tuple(
+                -5i32
+            )
+        )
+    )
+    # Arguments end here
+;
+DROP_ALL_PROOFS;
+"##;
+
+        let formatted = format_manifest(manifest).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_manifest(&formatted).unwrap(), formatted);
+
+        let network = NetworkDefinition::simulator();
+        assert_eq!(
+            compile(&formatted, &network, MockBlobProvider::new()),
+            compile(manifest, &network, MockBlobProvider::new())
+        );
+    }
+
+    #[test]
+    fn test_decompiled_manifest_is_already_formatted() {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .take_all_from_worktop(XRD, "xrd")
+            .create_proof_from_bucket_of_all("xrd", "proof")
+            .drop_proof("proof")
+            .try_deposit_entire_worktop_or_abort(
+                ComponentAddress::virtual_account_from_public_key(
+                    &Secp256k1PrivateKey::from_u64(1).unwrap().public_key(),
+                ),
+                None,
+            )
+            .drop_all_proofs()
+            .build();
+        let decompiled =
+            decompile(&manifest.instructions, &NetworkDefinition::simulator()).unwrap();
+
+        assert_eq!(format_manifest(&decompiled).unwrap(), decompiled);
+    }
+
+    #[test]
+    fn test_template_is_formatted_keeping_placeholders() {
+        let template = r##"PARAMETER $amount:Decimal; # The amount
+PARAMETER   $account :  Address;
+CALL_METHOD $account "withdraw" Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3") $amount;"##;
+        let expected = r##"PARAMETER $amount: Decimal; # The amount
+PARAMETER $account: Address;
+CALL_METHOD
+    $account
+    "withdraw"
+    Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3")
+    $amount
+;
+"##;
+
+        assert_eq!(format_manifest(template).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_invalid_manifest_is_not_formatted() {
+        assert!(matches!(
+            format_manifest("CALL_METHOD Address(\"a\") \"b\""),
+            Err(CompileError::ParserError(_))
+        ));
+        assert!(matches!(
+            format_manifest("NO_SUCH_INSTRUCTION;"),
+            Err(CompileError::ParserError(_))
+        ));
+        assert_eq!(format_manifest("").unwrap(), "");
+        assert_eq!(
+            format_manifest("# Nothing yet  ").unwrap(),
+            "# Nothing yet\n"
+        );
+    }
+}
@@ -1712,7 +1712,14 @@ pub fn generator_error_diagnostics(
     err: GeneratorError,
     style: CompileErrorDiagnosticsStyle,
 ) -> String {
-    let (title, label) = match err.error_kind {
+    let (title, label) = generator_error_title_and_label(err.error_kind);
+    create_snippet(s, &err.span, &title, &label, style)
+}
+
+/// Returns the title and the label of the diagnostics of an error, describing the error and
+/// annotating its span respectively.
+pub fn generator_error_title_and_label(error_kind: GeneratorErrorKind) -> (String, String) {
+    match error_kind {
         GeneratorErrorKind::InvalidAstType {
             expected_value_kind,
             actual,
@@ -1880,9 +1887,7 @@ pub fn generator_error_diagnostics(
             let title = format!("invalid internal address '{}'", string);
             (title, "invalid internal address".to_string())
         }
-    };
-
-    create_snippet(s, &err.span, &title, &label, style)
+    }
 }
 
 #[cfg(test)]
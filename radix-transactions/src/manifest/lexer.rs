@@ -8,6 +8,7 @@ pub enum ExpectedChar {
     Exact(char),
     OneOf(Vec<char>),
     HexDigit,
+    Letter,
     DigitLetterQuotePunctuation,
 }
 
@@ -47,15 +48,30 @@ impl LexerError {
     }
 }
 
+/// A comment, from its `#` to the end of its line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// The text of the comment, including the `#` but not the trailing whitespace
+    pub text: String,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone)]
 pub struct Lexer {
     /// The input text chars
     text: Vec<char>,
     /// The current position in the text (in case of end of file it equals to text length)
     current: Position,
+    /// The comments skipped so far
+    comments: Vec<Comment>,
 }
 
 pub fn tokenize(s: &str) -> Result<Vec<TokenWithSpan>, LexerError> {
+    tokenize_with_comments(s).map(|(tokens, _)| tokens)
+}
+
+/// Tokenizes the text, also returning its comments, which are otherwise discarded.
+pub fn tokenize_with_comments(s: &str) -> Result<(Vec<TokenWithSpan>, Vec<Comment>), LexerError> {
     let mut lexer = Lexer::new(s);
     let mut tokens = Vec::new();
     loop {
@@ -65,7 +81,7 @@ pub fn tokenize(s: &str) -> Result<Vec<TokenWithSpan>, LexerError> {
             break;
         }
     }
-    Ok(tokens)
+    Ok((tokens, lexer.comments))
 }
 
 impl Lexer {
@@ -77,6 +93,7 @@ impl Lexer {
                 line_idx: 0,
                 line_char_index: 0,
             },
+            comments: Vec::new(),
         }
     }
 
@@ -136,14 +153,9 @@ impl Lexer {
 
     pub fn next_token(&mut self) -> Result<Option<TokenWithSpan>, LexerError> {
         // skip comment and whitespace
-        let mut in_comment = false;
         while !self.is_eof() {
-            if in_comment {
-                if self.advance()? == '\n' {
-                    in_comment = false;
-                }
-            } else if self.peek()? == '#' {
-                in_comment = true;
+            if self.peek()? == '#' {
+                self.skip_comment()?;
             } else if Self::is_whitespace(self.peek()?) {
                 self.advance()?;
             } else {
@@ -161,7 +173,8 @@ impl Lexer {
             '-' | '0'..='9' => self.tokenize_number(),
             '"' => self.tokenize_string(),
             'a'..='z' | 'A'..='Z' => self.tokenize_identifier(),
-            '{' | '}' | '(' | ')' | '<' | '>' | ',' | ';' | '&' | '=' => {
+            '$' => self.tokenize_placeholder(),
+            '{' | '}' | '(' | ')' | '<' | '>' | ',' | ';' | ':' | '&' | '=' => {
                 self.tokenize_punctuation()
             }
             c => Err(LexerError::unexpected_char(
@@ -173,6 +186,22 @@ impl Lexer {
         .map(Option::from)
     }
 
+    fn skip_comment(&mut self) -> Result<(), LexerError> {
+        let start = self.current;
+        let mut text = String::new();
+        while !self.is_eof() && self.peek()? != '\n' {
+            text.push(self.advance()?);
+        }
+        self.comments.push(Comment {
+            text: text.trim_end().to_string(),
+            span: Span {
+                start,
+                end: self.current,
+            },
+        });
+        Ok(())
+    }
+
     // TODO: consider using DFA
     fn tokenize_number(&mut self) -> Result<TokenWithSpan, LexerError> {
         let literal_start = self.current;
@@ -376,6 +405,25 @@ impl Lexer {
         Ok(self.new_token(token, start, self.current))
     }
 
+    fn tokenize_placeholder(&mut self) -> Result<TokenWithSpan, LexerError> {
+        let start = self.current;
+
+        self.advance_expected('$')?;
+        let mut name = String::from(self.advance_matching(
+            |c| c.is_ascii_alphabetic() || c == '_',
+            ExpectedChar::Letter,
+        )?);
+        while !self.is_eof() {
+            let next_char = self.peek()?;
+            if !(next_char.is_ascii_alphanumeric() || next_char == '_') {
+                break;
+            }
+            name.push(self.advance()?);
+        }
+
+        Ok(self.new_token(Token::Placeholder(name), start, self.current))
+    }
+
     fn tokenize_punctuation(&mut self) -> Result<TokenWithSpan, LexerError> {
         let token_start = self.current;
 
@@ -386,6 +434,7 @@ impl Lexer {
             '>' => Token::GreaterThan,
             ',' => Token::Comma,
             ';' => Token::Semicolon,
+            ':' => Token::Colon,
             '=' => {
                 self.advance_expected('>')?;
                 Token::FatArrow
@@ -394,7 +443,7 @@ impl Lexer {
                 return Err(LexerError::unexpected_char(
                     token_start,
                     c,
-                    ExpectedChar::OneOf(vec!['(', ')', '<', '>', ',', ';', '=']),
+                    ExpectedChar::OneOf(vec!['(', ')', '<', '>', ',', ';', ':', '=']),
                 ))
             }
         };
@@ -415,7 +464,14 @@ pub fn lexer_error_diagnostics(
     err: LexerError,
     style: CompileErrorDiagnosticsStyle,
 ) -> String {
-    let (title, label) = match err.error_kind {
+    let (title, label) = lexer_error_title_and_label(err.error_kind);
+    create_snippet(s, &err.span, &title, &label, style)
+}
+
+/// Returns the title and the label of the diagnostics of an error, describing the error and
+/// annotating its span respectively.
+pub fn lexer_error_title_and_label(error_kind: LexerErrorKind) -> (String, String) {
+    match error_kind {
         LexerErrorKind::UnexpectedEof => (
             "unexpected end of file".to_string(),
             "unexpected end of file".to_string(),
@@ -433,7 +489,8 @@ pub fn lexer_error_diagnostics(
                     }
                 }
                 ExpectedChar::HexDigit => "hex digit".to_string(),
-                ExpectedChar::DigitLetterQuotePunctuation => "digit, letter, quotation mark or one of punctuation characters '(', ')', '<', '>', ',', ';', '='".to_string(),
+                ExpectedChar::Letter => "letter".to_string(),
+                ExpectedChar::DigitLetterQuotePunctuation => "digit, letter, quotation mark, '$' or one of punctuation characters '(', ')', '<', '>', ',', ';', ':', '='".to_string(),
             };
             (
                 format!("unexpected character {:?}, expected {}", c, expected),
@@ -460,8 +517,7 @@ pub fn lexer_error_diagnostics(
             format!("missing unicode '{:X}' surrogate pair", value),
             "missing unicode surrogate pair".to_string(),
         ),
-    };
-    create_snippet(s, &err.span, &title, &label, style)
+    }
 }
 
 #[cfg(test)]
@@ -547,6 +603,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_comments_are_returned_on_request() {
+        let (tokens, comments) = tokenize_with_comments("1u8 # one \r\n# two\n2u8").unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(
+            comments,
+            vec![
+                Comment {
+                    text: "# one".to_string(),
+                    span: span!(start = (4, 0, 4), end = (11, 0, 11)),
+                },
+                Comment {
+                    text: "# two".to_string(),
+                    span: span!(start = (12, 1, 0), end = (17, 1, 5)),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_string() {
         lex_ok!(
@@ -6,13 +6,17 @@ pub mod diagnostic_snippets;
 #[cfg(feature = "std")]
 pub mod dumper;
 pub mod e2e;
+pub mod formatter;
 pub mod generator;
 pub mod lexer;
 pub mod manifest_enums;
 pub mod parser;
+pub mod template;
 pub mod token;
 
 pub use blob_provider::*;
 pub use compiler::{compile, CompileError};
 pub use decompiler::{decompile, DecompileError};
+pub use formatter::format_manifest;
 pub use manifest_enums::*;
+pub use template::{compile_template, TemplateValue};
@@ -1,5 +1,6 @@
 use crate::manifest::ast::{
-    Instruction, InstructionWithSpan, Value, ValueKind, ValueKindWithSpan, ValueWithSpan,
+    Instruction, InstructionWithSpan, ParameterDeclaration, Value, ValueKind, ValueKindWithSpan,
+    ValueWithSpan,
 };
 use crate::manifest::compiler::CompileErrorDiagnosticsStyle;
 use crate::manifest::diagnostic_snippets::create_snippet;
@@ -12,6 +13,9 @@ use sbor::prelude::*;
 //   EncodeError::MaxDepthExceeded(MANIFEST_SBOR_V1_MAX_DEPTH)
 pub const PARSER_MAX_DEPTH: usize = MANIFEST_SBOR_V1_MAX_DEPTH - 4;
 
+/// The keyword of the declarations of the parameters of a manifest template.
+pub const PARAMETER_DECLARATION_IDENT: &str = "PARAMETER";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParserErrorKind {
     UnexpectedEof,
@@ -47,6 +51,7 @@ pub enum TokenType {
     Value,
     ValueKind,
     EnumDiscriminator,
+    Placeholder,
     Exact(Token),
 }
 
@@ -59,6 +64,7 @@ impl fmt::Display for TokenType {
             TokenType::EnumDiscriminator => {
                 write!(f, "a u8 enum discriminator or valid discriminator alias")
             }
+            TokenType::Placeholder => write!(f, "a template parameter"),
             TokenType::Exact(token) => write!(f, "exactly {}", token),
         }
     }
@@ -525,6 +531,34 @@ impl Parser {
         Ok(instructions)
     }
 
+    /// Parses the parameter declarations at the start of a manifest template, leaving the parser
+    /// at its first instruction.
+    pub fn parse_parameter_declarations(
+        &mut self,
+    ) -> Result<Vec<ParameterDeclaration>, ParserError> {
+        let mut declarations = Vec::new();
+
+        while !self.is_eof()
+            && self.peek()?.token == Token::Ident(PARAMETER_DECLARATION_IDENT.to_string())
+        {
+            let start = self.advance()?.span.start;
+            let token = self.advance()?;
+            let Token::Placeholder(name) = token.token else {
+                return Err(ParserError::unexpected_token(token, TokenType::Placeholder));
+            };
+            self.advance_exact(Token::Colon)?;
+            let value_kind = self.parse_value_kind()?;
+            let end = self.advance_exact(Token::Semicolon)?.span.end;
+            declarations.push(ParameterDeclaration {
+                name,
+                value_kind,
+                span: Span { start, end },
+            });
+        }
+
+        Ok(declarations)
+    }
+
     fn parse_instruction_arguments(&mut self) -> Result<Vec<ValueWithSpan>, ParserError> {
         let mut args = Vec::new();
         while self.peek()?.token != Token::Semicolon {
@@ -1116,7 +1150,14 @@ pub fn parser_error_diagnostics(
     err: ParserError,
     style: CompileErrorDiagnosticsStyle,
 ) -> String {
-    let (title, label) = match err.error_kind {
+    let (title, label) = parser_error_title_and_label(err.error_kind);
+    create_snippet(s, &err.span, &title, &label, style)
+}
+
+/// Returns the title and the label of the diagnostics of an error, describing the error and
+/// annotating its span respectively.
+pub fn parser_error_title_and_label(error_kind: ParserErrorKind) -> (String, String) {
+    match error_kind {
         ParserErrorKind::UnexpectedEof => (
             "unexpected end of file".to_string(),
             "unexpected end of file".to_string(),
@@ -1152,9 +1193,7 @@ pub fn parser_error_diagnostics(
             let title = format!("unknown enum discriminator found '{}'", actual);
             (title, "unknown enum discriminator".to_string())
         }
-    };
-
-    create_snippet(s, &err.span, &title, &label, style)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,597 @@
+//! Manifest templates, i.e. manifests with typed parameters which are bound to values at compile
+//! time.
+//!
+//! The parameters are declared at the start of the template, and can then be used in place of any
+//! value of the manifest:
+//!
+//! ```text
+//! PARAMETER $account: Address;
+//! PARAMETER $amount: Decimal;
+//!
+//! CALL_METHOD
+//!     $account
+//!     "withdraw"
+//!     Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3")
+//!     $amount
+//! ;
+//! ```
+
+use crate::data::*;
+use crate::internal_prelude::*;
+use crate::manifest::ast::{ParameterDeclaration, ValueKind};
+use crate::manifest::compiler::{compile_tokens_with_spans, CompileErrorDiagnosticsStyle};
+use crate::manifest::diagnostic_snippets::create_snippet;
+use crate::manifest::generator::{GeneratorError, GeneratorErrorKind, NameResolver};
+use crate::manifest::parser::{Parser, PARSER_MAX_DEPTH};
+use crate::manifest::token::{Position, Span, Token, TokenWithSpan};
+use sbor::rust::hash::BuildHasher;
+use sbor::rust::str::FromStr;
+
+/// A value bound to a parameter of a manifest template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateValue {
+    /// A value in its textual form, as found in bindings files.
+    ///
+    /// Values of kinds with a string representation are given as such, e.g. `10` for a
+    /// `Decimal`, the Bech32m address for an `Address` or the name of the bucket for a `Bucket`.
+    /// Values of the `Enum`, `Array`, `Tuple` and `Map` kinds are given in the manifest syntax,
+    /// e.g. `Tuple(1u8, "one")`.
+    Text(String),
+    /// A manifest value, e.g. as created with `to_manifest_value_and_unwrap!`.
+    Value(ManifestValue),
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<ManifestValue> for TemplateValue {
+    fn from(value: ManifestValue) -> Self {
+        Self::Value(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateErrorKind {
+    ParameterAlreadyDeclared(String),
+    UndeclaredParameter(String),
+    MissingBinding(String),
+    UnknownBinding(String),
+    InvalidBinding {
+        name: String,
+        expected_value_kind: ValueKind,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    pub error_kind: TemplateErrorKind,
+    pub span: Span,
+}
+
+/// Compiles a manifest template, substituting each of its parameters with the value bound to it.
+///
+/// Every declared parameter must be bound to a value of the declared kind, and every bound value
+/// must be to a declared parameter. A manifest without parameters compiles as with [`compile`].
+pub fn compile_template<B, S>(
+    s: &str,
+    network: &NetworkDefinition,
+    blobs: B,
+    bindings: &HashMap<String, TemplateValue, S>,
+) -> Result<TransactionManifestV1, CompileError>
+where
+    B: IsBlobProvider,
+    S: BuildHasher,
+{
+    let address_bech32_decoder = AddressBech32Decoder::new(network);
+    let address_bech32_encoder = AddressBech32Encoder::new(network);
+
+    let tokens = lexer::tokenize(s).map_err(CompileError::LexerError)?;
+    let declarations = Parser::new(tokens.clone(), PARSER_MAX_DEPTH)
+        .and_then(|mut parser| parser.parse_parameter_declarations())
+        .map_err(CompileError::ParserError)?;
+
+    let mut bound_tokens: IndexMap<String, Vec<Token>> = index_map_new();
+    for declaration in &declarations {
+        if bound_tokens.contains_key(&declaration.name) {
+            return Err(template_error(
+                TemplateErrorKind::ParameterAlreadyDeclared(declaration.name.clone()),
+                declaration.span,
+            ));
+        }
+        let value = bindings.get(&declaration.name).ok_or_else(|| {
+            template_error(
+                TemplateErrorKind::MissingBinding(declaration.name.clone()),
+                declaration.span,
+            )
+        })?;
+        let tokens = bind(
+            declaration,
+            value,
+            &address_bech32_encoder,
+            &address_bech32_decoder,
+            &blobs,
+        )?;
+        bound_tokens.insert(declaration.name.clone(), tokens);
+    }
+    if let Some(name) = bindings
+        .keys()
+        .filter(|name| !bound_tokens.contains_key(*name))
+        .min()
+    {
+        let start = Position {
+            full_index: 0,
+            line_idx: 0,
+            line_char_index: 0,
+        };
+        return Err(template_error(
+            TemplateErrorKind::UnknownBinding(name.clone()),
+            Span { start, end: start },
+        ));
+    }
+
+    // The substituted tokens take the span of the parameter, for the errors in their values to be
+    // reported there.
+    let instructions_start = declarations
+        .last()
+        .map(|declaration| declaration.span.end.full_index)
+        .unwrap_or(0);
+    let mut substituted_tokens = Vec::new();
+    for token in tokens
+        .into_iter()
+        .filter(|token| token.span.start.full_index >= instructions_start)
+    {
+        match &token.token {
+            Token::Placeholder(name) => {
+                let bound = bound_tokens.get(name).ok_or_else(|| {
+                    template_error(
+                        TemplateErrorKind::UndeclaredParameter(name.clone()),
+                        token.span,
+                    )
+                })?;
+                substituted_tokens.extend(bound.iter().map(|bound_token| TokenWithSpan {
+                    token: bound_token.clone(),
+                    span: token.span,
+                }));
+            }
+            _ => substituted_tokens.push(token),
+        }
+    }
+
+    compile_tokens_with_spans(substituted_tokens, network, blobs).map(|(manifest, _)| manifest)
+}
+
+/// Converts the value bound to a parameter into tokens, checking it against the declared kind.
+fn bind<B>(
+    declaration: &ParameterDeclaration,
+    value: &TemplateValue,
+    address_bech32_encoder: &AddressBech32Encoder,
+    address_bech32_decoder: &AddressBech32Decoder,
+    blobs: &B,
+) -> Result<Vec<Token>, CompileError>
+where
+    B: IsBlobProvider,
+{
+    let value_kind = declaration.value_kind.value_kind;
+    let invalid_binding = |reason: String| {
+        template_error(
+            TemplateErrorKind::InvalidBinding {
+                name: declaration.name.clone(),
+                expected_value_kind: value_kind,
+                reason,
+            },
+            declaration.span,
+        )
+    };
+
+    let tokens = match value {
+        TemplateValue::Text(text) => text_tokens(text, value_kind),
+        TemplateValue::Value(value) => {
+            let context = ManifestDecompilationDisplayContext::with_optional_bech32(Some(
+                address_bech32_encoder,
+            ));
+            let mut text = String::new();
+            format_manifest_value(&mut text, value, &context, false, 0)
+                .map_err(|_| "value can't be formatted".to_string())
+                .and_then(|_| tokenize_value(&text))
+        }
+    }
+    .map_err(invalid_binding)?;
+
+    let mut parser = Parser::new(
+        tokens
+            .iter()
+            .map(|token| TokenWithSpan {
+                token: token.clone(),
+                span: declaration.span,
+            })
+            .collect(),
+        PARSER_MAX_DEPTH,
+    )
+    .map_err(|err| invalid_binding(parser::parser_error_title_and_label(err.error_kind).0))?;
+    let parsed_value = parser
+        .parse_value()
+        .map_err(|err| invalid_binding(parser::parser_error_title_and_label(err.error_kind).0))?;
+    if !parser.is_eof() {
+        return Err(invalid_binding(
+            "unexpected tokens after the value".to_string(),
+        ));
+    }
+
+    match generator::generate_value(
+        &parsed_value,
+        Some(value_kind.value_kind()),
+        &mut NameResolver::new(),
+        address_bech32_decoder,
+        blobs,
+    ) {
+        Ok(_) => Ok(tokens),
+        // Named values can only be resolved within the manifest, once substituted.
+        Err(GeneratorError {
+            error_kind: GeneratorErrorKind::NameResolverError(_),
+            ..
+        }) => Ok(tokens),
+        Err(err) => Err(invalid_binding(
+            generator::generator_error_title_and_label(err.error_kind).0,
+        )),
+    }
+}
+
+fn text_tokens(text: &str, value_kind: ValueKind) -> Result<Vec<Token>, String> {
+    fn literal<T: FromStr>(
+        text: &str,
+        token: impl FnOnce(T) -> Token,
+    ) -> Result<Vec<Token>, String> {
+        text.parse::<T>()
+            .map(|value| vec![token(value)])
+            .map_err(|_| format!("invalid literal {:?}", text))
+    }
+
+    let ident = match value_kind {
+        ValueKind::Bool => return literal(text, Token::BoolLiteral),
+        ValueKind::I8 => return literal(text, Token::I8Literal),
+        ValueKind::I16 => return literal(text, Token::I16Literal),
+        ValueKind::I32 => return literal(text, Token::I32Literal),
+        ValueKind::I64 => return literal(text, Token::I64Literal),
+        ValueKind::I128 => return literal(text, Token::I128Literal),
+        ValueKind::U8 => return literal(text, Token::U8Literal),
+        ValueKind::U16 => return literal(text, Token::U16Literal),
+        ValueKind::U32 => return literal(text, Token::U32Literal),
+        ValueKind::U64 => return literal(text, Token::U64Literal),
+        ValueKind::U128 => return literal(text, Token::U128Literal),
+        ValueKind::String => return Ok(vec![Token::StringLiteral(text.to_string())]),
+        ValueKind::Enum | ValueKind::Array | ValueKind::Tuple | ValueKind::Map => {
+            return tokenize_value(text)
+        }
+        ValueKind::Bytes => "Bytes",
+        ValueKind::NonFungibleGlobalId => "NonFungibleGlobalId",
+        ValueKind::PackageAddress
+        | ValueKind::ComponentAddress
+        | ValueKind::ResourceAddress
+        | ValueKind::Address => "Address",
+        ValueKind::Bucket => "Bucket",
+        ValueKind::Proof => "Proof",
+        ValueKind::Expression => "Expression",
+        ValueKind::Blob => "Blob",
+        ValueKind::Decimal => "Decimal",
+        ValueKind::PreciseDecimal => "PreciseDecimal",
+        ValueKind::NonFungibleLocalId => "NonFungibleLocalId",
+        ValueKind::AddressReservation => "AddressReservation",
+        ValueKind::NamedAddress => "NamedAddress",
+    };
+    Ok(vec![
+        Token::Ident(ident.to_string()),
+        Token::OpenParenthesis,
+        Token::StringLiteral(text.to_string()),
+        Token::CloseParenthesis,
+    ])
+}
+
+fn tokenize_value(text: &str) -> Result<Vec<Token>, String> {
+    lexer::tokenize(text)
+        .map(|tokens| tokens.into_iter().map(|token| token.token).collect())
+        .map_err(|err| lexer::lexer_error_title_and_label(err.error_kind).0)
+}
+
+fn template_error(error_kind: TemplateErrorKind, span: Span) -> CompileError {
+    CompileError::TemplateError(TemplateError { error_kind, span })
+}
+
+pub fn template_error_diagnostics(
+    s: &str,
+    err: TemplateError,
+    style: CompileErrorDiagnosticsStyle,
+) -> String {
+    let (title, label) = template_error_title_and_label(err.error_kind);
+    create_snippet(s, &err.span, &title, &label, style)
+}
+
+/// Returns the title and the label of the diagnostics of an error, describing the error and
+/// annotating its span respectively.
+pub fn template_error_title_and_label(error_kind: TemplateErrorKind) -> (String, String) {
+    match error_kind {
+        TemplateErrorKind::ParameterAlreadyDeclared(name) => (
+            format!("parameter `${}` is already declared", name),
+            "parameter already declared".to_string(),
+        ),
+        TemplateErrorKind::UndeclaredParameter(name) => (
+            format!("parameter `${}` is not declared", name),
+            "undeclared parameter".to_string(),
+        ),
+        TemplateErrorKind::MissingBinding(name) => (
+            format!("no value bound to parameter `${}`", name),
+            "missing value".to_string(),
+        ),
+        TemplateErrorKind::UnknownBinding(name) => (
+            format!("value bound to undeclared parameter `${}`", name),
+            "unknown parameter".to_string(),
+        ),
+        TemplateErrorKind::InvalidBinding {
+            name,
+            expected_value_kind,
+            reason,
+        } => (
+            format!(
+                "invalid value bound to parameter `${}` of kind {:?}: {}",
+                name, expected_value_kind, reason
+            ),
+            "invalid value".to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radix_common::constants::{FAUCET, XRD};
+
+    const TEMPLATE: &str = r#"
+PARAMETER $account: Address;
+PARAMETER $amount: Decimal;
+PARAMETER $ids: Array;
+
+CALL_METHOD
+    $account
+    "withdraw"
+    Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3")
+    $amount
+;
+TAKE_FROM_WORKTOP
+    Address("resource_sim1tknxxxxxxxxxradxrdxxxxxxxxx009923554798xxxxxxxxxakj8n3")
+    $amount
+    Bucket("xrd")
+;
+CALL_METHOD
+    $account
+    "deposit_batch"
+    Expression("ENTIRE_WORKTOP")
+    $ids
+;
+"#;
+
+    fn faucet_address() -> String {
+        AddressBech32Encoder::for_simulator()
+            .encode(FAUCET.as_node_id().as_bytes())
+            .unwrap()
+    }
+
+    fn compile_with_bindings(
+        s: &str,
+        bindings: Vec<(&str, TemplateValue)>,
+    ) -> Result<TransactionManifestV1, CompileError> {
+        let bindings: HashMap<String, TemplateValue> = bindings
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        compile_template(
+            s,
+            &NetworkDefinition::simulator(),
+            BlobProvider::default(),
+            &bindings,
+        )
+    }
+
+    fn expected_manifest() -> TransactionManifestV1 {
+        let manifest = TEMPLATE
+            .replace(
+                "PARAMETER $account: Address;\nPARAMETER $amount: Decimal;\nPARAMETER $ids: Array;\n",
+                "",
+            )
+            .replace("$account", &format!("Address(\"{}\")", faucet_address()))
+            .replace("$amount", "Decimal(\"10.5\")")
+            .replace("$ids", "Array<U32>(1u32, 2u32)");
+        compile(
+            &manifest,
+            &NetworkDefinition::simulator(),
+            BlobProvider::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_template_with_bound_text_values() {
+        let manifest = compile_with_bindings(
+            TEMPLATE,
+            vec![
+                ("account", faucet_address().into()),
+                ("amount", "10.5".into()),
+                ("ids", "Array<U32>(1u32, 2u32)".into()),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(manifest, expected_manifest());
+    }
+
+    #[test]
+    fn test_template_with_bound_manifest_values() {
+        let manifest = compile_with_bindings(
+            TEMPLATE,
+            vec![
+                (
+                    "account",
+                    manifest_decode::<ManifestValue>(&manifest_encode(&FAUCET).unwrap())
+                        .unwrap()
+                        .into(),
+                ),
+                (
+                    "amount",
+                    manifest_decode::<ManifestValue>(
+                        &manifest_encode(&Decimal::from_str("10.5").unwrap()).unwrap(),
+                    )
+                    .unwrap()
+                    .into(),
+                ),
+                (
+                    "ids",
+                    manifest_decode::<ManifestValue>(&manifest_encode(&vec![1u32, 2u32]).unwrap())
+                        .unwrap()
+                        .into(),
+                ),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(manifest, expected_manifest());
+    }
+
+    #[test]
+    fn test_template_values_are_checked_against_the_declared_kinds() {
+        let cases = vec![
+            ("amount", TemplateValue::from("ten"), ValueKind::Decimal),
+            (
+                "amount",
+                TemplateValue::from("Decimal(\"10\")"),
+                ValueKind::Decimal,
+            ),
+            ("ids", TemplateValue::from("Tuple(1u32)"), ValueKind::Array),
+            (
+                "account",
+                manifest_decode::<ManifestValue>(&manifest_encode(&XRD).unwrap())
+                    .unwrap()
+                    .into(),
+                ValueKind::Address,
+            ),
+        ];
+
+        for (invalid_name, invalid_value, expected_kind) in cases {
+            let mut bindings: Vec<(&str, TemplateValue)> = vec![
+                ("account", faucet_address().into()),
+                ("amount", "10.5".into()),
+                ("ids", "Array<U32>(1u32, 2u32)".into()),
+            ];
+            let valid_value = bindings
+                .iter_mut()
+                .find(|(name, _)| *name == invalid_name)
+                .unwrap();
+            valid_value.1 = invalid_value;
+
+            let result = compile_with_bindings(TEMPLATE, bindings);
+
+            // The XRD address is a valid address, but not one of a component which can be
+            // withdrawn from, which isn't something that the template can know about.
+            if invalid_name == "account" {
+                assert!(result.is_ok());
+                continue;
+            }
+            match result {
+                Err(CompileError::TemplateError(TemplateError {
+                    error_kind:
+                        TemplateErrorKind::InvalidBinding {
+                            name,
+                            expected_value_kind,
+                            ..
+                        },
+                    ..
+                })) => {
+                    assert_eq!(name, invalid_name);
+                    assert_eq!(expected_value_kind, expected_kind);
+                }
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_template_parameters_must_be_declared_and_bound() {
+        let valid_bindings = || -> Vec<(&str, TemplateValue)> {
+            vec![
+                ("account", faucet_address().into()),
+                ("amount", "10.5".into()),
+                ("ids", "Array<U32>()".into()),
+            ]
+        };
+
+        let missing = compile_with_bindings(TEMPLATE, valid_bindings()[..2].to_vec());
+        let mut unknown_bindings = valid_bindings();
+        unknown_bindings.push(("amout", "1".into()));
+        let unknown = compile_with_bindings(TEMPLATE, unknown_bindings);
+        let undeclared = compile_with_bindings(
+            &TEMPLATE.replace("PARAMETER $amount: Decimal;", ""),
+            vec![
+                ("account", faucet_address().into()),
+                ("ids", "Array<U32>()".into()),
+            ],
+        );
+        let redeclared = compile_with_bindings(
+            &TEMPLATE.replace(
+                "PARAMETER $ids: Array;",
+                "PARAMETER $ids: Array;\nPARAMETER $amount: Decimal;",
+            ),
+            valid_bindings(),
+        );
+
+        assert!(matches!(
+            missing.unwrap_err(),
+            CompileError::TemplateError(TemplateError {
+                error_kind: TemplateErrorKind::MissingBinding(name),
+                span,
+            }) if name == "ids" && span.start.line_number() == 4
+        ));
+        assert!(matches!(
+            unknown.unwrap_err(),
+            CompileError::TemplateError(TemplateError {
+                error_kind: TemplateErrorKind::UnknownBinding(name),
+                ..
+            }) if name == "amout"
+        ));
+        assert!(matches!(
+            undeclared.unwrap_err(),
+            CompileError::TemplateError(TemplateError {
+                error_kind: TemplateErrorKind::UndeclaredParameter(name),
+                span,
+            }) if name == "amount" && span.start.line_number() == 10
+        ));
+        assert!(matches!(
+            redeclared.unwrap_err(),
+            CompileError::TemplateError(TemplateError {
+                error_kind: TemplateErrorKind::ParameterAlreadyDeclared(name),
+                ..
+            }) if name == "amount"
+        ));
+    }
+
+    #[test]
+    fn test_manifest_without_parameters_compiles_as_a_template() {
+        let manifest = r#"CALL_METHOD Address("component_sim1cptxxxxxxxxxfaucetxxxxxxxxx000527798379xxxxxxxxxhkrefh") "free";"#;
+
+        assert_eq!(
+            compile_with_bindings(manifest, vec![]).unwrap(),
+            compile(
+                manifest,
+                &NetworkDefinition::simulator(),
+                BlobProvider::default()
+            )
+            .unwrap()
+        );
+    }
+}
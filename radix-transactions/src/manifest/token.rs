@@ -80,6 +80,8 @@ pub enum Token {
     StringLiteral(String),
 
     Ident(String),
+    /// A parameter of a manifest template, e.g. `$amount`
+    Placeholder(String),
 
     /* Punctuations */
     OpenParenthesis,
@@ -89,6 +91,7 @@ pub enum Token {
     Comma,
     Semicolon,
     FatArrow,
+    Colon,
 }
 
 impl fmt::Display for Token {
@@ -107,6 +110,7 @@ impl fmt::Display for Token {
             Token::U128Literal(value) => write!(f, "'{:?}u128'", value),
             Token::StringLiteral(value) => write!(f, "{:?}", value),
             Token::Ident(value) => write!(f, "'{}'", value),
+            Token::Placeholder(value) => write!(f, "'${}'", value),
             Token::OpenParenthesis => write!(f, "'('"),
             Token::CloseParenthesis => write!(f, "')'",),
             Token::LessThan => write!(f, "'<'"),
@@ -114,6 +118,7 @@ impl fmt::Display for Token {
             Token::Comma => write!(f, "','"),
             Token::Semicolon => write!(f, "';'",),
             Token::FatArrow => write!(f, "'=>'"),
+            Token::Colon => write!(f, "':'"),
         }
     }
 }
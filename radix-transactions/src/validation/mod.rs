@@ -1,9 +1,11 @@
 mod id_allocator;
 mod id_validator;
+mod resource_flow_analyzer;
 mod signature_validator;
 mod transaction_validator;
 
 pub use id_allocator::*;
 pub use id_validator::*;
+pub use resource_flow_analyzer::*;
 pub use signature_validator::*;
 pub use transaction_validator::*;
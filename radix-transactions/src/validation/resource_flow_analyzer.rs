@@ -0,0 +1,729 @@
+use crate::data::{transform, TransformHandler};
+use crate::internal_prelude::*;
+use radix_common::data::manifest::model::*;
+use radix_common::data::scrypto::model::{Own, Reference};
+use radix_engine_interface::blueprints::account::*;
+use radix_engine_interface::blueprints::resource::*;
+use sbor::rust::convert::Infallible;
+
+/// The amount of a resource, as far as it's known statically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceAmount {
+    Known(Decimal),
+    /// The amount depends on the state of the ledger, e.g. as returned by an arbitrary call.
+    Unknown,
+}
+
+impl ResourceAmount {
+    fn add(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Known(a), Self::Known(b)) => a.checked_add(b).map_or(Self::Unknown, Self::Known),
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceFlowWarning {
+    /// A bucket is still held at the end of the manifest, i.e. it's never deposited, burnt,
+    /// returned to the worktop or otherwise passed to a call.
+    BucketNeverDeposited {
+        bucket_id: ManifestBucket,
+        created_at: usize,
+    },
+    /// The resources returned by a call which was given resources, e.g. a swap, aren't asserted on
+    /// the worktop, nor taken from it by amount.
+    MissingWorktopAssertion { call_index: usize },
+    /// A proof is left on the auth zone at the end of the manifest, having been available to the
+    /// authorization of every call since it was pushed.
+    ProofLeftOnAuthZone { pushed_at: usize },
+    /// Resources are deposited into an account which isn't known to be controlled by the signers.
+    DepositIntoUncontrolledAccount {
+        instruction_index: usize,
+        account: ComponentAddress,
+    },
+    /// More of a resource is taken from the worktop than it's known to contain.
+    InsufficientWorktopContents {
+        instruction_index: usize,
+        resource_address: ResourceAddress,
+        required: Decimal,
+        available: Decimal,
+    },
+    /// A resource is known to be left on the worktop at the end of the manifest, which fails the
+    /// transaction.
+    ResourcesLeftOnWorktop {
+        resource_address: ResourceAddress,
+        amount: Decimal,
+    },
+}
+
+/// A static analyzer of the flow of resources through the worktop, buckets and auth zone of a
+/// manifest.
+///
+/// The contents of the worktop and buckets are tracked symbolically, with known amounts where
+/// they are deterministic, e.g. after withdrawals from accounts, and unknown amounts after calls to
+/// arbitrary methods or functions. Unlike [`ManifestValidator`], which rejects manifests with
+/// invalid ids, the analyzer only warns about manifests which are valid but likely mistaken or
+/// malicious.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceFlowAnalyzer {
+    controlled_accounts: IndexSet<ComponentAddress>,
+}
+
+impl ResourceFlowAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats the account as controlled by the signers, e.g. as it's owned by one of their badges.
+    ///
+    /// Only the accounts given here or derived from the keys given to [`Self::with_signer`] are
+    /// treated as controlled. Calls to owner methods, e.g. `withdraw`, aren't evidence of control,
+    /// as they would only fail at runtime.
+    pub fn with_controlled_account(mut self, account: ComponentAddress) -> Self {
+        self.controlled_accounts.insert(account);
+        self
+    }
+
+    /// Treats the virtual account of the signer as controlled.
+    pub fn with_signer(self, public_key: &PublicKey) -> Self {
+        self.with_controlled_account(ComponentAddress::virtual_account_from_public_key(
+            public_key,
+        ))
+    }
+
+    pub fn analyze(&self, instructions: &[InstructionV1]) -> Vec<ResourceFlowWarning> {
+        let mut state = FlowState {
+            controlled_accounts: self.controlled_accounts.clone(),
+            ..Default::default()
+        };
+        for (index, instruction) in instructions.iter().enumerate() {
+            state.process_instruction(index, instruction);
+        }
+        state.finish()
+    }
+}
+
+/// A call which was given resources, and which may have returned arbitrary ones in exchange.
+struct ExchangeCall {
+    index: usize,
+    given_resources: IndexSet<ResourceAddress>,
+    /// The resources taken from the worktop in full since the call, without a guaranteed amount
+    taken_resources: IndexSet<ResourceAddress>,
+    /// The resources guaranteed on the worktop since the call, by assertions or by taking amounts
+    guaranteed_resources: IndexSet<ResourceAddress>,
+}
+
+impl ExchangeCall {
+    /// Whether the resources returned by the call are guaranteed, i.e. those taken from the worktop
+    /// in full are all asserted on, or else, if the returned resources aren't known, at least one
+    /// resource other than those given is.
+    fn is_guaranteed(&self) -> bool {
+        let returned_resources: IndexSet<_> = self
+            .taken_resources
+            .difference(&self.given_resources)
+            .collect();
+        if returned_resources.is_empty() {
+            self.guaranteed_resources
+                .iter()
+                .any(|resource_address| !self.given_resources.contains(resource_address))
+        } else {
+            returned_resources
+                .iter()
+                .all(|resource_address| self.guaranteed_resources.contains(*resource_address))
+        }
+    }
+}
+
+struct BucketState {
+    resource_address: ResourceAddress,
+    amount: ResourceAmount,
+    created_at: usize,
+}
+
+#[derive(Default)]
+struct FlowState {
+    controlled_accounts: IndexSet<ComponentAddress>,
+    id_allocator: ManifestIdAllocator,
+    /// The resources known to be on the worktop
+    worktop: IndexMap<ResourceAddress, ResourceAmount>,
+    /// Whether any other resources may be on the worktop, e.g. as returned by arbitrary calls
+    worktop_may_hold_others: bool,
+    buckets: IndexMap<ManifestBucket, BucketState>,
+    /// The indices of the instructions which pushed the proofs known to be on the auth zone
+    auth_zone: Vec<usize>,
+    /// The calls which were given resources, and which may have returned arbitrary ones
+    exchanges: Vec<ExchangeCall>,
+    warnings: Vec<ResourceFlowWarning>,
+}
+
+impl FlowState {
+    fn process_instruction(&mut self, index: usize, instruction: &InstructionV1) {
+        match instruction {
+            InstructionV1::TakeAllFromWorktop { resource_address } => {
+                for exchange in &mut self.exchanges {
+                    exchange.taken_resources.insert(*resource_address);
+                }
+                let amount = self.worktop_amount(resource_address);
+                self.worktop
+                    .insert(*resource_address, ResourceAmount::Known(Decimal::ZERO));
+                self.new_bucket(index, *resource_address, amount);
+            }
+            InstructionV1::TakeFromWorktop {
+                resource_address,
+                amount,
+            } => {
+                self.guarantee(*resource_address);
+                self.take_from_worktop(index, *resource_address, *amount);
+                self.new_bucket(index, *resource_address, ResourceAmount::Known(*amount));
+            }
+            InstructionV1::TakeNonFungiblesFromWorktop {
+                resource_address,
+                ids,
+            } => {
+                let amount = Decimal::from(ids.len());
+                self.guarantee(*resource_address);
+                self.take_from_worktop(index, *resource_address, amount);
+                self.new_bucket(index, *resource_address, ResourceAmount::Known(amount));
+            }
+            InstructionV1::ReturnToWorktop { bucket_id } => {
+                if let Some(bucket) = self.buckets.swap_remove(bucket_id) {
+                    self.put_on_worktop(bucket.resource_address, bucket.amount);
+                }
+            }
+            InstructionV1::AssertWorktopContainsAny { resource_address }
+            | InstructionV1::AssertWorktopContains {
+                resource_address, ..
+            }
+            | InstructionV1::AssertWorktopContainsNonFungibles {
+                resource_address, ..
+            } => {
+                self.guarantee(*resource_address);
+            }
+            InstructionV1::PopFromAuthZone => {
+                self.auth_zone.pop();
+            }
+            InstructionV1::PushToAuthZone { .. } => {
+                self.auth_zone.push(index);
+            }
+            InstructionV1::DropAuthZoneProofs
+            | InstructionV1::DropAuthZoneRegularProofs
+            | InstructionV1::DropAllProofs => {
+                self.auth_zone.clear();
+            }
+            InstructionV1::BurnResource { bucket_id } => {
+                self.buckets.swap_remove(bucket_id);
+            }
+            InstructionV1::CallMethod {
+                address,
+                method_name,
+                args,
+            } => {
+                let address = match address {
+                    DynamicGlobalAddress::Static(address) => Some(*address),
+                    DynamicGlobalAddress::Named(_) => None,
+                };
+                self.call(index, address, method_name, args);
+            }
+            InstructionV1::CallFunction { args, .. } => {
+                self.call(index, None, "", args);
+            }
+            InstructionV1::CallRoyaltyMethod { args, .. }
+            | InstructionV1::CallMetadataMethod { args, .. }
+            | InstructionV1::CallRoleAssignmentMethod { args, .. }
+            | InstructionV1::CallDirectVaultMethod { args, .. } => {
+                self.call(index, None, "", args);
+            }
+            InstructionV1::DropAuthZoneSignatureProofs
+            | InstructionV1::CreateProofFromAuthZoneOfAmount { .. }
+            | InstructionV1::CreateProofFromAuthZoneOfNonFungibles { .. }
+            | InstructionV1::CreateProofFromAuthZoneOfAll { .. }
+            | InstructionV1::CreateProofFromBucketOfAmount { .. }
+            | InstructionV1::CreateProofFromBucketOfNonFungibles { .. }
+            | InstructionV1::CreateProofFromBucketOfAll { .. }
+            | InstructionV1::CloneProof { .. }
+            | InstructionV1::DropProof { .. }
+            | InstructionV1::DropNamedProofs
+            | InstructionV1::AllocateGlobalAddress { .. } => {}
+        }
+    }
+
+    /// Processes a call to the method of the given address, or else to an arbitrary method or
+    /// function.
+    fn call(
+        &mut self,
+        index: usize,
+        address: Option<GlobalAddress>,
+        method_name: &str,
+        args: &ManifestValue,
+    ) {
+        let mut call_data = CallData::default();
+        let _ = transform(args.clone(), &mut call_data);
+
+        let mut given_resources = index_set_new();
+        for bucket_id in &call_data.buckets {
+            if let Some(bucket) = self.buckets.swap_remove(bucket_id) {
+                given_resources.insert(bucket.resource_address);
+            }
+        }
+        if call_data.entire_worktop {
+            given_resources.extend(
+                self.worktop
+                    .drain(..)
+                    .map(|(resource_address, _)| resource_address),
+            );
+            self.worktop_may_hold_others = false;
+        }
+        if call_data.entire_auth_zone {
+            self.auth_zone.clear();
+        }
+
+        if let Some(account) = address.as_ref().and_then(account_address) {
+            match method_name {
+                ACCOUNT_DEPOSIT_IDENT
+                | ACCOUNT_DEPOSIT_BATCH_IDENT
+                | ACCOUNT_TRY_DEPOSIT_OR_ABORT_IDENT
+                | ACCOUNT_TRY_DEPOSIT_BATCH_OR_ABORT_IDENT
+                | ACCOUNT_TRY_DEPOSIT_OR_REFUND_IDENT
+                | ACCOUNT_TRY_DEPOSIT_BATCH_OR_REFUND_IDENT => {
+                    if !self.controlled_accounts.contains(&account) {
+                        self.warnings
+                            .push(ResourceFlowWarning::DepositIntoUncontrolledAccount {
+                                instruction_index: index,
+                                account,
+                            });
+                    }
+                    if matches!(
+                        method_name,
+                        ACCOUNT_TRY_DEPOSIT_OR_REFUND_IDENT
+                            | ACCOUNT_TRY_DEPOSIT_BATCH_OR_REFUND_IDENT
+                    ) {
+                        // Any of the resources may be refunded.
+                        for resource_address in given_resources {
+                            self.put_on_worktop(resource_address, ResourceAmount::Unknown);
+                        }
+                    }
+                    return;
+                }
+                ACCOUNT_WITHDRAW_IDENT => {
+                    if let Some(input) = decode_args::<AccountWithdrawInput>(args) {
+                        self.put_on_worktop(
+                            input.resource_address,
+                            ResourceAmount::Known(input.amount),
+                        );
+                        return;
+                    }
+                }
+                ACCOUNT_WITHDRAW_NON_FUNGIBLES_IDENT => {
+                    if let Some(input) = decode_args::<AccountWithdrawNonFungiblesInput>(args) {
+                        self.put_on_worktop(
+                            input.resource_address,
+                            ResourceAmount::Known(Decimal::from(input.ids.len())),
+                        );
+                        return;
+                    }
+                }
+                ACCOUNT_LOCK_FEE_AND_WITHDRAW_IDENT => {
+                    if let Some(input) = decode_args::<AccountLockFeeAndWithdrawInput>(args) {
+                        self.put_on_worktop(
+                            input.resource_address,
+                            ResourceAmount::Known(input.amount),
+                        );
+                        return;
+                    }
+                }
+                ACCOUNT_LOCK_FEE_AND_WITHDRAW_NON_FUNGIBLES_IDENT => {
+                    if let Some(input) =
+                        decode_args::<AccountLockFeeAndWithdrawNonFungiblesInput>(args)
+                    {
+                        self.put_on_worktop(
+                            input.resource_address,
+                            ResourceAmount::Known(Decimal::from(input.ids.len())),
+                        );
+                        return;
+                    }
+                }
+                ACCOUNT_CREATE_PROOF_OF_AMOUNT_IDENT
+                | ACCOUNT_CREATE_PROOF_OF_NON_FUNGIBLES_IDENT => {
+                    self.auth_zone.push(index);
+                    return;
+                }
+                ACCOUNT_LOCK_FEE_IDENT
+                | ACCOUNT_LOCK_CONTINGENT_FEE_IDENT
+                | ACCOUNT_BURN_IDENT
+                | ACCOUNT_BURN_NON_FUNGIBLES_IDENT => return,
+                _ => {}
+            }
+            self.receive_arbitrary_resources();
+            return;
+        }
+
+        if let Some(resource_address) = address
+            .as_ref()
+            .and_then(|address| ResourceAddress::try_from(address.as_node_id().as_bytes()).ok())
+        {
+            if resource_address.is_fungible() && method_name == FUNGIBLE_RESOURCE_MANAGER_MINT_IDENT
+            {
+                if let Some(input) = decode_args::<FungibleResourceManagerMintInput>(args) {
+                    self.put_on_worktop(resource_address, ResourceAmount::Known(input.amount));
+                    return;
+                }
+            }
+            // Resource managers never exchange the resources they are given for others.
+            self.receive_arbitrary_resources();
+            return;
+        }
+
+        self.receive_arbitrary_resources();
+        if !given_resources.is_empty() || call_data.entire_worktop {
+            self.exchanges.push(ExchangeCall {
+                index,
+                given_resources,
+                taken_resources: index_set_new(),
+                guaranteed_resources: index_set_new(),
+            });
+        }
+    }
+
+    fn new_bucket(
+        &mut self,
+        index: usize,
+        resource_address: ResourceAddress,
+        amount: ResourceAmount,
+    ) {
+        let bucket_id = self.id_allocator.new_bucket_id();
+        self.buckets.insert(
+            bucket_id,
+            BucketState {
+                resource_address,
+                amount,
+                created_at: index,
+            },
+        );
+    }
+
+    fn guarantee(&mut self, resource_address: ResourceAddress) {
+        for exchange in &mut self.exchanges {
+            exchange.guaranteed_resources.insert(resource_address);
+        }
+    }
+
+    fn worktop_amount(&self, resource_address: &ResourceAddress) -> ResourceAmount {
+        match self.worktop.get(resource_address) {
+            Some(amount) => *amount,
+            None if self.worktop_may_hold_others => ResourceAmount::Unknown,
+            None => ResourceAmount::Known(Decimal::ZERO),
+        }
+    }
+
+    fn put_on_worktop(&mut self, resource_address: ResourceAddress, amount: ResourceAmount) {
+        let amount = self.worktop_amount(&resource_address).add(amount);
+        self.worktop.insert(resource_address, amount);
+    }
+
+    fn take_from_worktop(
+        &mut self,
+        index: usize,
+        resource_address: ResourceAddress,
+        amount: Decimal,
+    ) {
+        if let ResourceAmount::Known(available) = self.worktop_amount(&resource_address) {
+            if available < amount {
+                self.warnings
+                    .push(ResourceFlowWarning::InsufficientWorktopContents {
+                        instruction_index: index,
+                        resource_address,
+                        required: amount,
+                        available,
+                    });
+            }
+            let remaining = available.checked_sub(amount).unwrap_or(Decimal::ZERO);
+            self.worktop.insert(
+                resource_address,
+                ResourceAmount::Known(remaining.max(Decimal::ZERO)),
+            );
+        }
+    }
+
+    /// Makes the contents of the worktop unknown, after a call which may return any resources.
+    fn receive_arbitrary_resources(&mut self) {
+        for amount in self.worktop.values_mut() {
+            *amount = ResourceAmount::Unknown;
+        }
+        self.worktop_may_hold_others = true;
+    }
+
+    fn finish(mut self) -> Vec<ResourceFlowWarning> {
+        for exchange in self.exchanges {
+            if !exchange.is_guaranteed() {
+                self.warnings
+                    .push(ResourceFlowWarning::MissingWorktopAssertion {
+                        call_index: exchange.index,
+                    });
+            }
+        }
+        for (bucket_id, bucket) in self.buckets {
+            self.warnings
+                .push(ResourceFlowWarning::BucketNeverDeposited {
+                    bucket_id,
+                    created_at: bucket.created_at,
+                });
+        }
+        for pushed_at in self.auth_zone {
+            self.warnings
+                .push(ResourceFlowWarning::ProofLeftOnAuthZone { pushed_at });
+        }
+        for (resource_address, amount) in self.worktop {
+            if let ResourceAmount::Known(amount) = amount {
+                if amount.is_positive() {
+                    self.warnings
+                        .push(ResourceFlowWarning::ResourcesLeftOnWorktop {
+                            resource_address,
+                            amount,
+                        });
+                }
+            }
+        }
+        self.warnings
+    }
+}
+
+/// The buckets and expressions passed to a call.
+#[derive(Default)]
+struct CallData {
+    buckets: Vec<ManifestBucket>,
+    entire_worktop: bool,
+    entire_auth_zone: bool,
+}
+
+impl TransformHandler<Infallible> for CallData {
+    fn replace_bucket(&mut self, b: ManifestBucket) -> Result<Own, Infallible> {
+        self.buckets.push(b);
+        Ok(Own(NodeId([0u8; NodeId::LENGTH])))
+    }
+
+    fn replace_proof(&mut self, _p: ManifestProof) -> Result<Own, Infallible> {
+        Ok(Own(NodeId([0u8; NodeId::LENGTH])))
+    }
+
+    fn replace_address_reservation(
+        &mut self,
+        _r: ManifestAddressReservation,
+    ) -> Result<Own, Infallible> {
+        Ok(Own(NodeId([0u8; NodeId::LENGTH])))
+    }
+
+    fn replace_named_address(&mut self, _a: u32) -> Result<Reference, Infallible> {
+        Ok(Reference(NodeId([0u8; NodeId::LENGTH])))
+    }
+
+    fn replace_expression(&mut self, e: ManifestExpression) -> Result<Vec<Own>, Infallible> {
+        match e {
+            ManifestExpression::EntireWorktop => self.entire_worktop = true,
+            ManifestExpression::EntireAuthZone => self.entire_auth_zone = true,
+        }
+        Ok(Vec::new())
+    }
+
+    fn replace_blob(&mut self, _b: ManifestBlobRef) -> Result<Vec<u8>, Infallible> {
+        Ok(Vec::new())
+    }
+}
+
+fn account_address(address: &GlobalAddress) -> Option<ComponentAddress> {
+    match address.as_node_id().entity_type() {
+        Some(
+            EntityType::GlobalAccount
+            | EntityType::GlobalVirtualSecp256k1Account
+            | EntityType::GlobalVirtualEd25519Account,
+        ) => ComponentAddress::try_from(address.as_node_id().as_bytes()).ok(),
+        _ => None,
+    }
+}
+
+fn decode_args<T: ManifestDecode>(args: &ManifestValue) -> Option<T> {
+    manifest_encode(args)
+        .ok()
+        .and_then(|payload| manifest_decode(&payload).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::ManifestBuilder;
+
+    fn account(key: u64) -> (ComponentAddress, PublicKey) {
+        let public_key: PublicKey = Secp256k1PrivateKey::from_u64(key)
+            .unwrap()
+            .public_key()
+            .into();
+        (
+            ComponentAddress::virtual_account_from_public_key(&public_key),
+            public_key,
+        )
+    }
+
+    #[test]
+    fn test_deposit_into_uncontrolled_account() {
+        let (sender, _) = account(1);
+        let (recipient, recipient_key) = account(2);
+        let manifest = ManifestBuilder::new()
+            .lock_fee(sender, 10)
+            .withdraw_from_account(sender, XRD, 10)
+            .take_from_worktop(XRD, 10, "xrd")
+            .try_deposit_or_abort(recipient, None, "xrd")
+            .build();
+
+        assert_eq!(
+            ResourceFlowAnalyzer::new().analyze(&manifest.instructions),
+            vec![ResourceFlowWarning::DepositIntoUncontrolledAccount {
+                instruction_index: 3,
+                account: recipient,
+            }]
+        );
+        assert_eq!(
+            ResourceFlowAnalyzer::new()
+                .with_signer(&recipient_key)
+                .analyze(&manifest.instructions),
+            vec![]
+        );
+
+        // Withdrawing from an account doesn't make it controlled, without a matching signer
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account(sender, XRD, 10)
+            .deposit_batch(sender)
+            .build();
+        assert_eq!(
+            ResourceFlowAnalyzer::new().analyze(&manifest.instructions),
+            vec![ResourceFlowWarning::DepositIntoUncontrolledAccount {
+                instruction_index: 1,
+                account: sender,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_swap_without_worktop_assertion() {
+        let (account, public_key) = account(1);
+        let analyzer = ResourceFlowAnalyzer::new().with_signer(&public_key);
+        let dex = ComponentAddress::new_or_panic(
+            [EntityType::GlobalGenericComponent as u8; NodeId::LENGTH],
+        );
+        let token = ResourceAddress::new_or_panic(
+            [EntityType::GlobalFungibleResourceManager as u8; NodeId::LENGTH],
+        );
+        let other_token = ResourceAddress::new_or_panic(
+            [EntityType::GlobalNonFungibleResourceManager as u8; NodeId::LENGTH],
+        );
+        let swap = |builder: ManifestBuilder| {
+            builder
+                .withdraw_from_account(account, XRD, 10)
+                .take_from_worktop(XRD, 10, "xrd")
+                .with_name_lookup(|builder, lookup| {
+                    builder.call_method(dex, "swap", manifest_args!(lookup.bucket("xrd")))
+                })
+        };
+
+        let manifest = swap(ManifestBuilder::new())
+            .take_all_from_worktop(token, "token")
+            .build();
+        assert_eq!(
+            analyzer.analyze(&manifest.instructions),
+            vec![
+                ResourceFlowWarning::MissingWorktopAssertion { call_index: 2 },
+                ResourceFlowWarning::BucketNeverDeposited {
+                    bucket_id: ManifestBucket(1),
+                    created_at: 3,
+                },
+            ]
+        );
+
+        let manifest = swap(ManifestBuilder::new())
+            .assert_worktop_contains(token, 5)
+            .deposit_batch(account)
+            .build();
+        assert_eq!(analyzer.analyze(&manifest.instructions), vec![]);
+
+        let manifest = swap(ManifestBuilder::new())
+            .take_from_worktop(token, 5, "token")
+            .deposit(account, "token")
+            .deposit_batch(account)
+            .build();
+        assert_eq!(analyzer.analyze(&manifest.instructions), vec![]);
+
+        // An assertion on the given resource, e.g. on a refund, doesn't guarantee the returned one
+        let manifest = swap(ManifestBuilder::new())
+            .assert_worktop_contains(XRD, 1)
+            .deposit_batch(account)
+            .build();
+        assert_eq!(
+            analyzer.analyze(&manifest.instructions),
+            vec![ResourceFlowWarning::MissingWorktopAssertion { call_index: 2 }]
+        );
+
+        // Nor does an assertion on another resource than the one taken
+        let manifest = swap(ManifestBuilder::new())
+            .assert_worktop_contains(other_token, 1)
+            .take_all_from_worktop(token, "token")
+            .deposit(account, "token")
+            .deposit_batch(account)
+            .build();
+        assert_eq!(
+            analyzer.analyze(&manifest.instructions),
+            vec![ResourceFlowWarning::MissingWorktopAssertion { call_index: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_proof_left_on_auth_zone() {
+        let (account, public_key) = account(1);
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account, XRD, 1)
+            .call_method(FAUCET, "free", ())
+            .deposit_batch(account)
+            .build();
+        assert_eq!(
+            ResourceFlowAnalyzer::new()
+                .with_signer(&public_key)
+                .analyze(&manifest.instructions),
+            vec![ResourceFlowWarning::ProofLeftOnAuthZone { pushed_at: 0 }]
+        );
+
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account, XRD, 1)
+            .drop_auth_zone_proofs()
+            .build();
+        assert_eq!(
+            ResourceFlowAnalyzer::new().analyze(&manifest.instructions),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_known_worktop_contents() {
+        let (account, _) = account(1);
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account(account, XRD, 5)
+            .withdraw_from_account(account, XRD, 3)
+            .take_from_worktop(XRD, 10, "xrd")
+            .return_to_worktop("xrd")
+            .take_from_worktop(XRD, 4, "xrd2")
+            .burn_resource("xrd2")
+            .build();
+        assert_eq!(
+            ResourceFlowAnalyzer::new().analyze(&manifest.instructions),
+            vec![
+                ResourceFlowWarning::InsufficientWorktopContents {
+                    instruction_index: 2,
+                    resource_address: XRD,
+                    required: dec!(10),
+                    available: dec!(8),
+                },
+                ResourceFlowWarning::ResourcesLeftOnWorktop {
+                    resource_address: XRD,
+                    amount: dec!(6),
+                },
+            ]
+        );
+    }
+}
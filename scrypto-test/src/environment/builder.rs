@@ -11,7 +11,9 @@ use radix_engine::system::system::*;
 use radix_engine::system::system_callback::*;
 use radix_engine::system::system_modules::auth::*;
 use radix_engine::system::system_modules::costing::*;
+use radix_engine::system::system_modules::debugger::DebuggerModule;
 use radix_engine::system::system_modules::execution_trace::ExecutionTraceModule;
+use radix_engine::system::system_modules::instruction_deltas::InstructionDeltasModule;
 use radix_engine::system::system_modules::kernel_trace::KernelTraceModule;
 use radix_engine::system::system_modules::limits::LimitsModule;
 use radix_engine::system::system_modules::transaction_runtime::TransactionRuntimeModule;
@@ -280,13 +282,16 @@ where
                         EnabledModules::LIMITS
                             | EnabledModules::AUTH
                             | EnabledModules::TRANSACTION_RUNTIME,
-                        KernelTraceModule,
+                        KernelTraceModule::new(),
                         transaction_runtime_module,
                         auth_module,
                         limits_module,
                         costing_module,
                         ExecutionTraceModule::new(MAX_EXECUTION_TRACE_DEPTH),
+                        InstructionDeltasModule::new(),
+                        DebuggerModule::new(None),
                     ),
+                    enable_read_write_set: false,
                 }
             },
             |system_config, track, id_allocator| {
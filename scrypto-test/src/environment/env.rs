@@ -585,6 +585,46 @@ where
         rtn
     }
 
+    /// Attaches the given debugger to the Radix Engine and enables the debugger kernel module, or
+    /// detaches the debugger and disables the module if `None` is passed.
+    pub fn set_debugger(&mut self, debugger: Option<DebuggerConfig>) {
+        let enabled = debugger.is_some();
+        self.0.with_kernel_mut(|kernel| {
+            kernel
+                .kernel_callback_mut()
+                .modules
+                .debugger_mut()
+                .set_config(debugger)
+        });
+        if enabled {
+            self.enable_module(EnabledModules::DEBUGGER)
+        } else {
+            self.disable_module(EnabledModules::DEBUGGER)
+        }
+    }
+
+    /// Calls the passed `callback` with the given debugger attached and then detaches it.
+    pub fn with_debugger<F, O>(&mut self, debugger: DebuggerConfig, callback: F) -> O
+    where
+        F: FnOnce(&mut Self) -> O,
+    {
+        self.set_debugger(Some(debugger));
+        let rtn = callback(self);
+        self.set_debugger(None);
+        rtn
+    }
+
+    /// Takes the kernel operations recorded by the kernel trace kernel module so far.
+    pub fn take_kernel_trace(&mut self) -> KernelTrace {
+        self.0.with_kernel_mut(|kernel| {
+            kernel
+                .kernel_callback_mut()
+                .modules
+                .kernel_trace_mut()
+                .take_trace()
+        })
+    }
+
     /// Returns the bit flags representing the currently enabled kernel modules.
     pub fn enabled_modules(&self) -> EnabledModules {
         self.0
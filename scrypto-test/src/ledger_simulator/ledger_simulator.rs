@@ -11,8 +11,9 @@ use radix_engine::system::system_db_reader::{
 use radix_engine::system::system_substates::FieldSubstate;
 use radix_engine::system::type_info::TypeInfoSubstate;
 use radix_engine::transaction::{
-    execute_preview, execute_transaction_with_configuration, BalanceChange, CommitResult,
-    CostingParameters, ExecutionConfig, PreviewError, TransactionReceipt, TransactionResult,
+    execute_preview, execute_preview_batch, execute_preview_with_state_overrides,
+    execute_transaction_with_configuration, BalanceChange, CommitResult, CostingParameters,
+    ExecutionConfig, PreviewError, TransactionReceipt, TransactionResult,
 };
 use radix_engine::updates::*;
 use radix_engine::vm::wasm::{DefaultWasmEngine, WasmValidatorConfigV1};
@@ -1376,6 +1377,9 @@ impl<E: NativeVmExtension, D: TestDatabase> LedgerSimulator<E, D> {
                     enable_kernel_trace: execution_config.enable_kernel_trace,
                     enable_cost_breakdown: execution_config.enable_cost_breakdown,
                     execution_trace: execution_config.execution_trace,
+                    enable_instruction_deltas: execution_config.enable_instruction_deltas,
+                    enable_read_write_set: execution_config.enable_read_write_set,
+                    debugger: execution_config.debugger.clone(),
                     callback_init: vm_init,
                     system_overrides: execution_config.system_overrides.clone(),
                 },
@@ -1479,6 +1483,57 @@ impl<E: NativeVmExtension, D: TestDatabase> LedgerSimulator<E, D> {
         transaction_receipt
     }
 
+    /// Executes and commits a batch of transactions, speculatively in parallel on up to
+    /// `parallelism` threads. See [`execute_transactions_in_parallel`] for details.
+    #[cfg(feature = "moka")]
+    pub fn execute_transactions_in_parallel(
+        &mut self,
+        executables: &[Executable],
+        mut execution_config: ExecutionConfig,
+        parallelism: usize,
+    ) -> ParallelExecutionResult
+    where
+        D: Sync,
+        E: Send + Sync,
+    {
+        // Override the kernel trace config
+        execution_config = execution_config.with_kernel_trace(self.with_kernel_trace);
+
+        if executables.iter().any(|executable| {
+            executable
+                .costing_parameters()
+                .free_credit_in_xrd
+                .is_positive()
+        }) {
+            self.xrd_free_credits_used = true;
+        }
+
+        let vm_init = VmInit {
+            scrypto_vm: &self.scrypto_vm,
+            native_vm_extension: self.native_vm_extension.clone(),
+        };
+
+        let result = execute_transactions_in_parallel(
+            &self.database,
+            vm_init,
+            &execution_config,
+            executables,
+            parallelism,
+        );
+        self.database.commit(&result.database_updates);
+        for receipt in &result.receipts {
+            if let TransactionResult::Commit(commit) = &receipt.result {
+                self.collected_events
+                    .push(commit.application_events.clone());
+
+                if self.with_receipt_substate_check {
+                    assert_receipt_substate_changes_can_be_typed(commit);
+                }
+            }
+        }
+        result
+    }
+
     pub fn preview(
         &mut self,
         preview_intent: PreviewIntentV1,
@@ -1505,7 +1560,13 @@ impl<E: NativeVmExtension, D: TestDatabase> LedgerSimulator<E, D> {
         tip_percentage: u16,
         flags: PreviewFlags,
     ) -> TransactionReceipt {
-        let epoch = self.get_current_epoch();
+        let preview_intent = self.preview_intent_for_manifest(
+            manifest,
+            signer_public_keys,
+            tip_percentage,
+            flags,
+            0,
+        );
         let vm_init = VmInit {
             scrypto_vm: &self.scrypto_vm,
             native_vm_extension: self.native_vm_extension.clone(),
@@ -1514,31 +1575,111 @@ impl<E: NativeVmExtension, D: TestDatabase> LedgerSimulator<E, D> {
             &mut self.database,
             vm_init,
             &NetworkDefinition::simulator(),
-            PreviewIntentV1 {
-                intent: IntentV1 {
-                    header: TransactionHeaderV1 {
-                        network_id: NetworkDefinition::simulator().id,
-                        start_epoch_inclusive: epoch,
-                        end_epoch_exclusive: epoch.after(10).unwrap(),
-                        nonce: 0,
-                        notary_public_key: PublicKey::Secp256k1(Secp256k1PublicKey([0u8; 33])),
-                        notary_is_signatory: false,
-                        tip_percentage,
-                    },
-                    instructions: InstructionsV1(manifest.instructions),
-                    blobs: BlobsV1 {
-                        blobs: manifest.blobs.values().map(|x| BlobV1(x.clone())).collect(),
-                    },
-                    message: MessageV1::default(),
-                },
-                signer_public_keys,
-                flags,
-            },
+            preview_intent,
+            self.with_kernel_trace,
+        )
+        .unwrap()
+    }
+
+    /// Previews the manifest as if the given state updates had been committed first, without
+    /// committing anything to the ledger.
+    pub fn preview_manifest_with_state_overrides(
+        &mut self,
+        manifest: TransactionManifestV1,
+        signer_public_keys: Vec<PublicKey>,
+        tip_percentage: u16,
+        flags: PreviewFlags,
+        state_overrides: &StateUpdates,
+    ) -> TransactionReceipt {
+        let preview_intent = self.preview_intent_for_manifest(
+            manifest,
+            signer_public_keys,
+            tip_percentage,
+            flags,
+            0,
+        );
+        let vm_init = VmInit {
+            scrypto_vm: &self.scrypto_vm,
+            native_vm_extension: self.native_vm_extension.clone(),
+        };
+        execute_preview_with_state_overrides(
+            &self.database,
+            vm_init,
+            &NetworkDefinition::simulator(),
+            preview_intent,
+            self.with_kernel_trace,
+            &state_overrides.create_database_updates::<SpreadPrefixKeyMapper>(),
+        )
+        .unwrap()
+    }
+
+    /// Previews the manifests in order, each of them seeing the state updates of the previous ones,
+    /// without committing anything to the ledger.
+    pub fn preview_manifest_batch(
+        &mut self,
+        manifests: Vec<TransactionManifestV1>,
+        signer_public_keys: Vec<PublicKey>,
+        tip_percentage: u16,
+        flags: PreviewFlags,
+    ) -> Vec<TransactionReceipt> {
+        let preview_intents = manifests
+            .into_iter()
+            .enumerate()
+            .map(|(index, manifest)| {
+                self.preview_intent_for_manifest(
+                    manifest,
+                    signer_public_keys.clone(),
+                    tip_percentage,
+                    flags.clone(),
+                    index as u32,
+                )
+            })
+            .collect();
+        let vm_init = VmInit {
+            scrypto_vm: &self.scrypto_vm,
+            native_vm_extension: self.native_vm_extension.clone(),
+        };
+        execute_preview_batch(
+            &self.database,
+            vm_init,
+            &NetworkDefinition::simulator(),
+            preview_intents,
             self.with_kernel_trace,
         )
         .unwrap()
     }
 
+    fn preview_intent_for_manifest(
+        &mut self,
+        manifest: TransactionManifestV1,
+        signer_public_keys: Vec<PublicKey>,
+        tip_percentage: u16,
+        flags: PreviewFlags,
+        nonce: u32,
+    ) -> PreviewIntentV1 {
+        let epoch = self.get_current_epoch();
+        PreviewIntentV1 {
+            intent: IntentV1 {
+                header: TransactionHeaderV1 {
+                    network_id: NetworkDefinition::simulator().id,
+                    start_epoch_inclusive: epoch,
+                    end_epoch_exclusive: epoch.after(10).unwrap(),
+                    nonce,
+                    notary_public_key: PublicKey::Secp256k1(Secp256k1PublicKey([0u8; 33])),
+                    notary_is_signatory: false,
+                    tip_percentage,
+                },
+                instructions: InstructionsV1(manifest.instructions),
+                blobs: BlobsV1 {
+                    blobs: manifest.blobs.values().map(|x| BlobV1(x.clone())).collect(),
+                },
+                message: MessageV1::default(),
+            },
+            signer_public_keys,
+            flags,
+        }
+    }
+
     /// Calls a package blueprint function with the given arguments, paying the fee from the faucet.
     ///
     /// The arguments should be one of:
@@ -29,7 +29,9 @@ pub use radix_engine::system::system_callback::*;
 pub use radix_engine::system::system_callback_api::*;
 pub use radix_engine::system::system_modules::auth::*;
 pub use radix_engine::system::system_modules::costing::*;
+pub use radix_engine::system::system_modules::debugger::*;
 pub use radix_engine::system::system_modules::execution_trace::*;
+pub use radix_engine::system::system_modules::kernel_trace::*;
 pub use radix_engine::system::system_modules::*;
 pub use radix_engine::system::system_substates::*;
 pub use radix_engine::track::*;